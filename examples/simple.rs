@@ -26,13 +26,10 @@ fn main() {
         // print the assignments satisfy a given problem.
         // x1 = false x2 = false x3 = false x4 = true x5 = false
         solver.assigns.iter().enumerate().for_each(|(var, assign)| {
-            let b = match assign {
-                LitBool::True => true,
-                _ => false,
-            };
+            let b = matches!(assign, LitBool::True);
             print!("x{} = {} ", var + 1, b);
         });
-        println!("");
+        println!();
     }
 
     {
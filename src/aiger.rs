@@ -0,0 +1,263 @@
+use crate::solver::{Clause, Lit};
+use std::{collections::HashMap, io::Read};
+
+/// AigerData is parsed from an AIGER file.
+/// The variable `i` of AIGER is `Var(i)`, so an AIGER literal `2i` is `Lit::new(i, true)` and `2i+1` is its negation.
+/// `Var(0)` is the constant `false`. AND gates are converted into clauses by the Tseitin encoding.
+#[derive(Debug, Default, Clone)]
+pub struct AigerData {
+    // the number of variables including the constant
+    pub var_num: usize,
+    // clauses that define AND gates and the constant
+    pub clauses: Vec<Clause>,
+    // primary inputs in declaration order
+    pub inputs: Vec<Lit>,
+    // latches as pairs of a current state and a next state.
+    // A current state is free like a primary input.
+    pub latches: Vec<(Lit, Lit)>,
+    // reset values of latches. `None` is uninitialized.
+    pub resets: Vec<Option<bool>>,
+    // primary outputs in declaration order
+    pub outputs: Vec<Lit>,
+    // bad state properties(AIGER 1.9)
+    pub bad: Vec<Lit>,
+    // invariant constraints(AIGER 1.9)
+    pub constraints: Vec<Lit>,
+    // names of inputs, latches, outputs, bad states and constraints in the symbol table
+    pub names: HashMap<String, Lit>,
+}
+
+fn invalid(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// ASCII lines and binary numbers of an AIGER file.
+struct AigerReader {
+    bytes: Vec<u8>,
+    pos: usize,
+}
+
+impl AigerReader {
+    /// The next line without its `\n` or `None` at the end of a file.
+    fn line(&mut self) -> Option<String> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let rest = &self.bytes[self.pos..];
+        let len = rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+        let line = String::from_utf8_lossy(&rest[..len]).into_owned();
+        self.pos += len + 1;
+        Some(line)
+    }
+
+    /// The numbers of the next line.
+    fn numbers(&mut self, what: &str) -> std::io::Result<Vec<u64>> {
+        let line = self
+            .line()
+            .ok_or_else(|| invalid(format!("Missing {}", what)))?;
+        line.split_whitespace()
+            .map(|x| {
+                x.parse::<u64>()
+                    .map_err(|_| invalid(format!("Invalid {}: {}", what, line)))
+            })
+            .collect()
+    }
+
+    /// A number in 7-bit bytes from the lowest. The highest bit of a byte means that more bytes follow.
+    fn delta(&mut self) -> std::io::Result<u64> {
+        let mut x = 0u64;
+        let mut shift = 0;
+        loop {
+            let b = *self
+                .bytes
+                .get(self.pos)
+                .ok_or_else(|| invalid("Missing AND gates".to_string()))?;
+            self.pos += 1;
+            if shift >= 64 {
+                return Err(invalid("Too large AND gate".to_string()));
+            }
+            x |= ((b & 0x7f) as u64) << shift;
+            if b & 0x80 == 0 {
+                return Ok(x);
+            }
+            shift += 7;
+        }
+    }
+}
+
+/// Parse an ASCII(`aag`) or binary(`aig`) AIGER file.
+/// The header is `aag M I L O A` that may be followed by `B C J F` of AIGER 1.9.
+/// Justice and fairness properties(`J` and `F`) are not supported.
+/// A symbol table is read and comments after it are skipped.
+///
+/// ```text
+/// aag 3 2 0 1 1
+/// 2
+/// 4
+/// 7
+/// 6 2 4
+/// i0 a
+/// i1 b
+/// o0 nand
+/// ```
+pub fn parse_aiger<R: Read>(mut input: R) -> std::io::Result<AigerData> {
+    let mut bytes = vec![];
+    input.read_to_end(&mut bytes)?;
+    let mut reader = AigerReader { bytes, pos: 0 };
+
+    let header = reader
+        .line()
+        .ok_or_else(|| invalid("Missing header".to_string()))?;
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    let binary = match tokens.first() {
+        Some(&"aag") => false,
+        Some(&"aig") => true,
+        _ => return Err(invalid(format!("Invalid header: {}", header))),
+    };
+    let counts = tokens[1..]
+        .iter()
+        .map(|x| x.parse::<usize>().ok())
+        .collect::<Option<Vec<usize>>>()
+        .filter(|counts| (5..=9).contains(&counts.len()))
+        .ok_or_else(|| invalid(format!("Invalid header: {}", header)))?;
+    let count = |i: usize| counts.get(i).copied().unwrap_or(0);
+    let (max_var, input_num, latch_num, and_num) = (count(0), count(1), count(2), count(4));
+    if count(7) > 0 || count(8) > 0 {
+        return Err(invalid(
+            "Justice and fairness properties are not supported".to_string(),
+        ));
+    }
+    if binary && max_var != input_num + latch_num + and_num {
+        return Err(invalid(format!("Invalid header: {}", header)));
+    }
+
+    let mut aiger = AigerData {
+        var_num: max_var + 1,
+        // the constant `false`
+        clauses: vec![vec![Lit::new(0, false)]],
+        ..AigerData::default()
+    };
+    let lit = |x: u64| -> std::io::Result<Lit> {
+        if x > 2 * max_var as u64 + 1 {
+            return Err(invalid(format!("Too large literal: {}", x)));
+        }
+        Ok(Lit::new((x >> 1) as u32, x & 1 == 0))
+    };
+    // Each variable is defined by an input, a latch or an AND gate once.
+    let mut defined = vec![false; max_var + 1];
+    defined[0] = true;
+    let mut define = |x: u64| -> std::io::Result<Lit> {
+        let l = lit(x)?;
+        if x & 1 == 1 || x == 0 || defined[l.var().0 as usize] {
+            return Err(invalid(format!("Invalid definition: {}", x)));
+        }
+        defined[l.var().0 as usize] = true;
+        Ok(l)
+    };
+
+    for i in 0..input_num {
+        let x = if binary {
+            2 * (i as u64 + 1)
+        } else {
+            match reader.numbers("input")?[..] {
+                [x] => x,
+                _ => return Err(invalid("Invalid input".to_string())),
+            }
+        };
+        aiger.inputs.push(define(x)?);
+    }
+    let mut used = vec![];
+    for i in 0..latch_num {
+        let mut values = reader.numbers("latch")?;
+        if binary {
+            values.insert(0, 2 * (input_num + i + 1) as u64);
+        }
+        let (cur, next, reset) = match values[..] {
+            [cur, next] => (cur, next, 0),
+            [cur, next, reset] => (cur, next, reset),
+            _ => return Err(invalid("Invalid latch".to_string())),
+        };
+        let reset = match reset {
+            0 => Some(false),
+            1 => Some(true),
+            _ if reset == cur => None,
+            _ => return Err(invalid(format!("Invalid reset: {}", reset))),
+        };
+        let next = lit(next)?;
+        used.push(next);
+        aiger.latches.push((define(cur)?, next));
+        aiger.resets.push(reset);
+    }
+    let lines = [
+        ("output", count(3), &mut aiger.outputs),
+        ("bad state", count(5), &mut aiger.bad),
+        ("constraint", count(6), &mut aiger.constraints),
+    ];
+    for (what, num, lits) in lines {
+        for _ in 0..num {
+            let x = match reader.numbers(what)?[..] {
+                [x] => lit(x)?,
+                _ => return Err(invalid(format!("Invalid {}", what))),
+            };
+            used.push(x);
+            lits.push(x);
+        }
+    }
+    for i in 0..and_num {
+        let (lhs, rhs0, rhs1) = if binary {
+            // `lhs >= rhs0 >= rhs1` by the differences of them
+            let lhs = 2 * (input_num + latch_num + i + 1) as u64;
+            let (d0, d1) = (reader.delta()?, reader.delta()?);
+            let rhs0 = lhs.checked_sub(d0);
+            match (rhs0, rhs0.and_then(|rhs0| rhs0.checked_sub(d1))) {
+                (Some(rhs0), Some(rhs1)) => (lhs, rhs0, rhs1),
+                _ => return Err(invalid(format!("Invalid AND gate: {}", lhs))),
+            }
+        } else {
+            match reader.numbers("AND gate")?[..] {
+                [lhs, rhs0, rhs1] => (lhs, rhs0, rhs1),
+                _ => return Err(invalid("Invalid AND gate".to_string())),
+            }
+        };
+        let out = define(lhs)?;
+        let (a, b) = (lit(rhs0)?, lit(rhs1)?);
+        used.push(a);
+        used.push(b);
+        // out <-> a & b
+        aiger.clauses.push(vec![!out, a]);
+        aiger.clauses.push(vec![!out, b]);
+        aiger.clauses.push(vec![out, !a, !b]);
+    }
+    if let Some(x) = used.iter().find(|x| !defined[x.var().0 as usize]) {
+        return Err(invalid(format!("Undefined variable: {}", x.var().0)));
+    }
+
+    while let Some(line) = reader.line() {
+        if line == "c" {
+            // comments
+            break;
+        }
+        let (symbol, name) = line
+            .split_once(' ')
+            .ok_or_else(|| invalid(format!("Invalid symbol: {}", line)))?;
+        let index = symbol.get(1..).and_then(|i| i.parse::<usize>().ok());
+        let lits = match symbol.get(..1) {
+            Some("i") => &aiger.inputs,
+            Some("o") => &aiger.outputs,
+            Some("b") => &aiger.bad,
+            Some("c") => &aiger.constraints,
+            Some("l") => {
+                let l = index.and_then(|i| aiger.latches.get(i));
+                let l = l.ok_or_else(|| invalid(format!("Invalid symbol: {}", line)))?;
+                aiger.names.insert(name.to_string(), l.0);
+                continue;
+            }
+            _ => return Err(invalid(format!("Invalid symbol: {}", line))),
+        };
+        let l = *index
+            .and_then(|i| lits.get(i))
+            .ok_or_else(|| invalid(format!("Invalid symbol: {}", line)))?;
+        aiger.names.insert(name.to_string(), l);
+    }
+    Ok(aiger)
+}
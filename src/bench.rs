@@ -0,0 +1,285 @@
+use crate::solver::{Clause, Lit, LitBool, Solver, Status, Var};
+use std::{collections::HashMap, io::BufRead, time::Duration};
+
+/// BenchData is parsed from an ISCAS BENCH netlist.
+/// Each signal has its own variable and gates are converted into clauses by the Tseitin encoding.
+#[derive(Debug, Default, Clone)]
+pub struct BenchData {
+    // the number of variables including auxiliary variables of gates
+    pub var_num: usize,
+    // clauses that define gate outputs
+    pub clauses: Vec<Clause>,
+    // primary inputs in declaration order
+    pub inputs: Vec<(String, Var)>,
+    // primary outputs in declaration order
+    pub outputs: Vec<(String, Var)>,
+    // flip-flops as pairs of a current state and a next state.
+    // A current state is free like a primary input.
+    pub latches: Vec<(Var, Var)>,
+    // variables of all signals
+    pub names: HashMap<String, Var>,
+}
+
+impl BenchData {
+    fn var(&mut self, name: &str) -> Var {
+        if let Some(&v) = self.names.get(name) {
+            return v;
+        }
+        let v = self.new_var();
+        self.names.insert(name.to_string(), v);
+        v
+    }
+
+    fn new_var(&mut self) -> Var {
+        self.var_num += 1;
+        Var(self.var_num as u32 - 1)
+    }
+
+    /// Add clauses of `out <-> in_1 & in_2 & ...`.
+    fn and(&mut self, out: Lit, ins: &[Lit]) {
+        ins.iter()
+            .for_each(|&lit| self.clauses.push(vec![!out, lit]));
+        let mut clause: Clause = ins.iter().map(|&lit| !lit).collect();
+        clause.push(out);
+        self.clauses.push(clause);
+    }
+
+    /// Add clauses of `out <-> in_1 ^ in_2 ^ ...` by a chain of binary XORs.
+    fn xor(&mut self, out: Lit, ins: &[Lit]) {
+        let mut acc = ins[0];
+        for (i, &b) in ins.iter().enumerate().skip(1) {
+            let x = if i + 1 == ins.len() {
+                out
+            } else {
+                Lit::new(self.new_var().0, true)
+            };
+            self.clauses.push(vec![!x, acc, b]);
+            self.clauses.push(vec![!x, !acc, !b]);
+            self.clauses.push(vec![x, !acc, b]);
+            self.clauses.push(vec![x, acc, !b]);
+            acc = x;
+        }
+        if ins.len() == 1 {
+            self.clauses.push(vec![!out, acc]);
+            self.clauses.push(vec![out, !acc]);
+        }
+    }
+}
+
+/// Parse an ISCAS-85/89 BENCH netlist.
+/// Supported gates are `AND`, `NAND`, `OR`, `NOR`, `XOR`, `XNOR`, `NOT`, `BUF`(`BUFF`) and `DFF`.
+///
+/// ```text
+/// # Here is a comment.
+/// INPUT(a)
+/// INPUT(b)
+/// OUTPUT(z)
+/// n = NAND(a, b)
+/// z = XOR(n, a)
+/// ```
+pub fn parse_bench<R: std::io::Read>(input: R) -> std::io::Result<BenchData> {
+    let reader = std::io::BufReader::new(input);
+    let mut bench = BenchData::default();
+    let mut defined = HashMap::new();
+    let mut used = vec![];
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.split('#').next().unwrap().trim();
+        if line.is_empty() {
+            // comment or empty line
+            continue;
+        }
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            )
+        };
+        // `name(args)` or `lhs = name(args)`
+        let (lhs, rhs) = match line.split_once('=') {
+            Some((lhs, rhs)) => (Some(lhs.trim()), rhs.trim()),
+            None => (None, line),
+        };
+        let (gate, args) = rhs
+            .strip_suffix(')')
+            .and_then(|rhs| rhs.split_once('('))
+            .ok_or_else(invalid)?;
+        let gate = gate.trim().to_ascii_uppercase();
+        let args: Vec<&str> = args.split(',').map(|arg| arg.trim()).collect();
+        if args.iter().any(|arg| arg.is_empty()) {
+            return Err(invalid());
+        }
+
+        let lhs = match lhs {
+            Some(lhs) if !lhs.is_empty() => lhs,
+            Some(_) => return Err(invalid()),
+            None => {
+                if args.len() != 1 {
+                    return Err(invalid());
+                }
+                let v = bench.var(args[0]);
+                match gate.as_str() {
+                    "INPUT" => {
+                        if defined.insert(v, line.to_string()).is_some() {
+                            return Err(invalid());
+                        }
+                        bench.inputs.push((args[0].to_string(), v));
+                    }
+                    "OUTPUT" => {
+                        used.push((v, line.to_string()));
+                        bench.outputs.push((args[0].to_string(), v));
+                    }
+                    _ => return Err(invalid()),
+                }
+                continue;
+            }
+        };
+
+        let out = bench.var(lhs);
+        if defined.insert(out, line.to_string()).is_some() {
+            return Err(invalid());
+        }
+        let ins: Vec<Lit> = args
+            .iter()
+            .map(|arg| {
+                let v = bench.var(arg);
+                used.push((v, line.to_string()));
+                Lit::new(v.0, true)
+            })
+            .collect();
+        let out = Lit::new(out.0, true);
+        match (gate.as_str(), ins.len()) {
+            ("AND", _) => bench.and(out, &ins),
+            ("NAND", _) => bench.and(!out, &ins),
+            // a | b = !(!a & !b)
+            ("OR", _) => bench.and(!out, &ins.iter().map(|&lit| !lit).collect::<Vec<_>>()),
+            ("NOR", _) => bench.and(out, &ins.iter().map(|&lit| !lit).collect::<Vec<_>>()),
+            ("XOR", _) => bench.xor(out, &ins),
+            ("XNOR", _) => bench.xor(!out, &ins),
+            ("NOT", 1) => bench.xor(!out, &ins),
+            ("BUF", 1) | ("BUFF", 1) => bench.xor(out, &ins),
+            ("DFF", 1) => bench.latches.push((out.var(), ins[0].var())),
+            _ => return Err(invalid()),
+        }
+    }
+    // All signals must be driven by an input, a gate or a flip-flop.
+    if let Some((_, line)) = used.iter().find(|(v, _)| !defined.contains_key(v)) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Undefined signal: {}", line),
+        ));
+    }
+    Ok(bench)
+}
+
+/// The result of `check_equivalence`.
+/// - `Equivalent` all outputs are the same for all inputs.
+/// - `Different` values of inputs(in the order of inputs of the first circuit) and the names of outputs that differ for them.
+/// - `Indeterminate` a solver stopped searching.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Equivalence {
+    Equivalent,
+    Different {
+        inputs: Vec<(String, bool)>,
+        outputs: Vec<String>,
+    },
+    Indeterminate,
+}
+
+/// Check whether two combinational circuits are equivalent by a miter.
+/// Inputs and outputs are matched by their names.
+/// Outputs are checked one by one by an incremental solver if `per_output` is true, otherwise all at once.
+/// Proved outputs help the following checks.
+/// # Arguments
+/// * `a` `b` - Circuits that have the same names of inputs and outputs and no flip-flops.
+/// * `per_output` - Check outputs one by one.
+/// * `time_limit` - The time limitation for each check.
+pub fn check_equivalence(
+    a: &BenchData,
+    b: &BenchData,
+    per_output: bool,
+    time_limit: Option<Duration>,
+) -> std::io::Result<Equivalence> {
+    let invalid = |msg: String| std::io::Error::new(std::io::ErrorKind::InvalidInput, msg);
+    if !a.latches.is_empty() || !b.latches.is_empty() {
+        return Err(invalid("Flip-flops are not supported".to_string()));
+    }
+    let names = |signals: &[(String, Var)]| {
+        let mut names: Vec<String> = signals.iter().map(|(name, _)| name.clone()).collect();
+        names.sort();
+        names
+    };
+    if names(&a.inputs) != names(&b.inputs) {
+        return Err(invalid("Inputs are different".to_string()));
+    }
+    if names(&a.outputs) != names(&b.outputs) {
+        return Err(invalid("Outputs are different".to_string()));
+    }
+
+    // Variables of `b` follow variables of `a`.
+    let offset = a.var_num as u32;
+    let lit_a = |v: Var| Lit::new(v.0, true);
+    let lit_b = |v: Var| Lit::new(v.0 + offset, true);
+    let mut solver = Solver::new(a.var_num + b.var_num, &a.clauses);
+    for clause in b.clauses.iter() {
+        let clause: Clause = clause
+            .iter()
+            .map(|&lit| Lit::new(lit.var().0 + offset, lit.pos()))
+            .collect();
+        solver.add_clause(&clause);
+    }
+    for (name, v) in a.inputs.iter() {
+        let (x, y) = (lit_a(*v), lit_b(b.names[name]));
+        solver.add_clause(&[!x, y]);
+        solver.add_clause(&[x, !y]);
+    }
+    // `d <-> x ^ y` for each pair of outputs
+    let mut diffs = vec![];
+    for (name, v) in a.outputs.iter() {
+        let (x, y) = (lit_a(*v), lit_b(b.names[name]));
+        let d = Lit::new(solver.assigns.len() as u32, true);
+        solver.new_var();
+        solver.add_clause(&[!d, x, y]);
+        solver.add_clause(&[!d, !x, !y]);
+        solver.add_clause(&[d, !x, y]);
+        solver.add_clause(&[d, x, !y]);
+        diffs.push((name.clone(), d));
+    }
+
+    let checks: Vec<Vec<Lit>> = if per_output {
+        diffs.iter().map(|&(_, d)| vec![d]).collect()
+    } else {
+        vec![diffs.iter().map(|&(_, d)| d).collect()]
+    };
+    for check in checks.iter() {
+        // A check needs one of outputs to differ.
+        let s = Lit::new(solver.assigns.len() as u32, true);
+        solver.new_var();
+        let mut clause = check.clone();
+        clause.push(!s);
+        solver.add_clause(&clause);
+        match solver.solve_with_assumptions(&[s], time_limit) {
+            Status::Sat => {
+                let value = |lit: Lit| solver.assigns[lit.var().0 as usize] == LitBool::True;
+                return Ok(Equivalence::Different {
+                    inputs: a
+                        .inputs
+                        .iter()
+                        .map(|(name, v)| (name.clone(), value(lit_a(*v))))
+                        .collect(),
+                    outputs: diffs
+                        .iter()
+                        .filter(|&&(_, d)| value(d))
+                        .map(|(name, _)| name.clone())
+                        .collect(),
+                });
+            }
+            Status::Unsat => {
+                // Outputs of a check are proved to be equal.
+                check.iter().for_each(|&d| solver.add_clause(&[!d]));
+            }
+            Status::Indeterminate => return Ok(Equivalence::Indeterminate),
+        }
+    }
+    Ok(Equivalence::Equivalent)
+}
@@ -0,0 +1,152 @@
+use crate::solver::{Lit, Solver};
+
+fn fresh_lit(solver: &mut Solver) -> Lit {
+    Lit::new(solver.new_var().0, true)
+}
+
+/// Grow a solver so that fresh variables don't overlap with `lits`.
+fn reserve(solver: &mut Solver, lits: &[Lit]) {
+    lits.iter().for_each(|lit| {
+        while lit.var().0 as usize >= solver.assigns.len() {
+            solver.new_var();
+        }
+    });
+}
+
+/// At most one of `lits` is true.
+/// A few literals are encoded pairwise. Others are encoded by a sequential counter.
+pub fn at_most_one(solver: &mut Solver, lits: &[Lit]) {
+    if lits.len() <= 5 {
+        for i in 0..lits.len() {
+            for j in i + 1..lits.len() {
+                solver.add_clause(&[!lits[i], !lits[j]]);
+            }
+        }
+    } else {
+        at_most_k(solver, lits, 1);
+    }
+}
+
+/// Exactly one of `lits` is true.
+pub fn exactly_one(solver: &mut Solver, lits: &[Lit]) {
+    solver.add_clause(lits);
+    at_most_one(solver, lits);
+}
+
+/// At most `k` of `lits` are true by the sequential counter encoding(Sinz 2005).
+/// `s[i][j]` means that at least `j + 1` of `lits[..=i]` are true.
+/// It has `O(n * k)` clauses. `Solver::add_at_most` is a native constraint that stays as small as `lits` for large `k`.
+pub fn at_most_k(solver: &mut Solver, lits: &[Lit], k: usize) {
+    if k >= lits.len() {
+        return;
+    }
+    if k == 0 {
+        lits.iter().for_each(|&lit| solver.add_clause(&[!lit]));
+        return;
+    }
+    reserve(solver, lits);
+    let n = lits.len();
+    let s: Vec<Vec<Lit>> = (0..n - 1)
+        .map(|_| (0..k).map(|_| fresh_lit(solver)).collect())
+        .collect();
+    solver.add_clause(&[!lits[0], s[0][0]]);
+    s[0][1..].iter().for_each(|&lit| solver.add_clause(&[!lit]));
+    for i in 1..n - 1 {
+        solver.add_clause(&[!lits[i], s[i][0]]);
+        solver.add_clause(&[!s[i - 1][0], s[i][0]]);
+        for j in 1..k {
+            solver.add_clause(&[!lits[i], !s[i - 1][j - 1], s[i][j]]);
+            solver.add_clause(&[!s[i - 1][j], s[i][j]]);
+        }
+        // k literals of `lits[..i]` are already true.
+        solver.add_clause(&[!lits[i], !s[i - 1][k - 1]]);
+    }
+    solver.add_clause(&[!lits[n - 1], !s[n - 2][k - 1]]);
+}
+
+/// At least `k` of `lits` are true by the sequential counter encoding of at most `n - k` of their negations.
+pub fn at_least_k(solver: &mut Solver, lits: &[Lit], k: usize) {
+    if k > lits.len() {
+        solver.add_clause(&[]);
+        return;
+    }
+    let negations: Vec<Lit> = lits.iter().map(|&lit| !lit).collect();
+    at_most_k(solver, &negations, lits.len() - k);
+}
+
+/// Merge unary counts `a` and `b` into a count that is at most `cap`.
+fn merge(solver: &mut Solver, a: &[Lit], b: &[Lit], cap: usize) -> Vec<Lit> {
+    let len = std::cmp::min(a.len() + b.len(), cap);
+    let r: Vec<Lit> = (0..len).map(|_| fresh_lit(solver)).collect();
+    for i in 0..=a.len() {
+        for j in 0..=b.len() {
+            // a >= i & b >= j -> r >= i + j
+            let m = std::cmp::min(i + j, len);
+            if m > 0 {
+                let mut clause = vec![r[m - 1]];
+                clause.extend(i.checked_sub(1).map(|i| !a[i]));
+                clause.extend(j.checked_sub(1).map(|j| !b[j]));
+                solver.add_clause(&clause);
+            }
+            // r > i + j -> a > i | b > j
+            if i + j < len {
+                let mut clause = vec![!r[i + j]];
+                clause.extend(a.get(i).copied());
+                clause.extend(b.get(j).copied());
+                solver.add_clause(&clause);
+            }
+        }
+    }
+    r
+}
+
+/// Count true literals of `lits` by a totalizer(Bailleux and Boufkhad 2003).
+/// `outputs[j]` is true iff at least `j + 1` of `lits` are true. Counts that are greater than `cap` are merged into `cap`,
+/// so it returns `min(n, cap)` outputs. It has `O(n * cap)` clauses and outputs can be constrained again later.
+pub fn totalizer(solver: &mut Solver, lits: &[Lit], cap: usize) -> Vec<Lit> {
+    reserve(solver, lits);
+    if lits.len() <= 1 || cap == 0 {
+        return lits.iter().copied().take(cap).collect();
+    }
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let a = totalizer(solver, left, cap);
+    let b = totalizer(solver, right, cap);
+    merge(solver, &a, &b, cap)
+}
+
+impl Solver {
+    /// Add an at-most-k constraint `lits[0] + lits[1] + ... <= k` by clauses of a totalizer.
+    /// Use `Solver::add_at_most` for a native constraint.
+    pub fn add_at_most_k(&mut self, lits: &[Lit], k: usize) {
+        let outputs = totalizer(self, lits, k + 1);
+        if let Some(&lit) = outputs.get(k) {
+            self.add_clause(&[!lit]);
+        }
+    }
+
+    /// Add an at-least-k constraint `lits[0] + lits[1] + ... >= k` by clauses of a totalizer.
+    pub fn add_at_least_k(&mut self, lits: &[Lit], k: usize) {
+        self.add_between(lits, k, None);
+    }
+
+    /// Add an exactly-k constraint `lits[0] + lits[1] + ... = k` by clauses of one totalizer.
+    pub fn add_exactly_k(&mut self, lits: &[Lit], k: usize) {
+        self.add_between(lits, k, Some(k));
+    }
+
+    /// `lower <= lits[0] + lits[1] + ... <= upper`
+    fn add_between(&mut self, lits: &[Lit], lower: usize, upper: Option<usize>) {
+        if lower > lits.len() {
+            self.add_clause(&[]);
+            return;
+        }
+        let cap = upper.map_or(lower, |upper| upper + 1);
+        let outputs = totalizer(self, lits, cap);
+        if let Some(lower) = lower.checked_sub(1) {
+            self.add_clause(&[outputs[lower]]);
+        }
+        if let Some(&lit) = upper.and_then(|upper| outputs.get(upper)) {
+            self.add_clause(&[!lit]);
+        }
+    }
+}
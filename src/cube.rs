@@ -0,0 +1,291 @@
+use crate::solver::{Lit, LitBool, Solver, Status};
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::channel,
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Parameters of `Solver::solve_cube_and_conquer`.
+#[derive(Debug, Clone)]
+pub struct CubeConfig {
+    // the maximum number of split decisions in a cube
+    pub depth: usize,
+    // a cube isn't split any more once it assigns this fraction of free variables
+    pub cutoff: f64,
+    // the number of the most frequent free variables that lookahead tries for a split
+    pub candidates: usize,
+    // the number of threads that solve cubes. `1` solves them by the solver itself.
+    pub threads: usize,
+    // the time limitation of the whole search. Exceeding it returns `Indeterminate`.
+    pub time_limit: Option<Duration>,
+}
+
+impl Default for CubeConfig {
+    fn default() -> Self {
+        CubeConfig {
+            depth: 8,
+            cutoff: 0.5,
+            candidates: 50,
+            threads: 1,
+            time_limit: None,
+        }
+    }
+}
+
+enum Split {
+    // propagation of a cube fails
+    Refuted,
+    // a cube is small enough to be solved
+    Leaf,
+    // a cube is split on the variable of a literal
+    Branch(Lit),
+}
+
+enum Message {
+    Sat(Vec<LitBool>),
+    Unsat(Vec<Lit>),
+    Indeterminate,
+}
+
+fn negation(lits: &[Lit]) -> Vec<Lit> {
+    lits.iter().map(|&lit| !lit).collect()
+}
+
+/// The number of new assignments by `lit` under `cube` or `None` if propagation fails.
+fn gain(solver: &mut Solver, cube: &[Lit], lit: Lit, base: usize) -> Option<usize> {
+    let mut lits = cube.to_vec();
+    lits.push(lit);
+    solver
+        .propagate_lits(&lits)
+        .map(|assigned| assigned.len() - base)
+}
+
+/// Look ahead the `candidates` most frequent free variables of `order` under `cube`.
+/// The best split has the largest product of the numbers of assignments of both literals.
+/// A literal that fails is refuted, so its negation is added to `cube` and all candidates are tried again.
+fn lookahead(
+    solver: &mut Solver,
+    order: &[usize],
+    config: &CubeConfig,
+    cube: &mut Vec<Lit>,
+    depth: usize,
+) -> Split {
+    loop {
+        let assigned = match solver.propagate_lits(cube) {
+            Some(assigned) => assigned,
+            None => return Split::Refuted,
+        };
+        if depth >= config.depth || assigned.len() as f64 >= config.cutoff * order.len() as f64 {
+            return Split::Leaf;
+        }
+        let mut in_cube = vec![false; solver.assigns.len()];
+        assigned
+            .iter()
+            .for_each(|lit| in_cube[lit.var().0 as usize] = true);
+        let candidates: Vec<usize> = order
+            .iter()
+            .copied()
+            .filter(|&v| solver.assigns[v] == LitBool::Undef && !in_cube[v])
+            .take(config.candidates)
+            .collect();
+        let mut best: Option<(usize, Lit)> = None;
+        let mut forced = None;
+        for v in candidates {
+            let p = Lit::new(v as u32, true);
+            match (
+                gain(solver, cube, p, assigned.len()),
+                gain(solver, cube, !p, assigned.len()),
+            ) {
+                (None, None) => return Split::Refuted,
+                (None, Some(_)) => forced = Some(!p),
+                (Some(_), None) => forced = Some(p),
+                (Some(a), Some(b)) => {
+                    let score = (a + 1) * (b + 1);
+                    if best.is_none_or(|(s, _)| score > s) {
+                        best = Some((score, p));
+                    }
+                }
+            }
+            if forced.is_some() {
+                break;
+            }
+        }
+        match (forced, best) {
+            (Some(lit), _) => cube.push(lit),
+            (None, Some((_, p))) => return Split::Branch(p),
+            (None, None) => return Split::Leaf,
+        }
+    }
+}
+
+/// Split the search space into cubes by lookahead. It returns cubes and the cubes that are refuted by propagation.
+/// Both of them cover all assignments that aren't refuted by failed literals.
+fn cubes(solver: &mut Solver, config: &CubeConfig) -> (Vec<Vec<Lit>>, Vec<Vec<Lit>>) {
+    let occurrences = solver.occurrences();
+    let mut order: Vec<usize> = (0..occurrences.len())
+        .filter(|&v| occurrences[v] > 0 && solver.assigns[v] == LitBool::Undef)
+        .collect();
+    order.sort_by_key(|&v| std::cmp::Reverse(occurrences[v]));
+
+    let mut cubes = vec![];
+    let mut refuted = vec![];
+    let mut stack = vec![(vec![], 0)];
+    while let Some((mut cube, depth)) = stack.pop() {
+        match lookahead(solver, &order, config, &mut cube, depth) {
+            Split::Refuted => refuted.push(cube),
+            Split::Leaf => cubes.push(cube),
+            Split::Branch(p) => {
+                let mut other = cube.clone();
+                other.push(!p);
+                cube.push(p);
+                stack.push((other, depth + 1));
+                stack.push((cube, depth + 1));
+            }
+        }
+    }
+    (cubes, refuted)
+}
+
+/// Solve cubes one by one. A refuted cube is blocked by the negation of its failed assumptions for later cubes.
+/// `None` means that all cubes are refuted.
+fn conquer(
+    solver: &mut Solver,
+    cubes: &[Vec<Lit>],
+    remaining: &dyn Fn() -> Option<Duration>,
+) -> Option<Status> {
+    for cube in cubes.iter() {
+        match solver.solve_with_assumptions(cube, remaining()) {
+            Status::Unsat => {
+                if solver.unsat_core().is_empty() {
+                    return Some(Status::Unsat);
+                }
+                if !solver.logs_proof() {
+                    let clause = negation(solver.unsat_core());
+                    solver.add_clause(&clause);
+                }
+            }
+            status => return Some(status),
+        }
+    }
+    None
+}
+
+/// Solve cubes by `threads` copies of a solver that are loaded from its snapshot.
+/// A model of a thread is checked by the solver itself, so the solver has the model after it returns `Sat`.
+/// A solver that has native constraints can't be copied, so it solves cubes by itself.
+fn conquer_parallel(
+    solver: &mut Solver,
+    cubes: Vec<Vec<Lit>>,
+    threads: usize,
+    remaining: &dyn Fn() -> Option<Duration>,
+) -> Option<Status> {
+    let mut snapshot = vec![];
+    if solver.write_snapshot(&mut snapshot, true).is_err() {
+        return conquer(solver, &cubes, remaining);
+    }
+    let snapshot = Arc::new(snapshot);
+    let cubes = Arc::new(cubes);
+    let next_cube = Arc::new(AtomicUsize::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = channel();
+    for _ in 0..std::cmp::min(threads, cubes.len()) {
+        let snapshot = snapshot.clone();
+        let cubes = cubes.clone();
+        let next_cube = next_cube.clone();
+        let stop = stop.clone();
+        let sender = sender.clone();
+        let config = solver.config().clone();
+        let time_limit = remaining();
+        // A thread finishes its cube in the background even if another thread has found the result.
+        thread::spawn(move || {
+            let start = Instant::now();
+            let mut worker =
+                Solver::read_snapshot(&snapshot[..]).expect("a snapshot is written by a solver");
+            worker.set_config(config);
+            while !stop.load(Ordering::SeqCst) {
+                let cube = next_cube.fetch_add(1, Ordering::SeqCst);
+                if cube >= cubes.len() {
+                    break;
+                }
+                let limit = time_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+                let message = match worker.solve_with_assumptions(&cubes[cube], limit) {
+                    Status::Sat => Message::Sat(worker.assigns.clone()),
+                    Status::Unsat => Message::Unsat(worker.unsat_core().to_vec()),
+                    Status::Indeterminate => Message::Indeterminate,
+                };
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    let occurrences = solver.occurrences();
+    // The receiver ends when all threads end.
+    for message in receiver {
+        match message {
+            Message::Unsat(failed) => {
+                solver.add_clause(&negation(&failed));
+                if failed.is_empty() {
+                    stop.store(true, Ordering::SeqCst);
+                    return Some(Status::Unsat);
+                }
+            }
+            Message::Sat(model) => {
+                stop.store(true, Ordering::SeqCst);
+                // Eliminated variables don't occur in clauses. They are reconstructed by the solver itself.
+                let assumptions: Vec<Lit> = (0..occurrences.len())
+                    .filter(|&v| occurrences[v] > 0)
+                    .map(|v| Lit::new(v as u32, model[v] == LitBool::True))
+                    .collect();
+                return Some(solver.solve_with_assumptions(&assumptions, remaining()));
+            }
+            Message::Indeterminate => {
+                stop.store(true, Ordering::SeqCst);
+                return Some(Status::Indeterminate);
+            }
+        }
+    }
+    None
+}
+
+impl Solver {
+    /// Solve a problem by cube-and-conquer and return a enum `Status`.
+    /// The search space is split into cubes(partial assignments) by lookahead on the most frequent variables,
+    /// then each cube is solved under assumptions by CDCL, optionally by multiple threads.
+    /// The clauses that block refuted cubes are solved again at last, so the result is the same as `solve`.
+    /// A solver that writes a proof solves cubes by itself and doesn't add the clauses.
+    /// # Arguments
+    /// * `config` - Parameters of splitting and conquering
+    pub fn solve_cube_and_conquer(&mut self, config: &CubeConfig) -> Status {
+        let start = Instant::now();
+        let remaining = || {
+            config
+                .time_limit
+                .map(|limit| limit.saturating_sub(start.elapsed()))
+        };
+        let (cubes, refuted) = cubes(self, config);
+        let status = if self.logs_proof() {
+            conquer(self, &cubes, &remaining)
+        } else {
+            // A refuted cube is blocked by unit propagation, so its negation can be learnt.
+            refuted
+                .iter()
+                .for_each(|cube| self.add_clause(&negation(cube)));
+            if config.threads > 1 {
+                conquer_parallel(self, cubes, config.threads, &remaining)
+            } else {
+                conquer(self, &cubes, &remaining)
+            }
+        };
+        match status {
+            Some(status) => status,
+            None => self.solve(remaining()),
+        }
+    }
+}
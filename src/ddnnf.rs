@@ -0,0 +1,436 @@
+use crate::solver::{Clause, Lit, LitBool, Var};
+use std::{
+    collections::HashMap,
+    ops::{Add, Mul},
+};
+
+/// A node of a d-DNNF.
+/// - `And` children have disjoint variables(decomposable).
+/// - `Or` children have no common models(deterministic).
+///
+/// Children are always before their parent in `DDnnf::nodes`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Node {
+    True,
+    False,
+    Lit(Lit),
+    And(Vec<usize>),
+    Or(Vec<usize>),
+}
+
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+
+/// A formula that is compiled into d-DNNF(deterministic decomposable negation normal form).
+/// Model counting, conditioning and enumeration are linear in the size of a compiled formula.
+#[derive(Debug, Clone)]
+pub struct DDnnf {
+    nodes: Vec<Node>,
+    // sorted variables of each node
+    vars: Vec<Vec<u32>>,
+    root: usize,
+    var_num: usize,
+    // values of variables that are fixed by `condition`
+    fixed: Vec<Option<bool>>,
+}
+
+/// A top-down compiler. It branches on a variable, propagates units and splits a formula into components.
+/// Components that are compiled once are cached.
+struct Compiler {
+    nodes: Vec<Node>,
+    vars: Vec<Vec<u32>>,
+    cache: HashMap<Vec<Clause>, usize>,
+}
+
+fn union(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut v: Vec<u32> = a.iter().chain(b.iter()).copied().collect();
+    v.sort_unstable();
+    v.dedup();
+    v
+}
+
+/// Assign `lit` to clauses. `None` is a conflict.
+fn assign(clauses: &[Clause], lit: Lit) -> Option<Vec<Clause>> {
+    let mut result = Vec::with_capacity(clauses.len());
+    for clause in clauses.iter() {
+        if clause.contains(&lit) {
+            continue;
+        }
+        let clause: Clause = clause.iter().filter(|&&l| l != !lit).copied().collect();
+        if clause.is_empty() {
+            return None;
+        }
+        result.push(clause);
+    }
+    Some(result)
+}
+
+impl Compiler {
+    fn add(&mut self, node: Node) -> usize {
+        let vars = match &node {
+            Node::True | Node::False => vec![],
+            Node::Lit(lit) => vec![lit.var().0],
+            Node::And(children) | Node::Or(children) => children
+                .iter()
+                .fold(vec![], |vars, &c| union(&vars, &self.vars[c])),
+        };
+        self.nodes.push(node);
+        self.vars.push(vars);
+        self.nodes.len() - 1
+    }
+
+    fn and(&mut self, children: Vec<usize>) -> usize {
+        if children.contains(&FALSE) {
+            return FALSE;
+        }
+        let children: Vec<usize> = children.into_iter().filter(|&c| c != TRUE).collect();
+        match children.len() {
+            0 => TRUE,
+            1 => children[0],
+            _ => self.add(Node::And(children)),
+        }
+    }
+
+    fn compile(&mut self, mut clauses: Vec<Clause>) -> usize {
+        // Unit propagation
+        let mut units = vec![];
+        while let Some(unit) = clauses.iter().find(|c| c.len() == 1).map(|c| c[0]) {
+            units.push(unit);
+            clauses = match assign(&clauses, unit) {
+                Some(clauses) => clauses,
+                None => return FALSE,
+            };
+        }
+        let mut children: Vec<usize> = units
+            .into_iter()
+            .map(|lit| self.add(Node::Lit(lit)))
+            .collect();
+        for component in components(clauses) {
+            let node = self.compile_component(component);
+            if node == FALSE {
+                return FALSE;
+            }
+            children.push(node);
+        }
+        self.and(children)
+    }
+
+    fn compile_component(&mut self, mut clauses: Vec<Clause>) -> usize {
+        clauses.iter_mut().for_each(|clause| clause.sort());
+        clauses.sort();
+        if let Some(&node) = self.cache.get(&clauses) {
+            return node;
+        }
+        // Branch on the most frequent variable.
+        let mut occurrences: HashMap<Var, usize> = HashMap::new();
+        clauses
+            .iter()
+            .flatten()
+            .for_each(|lit| *occurrences.entry(lit.var()).or_insert(0) += 1);
+        let v = occurrences
+            .into_iter()
+            .max_by_key(|&(v, n)| (n, std::cmp::Reverse(v)))
+            .unwrap()
+            .0;
+
+        let mut children = vec![];
+        for &positive in [true, false].iter() {
+            let lit = Lit::new(v.0, positive);
+            let branch = match assign(&clauses, lit) {
+                Some(rest) => self.compile(rest),
+                None => FALSE,
+            };
+            if branch != FALSE {
+                let lit = self.add(Node::Lit(lit));
+                children.push(self.and(vec![lit, branch]));
+            }
+        }
+        let node = match children.len() {
+            0 => FALSE,
+            1 => children[0],
+            _ => self.add(Node::Or(children)),
+        };
+        self.cache.insert(clauses, node);
+        node
+    }
+}
+
+/// Split clauses into groups that share no variables.
+fn components(clauses: Vec<Clause>) -> Vec<Vec<Clause>> {
+    // union-find over variables
+    fn find(parent: &mut HashMap<Var, Var>, v: Var) -> Var {
+        let p = *parent.entry(v).or_insert(v);
+        if p == v {
+            return v;
+        }
+        let root = find(parent, p);
+        parent.insert(v, root);
+        root
+    }
+    let mut parent = HashMap::new();
+    for clause in clauses.iter() {
+        let first = find(&mut parent, clause[0].var());
+        for lit in clause.iter().skip(1) {
+            let root = find(&mut parent, lit.var());
+            parent.insert(root, first);
+        }
+    }
+    let mut groups: HashMap<Var, Vec<Clause>> = HashMap::new();
+    for clause in clauses {
+        let root = find(&mut parent, clause[0].var());
+        groups.entry(root).or_default().push(clause);
+    }
+    let mut groups: Vec<Vec<Clause>> = groups.into_values().collect();
+    // Make compilation deterministic.
+    groups.sort();
+    groups
+}
+
+impl DDnnf {
+    /// Compile a CNF formula into d-DNNF.
+    /// # Arguments
+    /// * `var_num` - The number of variable. Models have `var_num` assignments at least.
+    /// * `clauses` - All clauses of a problem
+    pub fn compile(var_num: usize, clauses: &[Clause]) -> DDnnf {
+        let var_num = clauses
+            .iter()
+            .flatten()
+            .fold(var_num, |n, lit| std::cmp::max(n, lit.var().0 as usize + 1));
+        let mut compiler = Compiler {
+            nodes: vec![],
+            vars: vec![],
+            cache: HashMap::new(),
+        };
+        compiler.add(Node::False);
+        compiler.add(Node::True);
+
+        let mut simplified = vec![];
+        let mut root = None;
+        for clause in clauses.iter() {
+            let mut clause = clause.clone();
+            clause.sort();
+            clause.dedup();
+            // A tautology is always satisfied.
+            if clause.windows(2).any(|w| w[0] == !w[1]) {
+                continue;
+            }
+            if clause.is_empty() {
+                root = Some(FALSE);
+            }
+            simplified.push(clause);
+        }
+        let root = root.unwrap_or_else(|| compiler.compile(simplified));
+        DDnnf {
+            nodes: compiler.nodes,
+            vars: compiler.vars,
+            root,
+            var_num,
+            fixed: vec![None; var_num],
+        }
+    }
+
+    /// All nodes of a formula.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+    /// The root node of a formula.
+    pub fn root(&self) -> usize {
+        self.root
+    }
+
+    /// Assign weights to literals and evaluate all nodes.
+    /// The value of a node is the sum of weights of its models over its variables.
+    fn evaluate<T, F>(&self, weight: F) -> Vec<T>
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + From<u8>,
+        F: Fn(Lit) -> T,
+    {
+        let mut values: Vec<T> = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let value = match node {
+                Node::False => T::from(0),
+                Node::True => T::from(1),
+                Node::Lit(lit) => weight(*lit),
+                Node::And(children) => children.iter().fold(T::from(1), |acc, &c| acc * values[c]),
+                Node::Or(children) => children.iter().fold(T::from(0), |acc, &c| {
+                    acc + values[c] * self.free(&self.vars[i], &self.vars[c], &weight)
+                }),
+            };
+            values.push(value);
+        }
+        values
+    }
+
+    /// The product of weights of variables in `all` but not in `part` that can be any value.
+    fn free<T, F>(&self, all: &[u32], part: &[u32], weight: &F) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + From<u8>,
+        F: Fn(Lit) -> T,
+    {
+        all.iter()
+            .filter(|v| part.binary_search(v).is_err())
+            .fold(T::from(1), |acc, &v| {
+                acc * (weight(Lit::new(v, true)) + weight(Lit::new(v, false)))
+            })
+    }
+
+    /// The weight of a literal that is 0 if it contradicts a fixed value
+    fn fixed_weight(&self, lit: Lit) -> u128 {
+        match self.fixed[lit.var().0 as usize] {
+            Some(value) if value != lit.pos() => 0,
+            _ => 1,
+        }
+    }
+
+    /// The sum of weights of all models of a formula.
+    /// The weight of a model is the product of weights of its literals.
+    fn weighted<T, F>(&self, weight: F) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + From<u8>,
+        F: Fn(Lit) -> T,
+    {
+        let values = self.evaluate(&weight);
+        let all: Vec<u32> = (0..self.var_num as u32).collect();
+        values[self.root] * self.free(&all, &self.vars[self.root], &weight)
+    }
+
+    /// The number of models.
+    /// Variables that are fixed by `condition` are not counted.
+    /// The number must fit in `u128`.
+    pub fn count(&self) -> u128 {
+        self.weighted(|lit| self.fixed_weight(lit))
+    }
+
+    /// The weighted model count. The weight of a model is the product of weights of its literals.
+    /// Both literals of a variable need weights. A variable that isn't in a formula contributes `w(x) + w(!x)`.
+    /// Literals that contradict values fixed by `condition` weigh 0.
+    /// `T` is for example `f64` for probabilities or `u128` for exact integer weights.
+    pub fn weighted_count<T, F>(&self, weight: F) -> T
+    where
+        T: Copy + Add<Output = T> + Mul<Output = T> + From<u8>,
+        F: Fn(Lit) -> T,
+    {
+        self.weighted(|lit| match self.fixed[lit.var().0 as usize] {
+            Some(value) if value != lit.pos() => T::from(0),
+            _ => weight(lit),
+        })
+    }
+
+    /// A formula whose literals in `lits` are fixed to true.
+    /// Its models are the models of the original formula that have all literals in `lits`.
+    pub fn condition(&self, lits: &[Lit]) -> DDnnf {
+        let mut ddnnf = self.clone();
+        for &lit in lits.iter() {
+            let v = lit.var().0 as usize;
+            if v >= ddnnf.var_num {
+                ddnnf.var_num = v + 1;
+                ddnnf.fixed.resize(v + 1, None);
+            }
+            if ddnnf.fixed[v] == Some(!lit.pos()) {
+                // Contradicted literals have no models.
+                ddnnf.root = FALSE;
+            }
+            ddnnf.fixed[v] = Some(lit.pos());
+        }
+        ddnnf
+    }
+
+    /// An iterator over all models.
+    pub fn models(&self) -> DDnnfModels<'_> {
+        let counts = self.evaluate(|lit| self.fixed_weight(lit));
+        let all: Vec<u32> = (0..self.var_num as u32).collect();
+        let total = counts[self.root]
+            * self.free(&all, &self.vars[self.root], &|lit| self.fixed_weight(lit));
+        DDnnfModels {
+            ddnnf: self,
+            counts,
+            next: 0,
+            total,
+        }
+    }
+}
+
+/// An iterator over models of a d-DNNF. It is created by `DDnnf::models`.
+/// The k-th model is decoded from k by model counts, so models are never stored.
+pub struct DDnnfModels<'a> {
+    ddnnf: &'a DDnnf,
+    counts: Vec<u128>,
+    next: u128,
+    total: u128,
+}
+
+impl DDnnfModels<'_> {
+    /// Assign variables in `all` but not in `part` by `k`.
+    fn assign_free(&self, all: &[u32], part: &[u32], mut k: u128, model: &mut [LitBool]) {
+        for &v in all.iter().filter(|v| part.binary_search(v).is_err()) {
+            let value = match self.ddnnf.fixed[v as usize] {
+                Some(value) => value,
+                None => {
+                    let value = k & 1 == 1;
+                    k >>= 1;
+                    value
+                }
+            };
+            model[v as usize] = if value { LitBool::True } else { LitBool::False };
+        }
+    }
+
+    /// Decode the k-th model of a node.
+    fn decode(&self, node: usize, mut k: u128, model: &mut [LitBool]) {
+        let ddnnf = self.ddnnf;
+        match &ddnnf.nodes[node] {
+            Node::True | Node::False => {}
+            Node::Lit(lit) => {
+                model[lit.var().0 as usize] = if lit.pos() {
+                    LitBool::True
+                } else {
+                    LitBool::False
+                };
+            }
+            Node::And(children) => {
+                for &c in children.iter() {
+                    self.decode(c, k % self.counts[c], model);
+                    k /= self.counts[c];
+                }
+            }
+            Node::Or(children) => {
+                for &c in children.iter() {
+                    let free: u128 = ddnnf.free(&ddnnf.vars[node], &ddnnf.vars[c], &|lit| {
+                        ddnnf.fixed_weight(lit)
+                    });
+                    let total = self.counts[c] * free;
+                    if k < total {
+                        self.decode(c, k % self.counts[c], model);
+                        self.assign_free(
+                            &ddnnf.vars[node],
+                            &ddnnf.vars[c],
+                            k / self.counts[c],
+                            model,
+                        );
+                        return;
+                    }
+                    k -= total;
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for DDnnfModels<'_> {
+    type Item = Vec<LitBool>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.total {
+            return None;
+        }
+        let ddnnf = self.ddnnf;
+        let k = self.next;
+        self.next += 1;
+        let mut model = vec![LitBool::Undef; ddnnf.var_num];
+        let root = ddnnf.root;
+        let count = self.counts[root];
+        self.decode(root, k % count, &mut model);
+        let all: Vec<u32> = (0..ddnnf.var_num as u32).collect();
+        self.assign_free(&all, &ddnnf.vars[root], k / count, &mut model);
+        Some(model)
+    }
+}
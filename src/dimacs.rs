@@ -0,0 +1,27 @@
+use crate::util::{self, CnfData};
+use std::io::{Read, Write};
+
+pub use crate::util::ParseError;
+
+/// A parsed DIMACS CNF file. It is `util::CnfData`, so both APIs share the same parser and writer.
+pub type CnfFormula = CnfData;
+
+/// Parse a DIMACS CNF file like `util::parse_cnf`, but errors tell what is wrong and where.
+///
+/// ```text
+/// c Here is a comment.
+/// p cnf 3 2
+/// 1 -3 0
+/// -1 2
+///    3 0
+/// ```
+pub fn parse_cnf<R: Read>(input: R) -> Result<CnfFormula, ParseError> {
+    util::parse_cnf_data(input)
+}
+
+impl CnfData {
+    /// Write a formula in DIMACS CNF like `util::write_cnf`.
+    pub fn write_dimacs<W: Write>(&self, writer: W) -> std::io::Result<()> {
+        util::write_cnf(self, writer)
+    }
+}
@@ -0,0 +1,131 @@
+use crate::solver::{Clause, Lit, LitBool, Solver, Status, Var};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{sync_channel, Receiver},
+        Arc,
+    },
+    thread,
+};
+
+/// An iterator over all models of a problem.
+/// Each model is blocked by a new clause after it is found.
+/// So a model never appears twice.
+pub struct Models {
+    solver: Solver,
+    done: bool,
+}
+
+impl Models {
+    /// Create a new `Models` iterator
+    /// # Arguments
+    /// * `solver` - A solver that already has all clauses of a problem
+    pub fn new(solver: Solver) -> Models {
+        Models {
+            solver,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Models {
+    type Item = Vec<LitBool>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.solver.solve(None) != Status::Sat {
+            self.done = true;
+            return None;
+        }
+        let model = self.solver.assigns.clone();
+        // Block a found model.
+        let block: Clause = model
+            .iter()
+            .enumerate()
+            .map(|(v, &b)| Lit::new(v as u32, b != LitBool::True))
+            .collect();
+        self.solver.add_clause(&block);
+        Some(model)
+    }
+}
+
+/// An iterator over models that are found by multiple threads.
+/// It is created by the function `parallel`.
+pub struct ParallelModels {
+    receiver: Receiver<Vec<LitBool>>,
+}
+
+impl Iterator for ParallelModels {
+    type Item = Vec<LitBool>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+/// Enumerate all models of a problem by multiple threads.
+/// The search space is partitioned into cubes by fixing the most frequent variables.
+/// Cubes are disjoint, so no model is found by two threads.
+/// # Arguments
+/// * `var_num` - The number of variable. Models have at least `var_num` assignments.
+/// * `clauses` - All clauses of a problem
+/// * `threads` - The number of thread
+pub fn parallel(var_num: usize, clauses: &[Clause], threads: usize) -> ParallelModels {
+    let threads = std::cmp::max(threads, 1);
+    let var_num = clauses
+        .iter()
+        .flat_map(|clause| clause.iter())
+        .fold(var_num, |n, lit| std::cmp::max(n, lit.var().0 as usize + 1));
+
+    // Split variables are the most frequent variables.
+    // The number of cubes is larger than the number of threads to balance loads.
+    let mut occurrences = vec![0usize; var_num];
+    clauses
+        .iter()
+        .flat_map(|clause| clause.iter())
+        .for_each(|lit| occurrences[lit.var().0 as usize] += 1);
+    let mut order: Vec<usize> = (0..var_num).collect();
+    order.sort_by_key(|&v| std::cmp::Reverse(occurrences[v]));
+    let bits = std::cmp::min(
+        ((threads as f64).log2().ceil() as usize) + 2,
+        std::cmp::min(var_num, 16),
+    );
+    let split_vars: Arc<Vec<Var>> = Arc::new(
+        order
+            .into_iter()
+            .take(bits)
+            .map(|v| Var(v as u32))
+            .collect(),
+    );
+
+    let clauses: Arc<Vec<Clause>> = Arc::new(clauses.to_vec());
+    let next_cube = Arc::new(AtomicUsize::new(0));
+    let (sender, receiver) = sync_channel(1024);
+    for _ in 0..threads {
+        let split_vars = split_vars.clone();
+        let clauses = clauses.clone();
+        let next_cube = next_cube.clone();
+        let sender = sender.clone();
+        thread::spawn(move || loop {
+            let cube = next_cube.fetch_add(1, Ordering::SeqCst);
+            if cube >= 1 << split_vars.len() {
+                break;
+            }
+            let mut solver = Solver::default();
+            while solver.assigns.len() < var_num {
+                solver.new_var();
+            }
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            for (i, v) in split_vars.iter().enumerate() {
+                solver.add_clause(&[Lit::new(v.0, cube >> i & 1 == 1)]);
+            }
+            for model in Models::new(solver) {
+                // A receiver is dropped. Nobody needs more models.
+                if sender.send(model).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+    ParallelModels { receiver }
+}
@@ -0,0 +1,40 @@
+use crate::cardinality;
+use crate::solver::{Lit, LitBool, Solver};
+
+/// Encode an exact cover problem and return a literal of each row.
+/// Each primary column must be covered by exactly one selected row.
+/// Each secondary column may be covered by at most one selected row.
+/// # Arguments
+/// * `rows` - Columns that each row covers
+/// * `primary` - Columns `0..primary` are primary and others are secondary
+pub fn encode(solver: &mut Solver, rows: &[Vec<usize>], primary: usize) -> Vec<Lit> {
+    let lits: Vec<Lit> = rows
+        .iter()
+        .map(|_| Lit::new(solver.new_var().0, true))
+        .collect();
+    let columns = rows
+        .iter()
+        .flatten()
+        .fold(primary, |n, &c| std::cmp::max(n, c + 1));
+    let mut covers = vec![vec![]; columns];
+    for (row, &lit) in rows.iter().zip(lits.iter()) {
+        row.iter().for_each(|&c| covers[c].push(lit));
+    }
+    for (c, lits) in covers.iter().enumerate() {
+        if c < primary {
+            cardinality::exactly_one(solver, lits);
+        } else {
+            cardinality::at_most_one(solver, lits);
+        }
+    }
+    lits
+}
+
+/// Decode a model of the last `solve` into selected rows.
+pub fn decode(solver: &Solver, lits: &[Lit]) -> Vec<usize> {
+    lits.iter()
+        .enumerate()
+        .filter(|(_, lit)| solver.assigns[lit.var().0 as usize] == LitBool::True)
+        .map(|(row, _)| row)
+        .collect()
+}
@@ -0,0 +1,395 @@
+use crate::solver::{Lit, Solver, Var};
+use crate::util::CnfData;
+use std::collections::HashMap;
+
+/// A Boolean formula over literals of a solver.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Formula {
+    Const(bool),
+    Lit(Lit),
+    Not(Box<Formula>),
+    And(Vec<Formula>),
+    Or(Vec<Formula>),
+    Xor(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
+    Ite(Box<Formula>, Box<Formula>, Box<Formula>),
+}
+
+impl std::ops::Not for Formula {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        Formula::Not(Box::new(self))
+    }
+}
+
+impl Formula {
+    pub fn implies(a: Formula, b: Formula) -> Formula {
+        Formula::Or(vec![!a, b])
+    }
+    pub fn xor(a: Formula, b: Formula) -> Formula {
+        Formula::Xor(Box::new(a), Box::new(b))
+    }
+    pub fn iff(a: Formula, b: Formula) -> Formula {
+        Formula::Iff(Box::new(a), Box::new(b))
+    }
+    pub fn ite(c: Formula, t: Formula, e: Formula) -> Formula {
+        Formula::Ite(Box::new(c), Box::new(t), Box::new(e))
+    }
+
+    /// Call `f` for each literal of a formula.
+    fn for_each_lit<F: FnMut(Lit)>(&self, f: &mut F) {
+        match self {
+            Formula::Const(_) => {}
+            Formula::Lit(lit) => f(*lit),
+            Formula::Not(a) => a.for_each_lit(f),
+            Formula::And(fs) | Formula::Or(fs) => fs.iter().for_each(|a| a.for_each_lit(f)),
+            Formula::Xor(a, b) | Formula::Iff(a, b) => {
+                a.for_each_lit(f);
+                b.for_each_lit(f);
+            }
+            Formula::Ite(c, t, e) => {
+                c.for_each_lit(f);
+                t.for_each_lit(f);
+                e.for_each_lit(f);
+            }
+        }
+    }
+
+    /// Evaluate a formula under a function that returns the value of a literal.
+    pub fn eval<F: Fn(Lit) -> bool>(&self, value: &F) -> bool {
+        match self {
+            Formula::Const(b) => *b,
+            Formula::Lit(lit) => value(*lit),
+            Formula::Not(f) => !f.eval(value),
+            Formula::And(fs) => fs.iter().all(|f| f.eval(value)),
+            Formula::Or(fs) => fs.iter().any(|f| f.eval(value)),
+            Formula::Xor(a, b) => a.eval(value) != b.eval(value),
+            Formula::Iff(a, b) => a.eval(value) == b.eval(value),
+            Formula::Ite(c, t, e) => {
+                if c.eval(value) {
+                    t.eval(value)
+                } else {
+                    e.eval(value)
+                }
+            }
+        }
+    }
+}
+
+// A formula is encoded into a literal or folded into a constant.
+#[derive(Clone, Copy)]
+enum Encoded {
+    Const(bool),
+    Lit(Lit),
+}
+
+impl std::ops::Not for Encoded {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        match self {
+            Encoded::Const(b) => Encoded::Const(!b),
+            Encoded::Lit(lit) => Encoded::Lit(!lit),
+        }
+    }
+}
+
+/// A target of encoded clauses. A solver and a `CnfData` are targets.
+pub trait ClauseSink {
+    /// The number of variables.
+    fn var_num(&self) -> usize;
+    /// Create a new variable after all variables.
+    fn new_var(&mut self) -> Var;
+    fn add_clause(&mut self, clause: &[Lit]);
+}
+
+impl ClauseSink for Solver {
+    fn var_num(&self) -> usize {
+        self.assigns.len()
+    }
+    fn new_var(&mut self) -> Var {
+        Solver::new_var(self)
+    }
+    fn add_clause(&mut self, clause: &[Lit]) {
+        Solver::add_clause(self, clause)
+    }
+}
+
+/// `var_num` of a header is updated by new variables and `cla_num` isn't.
+impl ClauseSink for CnfData {
+    fn var_num(&self) -> usize {
+        self.var_num.unwrap_or_else(|| {
+            self.clauses
+                .iter()
+                .flatten()
+                .map(|lit| lit.var().0 as usize + 1)
+                .max()
+                .unwrap_or(0)
+        })
+    }
+    fn new_var(&mut self) -> Var {
+        let v = ClauseSink::var_num(self);
+        self.var_num = Some(v + 1);
+        Var(v as u32)
+    }
+    fn add_clause(&mut self, clause: &[Lit]) {
+        self.clauses.push(clause.to_vec());
+    }
+}
+
+/// Grow a target so that new variables don't overlap with literals of a formula.
+fn reserve<S: ClauseSink>(sink: &mut S, formula: &Formula) {
+    let mut var_num = 0;
+    formula.for_each_lit(&mut |lit| var_num = std::cmp::max(var_num, lit.var().0 as usize + 1));
+    while var_num > sink.var_num() {
+        sink.new_var();
+    }
+}
+
+fn fresh_lit<S: ClauseSink>(sink: &mut S) -> Lit {
+    Lit::new(sink.new_var().0, true)
+}
+
+// A gate over encoded literals in a normal form.
+// `And` literals are sorted. `Xor` inputs are positive and sorted. `Ite` condition is positive.
+#[derive(PartialEq, Eq, Hash, Debug)]
+enum Gate {
+    And(Vec<Lit>),
+    Xor(Lit, Lit),
+    Ite(Lit, Lit, Lit),
+}
+
+/// A Tseitin encoder with structural hashing.
+/// Structurally identical subformulas are encoded into one literal even if they are in different formulas.
+/// An encoder must be used with the same target.
+///
+/// The Plaisted-Greenbaum encoder(`Encoder::plaisted_greenbaum`) adds only the clauses of the polarities
+/// that subformulas occur in, so it adds about half of the clauses.
+/// Then a literal of `encode` implies a formula and isn't equivalent to it.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    // gates and whether the clauses of `x -> gate` and `gate -> x` are added
+    gates: HashMap<Gate, (Lit, [bool; 2])>,
+    // a literal that is forced to be true
+    truth: Option<Lit>,
+    plaisted_greenbaum: bool,
+}
+
+impl Encoder {
+    /// An encoder by the Plaisted-Greenbaum encoding.
+    pub fn plaisted_greenbaum() -> Encoder {
+        Encoder {
+            plaisted_greenbaum: true,
+            ..Encoder::default()
+        }
+    }
+
+    /// Encode a formula into a target and return a literal that is equivalent to it.
+    /// A literal of the Plaisted-Greenbaum encoding only implies a formula.
+    /// New variables are allocated after the existing variables of a target.
+    pub fn encode<S: ClauseSink>(&mut self, sink: &mut S, formula: &Formula) -> Lit {
+        reserve(sink, formula);
+        let e = self.tseitin(sink, formula, [true, !self.plaisted_greenbaum]);
+        self.lit_of(sink, e)
+    }
+
+    /// Add a formula to a target as a constraint.
+    /// Top-level conjunctions and disjunctions are added as clauses directly.
+    pub fn add_formula<S: ClauseSink>(&mut self, sink: &mut S, formula: &Formula) {
+        reserve(sink, formula);
+        // A constraint occurs positively.
+        let polarity = [true, !self.plaisted_greenbaum];
+        match formula {
+            Formula::And(fs) => fs.iter().for_each(|f| self.add_formula(sink, f)),
+            Formula::Or(fs) => {
+                let mut clause = vec![];
+                for f in fs.iter() {
+                    match self.tseitin(sink, f, polarity) {
+                        Encoded::Const(true) => return,
+                        Encoded::Const(false) => {}
+                        Encoded::Lit(lit) => clause.push(lit),
+                    }
+                }
+                sink.add_clause(&clause);
+            }
+            _ => match self.tseitin(sink, formula, polarity) {
+                Encoded::Const(true) => {}
+                Encoded::Const(false) => sink.add_clause(&[]),
+                Encoded::Lit(lit) => sink.add_clause(&[lit]),
+            },
+        }
+    }
+
+    /// A literal that is forced to be `b`.
+    fn const_lit<S: ClauseSink>(&mut self, sink: &mut S, b: bool) -> Lit {
+        let lit = match self.truth {
+            Some(lit) => lit,
+            None => {
+                let lit = fresh_lit(sink);
+                sink.add_clause(&[lit]);
+                self.truth = Some(lit);
+                lit
+            }
+        };
+        if b {
+            lit
+        } else {
+            !lit
+        }
+    }
+
+    fn lit_of<S: ClauseSink>(&mut self, sink: &mut S, e: Encoded) -> Lit {
+        match e {
+            Encoded::Const(b) => self.const_lit(sink, b),
+            Encoded::Lit(lit) => lit,
+        }
+    }
+
+    /// The literal of a gate. Clauses of `x -> gate` and `gate -> x` are added when `polarity` needs them first.
+    fn gate<S: ClauseSink>(&mut self, sink: &mut S, gate: Gate, polarity: [bool; 2]) -> Lit {
+        let (x, added) = match self.gates.get(&gate) {
+            Some(&(x, added)) => (x, added),
+            None => (fresh_lit(sink), [false, false]),
+        };
+        let (pos, neg) = (polarity[0] && !added[0], polarity[1] && !added[1]);
+        match &gate {
+            Gate::And(lits) => {
+                if pos {
+                    // x -> lit
+                    lits.iter().for_each(|&lit| sink.add_clause(&[!x, lit]));
+                }
+                if neg {
+                    // lit1 & lit2 & ... -> x
+                    let mut clause: Vec<Lit> = lits.iter().map(|&lit| !lit).collect();
+                    clause.push(x);
+                    sink.add_clause(&clause);
+                }
+            }
+            &Gate::Xor(a, b) => {
+                if pos {
+                    sink.add_clause(&[!x, a, b]);
+                    sink.add_clause(&[!x, !a, !b]);
+                }
+                if neg {
+                    sink.add_clause(&[x, !a, b]);
+                    sink.add_clause(&[x, a, !b]);
+                }
+            }
+            &Gate::Ite(c, t, e) => {
+                // redundant clauses `t & e -> x` and `x -> t | e` help propagation
+                if pos {
+                    sink.add_clause(&[!c, t, !x]);
+                    sink.add_clause(&[c, e, !x]);
+                    sink.add_clause(&[t, e, !x]);
+                }
+                if neg {
+                    sink.add_clause(&[!c, !t, x]);
+                    sink.add_clause(&[c, !e, x]);
+                    sink.add_clause(&[!t, !e, x]);
+                }
+            }
+        }
+        let added = [added[0] || polarity[0], added[1] || polarity[1]];
+        self.gates.insert(gate, (x, added));
+        x
+    }
+
+    /// Tseitin encoding. A new literal `x` is defined by `x <-> f` for each compound formula `f`.
+    /// `polarity` is whether `x -> f` and `f -> x` are needed.
+    fn tseitin<S: ClauseSink>(
+        &mut self,
+        sink: &mut S,
+        formula: &Formula,
+        polarity: [bool; 2],
+    ) -> Encoded {
+        // Operands of xor and conditions of ite occur in both polarities.
+        let both = [true, true];
+        match formula {
+            Formula::Const(b) => Encoded::Const(*b),
+            Formula::Lit(lit) => Encoded::Lit(*lit),
+            Formula::Not(f) => !self.tseitin(sink, f, [polarity[1], polarity[0]]),
+            Formula::And(fs) => {
+                let mut lits = vec![];
+                for f in fs.iter() {
+                    match self.tseitin(sink, f, polarity) {
+                        Encoded::Const(true) => {}
+                        Encoded::Const(false) => return Encoded::Const(false),
+                        Encoded::Lit(lit) => lits.push(lit),
+                    }
+                }
+                lits.sort();
+                lits.dedup();
+                // x & !x is false
+                if lits.windows(2).any(|w| w[0] == !w[1]) {
+                    return Encoded::Const(false);
+                }
+                match lits.len() {
+                    0 => Encoded::Const(true),
+                    1 => Encoded::Lit(lits[0]),
+                    _ => Encoded::Lit(self.gate(sink, Gate::And(lits), polarity)),
+                }
+            }
+            Formula::Or(fs) => {
+                // a | b = !(!a & !b)
+                let fs = fs.iter().map(|f| !f.clone()).collect();
+                !self.tseitin(sink, &Formula::And(fs), [polarity[1], polarity[0]])
+            }
+            Formula::Xor(a, b) => {
+                match (self.tseitin(sink, a, both), self.tseitin(sink, b, both)) {
+                    (Encoded::Const(a), b) | (b, Encoded::Const(a)) => {
+                        if a {
+                            !b
+                        } else {
+                            b
+                        }
+                    }
+                    (Encoded::Lit(a), Encoded::Lit(b)) if a.var() == b.var() => {
+                        Encoded::Const(a != b)
+                    }
+                    (Encoded::Lit(a), Encoded::Lit(b)) => {
+                        // !a ^ b = !(a ^ b)
+                        let negated = a.neg() != b.neg();
+                        let (a, b) = (Lit::new(a.var().0, true), Lit::new(b.var().0, true));
+                        let polarity = if negated {
+                            [polarity[1], polarity[0]]
+                        } else {
+                            polarity
+                        };
+                        let x = self.gate(sink, Gate::Xor(a.min(b), a.max(b)), polarity);
+                        Encoded::Lit(if negated { !x } else { x })
+                    }
+                }
+            }
+            Formula::Iff(a, b) => !self.tseitin(
+                sink,
+                &Formula::Xor(a.clone(), b.clone()),
+                [polarity[1], polarity[0]],
+            ),
+            Formula::Ite(c, t, e) => match self.tseitin(sink, c, both) {
+                Encoded::Const(true) => self.tseitin(sink, t, polarity),
+                Encoded::Const(false) => self.tseitin(sink, e, polarity),
+                Encoded::Lit(c) => {
+                    let t = self.tseitin(sink, t, polarity);
+                    let e = self.tseitin(sink, e, polarity);
+                    let t = self.lit_of(sink, t);
+                    let e = self.lit_of(sink, e);
+                    // ite(!c, t, e) = ite(c, e, t)
+                    let (c, t, e) = if c.neg() { (!c, e, t) } else { (c, t, e) };
+                    Encoded::Lit(self.gate(sink, Gate::Ite(c, t, e), polarity))
+                }
+            },
+        }
+    }
+}
+
+/// Encode a formula into a target and return a literal that is equivalent to it.
+/// New variables are allocated after the existing variables of a target.
+/// Use `Encoder` to share subformulas between formulas.
+pub fn encode<S: ClauseSink>(sink: &mut S, formula: &Formula) -> Lit {
+    Encoder::default().encode(sink, formula)
+}
+
+/// Add a formula to a target as a constraint.
+/// Top-level conjunctions and disjunctions are added as clauses directly.
+pub fn add_formula<S: ClauseSink>(sink: &mut S, formula: &Formula) {
+    Encoder::default().add_formula(sink, formula)
+}
@@ -0,0 +1,407 @@
+use std::io::Read;
+
+// the distance limit of DEFLATE matches
+const WINDOW: usize = 32 * 1024;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+// the order of the code lengths of the code length alphabet in a dynamic block
+const CLEN_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!("Invalid gzip stream: {}", msg),
+    )
+}
+
+/// Bytes and bits of a stream. DEFLATE packs bits from the least significant bit of each byte.
+struct Bits<R: Read> {
+    input: R,
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    // bits that are read from `buf` but not consumed yet
+    bitbuf: u32,
+    bitcnt: u32,
+}
+
+impl<R: Read> Bits<R> {
+    /// The next byte or `None` at the end of the stream.
+    fn byte(&mut self) -> std::io::Result<Option<u8>> {
+        if self.pos == self.len {
+            self.len = loop {
+                match self.input.read(&mut self.buf) {
+                    Ok(len) => break len,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                }
+            };
+            self.pos = 0;
+            if self.len == 0 {
+                return Ok(None);
+            }
+        }
+        self.pos += 1;
+        Ok(Some(self.buf[self.pos - 1]))
+    }
+    fn next_byte(&mut self) -> std::io::Result<u8> {
+        self.byte()?.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "Invalid gzip stream: unexpected end",
+            )
+        })
+    }
+    /// A little-endian integer of `n` bytes.
+    fn le(&mut self, n: usize) -> std::io::Result<u32> {
+        let mut x = 0;
+        for i in 0..n {
+            x |= (self.next_byte()? as u32) << (8 * i);
+        }
+        Ok(x)
+    }
+    /// Consume `n` bits(at most 16).
+    fn bits(&mut self, n: u32) -> std::io::Result<u32> {
+        while self.bitcnt < n {
+            self.bitbuf |= (self.next_byte()? as u32) << self.bitcnt;
+            self.bitcnt += 8;
+        }
+        let x = self.bitbuf & ((1 << n) - 1);
+        self.bitbuf >>= n;
+        self.bitcnt -= n;
+        Ok(x)
+    }
+    /// Skip the rest of the current byte. Less than 8 bits are buffered, so they are in the same byte.
+    fn align(&mut self) {
+        self.bitbuf = 0;
+        self.bitcnt = 0;
+    }
+}
+
+/// A canonical Huffman code by the number of codes of each length and symbols in the order of codes.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// `None` means that the lengths are over-subscribed. An incomplete code is allowed.
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let mut counts = [0u16; 16];
+        lengths.iter().for_each(|&len| counts[len as usize] += 1);
+        counts[0] = 0;
+        let mut left = 1i32;
+        for &count in counts[1..].iter() {
+            left = 2 * left - count as i32;
+            if left < 0 {
+                return None;
+            }
+        }
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Some(Huffman { counts, symbols })
+    }
+    /// Decode a symbol bit by bit. Codes are packed from their most significant bits.
+    fn decode<R: Read>(&self, bits: &mut Bits<R>) -> std::io::Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= bits.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("invalid code"))
+    }
+}
+
+/// A reader that decompresses gzip data(RFC 1952) of `input` incrementally, block by block.
+/// Concatenated members are read as one stream. The CRC-32 and the size of each member are checked.
+/// Only DEFLATE is supported, so xz, bzip2 and zstd files aren't.
+///
+/// ```no_run
+/// use screwsat::{gzip::GzDecoder, util};
+/// let input = std::fs::File::open("problem.cnf.gz").unwrap();
+/// let cnf = util::parse_cnf(GzDecoder::new(input)).unwrap();
+/// ```
+pub struct GzDecoder<R: Read> {
+    bits: Bits<R>,
+    // decompressed bytes that are read or not yet. The last `WINDOW` bytes are kept for matches.
+    history: Vec<u8>,
+    // the first unread byte of `history`
+    pos: usize,
+    // a member is being decompressed
+    in_member: bool,
+    // the last block of a member is decompressed
+    last: bool,
+    // the CRC-32 and the size of the decompressed bytes of a member
+    crc: u32,
+    size: u32,
+    // the first byte of `history` that isn't in `crc` yet
+    checked: usize,
+    members: usize,
+    crc_table: Vec<u32>,
+}
+
+impl<R: Read> GzDecoder<R> {
+    pub fn new(input: R) -> GzDecoder<R> {
+        let crc_table = (0..256u32)
+            .map(|n| {
+                (0..8).fold(n, |c, _| {
+                    if c & 1 == 1 {
+                        0xedb8_8320 ^ (c >> 1)
+                    } else {
+                        c >> 1
+                    }
+                })
+            })
+            .collect();
+        GzDecoder {
+            bits: Bits {
+                input,
+                buf: vec![0; 64 * 1024],
+                pos: 0,
+                len: 0,
+                bitbuf: 0,
+                bitcnt: 0,
+            },
+            history: Vec::new(),
+            pos: 0,
+            in_member: false,
+            last: false,
+            crc: 0,
+            size: 0,
+            checked: 0,
+            members: 0,
+            crc_table,
+        }
+    }
+
+    /// Read the header of a member. `false` means the end of the stream after a member.
+    fn header(&mut self) -> std::io::Result<bool> {
+        let id1 = match self.bits.byte()? {
+            Some(byte) => byte,
+            None if self.members > 0 => return Ok(false),
+            None => return Err(invalid("empty input")),
+        };
+        if id1 != 0x1f || self.bits.next_byte()? != 0x8b {
+            return Err(invalid("not a gzip file"));
+        }
+        if self.bits.next_byte()? != 8 {
+            return Err(invalid("unknown compression method"));
+        }
+        let flags = self.bits.next_byte()?;
+        // the modification time, extra flags and the operating system
+        for _ in 0..6 {
+            self.bits.next_byte()?;
+        }
+        if flags & 4 != 0 {
+            let len = self.bits.le(2)?;
+            for _ in 0..len {
+                self.bits.next_byte()?;
+            }
+        }
+        // the file name and the comment end with zero
+        for flag in [8, 16].iter() {
+            if flags & flag != 0 {
+                while self.bits.next_byte()? != 0 {}
+            }
+        }
+        if flags & 2 != 0 {
+            self.bits.le(2)?;
+        }
+        self.in_member = true;
+        self.last = false;
+        self.crc = 0xffff_ffff;
+        self.size = 0;
+        Ok(true)
+    }
+
+    /// Check the CRC-32 and the size of a member.
+    fn trailer(&mut self) -> std::io::Result<()> {
+        self.update_crc();
+        self.bits.align();
+        if self.bits.le(4)? != !self.crc {
+            return Err(invalid("CRC-32 mismatch"));
+        }
+        if self.bits.le(4)? != self.size {
+            return Err(invalid("size mismatch"));
+        }
+        self.in_member = false;
+        self.members += 1;
+        Ok(())
+    }
+
+    fn update_crc(&mut self) {
+        for &byte in self.history[self.checked..].iter() {
+            self.crc = self.crc_table[((self.crc ^ byte as u32) & 0xff) as usize] ^ (self.crc >> 8);
+        }
+        self.size = self
+            .size
+            .wrapping_add((self.history.len() - self.checked) as u32);
+        self.checked = self.history.len();
+    }
+
+    /// Decompress a block.
+    fn block(&mut self) -> std::io::Result<()> {
+        self.last = self.bits.bits(1)? == 1;
+        match self.bits.bits(2)? {
+            0 => {
+                self.bits.align();
+                let len = self.bits.le(2)?;
+                if self.bits.le(2)? != !len & 0xffff {
+                    return Err(invalid("invalid stored block length"));
+                }
+                for _ in 0..len {
+                    let byte = self.bits.next_byte()?;
+                    self.history.push(byte);
+                }
+                Ok(())
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[..144].iter_mut().for_each(|len| *len = 8);
+                lengths[144..256].iter_mut().for_each(|len| *len = 9);
+                lengths[256..280].iter_mut().for_each(|len| *len = 7);
+                lengths[280..].iter_mut().for_each(|len| *len = 8);
+                let litlen = Huffman::new(&lengths).unwrap();
+                let dist = Huffman::new(&[5; 30]).unwrap();
+                self.codes(&litlen, &dist)
+            }
+            2 => {
+                let (litlen, dist) = self.dynamic()?;
+                self.codes(&litlen, &dist)
+            }
+            _ => Err(invalid("invalid block type")),
+        }
+    }
+
+    /// Read the Huffman codes of a dynamic block.
+    fn dynamic(&mut self) -> std::io::Result<(Huffman, Huffman)> {
+        let nlen = self.bits.bits(5)? as usize + 257;
+        let ndist = self.bits.bits(5)? as usize + 1;
+        let ncode = self.bits.bits(4)? as usize + 4;
+        if nlen > 286 || ndist > 30 {
+            return Err(invalid("too many codes"));
+        }
+        let mut clens = [0u8; 19];
+        for &i in CLEN_ORDER[..ncode].iter() {
+            clens[i] = self.bits.bits(3)? as u8;
+        }
+        let clen = Huffman::new(&clens).ok_or_else(|| invalid("invalid code lengths"))?;
+        let mut lengths = Vec::with_capacity(nlen + ndist);
+        while lengths.len() < nlen + ndist {
+            let symbol = clen.decode(&mut self.bits)?;
+            let (len, repeat) = match symbol {
+                0..=15 => (symbol as u8, 1),
+                16 => match lengths.last() {
+                    Some(&len) => (len, 3 + self.bits.bits(2)?),
+                    None => return Err(invalid("no length to repeat")),
+                },
+                17 => (0, 3 + self.bits.bits(3)?),
+                _ => (0, 11 + self.bits.bits(7)?),
+            };
+            if lengths.len() + repeat as usize > nlen + ndist {
+                return Err(invalid("too many lengths"));
+            }
+            (0..repeat).for_each(|_| lengths.push(len));
+        }
+        if lengths[256] == 0 {
+            return Err(invalid("no end of block code"));
+        }
+        let litlen = Huffman::new(&lengths[..nlen]).ok_or_else(|| invalid("invalid lengths"))?;
+        let dist = Huffman::new(&lengths[nlen..]).ok_or_else(|| invalid("invalid distances"))?;
+        Ok((litlen, dist))
+    }
+
+    /// Decompress literals and matches until the end of a block.
+    fn codes(&mut self, litlen: &Huffman, dist: &Huffman) -> std::io::Result<()> {
+        loop {
+            let symbol = litlen.decode(&mut self.bits)? as usize;
+            if symbol < 256 {
+                self.history.push(symbol as u8);
+                continue;
+            }
+            if symbol == 256 {
+                return Ok(());
+            }
+            let symbol = symbol - 257;
+            if symbol >= LENGTH_BASE.len() {
+                return Err(invalid("invalid length code"));
+            }
+            let len = LENGTH_BASE[symbol] as usize
+                + self.bits.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+            let symbol = dist.decode(&mut self.bits)? as usize;
+            if symbol >= DIST_BASE.len() {
+                return Err(invalid("invalid distance code"));
+            }
+            let distance =
+                DIST_BASE[symbol] as usize + self.bits.bits(DIST_EXTRA[symbol] as u32)? as usize;
+            if distance > self.history.len() {
+                return Err(invalid("distance too far back"));
+            }
+            // A match may overlap the bytes that it copies.
+            let start = self.history.len() - distance;
+            for i in start..start + len {
+                let byte = self.history[i];
+                self.history.push(byte);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for GzDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.history.len() {
+            // Read bytes are dropped except for the window of matches.
+            if self.history.len() > 2 * WINDOW {
+                self.update_crc();
+                let dropped = self.history.len() - WINDOW;
+                self.history.drain(..dropped);
+                self.pos -= dropped;
+                self.checked -= dropped;
+            }
+            if !self.in_member {
+                if !self.header()? {
+                    return Ok(0);
+                }
+            } else if self.last {
+                self.trailer()?;
+            } else {
+                self.block()?;
+            }
+        }
+        let len = std::cmp::min(buf.len(), self.history.len() - self.pos);
+        buf[..len].copy_from_slice(&self.history[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
@@ -0,0 +1,486 @@
+use crate::solver::{Lit, LitBool, Status, Var};
+
+// `None` of indices are represented by `NONE` to keep arrays small.
+const NONE: u32 = u32::MAX;
+
+/// A CDCL SAT Solver whose capacity is fixed at compile time.
+/// It never allocates memory, so it can be placed in a `static` on microcontrollers.
+/// This solver only depends on `core` items.
+/// # Parameters
+/// * `V` - The maximum number of variable
+/// * `C` - The maximum number of clause(original and learnt clauses)
+/// * `L` - The maximum number of literal in all clauses
+///
+/// Learnt clauses are deleted when the clause database is full.
+pub struct HeaplessSolver<const V: usize, const C: usize, const L: usize> {
+    // the number of variables
+    n: usize,
+    // assignments for each variable
+    assigns: [LitBool; V],
+    polarity: [bool; V],
+    activity: [f32; V],
+    bump_inc: f32,
+    // a clause index represents that a variable is forced to be assigned.
+    reason: [u32; V],
+    // decision level(0: root level)
+    level: [u32; V],
+    seen: [bool; V],
+    // assigned literals
+    trail: [Lit; V],
+    trail_len: usize,
+    // the head index of `trail` points unprocessed elements
+    head: usize,
+    // the start index of `trail` for each decision level
+    trail_lim: [u32; V],
+    decision_level: usize,
+    // clause database. `lits[start[i]..start[i] + len[i]]` is the i-th clause.
+    lits: [Lit; L],
+    lits_len: usize,
+    start: [u32; C],
+    len: [u32; C],
+    learnt: [bool; C],
+    clauses_len: usize,
+    // Watchers are linked lists through clauses.
+    // `watch_head[var][sign]` is the first clause that watches a literal.
+    // `watch_next[i][k]` is the next clause that watches the same literal as the k-th literal of the i-th clause.
+    watch_head: [[u32; 2]; V],
+    watch_next: [[u32; 2]; C],
+    // a buffer for a learnt clause
+    learnt_clause: [Lit; V],
+    // the solver status. this value may be set by the functions `add_clause` and `solve`.
+    status: Option<Status>,
+}
+
+impl<const V: usize, const C: usize, const L: usize> Default for HeaplessSolver<V, C, L> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const V: usize, const C: usize, const L: usize> HeaplessSolver<V, C, L> {
+    /// Create a new `HeaplessSolver` struct
+    pub const fn new() -> Self {
+        HeaplessSolver {
+            n: 0,
+            assigns: [LitBool::Undef; V],
+            polarity: [false; V],
+            activity: [0.0; V],
+            bump_inc: 1.0,
+            reason: [NONE; V],
+            level: [0; V],
+            seen: [false; V],
+            trail: [Lit::new(0, true); V],
+            trail_len: 0,
+            head: 0,
+            trail_lim: [0; V],
+            decision_level: 0,
+            lits: [Lit::new(0, true); L],
+            lits_len: 0,
+            start: [0; C],
+            len: [0; C],
+            learnt: [false; C],
+            clauses_len: 0,
+            watch_head: [[NONE; 2]; V],
+            watch_next: [[NONE; 2]; C],
+            learnt_clause: [Lit::new(0, true); V],
+            status: None,
+        }
+    }
+
+    /// Assignments for each variable. They are valid after `solve` returns `Sat`.
+    pub fn assigns(&self) -> &[LitBool] {
+        &self.assigns[..self.n]
+    }
+
+    /// The solver status. `None` means that the problem isn't solved yet.
+    pub fn status(&self) -> Option<Status> {
+        self.status
+    }
+
+    fn eval(&self, lit: Lit) -> LitBool {
+        match self.assigns[lit.var().0 as usize] {
+            LitBool::True if lit.pos() => LitBool::True,
+            LitBool::False if lit.neg() => LitBool::True,
+            LitBool::Undef => LitBool::Undef,
+            _ => LitBool::False,
+        }
+    }
+
+    fn head_mut(&mut self, lit: Lit) -> &mut u32 {
+        &mut self.watch_head[lit.var().0 as usize][lit.neg() as usize]
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: u32) {
+        let v = lit.var().0 as usize;
+        debug_assert!(self.assigns[v] == LitBool::Undef);
+        self.assigns[v] = if lit.pos() {
+            LitBool::True
+        } else {
+            LitBool::False
+        };
+        self.reason[v] = reason;
+        self.level[v] = self.decision_level as u32;
+        self.trail[self.trail_len] = lit;
+        self.trail_len += 1;
+    }
+
+    fn watch(&mut self, cr: usize) {
+        let s = self.start[cr] as usize;
+        for k in 0..2 {
+            let lit = self.lits[s + k];
+            self.watch_next[cr][k] = *self.head_mut(lit);
+            *self.head_mut(lit) = cr as u32;
+        }
+    }
+
+    /// Store a clause that has at least two literals and watch it.
+    /// Returns `false` if there isn't enough space.
+    fn store_clause(&mut self, clause: &[Lit], learnt: bool) -> bool {
+        debug_assert!(clause.len() >= 2);
+        if self.clauses_len >= C || self.lits_len + clause.len() > L {
+            return false;
+        }
+        let cr = self.clauses_len;
+        self.start[cr] = self.lits_len as u32;
+        self.len[cr] = clause.len() as u32;
+        self.learnt[cr] = learnt;
+        self.lits[self.lits_len..self.lits_len + clause.len()].copy_from_slice(clause);
+        self.lits_len += clause.len();
+        self.clauses_len += 1;
+        self.watch(cr);
+        true
+    }
+
+    /// Add a new clause.
+    /// Returns `false` if a clause doesn't fit the capacity of the solver.
+    /// # Arguments
+    /// * `clause` - a clause has one or some literal variables
+    pub fn add_clause(&mut self, clause: &[Lit]) -> bool {
+        if self.status == Some(Status::Unsat) {
+            return true;
+        }
+        self.status = None;
+        self.cancel_until(0);
+
+        // Simplify a clause into the buffer
+        let mut len = 0;
+        for &lit in clause.iter() {
+            let v = lit.var().0 as usize;
+            if v >= V {
+                return false;
+            }
+            if v >= self.n {
+                self.n = v + 1;
+            }
+            match self.eval(lit) {
+                // a clause is already satisfied. You don't need to add it.
+                LitBool::True => return true,
+                // a literal is already false. You can remove it from a clause.
+                LitBool::False => continue,
+                _ => {}
+            }
+            let mut duplicated = false;
+            for &x in self.learnt_clause[..len].iter() {
+                // x0 v !x0 means a clause is already satisfied.
+                if x == !lit {
+                    return true;
+                }
+                if x == lit {
+                    duplicated = true;
+                }
+            }
+            if !duplicated {
+                self.learnt_clause[len] = lit;
+                len += 1;
+            }
+        }
+
+        match len {
+            0 => self.status = Some(Status::Unsat),
+            1 => {
+                self.enqueue(self.learnt_clause[0], NONE);
+                if self.propagate().is_some() {
+                    self.status = Some(Status::Unsat);
+                }
+            }
+            _ => {
+                let buffer = self.learnt_clause;
+                return self.store_clause(&buffer[..len], false);
+            }
+        }
+        true
+    }
+
+    /// Propagate it by all enqueued values and check conflicts.
+    /// If a conflict is detected, this function returns a conflicted clause index.
+    fn propagate(&mut self) -> Option<u32> {
+        while self.head < self.trail_len {
+            let p = self.trail[self.head];
+            self.head += 1;
+            let false_lit = !p;
+
+            // `prev` is the previous clause that still watches `false_lit`.
+            let mut prev = NONE;
+            let mut cr = *self.head_mut(false_lit);
+            while cr != NONE {
+                let c = cr as usize;
+                let s = self.start[c] as usize;
+                // make sure that the lits[s + 1] is the false literal.
+                if self.lits[s] == false_lit {
+                    self.lits.swap(s, s + 1);
+                    self.watch_next[c].swap(0, 1);
+                }
+                let next = self.watch_next[c][1];
+                let first = self.lits[s];
+
+                if self.eval(first) != LitBool::True {
+                    // Found a literal isn't false(true or undefined)
+                    let end = s + self.len[c] as usize;
+                    if let Some(k) =
+                        (s + 2..end).find(|&k| self.eval(self.lits[k]) != LitBool::False)
+                    {
+                        self.lits.swap(s + 1, k);
+                        // unlink the clause from the watchers of `false_lit`
+                        if prev == NONE {
+                            *self.head_mut(false_lit) = next;
+                        } else {
+                            let ps = self.start[prev as usize] as usize;
+                            let slot = if self.lits[ps] == false_lit { 0 } else { 1 };
+                            self.watch_next[prev as usize][slot] = next;
+                        }
+                        let lit = self.lits[s + 1];
+                        self.watch_next[c][1] = *self.head_mut(lit);
+                        *self.head_mut(lit) = cr;
+                        cr = next;
+                        continue;
+                    }
+                    if self.eval(first) == LitBool::False {
+                        // CONFLICT
+                        self.head = self.trail_len;
+                        return Some(cr);
+                    }
+                    // UNIT PROPAGATION
+                    self.enqueue(first, cr);
+                }
+                prev = cr;
+                cr = next;
+            }
+        }
+        None
+    }
+
+    fn bump_activity(&mut self, v: usize) {
+        self.activity[v] += self.bump_inc;
+        if self.activity[v] > 1e20 {
+            self.activity[..self.n].iter_mut().for_each(|a| *a *= 1e-20);
+            self.bump_inc *= 1e-20;
+        }
+    }
+
+    /// Analyze a conflict clause and store a learnt clause into `learnt_clause`.
+    /// Returns the length of a learnt clause and a backtrack level.
+    fn analyze(&mut self, mut confl: u32) -> (usize, usize) {
+        let mut len = 1;
+        let mut path_cnt = 0;
+        let mut idx = self.trail_len;
+        let mut p: Option<Lit> = None;
+        loop {
+            let c = confl as usize;
+            let s = self.start[c] as usize;
+            let skip = if p.is_some() { 1 } else { 0 };
+            for k in s + skip..s + self.len[c] as usize {
+                let q = self.lits[k];
+                let v = q.var().0 as usize;
+                if !self.seen[v] && self.level[v] > 0 {
+                    self.seen[v] = true;
+                    self.bump_activity(v);
+                    if self.level[v] as usize >= self.decision_level {
+                        path_cnt += 1;
+                    } else {
+                        self.learnt_clause[len] = q;
+                        len += 1;
+                    }
+                }
+            }
+            // Select the next literal to look at.
+            loop {
+                idx -= 1;
+                if self.seen[self.trail[idx].var().0 as usize] {
+                    break;
+                }
+            }
+            let lit = self.trail[idx];
+            let v = lit.var().0 as usize;
+            self.seen[v] = false;
+            p = Some(lit);
+            path_cnt -= 1;
+            if path_cnt == 0 {
+                break;
+            }
+            confl = self.reason[v];
+        }
+        // p is 1-UIP.
+        self.learnt_clause[0] = !p.unwrap();
+
+        let mut backtrack_level = 0;
+        if len > 1 {
+            let mut max_idx = 1;
+            for i in 1..len {
+                let l = self.level[self.learnt_clause[i].var().0 as usize];
+                if l > self.level[self.learnt_clause[max_idx].var().0 as usize] {
+                    max_idx = i;
+                }
+            }
+            self.learnt_clause.swap(1, max_idx);
+            backtrack_level = self.level[self.learnt_clause[1].var().0 as usize] as usize;
+        }
+        // Clear seen
+        for i in 1..len {
+            self.seen[self.learnt_clause[i].var().0 as usize] = false;
+        }
+        (len, backtrack_level)
+    }
+
+    fn cancel_until(&mut self, level: usize) {
+        if self.decision_level <= level {
+            return;
+        }
+        let lim = self.trail_lim[level] as usize;
+        for i in lim..self.trail_len {
+            let v = self.trail[i].var().0 as usize;
+            self.polarity[v] = self.assigns[v] == LitBool::True;
+            self.assigns[v] = LitBool::Undef;
+            self.reason[v] = NONE;
+        }
+        self.trail_len = lim;
+        self.head = lim;
+        self.decision_level = level;
+    }
+
+    /// Delete learnt clauses and clauses satisfied at the root level, then rebuild the watchers.
+    /// This function must be called at the root level.
+    /// # Arguments
+    /// * `max_learnt_len` - Learnt clauses that are longer than this are deleted
+    fn reduce_db(&mut self, max_learnt_len: u32) {
+        debug_assert_eq!(self.decision_level, 0);
+        // Root level assignments never need their reasons.
+        for i in 0..self.trail_len {
+            self.reason[self.trail[i].var().0 as usize] = NONE;
+        }
+        let mut new_len = 0;
+        let mut new_lits_len = 0;
+        for cr in 0..self.clauses_len {
+            let s = self.start[cr] as usize;
+            let n = self.len[cr] as usize;
+            let satisfied = (s..s + n).any(|k| self.eval(self.lits[k]) == LitBool::True);
+            if satisfied || (self.learnt[cr] && self.len[cr] > max_learnt_len) {
+                continue;
+            }
+            self.lits.copy_within(s..s + n, new_lits_len);
+            self.start[new_len] = new_lits_len as u32;
+            self.len[new_len] = n as u32;
+            self.learnt[new_len] = self.learnt[cr];
+            new_lits_len += n;
+            new_len += 1;
+        }
+        self.clauses_len = new_len;
+        self.lits_len = new_lits_len;
+        self.watch_head[..self.n]
+            .iter_mut()
+            .for_each(|h| *h = [NONE; 2]);
+        for cr in 0..self.clauses_len {
+            self.watch(cr);
+        }
+    }
+
+    /// Add a learnt clause. If the clause database is full, learnt clauses are deleted.
+    /// Returns `false` if the learnt clause doesn't fit even after deleting all learnt clauses.
+    fn add_learnt(&mut self, len: usize, backtrack_level: usize) -> bool {
+        let buffer = self.learnt_clause;
+        if len == 1 {
+            self.cancel_until(0);
+            self.enqueue(buffer[0], NONE);
+            return true;
+        }
+        self.cancel_until(backtrack_level);
+        if self.store_clause(&buffer[..len], true) {
+            self.enqueue(buffer[0], (self.clauses_len - 1) as u32);
+            return true;
+        }
+        // The clause database is full.
+        // All literals of the learnt clause are unassigned at the root level.
+        self.cancel_until(0);
+        for max_learnt_len in [8, 3, 0] {
+            self.reduce_db(max_learnt_len);
+            if self.store_clause(&buffer[..len], true) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Select a decision variable that isn't decided yet and has the highest activity
+    fn pick_branch(&self) -> Option<Var> {
+        let mut best: Option<usize> = None;
+        for v in 0..self.n {
+            if self.assigns[v] == LitBool::Undef
+                && best.is_none_or(|b| self.activity[v] > self.activity[b])
+            {
+                best = Some(v);
+            }
+        }
+        best.map(|v| Var(v as u32))
+    }
+
+    /// Solve a problem and return a enum `Status`.
+    /// # Arguments
+    /// * `conflict_limit` - The maximum number of conflicts.
+    ///   Exceeding the limit or the capacity of the solver returns `Indeterminate`
+    pub fn solve(&mut self, conflict_limit: Option<u64>) -> Status {
+        if self.status == Some(Status::Unsat) {
+            return Status::Unsat;
+        }
+        let mut conflict_cnt: u64 = 0;
+        let mut restart_cnt = 0;
+        let mut restart_limit = 100;
+
+        loop {
+            if let Some(confl) = self.propagate() {
+                //Conflict
+                if self.decision_level == 0 {
+                    self.status = Some(Status::Unsat);
+                    return Status::Unsat;
+                }
+                conflict_cnt += 1;
+                restart_cnt += 1;
+                let (len, backtrack_level) = self.analyze(confl);
+                if !self.add_learnt(len, backtrack_level) {
+                    self.status = Some(Status::Indeterminate);
+                    return Status::Indeterminate;
+                }
+                self.bump_inc *= 1.0 / 0.95;
+            } else {
+                // No Conflict
+                if conflict_limit.is_some_and(|limit| conflict_cnt >= limit) {
+                    self.status = Some(Status::Indeterminate);
+                    return Status::Indeterminate;
+                }
+                if restart_cnt >= restart_limit {
+                    restart_cnt = 0;
+                    restart_limit += restart_limit / 2;
+                    self.cancel_until(0);
+                }
+                if let Some(v) = self.pick_branch() {
+                    let idx = v.0 as usize;
+                    self.trail_lim[self.decision_level] = self.trail_len as u32;
+                    self.decision_level += 1;
+                    self.enqueue(Lit::new(v.0, self.polarity[idx]), NONE);
+                } else {
+                    // all variables are selected. which means that a formula is satisfied
+                    self.status = Some(Status::Sat);
+                    return Status::Sat;
+                }
+            }
+        }
+    }
+}
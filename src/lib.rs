@@ -1,21 +1,37 @@
 pub mod solver {
+    // `Idx` is `usize` or `u32`, so some casts are unnecessary for one of them.
+    #![allow(clippy::unnecessary_cast)]
 
     use std::{
-        cell::RefCell,
-        collections::VecDeque,
+        collections::{HashMap, HashSet, VecDeque},
+        io::{Read, Write},
         ops::{Index, IndexMut},
-        rc::{Rc, Weak},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
         time::{Duration, Instant},
         vec,
     };
 
+    /// The integer type of internal indices(clause references, decision levels and heap positions).
+    /// The feature `index32` makes them 32-bit to save memory on 64-bit hosts.
+    #[cfg(feature = "index32")]
+    type Idx = u32;
+    #[cfg(feature = "index32")]
+    type NonZeroIdx = std::num::NonZeroU32;
+    #[cfg(not(feature = "index32"))]
+    type Idx = usize;
+    #[cfg(not(feature = "index32"))]
+    type NonZeroIdx = std::num::NonZeroUsize;
+
     #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
     pub struct Var(pub u32);
 
     #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
     pub struct Lit(u32);
     impl Lit {
-        pub fn new(var: u32, positive: bool) -> Lit {
+        pub const fn new(var: u32, positive: bool) -> Lit {
             Lit(if positive { var << 1 } else { (var << 1) + 1 })
         }
         pub fn var(self) -> Var {
@@ -31,7 +47,7 @@ pub mod solver {
     impl From<i32> for Lit {
         fn from(x: i32) -> Self {
             debug_assert!(x != 0);
-            let d = x.abs() as u32 - 1;
+            let d = x.unsigned_abs() - 1;
             if x > 0 {
                 Lit(2 * d)
             } else {
@@ -45,15 +61,201 @@ pub mod solver {
             Lit(self.0 ^ 1)
         }
     }
+    // Variables and literals are shown as DIMACS integers from 1.
+    impl std::fmt::Display for Var {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{}", self.0 + 1)
+        }
+    }
+    impl std::fmt::Display for Lit {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if self.neg() {
+                write!(f, "-")?;
+            }
+            write!(f, "{}", self.var())
+        }
+    }
 
     pub type Clause = Vec<Lit>;
 
-    #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord, Clone)]
-    struct CRef(Rc<RefCell<Clause>>);
-    type CWRef = Weak<RefCell<Clause>>;
-    impl CRef {
-        fn new(clause: Clause) -> CRef {
-            CRef(Rc::new(RefCell::new(clause)))
+    /// A reference to a clause in `ClauseAllocator`.
+    /// It is the offset of a clause and never zero, so `Option<CRef>` is as small as `CRef`.
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+    struct CRef(NonZeroIdx);
+
+    // flags in the header of a clause
+    const LEARNT: u32 = 1;
+    const DELETED: u32 = 1 << 1;
+    const RELOCATED: u32 = 1 << 2;
+    // an original clause that has been probed by asymmetric literal elimination
+    const PROBED: u32 = 1 << 3;
+    // a clause that explains a propagation or a conflict of a native(cardinality or XOR) constraint.
+    // It isn't watched and it is freed when its propagated literal is unassigned.
+    const EXPLANATION: u32 = 1 << 4;
+    // a learnt clause that has taken part in conflict analysis since the last reduction of the learnt clause database
+    const USED: u32 = 1 << 5;
+    // an original clause that is a part of an extracted XOR constraint
+    const XOR: u32 = 1 << 6;
+    // The upper bits of flags are the LBD of a learnt clause.
+    const LBD_SHIFT: u32 = 8;
+    // the number of header words of a clause
+    const HEADER: usize = 2;
+    // the magic number and the version of a snapshot of `Solver::write_snapshot`
+    const SNAPSHOT_MAGIC: &[u8; 8] = b"SCRWSNP1";
+
+    /// All clauses are stored in one array to save memory and cache misses.
+    /// A clause is `[length, flags, literals...]`. The header words are stored as raw `Lit` values.
+    /// A learnt clause has one more word of its activity(`f32` bits) after literals.
+    #[derive(Debug, Clone)]
+    struct ClauseAllocator {
+        data: Vec<Lit>,
+        // the number of words of deleted clauses
+        wasted: usize,
+        // the amount of a bump of the activity of a learnt clause
+        activity_inc: f64,
+    }
+    impl Default for ClauseAllocator {
+        fn default() -> Self {
+            ClauseAllocator::with_capacity(0)
+        }
+    }
+    impl ClauseAllocator {
+        fn with_capacity(capacity: usize) -> ClauseAllocator {
+            let mut data = Vec::with_capacity(capacity + 1);
+            // The offset 0 is never used by a clause.
+            data.push(Lit(0));
+            ClauseAllocator {
+                data,
+                wasted: 0,
+                activity_inc: 1.0,
+            }
+        }
+        /// Reuse the memory of `data`.
+        fn from_buffer(mut data: Vec<Lit>) -> ClauseAllocator {
+            data.clear();
+            data.push(Lit(0));
+            ClauseAllocator {
+                data,
+                wasted: 0,
+                activity_inc: 1.0,
+            }
+        }
+        fn alloc(&mut self, clause: &[Lit], learnt: bool) -> CRef {
+            let offset = self.data.len();
+            self.data.push(Lit(clause.len() as u32));
+            self.data.push(Lit(if learnt { LEARNT } else { 0 }));
+            self.data.extend_from_slice(clause);
+            if learnt {
+                self.data.push(Lit(0f32.to_bits()));
+            }
+            CRef(NonZeroIdx::new(offset as Idx).expect("the clause database is too large"))
+        }
+        #[inline]
+        fn offset(cr: CRef) -> usize {
+            cr.0.get() as usize
+        }
+        #[inline]
+        fn len(&self, cr: CRef) -> usize {
+            self.data[ClauseAllocator::offset(cr)].0 as usize
+        }
+        #[inline]
+        fn flags(&self, cr: CRef) -> u32 {
+            self.data[ClauseAllocator::offset(cr) + 1].0
+        }
+        #[inline]
+        fn lits(&self, cr: CRef) -> &[Lit] {
+            let offset = ClauseAllocator::offset(cr) + HEADER;
+            &self.data[offset..offset + self.len(cr)]
+        }
+        #[inline]
+        fn lits_mut(&mut self, cr: CRef) -> &mut [Lit] {
+            let offset = ClauseAllocator::offset(cr) + HEADER;
+            let len = self.len(cr);
+            &mut self.data[offset..offset + len]
+        }
+        #[inline]
+        fn is_learnt(&self, cr: CRef) -> bool {
+            self.flags(cr) & LEARNT != 0
+        }
+        // the offset of the activity of a learnt clause
+        fn activity_offset(&self, cr: CRef) -> usize {
+            debug_assert!(self.is_learnt(cr));
+            ClauseAllocator::offset(cr) + HEADER + self.len(cr)
+        }
+        fn activity(&self, cr: CRef) -> f32 {
+            f32::from_bits(self.data[self.activity_offset(cr)].0)
+        }
+        fn set_activity(&mut self, cr: CRef, activity: f32) {
+            let offset = self.activity_offset(cr);
+            self.data[offset] = Lit(activity.to_bits());
+        }
+        /// Bump the activity of a learnt clause. Activities of `learnts` are scaled down if it is too large.
+        fn bump_activity(&mut self, cr: CRef, learnts: &[CRef]) {
+            let activity = self.activity(cr) as f64 + self.activity_inc;
+            self.set_activity(cr, activity as f32);
+            self.mark(cr, USED);
+            if activity > 1e20 {
+                for &cr in learnts.iter() {
+                    let activity = self.activity(cr);
+                    self.set_activity(cr, activity * 1e-20);
+                }
+                self.activity_inc *= 1e-20;
+            }
+        }
+        fn lbd(&self, cr: CRef) -> u32 {
+            self.flags(cr) >> LBD_SHIFT
+        }
+        fn set_lbd(&mut self, cr: CRef, lbd: usize) {
+            let lbd = std::cmp::min(lbd, (u32::MAX >> LBD_SHIFT) as usize) as u32;
+            let offset = ClauseAllocator::offset(cr) + 1;
+            self.data[offset].0 = self.data[offset].0 & ((1 << LBD_SHIFT) - 1) | lbd << LBD_SHIFT;
+        }
+        /// Drop literals after `len` of a clause. The dropped words are wasted until garbage collection.
+        fn shrink(&mut self, cr: CRef, len: usize) {
+            debug_assert!(len >= 2 && len <= self.len(cr));
+            let activity = if self.is_learnt(cr) {
+                Some(self.activity(cr))
+            } else {
+                None
+            };
+            self.wasted += self.len(cr) - len;
+            self.data[ClauseAllocator::offset(cr)] = Lit(len as u32);
+            if let Some(activity) = activity {
+                self.set_activity(cr, activity);
+            }
+        }
+        fn mark(&mut self, cr: CRef, flag: u32) {
+            self.data[ClauseAllocator::offset(cr) + 1].0 |= flag;
+        }
+        fn unmark(&mut self, cr: CRef, flag: u32) {
+            self.data[ClauseAllocator::offset(cr) + 1].0 &= !flag;
+        }
+        fn free(&mut self, cr: CRef) {
+            debug_assert!(self.flags(cr) & DELETED == 0);
+            self.data[ClauseAllocator::offset(cr) + 1].0 |= DELETED;
+            self.wasted += HEADER + self.len(cr) + self.is_learnt(cr) as usize;
+        }
+        /// Move a clause to `to` and return the new reference.
+        /// The old clause remembers the new reference in its first two literals
+        /// so that all references to the same clause are moved to the same place.
+        fn relocate(&mut self, cr: CRef, to: &mut ClauseAllocator) -> CRef {
+            let offset = ClauseAllocator::offset(cr);
+            if self.flags(cr) & RELOCATED != 0 {
+                let lo = self.data[offset + HEADER].0 as u64;
+                let hi = self.data[offset + HEADER + 1].0 as u64;
+                return CRef(NonZeroIdx::new((hi << 32 | lo) as Idx).unwrap());
+            }
+            debug_assert!(self.flags(cr) & DELETED == 0);
+            let new_cr = to.alloc(self.lits(cr), self.is_learnt(cr));
+            to.data[ClauseAllocator::offset(new_cr) + 1] = Lit(self.flags(cr));
+            if self.is_learnt(cr) {
+                to.set_activity(new_cr, self.activity(cr));
+            }
+            let new_offset = ClauseAllocator::offset(new_cr) as u64;
+            self.data[offset + 1].0 |= RELOCATED;
+            self.data[offset + HEADER] = Lit(new_offset as u32);
+            self.data[offset + HEADER + 1] = Lit((new_offset >> 32) as u32);
+            new_cr
         }
     }
 
@@ -67,7 +269,8 @@ pub mod solver {
         Unsat,
         Indeterminate,
     }
-    #[derive(PartialEq, Debug, Copy, Clone)]
+    #[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+    #[repr(u8)]
     pub enum LitBool {
         True = 0,
         False = 1,
@@ -112,7 +315,7 @@ pub mod solver {
         fn index(&self, lit: Lit) -> &Self::Output {
             #[cfg(feature = "unsafe")]
             unsafe {
-                &self.get_unchecked(lit.0 as usize)
+                self.get_unchecked(lit.0 as usize)
             }
             #[cfg(not(feature = "unsafe"))]
             &self[lit.0 as usize]
@@ -130,10 +333,642 @@ pub mod solver {
         }
     }
 
+    /// A model of a problem that `Solver::model` returns after `Sat`.
+    /// `model[var]` and `model[lit]` are the values of a variable and a literal.
+    /// A variable that is created after a model is found is `Undef`.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct Model {
+        assigns: Vec<LitBool>,
+    }
+    impl Model {
+        /// The literals that are true in a model in the order of variables.
+        pub fn lits(&self) -> impl Iterator<Item = Lit> + '_ {
+            self.assigns
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b != LitBool::Undef)
+                .map(|(v, &b)| Lit::new(v as u32, b == LitBool::True))
+        }
+    }
+    impl Index<Var> for Model {
+        type Output = LitBool;
+        fn index(&self, var: Var) -> &LitBool {
+            self.assigns.get(var.0 as usize).unwrap_or(&LitBool::Undef)
+        }
+    }
+    impl Index<Lit> for Model {
+        type Output = LitBool;
+        fn index(&self, lit: Lit) -> &LitBool {
+            match (self[lit.var()], lit.pos()) {
+                (LitBool::Undef, _) => &LitBool::Undef,
+                (b, positive) if (b == LitBool::True) == positive => &LitBool::True,
+                _ => &LitBool::False,
+            }
+        }
+    }
+
+    /// Evaluate a literal by assignments.
+    /// This is a function instead of a method so that it can be used while a clause is borrowed.
+    #[inline]
+    #[allow(clippy::ptr_arg)]
+    fn eval(assigns: &Vec<LitBool>, lit: Lit) -> LitBool {
+        LitBool::from(assigns[lit.var()] as i8 ^ lit.neg() as i8)
+    }
+
+    /// The index of the first literal in `lits` that is `value`(`equal`) or isn't `value`(`!equal`).
+    #[inline]
+    fn find_lit(
+        assigns: &Vec<LitBool>,
+        lits: &[Lit],
+        value: LitBool,
+        equal: bool,
+    ) -> Option<usize> {
+        lits.iter()
+            .position(|&lit| (eval(assigns, lit) == value) == equal)
+    }
+
+    /// A node of a resolution proof.
+    /// `clause` is derived from `antecedents` by resolution. An original clause has no antecedents.
+    /// Antecedents are in the order of unit propagation that derives `clause`.
+    #[derive(Debug, Clone)]
+    pub struct ProofNode {
+        pub clause: Clause,
+        pub antecedents: Vec<usize>,
+    }
+
+    /// A resolution proof that a solver builds while searching.
+    /// Nodes are in topological order. Antecedents of a node are always before the node.
+    #[derive(Debug, Default, Clone)]
+    pub struct Proof {
+        nodes: Vec<ProofNode>,
+        // the node of the empty clause
+        empty: Option<usize>,
+        // the nodes of the negations of failed assumptions
+        failed: Vec<usize>,
+    }
+
+    impl Proof {
+        /// All nodes of a proof.
+        pub fn nodes(&self) -> &[ProofNode] {
+            &self.nodes
+        }
+        /// The node of the empty clause if a solver refuted a problem.
+        pub fn empty_clause(&self) -> Option<usize> {
+            self.empty
+        }
+        /// The nodes of clauses that refute assumptions, one for each `Unsat` answer under assumptions in order.
+        /// A clause is the negation of assumptions that are enough for the answer.
+        pub fn failed(&self) -> &[usize] {
+            &self.failed
+        }
+        /// Nodes that the empty clause depends on in topological order.
+        pub fn used(&self) -> Vec<usize> {
+            let mut used = vec![false; self.nodes.len()];
+            let mut stack: Vec<usize> = self.empty.into_iter().collect();
+            while let Some(i) = stack.pop() {
+                if !used[i] {
+                    used[i] = true;
+                    stack.extend(self.nodes[i].antecedents.iter().copied());
+                }
+            }
+            (0..self.nodes.len()).filter(|&i| used[i]).collect()
+        }
+        /// Original clauses that the empty clause depends on(an unsatisfiable core).
+        pub fn core(&self) -> Vec<Clause> {
+            self.used()
+                .into_iter()
+                .filter(|&i| self.nodes[i].antecedents.is_empty())
+                .map(|i| self.nodes[i].clause.clone())
+                .collect()
+        }
+        /// Write all nodes in the TraceCheck format.
+        /// A line is `<id> <literals> 0 <antecedent ids> 0`. Ids start from 1.
+        pub fn write_tracecheck<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+            for (i, node) in self.nodes.iter().enumerate() {
+                write!(writer, "{}", i + 1)?;
+                write_lits(&mut writer, &node.clause)?;
+                write!(writer, " 0")?;
+                for &a in node.antecedents.iter() {
+                    write!(writer, " {}", a + 1)?;
+                }
+                writeln!(writer, " 0")?;
+            }
+            writer.flush()
+        }
+        /// Write the steps of resolution that derive the empty clause in plain words.
+        /// Original clauses are numbered in the order that they are added from 1.
+        /// A derived clause is split into steps that resolve two clauses on one variable.
+        /// It is meant for small problems because the explanation grows with the proof.
+        ///
+        /// ```text
+        /// clause 1: x1 or x2
+        /// clause 2: not x1
+        /// clause 3: not x2
+        /// step 4: from clause 1 and clause 2 on x1 derive x2
+        /// step 5: from step 4 and clause 3 on x2 derive the empty clause
+        /// The empty clause is reached, so the clauses are contradictory.
+        /// ```
+        pub fn write_explanation<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+            let empty = match self.empty {
+                Some(empty) => empty,
+                None => {
+                    writeln!(writer, "The empty clause isn't reached.")?;
+                    return writer.flush();
+                }
+            };
+            // names of nodes that are written
+            let mut names: HashMap<usize, String> = HashMap::new();
+            let mut originals = 0;
+            for (i, node) in self.nodes.iter().enumerate() {
+                if node.antecedents.is_empty() {
+                    originals += 1;
+                    names.insert(i, format!("clause {}", originals));
+                }
+            }
+            let used = self.used();
+            for &i in used.iter() {
+                if self.nodes[i].antecedents.is_empty() {
+                    writeln!(
+                        writer,
+                        "{}: {}",
+                        names[&i],
+                        clause_words(&self.nodes[i].clause)
+                    )?;
+                }
+            }
+            let mut step = originals;
+            for &i in used.iter() {
+                let node = &self.nodes[i];
+                if node.antecedents.is_empty() {
+                    continue;
+                }
+                // Literals that are propagated by antecedents under the negation of the clause.
+                let mut assigned: HashSet<Lit> = node.clause.iter().map(|&lit| !lit).collect();
+                let (last, rest) = node.antecedents.split_last().unwrap();
+                let propagated: Vec<Lit> = rest
+                    .iter()
+                    .map(|&a| {
+                        let lit = *self.nodes[a]
+                            .clause
+                            .iter()
+                            .find(|lit| !assigned.contains(&!**lit))
+                            .unwrap();
+                        assigned.insert(lit);
+                        lit
+                    })
+                    .collect();
+                // Resolve the conflict clause with the antecedents backwards.
+                let mut resolvent = self.nodes[*last].clause.clone();
+                let mut name = names[last].clone();
+                for (&a, &lit) in rest.iter().zip(propagated.iter()).rev() {
+                    if !resolvent.contains(&!lit) {
+                        continue;
+                    }
+                    resolvent.retain(|&l| l != !lit);
+                    for &l in self.nodes[a].clause.iter() {
+                        if l != lit && !resolvent.contains(&l) {
+                            resolvent.push(l);
+                        }
+                    }
+                    step += 1;
+                    writeln!(
+                        writer,
+                        "step {}: from {} and {} on x{} derive {}",
+                        step,
+                        name,
+                        names[&a],
+                        lit.var().0 + 1,
+                        clause_words(&resolvent)
+                    )?;
+                    name = format!("step {}", step);
+                }
+                names.insert(i, name);
+            }
+            debug_assert!(names.contains_key(&empty));
+            writeln!(
+                writer,
+                "The empty clause is reached, so the clauses are contradictory."
+            )?;
+            writer.flush()
+        }
+    }
+
+    /// A clause in words like `x1 or not x2`.
+    fn clause_words(clause: &[Lit]) -> String {
+        if clause.is_empty() {
+            return "the empty clause".to_string();
+        }
+        let words: Vec<String> = clause
+            .iter()
+            .map(|lit| {
+                if lit.pos() {
+                    format!("x{}", lit.var().0 + 1)
+                } else {
+                    format!("not x{}", lit.var().0 + 1)
+                }
+            })
+            .collect();
+        words.join(" or ")
+    }
+
+    fn write_lits<W: Write>(writer: &mut W, clause: &[Lit]) -> std::io::Result<()> {
+        for lit in clause.iter() {
+            write!(writer, " {}", lit)?;
+        }
+        Ok(())
+    }
+
+    /// The format of a proof file.
+    /// - `Drat` added and deleted clauses.
+    /// - `BinaryDrat` the binary encoding of `Drat`. A line is `a` or `d` and literals in variable-length bytes
+    ///   that end with `0`. Each byte has 7 bits of `2 * var + sign` from the lowest and the highest bit means that more bytes follow.
+    /// - `Frat` original, added, deleted and finalized clauses with ids.
+    ///   `hints` writes the ids of the antecedents of an added clause.
+    /// - `Lrat` added clauses with the ids of their antecedents and deleted ids.
+    ///   Original clauses are numbered from 1 in the order that they are added, so they must be added
+    ///   before the first search and `Solver::flush_proof`. Derived clauses are written after them.
+    #[derive(PartialEq, Debug, Copy, Clone)]
+    pub enum ProofFormat {
+        Drat,
+        BinaryDrat,
+        Frat { hints: bool },
+        Lrat,
+    }
+
+    // an added clause with its antecedents or a deleted clause of an LRAT proof
+    type LratLine = (usize, Clause, Option<Vec<usize>>);
+
+    /// A proof file.
+    struct ProofWriter {
+        writer: Box<dyn Write>,
+        format: ProofFormat,
+        // clauses that are neither deleted nor finalized(FRAT)
+        alive: HashMap<usize, Clause>,
+        // the first error of writing
+        error: Option<std::io::Error>,
+        // A proof is complete when the empty clause is derived.
+        complete: bool,
+        // LRAT ids of clauses that aren't deleted
+        lrat_id: HashMap<usize, usize>,
+        // the numbers of original and derived clauses(LRAT)
+        originals: usize,
+        derived: usize,
+        // Derived ids follow all original ids, so lines before the first search wait for it(LRAT).
+        pending: Option<Vec<LratLine>>,
+    }
+
+    impl std::fmt::Debug for ProofWriter {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.debug_struct("ProofWriter")
+                .field("format", &self.format)
+                .finish()
+        }
+    }
+
+    impl ProofWriter {
+        fn new(writer: Box<dyn Write>, format: ProofFormat) -> ProofWriter {
+            ProofWriter {
+                writer,
+                format,
+                alive: HashMap::new(),
+                error: None,
+                complete: false,
+                lrat_id: HashMap::new(),
+                originals: 0,
+                derived: 0,
+                pending: Some(vec![]),
+            }
+        }
+        /// Write a line of tokens. Writing stops at the first error. It is reported by `Solver::flush_proof`.
+        fn write_tokens(&mut self, tokens: &[String]) {
+            if self.error.is_some() || self.complete {
+                return;
+            }
+            if let Err(e) = writeln!(self.writer, "{}", tokens.join(" ")) {
+                self.error = Some(e);
+            }
+        }
+        /// Write a line `[prefix] [id] <literals> 0 [l <hints> 0]`.
+        fn line(
+            &mut self,
+            prefix: &str,
+            id: Option<usize>,
+            clause: &[Lit],
+            hints: Option<&[usize]>,
+        ) {
+            let mut tokens: Vec<String> = vec![];
+            if !prefix.is_empty() {
+                tokens.push(prefix.to_string());
+            }
+            if let Some(id) = id {
+                tokens.push((id + 1).to_string());
+            }
+            tokens.extend(clause.iter().map(|lit| lit.to_string()));
+            tokens.push("0".to_string());
+            if let Some(hints) = hints {
+                tokens.push("l".to_string());
+                tokens.extend(hints.iter().map(|h| (h + 1).to_string()));
+                tokens.push("0".to_string());
+            }
+            self.write_tokens(&tokens);
+        }
+        /// Write a binary line `a <literals> 0` or `d <literals> 0`(BinaryDrat).
+        fn binary_line(&mut self, prefix: u8, clause: &[Lit]) {
+            if self.error.is_some() || self.complete {
+                return;
+            }
+            let mut bytes = vec![prefix];
+            for &lit in clause.iter() {
+                let mut x = 2 * (lit.var().0 as u64 + 1) + lit.neg() as u64;
+                while x > 0x7f {
+                    bytes.push((x & 0x7f) as u8 | 0x80);
+                    x >>= 7;
+                }
+                bytes.push(x as u8);
+            }
+            bytes.push(0);
+            if let Err(e) = self.writer.write_all(&bytes) {
+                self.error = Some(e);
+            }
+        }
+        /// Write `<id> <literals> 0 <hints> 0` of an added clause or `<id> d <id> 0` of a deleted clause(LRAT).
+        fn lrat_line(&mut self, id: usize, clause: &[Lit], hints: Option<&[usize]>) {
+            let mut tokens: Vec<String> = vec![];
+            match hints {
+                Some(hints) => {
+                    // Only original clauses after a search have no ids. They are errors already.
+                    let hints: Option<Vec<usize>> =
+                        hints.iter().map(|h| self.lrat_id.get(h).copied()).collect();
+                    let hints = match hints {
+                        Some(hints) => hints,
+                        None => return,
+                    };
+                    self.derived += 1;
+                    let lrat = self.originals + self.derived;
+                    self.lrat_id.insert(id, lrat);
+                    tokens.push(lrat.to_string());
+                    tokens.extend(clause.iter().map(|lit| lit.to_string()));
+                    tokens.push("0".to_string());
+                    tokens.extend(hints.iter().map(|h| h.to_string()));
+                }
+                None => match self.lrat_id.remove(&id) {
+                    Some(lrat) => {
+                        tokens.push((self.originals + self.derived).to_string());
+                        tokens.push("d".to_string());
+                        tokens.push(lrat.to_string());
+                    }
+                    None => return,
+                },
+            }
+            tokens.push("0".to_string());
+            self.write_tokens(&tokens);
+        }
+        /// Write the lines that wait for the first search or `Solver::flush_proof`(LRAT).
+        /// They may end with the empty clause.
+        fn start(&mut self) {
+            if let Some(pending) = self.pending.take() {
+                let complete = std::mem::replace(&mut self.complete, false);
+                for (id, clause, hints) in pending {
+                    self.lrat_line(id, &clause, hints.as_deref());
+                }
+                self.complete = complete;
+            }
+        }
+        fn add(&mut self, id: usize, clause: &[Lit], antecedents: &[usize]) {
+            // Original clauses after the empty clause still take their ids(LRAT).
+            if self.format == ProofFormat::Lrat && antecedents.is_empty() && self.pending.is_some()
+            {
+                self.originals += 1;
+                self.lrat_id.insert(id, self.originals);
+                return;
+            }
+            if self.complete {
+                return;
+            }
+            match self.format {
+                ProofFormat::Drat => {
+                    if !antecedents.is_empty() {
+                        self.line("", None, clause, None);
+                    }
+                }
+                ProofFormat::BinaryDrat => {
+                    if !antecedents.is_empty() {
+                        self.binary_line(b'a', clause);
+                    }
+                }
+                ProofFormat::Frat { hints } => {
+                    if antecedents.is_empty() {
+                        self.line("o", Some(id), clause, None);
+                    } else {
+                        let hints = if hints { Some(antecedents) } else { None };
+                        self.line("a", Some(id), clause, hints);
+                    }
+                    self.alive.insert(id, clause.to_vec());
+                }
+                ProofFormat::Lrat => {
+                    if !antecedents.is_empty() {
+                        match self.pending.as_mut() {
+                            Some(pending) => {
+                                pending.push((id, clause.to_vec(), Some(antecedents.to_vec())))
+                            }
+                            None => self.lrat_line(id, clause, Some(antecedents)),
+                        }
+                    } else if self.error.is_none() {
+                        self.error = Some(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            "An LRAT proof can't have clauses that are added after a search",
+                        ));
+                    }
+                }
+            }
+        }
+        fn delete(&mut self, id: usize, clause: &[Lit]) {
+            if self.complete {
+                return;
+            }
+            match self.format {
+                ProofFormat::Drat => self.line("d", None, clause, None),
+                ProofFormat::BinaryDrat => self.binary_line(b'd', clause),
+                ProofFormat::Frat { .. } => {
+                    self.alive.remove(&id);
+                    self.line("d", Some(id), clause, None);
+                }
+                ProofFormat::Lrat => match self.pending.as_mut() {
+                    Some(pending) => pending.push((id, vec![], None)),
+                    None => self.lrat_line(id, clause, None),
+                },
+            }
+        }
+        /// Finalize all clauses that are alive after the empty clause is derived(FRAT).
+        fn finalize(&mut self) {
+            let mut alive: Vec<(usize, Clause)> = self.alive.drain().collect();
+            alive.sort();
+            for (id, clause) in alive {
+                self.line("f", Some(id), &clause, None);
+            }
+            self.complete = true;
+            if self.error.is_none() {
+                self.error = self.writer.flush().err();
+            }
+        }
+    }
+
+    /// The ids of clauses and the outputs of a proof.
+    /// An id is the index of a node of an in-memory proof.
+    #[derive(Debug, Default)]
+    struct ProofLog {
+        proof: Option<Proof>,
+        writer: Option<ProofWriter>,
+        next_id: usize,
+        // ids of clauses in the clause database
+        clause_id: HashMap<CRef, usize>,
+        // ids of unit clauses of variables that are assigned at the root level
+        unit_id: Vec<Option<usize>>,
+    }
+
+    impl ProofLog {
+        fn add(&mut self, clause: &[Lit], antecedents: Vec<usize>) -> usize {
+            let id = self.next_id;
+            self.next_id += 1;
+            if let Some(writer) = self.writer.as_mut() {
+                writer.add(id, clause, &antecedents);
+            }
+            if let Some(proof) = self.proof.as_mut() {
+                proof.nodes.push(ProofNode {
+                    clause: clause.to_vec(),
+                    antecedents,
+                });
+            }
+            id
+        }
+        fn original(&mut self, clause: &[Lit]) -> usize {
+            self.add(clause, vec![])
+        }
+        /// Add a clause that is derived by unit propagation from antecedents.
+        /// Antecedents are sorted in the order of unit propagation. Unnecessary ones are dropped.
+        fn derive(&mut self, clause: &[Lit], antecedents: Vec<(usize, Clause)>) -> usize {
+            let mut assigned: HashSet<Lit> = clause.iter().map(|&lit| !lit).collect();
+            let mut used = vec![false; antecedents.len()];
+            let mut hints = vec![];
+            'propagate: loop {
+                for (i, (id, c)) in antecedents.iter().enumerate() {
+                    if used[i] || c.iter().any(|lit| assigned.contains(lit)) {
+                        continue;
+                    }
+                    let mut unassigned = c.iter().filter(|lit| !assigned.contains(&!**lit));
+                    match (unassigned.next(), unassigned.next()) {
+                        (None, _) => {
+                            // conflict
+                            hints.push(*id);
+                            break 'propagate;
+                        }
+                        (Some(&lit), None) => {
+                            used[i] = true;
+                            hints.push(*id);
+                            assigned.insert(lit);
+                            continue 'propagate;
+                        }
+                        _ => {}
+                    }
+                }
+                debug_assert!(false, "a clause is not derived from antecedents");
+                break;
+            }
+            self.add(clause, hints)
+        }
+        fn delete(&mut self, cr: CRef, clause: &[Lit]) {
+            if let Some(id) = self.clause_id.remove(&cr) {
+                if let Some(writer) = self.writer.as_mut() {
+                    writer.delete(id, clause);
+                }
+            }
+        }
+        fn failed(&mut self, id: usize) {
+            if let Some(proof) = self.proof.as_mut() {
+                proof.failed.push(id);
+            }
+        }
+        fn empty(&mut self, id: usize) {
+            if let Some(proof) = self.proof.as_mut() {
+                proof.empty = Some(id);
+            }
+            if let Some(writer) = self.writer.as_mut() {
+                writer.finalize();
+            }
+        }
+        /// The id and the clause of the unit clause that falsifies `lit` at the root level.
+        fn unit(&self, lit: Lit) -> (usize, Clause) {
+            let id = self.unit_id[lit.var().0 as usize]
+                .expect("a root level assignment without a proof");
+            (id, vec![!lit])
+        }
+        fn set_unit(&mut self, var: Var, id: usize) {
+            let v = var.0 as usize;
+            if self.unit_id.len() <= v {
+                self.unit_id.resize(v + 1, None);
+            }
+            self.unit_id[v] = Some(id);
+        }
+    }
+
+    /// A stack of clauses that are removed by model-affecting simplifications
+    /// such as variable elimination, blocked clause elimination and equivalent literal substitution.
+    /// Each clause has a witness literal. A model of a simplified formula is extended to a model of the original formula
+    /// by making the witness true for each removed clause that it doesn't satisfy, from the top of the stack.
+    #[derive(Debug, Default, Clone)]
+    pub struct Reconstruction {
+        // pairs of a witness literal and a removed clause
+        stack: Vec<(Lit, Clause)>,
+    }
+
+    impl Reconstruction {
+        /// Record a clause that is removed by eliminating `witness` or blocked on `witness`.
+        /// # Arguments
+        /// * `witness` - A literal in `clause`
+        /// * `clause` - A removed clause
+        pub fn push_clause(&mut self, witness: Lit, clause: &[Lit]) {
+            debug_assert!(clause.contains(&witness));
+            self.stack.push((witness, clause.to_vec()));
+        }
+        /// Record that `lit` is substituted by an equivalent literal `repr`.
+        pub fn push_equivalence(&mut self, lit: Lit, repr: Lit) {
+            self.stack.push((lit, vec![lit, !repr]));
+            self.stack.push((!lit, vec![!lit, repr]));
+        }
+        pub fn is_empty(&self) -> bool {
+            self.stack.is_empty()
+        }
+        pub fn len(&self) -> usize {
+            self.stack.len()
+        }
+        /// Variables of witness literals.
+        pub fn vars(&self) -> impl Iterator<Item = Var> + '_ {
+            self.stack.iter().map(|(witness, _)| witness.var())
+        }
+        /// Extend a model of a simplified formula to a model of the original formula.
+        /// Variables of witnesses that are unassigned are false unless a removed clause needs them.
+        pub fn extend(&self, model: &mut [LitBool]) {
+            for (witness, _) in self.stack.iter() {
+                if model[witness.var().0 as usize] == LitBool::Undef {
+                    model[witness.var().0 as usize] = LitBool::False;
+                }
+            }
+            for (witness, clause) in self.stack.iter().rev() {
+                let satisfied = clause.iter().any(|lit| {
+                    let value = model[lit.var().0 as usize];
+                    (value == LitBool::True && lit.pos()) || (value == LitBool::False && lit.neg())
+                });
+                if !satisfied {
+                    model[witness.var().0 as usize] = LitBool::from(witness.neg() as i8);
+                }
+            }
+        }
+    }
+
     #[derive(Debug, Clone)]
     struct Heap {
         heap: Vec<Var>,
-        indices: Vec<Option<usize>>,
+        indices: Vec<Option<Idx>>,
         activity: Vec<f64>,
         bump_inc: f64,
     }
@@ -148,15 +983,6 @@ pub mod solver {
         }
     }
     impl Heap {
-        pub fn new(n: usize, bump_inc: f64) -> Heap {
-            Heap {
-                heap: (0..n).map(|x| Var(x as u32)).collect(),
-                indices: (0..n).map(Some).collect(),
-                activity: vec![0.0; n],
-                bump_inc,
-            }
-        }
-
         fn gt(&self, left: Var, right: Var) -> bool {
             self.activity[left] > self.activity[right]
         }
@@ -167,8 +993,8 @@ pub mod solver {
             }
             Some(self.heap[0])
         }
-        pub fn decay_inc(&mut self) {
-            self.bump_inc *= 1.0 / 0.95;
+        pub fn decay_inc(&mut self, decay: f64) {
+            self.bump_inc *= 1.0 / decay;
         }
         pub fn bump_activity(&mut self, v: Var) {
             self.activity[v] += self.bump_inc;
@@ -180,16 +1006,22 @@ pub mod solver {
                 self.bump_inc *= 1e-100;
             }
             if self.in_heap(v) {
-                let idx = self.indices[v].unwrap();
+                let idx = self.indices[v].unwrap() as usize;
+                self.up(idx);
+            }
+        }
+        fn add_activity(&mut self, v: Var, score: f64) {
+            self.activity[v] += score;
+            if self.in_heap(v) {
+                let idx = self.indices[v].unwrap() as usize;
                 self.up(idx);
             }
         }
-        #[allow(dead_code)]
         fn update(&mut self, v: Var) {
             if !self.in_heap(v) {
                 self.push(v);
             } else {
-                let idx = self.indices[v].unwrap();
+                let idx = self.indices[v].unwrap() as usize;
                 self.up(idx);
                 self.down(idx);
             }
@@ -206,7 +1038,7 @@ pub mod solver {
                     break;
                 }
                 self.heap[idx] = self.heap[par];
-                self.indices[self.heap[par]] = Some(idx);
+                self.indices[self.heap[par]] = Some(idx as Idx);
                 idx = par;
                 if idx == 0 {
                     break;
@@ -214,7 +1046,7 @@ pub mod solver {
                 par = (par - 1) >> 1;
             }
             self.heap[idx] = x;
-            self.indices[x] = Some(idx);
+            self.indices[x] = Some(idx as Idx);
         }
 
         fn pop(&mut self) -> Option<Var> {
@@ -248,14 +1080,14 @@ pub mod solver {
                 };
                 if self.gt(self.heap[child], x) {
                     self.heap[idx] = self.heap[child];
-                    self.indices[self.heap[idx]] = Some(idx);
+                    self.indices[self.heap[idx]] = Some(idx as Idx);
                     idx = child;
                 } else {
                     break;
                 }
             }
             self.heap[idx] = x;
-            self.indices[x] = Some(idx);
+            self.indices[x] = Some(idx as Idx);
         }
 
         fn push(&mut self, v: Var) {
@@ -266,9 +1098,9 @@ pub mod solver {
                 self.indices.push(None);
                 self.activity.push(0.0);
             }
-            self.indices[v] = Some(self.heap.len());
+            self.indices[v] = Some(self.heap.len() as Idx);
             self.heap.push(v);
-            self.up(self.indices[v].unwrap());
+            self.up(self.indices[v].unwrap() as usize);
         }
 
         fn in_heap(&mut self, v: Var) -> bool {
@@ -276,401 +1108,3764 @@ pub mod solver {
         }
     }
 
-    #[derive(Debug, Default)]
-    // A SAT Solver
-    pub struct Solver {
-        // the number of variables
-        n: usize,
-        // assignments for each variable
-        pub assigns: Vec<LitBool>,
-        polarity: Vec<bool>,
-        // original clauses
-        clauses: Vec<CRef>,
-        // learnt clauses
-        learnts: Vec<CRef>,
-        // clauses that may be conflicted or propagated if a `lit` is false.
-        watchers: Vec<Vec<CWRef>>,
-        // a clause index represents that a variable is forced to be assigned.
-        reason: Vec<Option<CWRef>>,
-        seen: Vec<bool>,
-        ccmin_stack: VecDeque<Lit>,
-        ccmin_clear: Vec<Lit>,
-        // decision level(0: unassigned, 1: minimum level)
-        level: Vec<usize>,
-        // assigned variables
-        que: VecDeque<Lit>,
-        // the head index of `que` points unprocessed elements
-        head: usize,
-        // the solver status. this value may be set by the functions `add_clause` and `solve`.
-        pub status: Option<Status>,
-        order_heap: Heap,
-        skip_simplify: bool,
+    /// A heuristic that selects decision variables.
+    /// A heuristic of `Config` is a prototype. A solver keeps its own copy by `start` over solve calls.
+    /// A solver notifies a heuristic of these events.
+    /// - `new_var` a new variable is created. Variables are created in order from `Var(0)`.
+    /// - `assign` a variable is assigned.
+    /// - `unassign` a variable is unassigned by backtracking. It is a candidate of decisions again.
+    /// - `bump` a variable takes part in conflict analysis.
+    /// - `decay` a conflict is analyzed.
+    /// - `add_score` a variable gets an initial score from original clauses, e.g. a Jeroslow-Wang score. It may be ignored.
+    pub trait BranchingHeuristic: std::fmt::Debug + Send + Sync {
+        fn new_var(&mut self, v: Var);
+        fn add_score(&mut self, _v: Var, _score: f64) {}
+        fn assign(&mut self, _v: Var) {}
+        fn unassign(&mut self, v: Var);
+        fn bump(&mut self, v: Var);
+        fn decay(&mut self);
+        /// Pop the next candidate of a decision. A solver skips candidates that are assigned and asks again.
+        /// `None` means that no unassigned variable is left.
+        fn select(&mut self) -> Option<Var>;
+        /// A new heuristic in its initial state that has no variables.
+        fn start(&self) -> Box<dyn BranchingHeuristic>;
+        /// A copy of a heuristic in its current state for a copy of a solver.
+        /// `None` makes the copy start over by `start`.
+        fn duplicate(&self) -> Option<Box<dyn BranchingHeuristic>> {
+            None
+        }
     }
 
-    impl Solver {
-        /// Create a new `Solver` struct
-        ///
-        /// # Arguments
-        /// * `n` - The number of variable
-        /// * `clauses` - All clauses that solver solves
-        pub fn new(n: usize, clauses: &[Vec<Lit>]) -> Solver {
-            let mut solver = Solver {
-                n,
-                que: VecDeque::new(),
-                head: 0,
-                clauses: Vec::new(),
-                learnts: Vec::new(),
-                reason: vec![None; n],
-                level: vec![0; n],
-                seen: vec![false; n],
-                ccmin_stack: VecDeque::new(),
+    /// VSIDS(Variable State Independent Decaying Sum).
+    /// Select a variable that has the largest activity. Activities decay by `decay` on each conflict.
+    #[derive(Debug, Clone)]
+    pub struct Vsids {
+        pub decay: f64,
+        heap: Heap,
+    }
+    impl Vsids {
+        pub fn new(decay: f64) -> Vsids {
+            Vsids {
+                decay,
+                heap: Heap::default(),
+            }
+        }
+    }
+    impl Default for Vsids {
+        fn default() -> Self {
+            Vsids::new(0.95)
+        }
+    }
+    impl BranchingHeuristic for Vsids {
+        fn new_var(&mut self, v: Var) {
+            self.heap.push(v);
+        }
+        fn unassign(&mut self, v: Var) {
+            self.heap.push(v);
+        }
+        fn add_score(&mut self, v: Var, score: f64) {
+            self.heap.add_activity(v, score);
+        }
+        fn bump(&mut self, v: Var) {
+            self.heap.bump_activity(v);
+        }
+        fn decay(&mut self) {
+            self.heap.decay_inc(self.decay);
+        }
+        fn select(&mut self) -> Option<Var> {
+            self.heap.pop()
+        }
+        fn start(&self) -> Box<dyn BranchingHeuristic> {
+            Box::new(Vsids::new(self.decay))
+        }
+        fn duplicate(&self) -> Option<Box<dyn BranchingHeuristic>> {
+            Some(Box::new(self.clone()))
+        }
+    }
+
+    /// VMTF(Variable Move To Front).
+    /// Variables are kept in a queue and a bumped variable is moved to the front.
+    /// Select the unassigned variable that is bumped most recently.
+    #[derive(Debug, Clone, Default)]
+    pub struct Vmtf {
+        // the neighbors of each variable. `prev` is bumped later and `next` is bumped earlier.
+        prev: Vec<Option<Var>>,
+        next: Vec<Option<Var>>,
+        // the order of the last bump of each variable
+        stamp: Vec<u64>,
+        front: Option<Var>,
+        // All variables in front of `search` are assigned.
+        search: Option<Var>,
+        clock: u64,
+    }
+    impl Vmtf {
+        fn move_to_front(&mut self, v: Var) {
+            // unlink
+            if let Some(p) = self.prev[v] {
+                self.next[p] = self.next[v];
+            } else if self.front == Some(v) {
+                self.front = self.next[v];
+            }
+            if let Some(n) = self.next[v] {
+                self.prev[n] = self.prev[v];
+            }
+            // link at the front
+            self.prev[v] = None;
+            self.next[v] = self.front;
+            if let Some(f) = self.front {
+                self.prev[f] = Some(v);
+            }
+            self.front = Some(v);
+            self.clock += 1;
+            self.stamp[v] = self.clock;
+        }
+    }
+    impl BranchingHeuristic for Vmtf {
+        fn new_var(&mut self, v: Var) {
+            self.prev.push(None);
+            self.next.push(None);
+            self.stamp.push(0);
+            self.move_to_front(v);
+            self.search = Some(v);
+        }
+        fn unassign(&mut self, v: Var) {
+            match self.search {
+                Some(s) if self.stamp[s] >= self.stamp[v] => {}
+                _ => self.search = Some(v),
+            }
+        }
+        fn bump(&mut self, v: Var) {
+            // A variable in conflict analysis is assigned. `search` stays valid because it moves to the front.
+            self.move_to_front(v);
+        }
+        fn decay(&mut self) {}
+        fn select(&mut self) -> Option<Var> {
+            let v = self.search?;
+            self.search = self.next[v];
+            Some(v)
+        }
+        fn start(&self) -> Box<dyn BranchingHeuristic> {
+            Box::new(Vmtf::default())
+        }
+        fn duplicate(&self) -> Option<Box<dyn BranchingHeuristic>> {
+            Some(Box::new(self.clone()))
+        }
+    }
+
+    /// LRB(Learning Rate Based branching).
+    /// The reward of a variable is the number of conflicts that it takes part in while it is assigned, per conflict.
+    /// Select a variable that has the largest exponential moving average of rewards.
+    /// The step size starts at `alpha` and decreases to `min_alpha`.
+    #[derive(Debug, Clone)]
+    pub struct Lrb {
+        pub alpha: f64,
+        pub min_alpha: f64,
+        step: f64,
+        heap: Heap,
+        // the number of conflicts when each variable is assigned
+        assigned: Vec<Option<u64>>,
+        participated: Vec<u64>,
+        conflicts: u64,
+    }
+    impl Lrb {
+        pub fn new(alpha: f64, min_alpha: f64) -> Lrb {
+            Lrb {
+                alpha,
+                min_alpha,
+                step: alpha,
+                heap: Heap::default(),
+                assigned: Vec::new(),
+                participated: Vec::new(),
+                conflicts: 0,
+            }
+        }
+    }
+    impl Default for Lrb {
+        fn default() -> Self {
+            Lrb::new(0.4, 0.06)
+        }
+    }
+    impl BranchingHeuristic for Lrb {
+        fn new_var(&mut self, v: Var) {
+            self.assigned.push(None);
+            self.participated.push(0);
+            self.heap.push(v);
+        }
+        fn assign(&mut self, v: Var) {
+            self.assigned[v] = Some(self.conflicts);
+            self.participated[v] = 0;
+        }
+        fn unassign(&mut self, v: Var) {
+            if let Some(at) = self.assigned[v].take() {
+                let interval = self.conflicts - at;
+                if interval > 0 {
+                    let reward = self.participated[v] as f64 / interval as f64;
+                    let q = &mut self.heap.activity[v];
+                    *q = (1.0 - self.step) * *q + self.step * reward;
+                }
+            }
+            self.heap.update(v);
+        }
+        fn add_score(&mut self, v: Var, score: f64) {
+            self.heap.add_activity(v, score);
+        }
+        fn bump(&mut self, v: Var) {
+            self.participated[v] += 1;
+        }
+        fn decay(&mut self) {
+            self.conflicts += 1;
+            self.step = (self.step - 1e-6).max(self.min_alpha);
+        }
+        fn select(&mut self) -> Option<Var> {
+            self.heap.pop()
+        }
+        fn start(&self) -> Box<dyn BranchingHeuristic> {
+            Box::new(Lrb::new(self.alpha, self.min_alpha))
+        }
+        fn duplicate(&self) -> Option<Box<dyn BranchingHeuristic>> {
+            Some(Box::new(self.clone()))
+        }
+    }
+
+    /// Switch between two heuristics every `period` conflicts, e.g. LRB and VSIDS phases like MapleCOMSPS.
+    /// Both heuristics see every event, so the inactive one keeps its candidates and scores up to date.
+    #[derive(Debug)]
+    pub struct Alternating {
+        pub first: Box<dyn BranchingHeuristic>,
+        pub second: Box<dyn BranchingHeuristic>,
+        pub period: u64,
+        conflicts: u64,
+    }
+    impl Alternating {
+        pub fn new(
+            first: Box<dyn BranchingHeuristic>,
+            second: Box<dyn BranchingHeuristic>,
+            period: u64,
+        ) -> Alternating {
+            Alternating {
+                first,
+                second,
+                period,
+                conflicts: 0,
+            }
+        }
+    }
+    impl Default for Alternating {
+        fn default() -> Self {
+            Alternating::new(Box::new(Lrb::default()), Box::new(Vsids::default()), 10000)
+        }
+    }
+    impl BranchingHeuristic for Alternating {
+        fn new_var(&mut self, v: Var) {
+            self.first.new_var(v);
+            self.second.new_var(v);
+        }
+        fn assign(&mut self, v: Var) {
+            self.first.assign(v);
+            self.second.assign(v);
+        }
+        fn unassign(&mut self, v: Var) {
+            self.first.unassign(v);
+            self.second.unassign(v);
+        }
+        fn add_score(&mut self, v: Var, score: f64) {
+            self.first.add_score(v, score);
+            self.second.add_score(v, score);
+        }
+        fn bump(&mut self, v: Var) {
+            self.first.bump(v);
+            self.second.bump(v);
+        }
+        fn decay(&mut self) {
+            self.first.decay();
+            self.second.decay();
+            self.conflicts += 1;
+        }
+        fn select(&mut self) -> Option<Var> {
+            // A candidate that is popped by one heuristic is still in the other until it is unassigned.
+            if (self.conflicts / self.period.max(1)) & 1 == 0 {
+                self.first.select()
+            } else {
+                self.second.select()
+            }
+        }
+        fn start(&self) -> Box<dyn BranchingHeuristic> {
+            Box::new(Alternating::new(
+                self.first.start(),
+                self.second.start(),
+                self.period,
+            ))
+        }
+        fn duplicate(&self) -> Option<Box<dyn BranchingHeuristic>> {
+            Some(Box::new(Alternating {
+                first: self.first.duplicate()?,
+                second: self.second.duplicate()?,
+                period: self.period,
+                conflicts: self.conflicts,
+            }))
+        }
+    }
+
+    /// A learnt clause that `DeletionPolicy` sees.
+    /// - `len` the number of literals
+    /// - `lbd` the number of decision levels of literals when it is learnt(literal block distance)
+    /// - `activity` it is bumped whenever a clause takes part in conflict analysis and decays over conflicts
+    /// - `used` whether a clause has taken part in conflict analysis since the last reduction
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct LearntClause {
+        pub len: usize,
+        pub lbd: u32,
+        pub activity: f32,
+        pub used: bool,
+    }
+
+    /// A policy that decides which learnt clauses are deleted when the learnt clause database is reduced.
+    /// Binary clauses and reasons of current assignments are never deleted even if they are selected.
+    pub trait DeletionPolicy: std::fmt::Debug + Send + Sync {
+        /// Set `deleted[i]` to delete `learnts[i]`.
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]);
+    }
+
+    /// Delete `half` of clauses that are the worst by `key`. Smaller keys are better.
+    fn delete_worst<K: PartialOrd, F: Fn(&LearntClause) -> K>(
+        learnts: &[LearntClause],
+        deleted: &mut [bool],
+        candidates: Vec<usize>,
+        key: F,
+    ) {
+        let mut candidates = candidates;
+        candidates.sort_by(|&a, &b| {
+            key(&learnts[a])
+                .partial_cmp(&key(&learnts[b]))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let half = candidates.len() / 2;
+        candidates[half..].iter().for_each(|&i| deleted[i] = true);
+    }
+
+    /// Delete the longer half of learnt clauses.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SizePolicy;
+    impl DeletionPolicy for SizePolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            delete_worst(learnts, deleted, (0..learnts.len()).collect(), |c| c.len);
+        }
+    }
+
+    /// Delete the half of learnt clauses that have the lowest activities.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct ActivityPolicy;
+    impl DeletionPolicy for ActivityPolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            delete_worst(learnts, deleted, (0..learnts.len()).collect(), |c| {
+                -c.activity
+            });
+        }
+    }
+
+    /// Keep clauses whose LBD is at most `core` forever.
+    /// The half of the other clauses with higher LBDs are deleted. Ties are broken by activities.
+    #[derive(Debug, Clone, Copy)]
+    pub struct LbdPolicy {
+        pub core: u32,
+    }
+    impl Default for LbdPolicy {
+        fn default() -> Self {
+            LbdPolicy { core: 2 }
+        }
+    }
+    impl DeletionPolicy for LbdPolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            let candidates = (0..learnts.len())
+                .filter(|&i| learnts[i].lbd > self.core)
+                .collect();
+            delete_worst(learnts, deleted, candidates, |c| (c.lbd, -c.activity));
+        }
+    }
+
+    /// Tiers of learnt clauses by LBD like Glucose and CaDiCaL.
+    /// - core(LBD <= `core`): kept forever
+    /// - tier2(LBD <= `tier2`): kept while they are used between reductions
+    /// - local: the half of them with higher LBDs are deleted
+    ///
+    /// A tier2 clause that isn't used since the last reduction is reduced as a local clause.
+    #[derive(Debug, Clone, Copy)]
+    pub struct TieredPolicy {
+        pub core: u32,
+        pub tier2: u32,
+    }
+    impl Default for TieredPolicy {
+        fn default() -> Self {
+            TieredPolicy { core: 2, tier2: 6 }
+        }
+    }
+    impl DeletionPolicy for TieredPolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            let candidates = (0..learnts.len())
+                .filter(|&i| {
+                    let c = &learnts[i];
+                    c.lbd > self.core && (c.lbd > self.tier2 || !c.used)
+                })
+                .collect();
+            delete_worst(learnts, deleted, candidates, |c| (c.lbd, -c.activity));
+        }
+    }
+
+    /// Delete all clauses that are longer than `max_len` and the longer half of the others.
+    #[derive(Debug, Clone, Copy)]
+    pub struct SizeBoundPolicy {
+        pub max_len: usize,
+    }
+    impl DeletionPolicy for SizeBoundPolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            let mut candidates = vec![];
+            for (i, c) in learnts.iter().enumerate() {
+                if c.len > self.max_len {
+                    deleted[i] = true;
+                } else {
+                    candidates.push(i);
+                }
+            }
+            delete_worst(learnts, deleted, candidates, |c| c.len);
+        }
+    }
+
+    /// A policy that decides when a solver restarts.
+    /// A policy of `Config` is a prototype. Each `solve` call searches with a copy by `start`.
+    pub trait RestartPolicy: std::fmt::Debug + Send + Sync {
+        /// Called after each conflict with the LBD of a learnt clause and the number of assigned variables.
+        /// Return `true` to restart before the next decision.
+        fn on_conflict(&mut self, lbd: u32, trail_len: usize) -> bool;
+        /// Called when a solver restarts.
+        fn on_restart(&mut self) {}
+        /// A copy of a policy in its initial state.
+        fn start(&self) -> Box<dyn RestartPolicy>;
+    }
+
+    /// Restart after `first` conflicts and whenever the number of conflicts grows by a factor `inc`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct GeometricRestart {
+        pub first: f64,
+        pub inc: f64,
+        conflicts: u64,
+        limit: f64,
+    }
+    impl GeometricRestart {
+        pub fn new(first: f64, inc: f64) -> GeometricRestart {
+            GeometricRestart {
+                first,
+                inc,
+                conflicts: 0,
+                limit: first,
+            }
+        }
+    }
+    impl RestartPolicy for GeometricRestart {
+        fn on_conflict(&mut self, _lbd: u32, _trail_len: usize) -> bool {
+            self.conflicts += 1;
+            if self.conflicts as f64 >= self.limit {
+                self.limit *= self.inc;
+                true
+            } else {
+                false
+            }
+        }
+        fn start(&self) -> Box<dyn RestartPolicy> {
+            Box::new(GeometricRestart::new(self.first, self.inc))
+        }
+    }
+
+    /// Restart after `unit * luby(i)` conflicts for the `i`-th restart. `luby` is `1, 1, 2, 1, 1, 2, 4, ...`.
+    #[derive(Debug, Clone, Copy)]
+    pub struct LubyRestart {
+        pub unit: u64,
+        restarts: u64,
+        conflicts: u64,
+    }
+    impl LubyRestart {
+        pub fn new(unit: u64) -> LubyRestart {
+            LubyRestart {
+                unit,
+                restarts: 0,
+                conflicts: 0,
+            }
+        }
+    }
+    /// The `i`-th(from 0) element of the Luby sequence.
+    fn luby(mut i: u64) -> u64 {
+        // Find the finite subsequence that contains `i` and its size `2^k - 1`.
+        let (mut size, mut k) = (1, 0);
+        while size < i + 1 {
+            k += 1;
+            size = 2 * size + 1;
+        }
+        while size - 1 != i {
+            size = (size - 1) >> 1;
+            k -= 1;
+            i %= size;
+        }
+        1 << k
+    }
+    impl RestartPolicy for LubyRestart {
+        fn on_conflict(&mut self, _lbd: u32, _trail_len: usize) -> bool {
+            self.conflicts += 1;
+            self.conflicts >= self.unit * luby(self.restarts)
+        }
+        fn on_restart(&mut self) {
+            self.restarts += 1;
+            self.conflicts = 0;
+        }
+        fn start(&self) -> Box<dyn RestartPolicy> {
+            Box::new(LubyRestart::new(self.unit))
+        }
+    }
+
+    /// Glucose-style dynamic restarts.
+    /// Restart when the average LBD of `window` recent learnt clauses times `k` exceeds the average of all of them.
+    /// A restart is blocked when the trail is much longer than usual because the search may be close to a model.
+    #[derive(Debug, Clone)]
+    pub struct GlucoseRestart {
+        pub window: usize,
+        pub k: f64,
+        lbds: VecDeque<u32>,
+        lbd_sum: u64,
+        conflicts: u64,
+        lbd_total: f64,
+        trail_average: f64,
+    }
+    impl GlucoseRestart {
+        pub fn new(window: usize, k: f64) -> GlucoseRestart {
+            GlucoseRestart {
+                window,
+                k,
+                lbds: VecDeque::new(),
+                lbd_sum: 0,
+                conflicts: 0,
+                lbd_total: 0.0,
+                trail_average: 0.0,
+            }
+        }
+    }
+    impl Default for GlucoseRestart {
+        fn default() -> Self {
+            GlucoseRestart::new(50, 0.8)
+        }
+    }
+    impl RestartPolicy for GlucoseRestart {
+        fn on_conflict(&mut self, lbd: u32, trail_len: usize) -> bool {
+            self.conflicts += 1;
+            self.lbd_total += lbd as f64;
+            // an exponential moving average of the trail length
+            self.trail_average +=
+                (trail_len as f64 - self.trail_average) / 5000.0f64.min(self.conflicts as f64);
+            if self.conflicts > 10000
+                && self.lbds.len() == self.window
+                && trail_len as f64 > 1.4 * self.trail_average
+            {
+                self.lbds.clear();
+                self.lbd_sum = 0;
+            }
+            self.lbds.push_back(lbd);
+            self.lbd_sum += lbd as u64;
+            if self.lbds.len() > self.window {
+                self.lbd_sum -= self.lbds.pop_front().unwrap() as u64;
+            }
+            self.lbds.len() == self.window
+                && self.lbd_sum as f64 / self.window as f64 * self.k
+                    > self.lbd_total / self.conflicts as f64
+        }
+        fn on_restart(&mut self) {
+            self.lbds.clear();
+            self.lbd_sum = 0;
+        }
+        fn start(&self) -> Box<dyn RestartPolicy> {
+            Box::new(GlucoseRestart::new(self.window, self.k))
+        }
+    }
+
+    /// How literals of a learnt clause are removed by self-subsuming resolution with their reasons.
+    /// - `None` no literals are removed
+    /// - `Local` a literal is removed if the other literals of its reason are in a learnt clause
+    /// - `Recursive` a literal is removed if it is implied by the other literals through reasons(MiniSat)
+    #[derive(PartialEq, Eq, Debug, Clone, Copy)]
+    pub enum Minimization {
+        None,
+        Local,
+        Recursive,
+    }
+
+    /// A simplification pass of the clause database.
+    /// Passes of `Config::simplifiers` run in order at the root level after a restart, when all assignments are propagated.
+    /// The meaning of a budget is up to a pass.
+    /// A pass of users may add implied clauses by `Solver::add_clause`. They are treated as original clauses.
+    pub trait Simplifier: std::fmt::Debug + Send + Sync {
+        fn simplify(&self, solver: &mut Solver, budget: usize);
+    }
+
+    /// Remove clauses that are satisfied at the root level and literals that are false at the root level.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct RemoveSatisfied;
+    impl Simplifier for RemoveSatisfied {
+        fn simplify(&self, solver: &mut Solver, _budget: usize) {
+            solver.remove_satisfied(true);
+            solver.remove_satisfied(false);
+        }
+    }
+
+    /// Asymmetric literal elimination(ALE) and asymmetric tautology elimination(ATE) of original clauses.
+    /// Each clause is probed by at most `budget` propagations.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct AsymmetricElimination;
+    impl Simplifier for AsymmetricElimination {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.asymmetric_eliminate(budget, false);
+        }
+    }
+
+    /// Bounded variable elimination(BVE) of original clauses like SatELite.
+    /// A variable is resolved out if its resolvents aren't more than its clauses.
+    /// Removed clauses are kept by `Reconstruction` to extend models. At most `budget` resolvents are tried.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct VariableElimination;
+    impl Simplifier for VariableElimination {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.eliminate_variables(budget);
+        }
+    }
+
+    /// Pure literal elimination. A literal whose negation isn't in any original clause is made true by removing its clauses.
+    /// Removed clauses are kept by `Reconstruction` to extend models. The budget isn't used.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct PureLiteralElimination;
+    impl Simplifier for PureLiteralElimination {
+        fn simplify(&self, solver: &mut Solver, _budget: usize) {
+            solver.eliminate_pure_literals();
+        }
+    }
+
+    /// Equivalent literal substitution. Literals in a strongly connected component of the binary implication graph are equivalent.
+    /// Each of them is replaced by the representative of its component in all clauses.
+    /// Substituted variables are recorded by `Reconstruction` to extend models. The budget isn't used.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct EquivalentLiteralSubstitution;
+    impl Simplifier for EquivalentLiteralSubstitution {
+        fn simplify(&self, solver: &mut Solver, _budget: usize) {
+            solver.substitute_equivalences();
+        }
+    }
+
+    /// Remove subsumed clauses and strengthen clauses by self-subsuming resolution.
+    /// Original and learnt clauses are checked whenever the pass runs. A learnt clause doesn't remove an original clause.
+    /// At most `budget` pairs of clauses are compared.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Subsumption;
+    impl Simplifier for Subsumption {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.subsume(budget);
+        }
+    }
+
+    /// Failed literal probing with hyper-binary resolution.
+    /// A literal whose propagation leads to a conflict is fixed to false. Literals that long clauses imply
+    /// during the propagation of a probe are learnt as binary clauses. At most `budget` literals are probed.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct FailedLiteralProbing;
+    impl Simplifier for FailedLiteralProbing {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.probe(budget);
+        }
+    }
+
+    /// Extract XOR constraints that are encoded by original clauses and propagate them by Gauss-Jordan elimination.
+    /// Each XOR of 3 to 6 variables becomes a native constraint like `Solver::add_xor` while the matrix has at most `budget` rows.
+    /// It does nothing with a proof.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct XorExtraction;
+    impl Simplifier for XorExtraction {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.extract_xors(budget);
+        }
+    }
+
+    /// Vivification of learnt clauses by the same probing as `AsymmetricElimination`.
+    /// Each learnt clause of at least three literals is probed once by at most `budget` propagations.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct Vivification;
+    impl Simplifier for Vivification {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            solver.asymmetric_eliminate(budget, true);
+        }
+    }
+
+    /// Parameters of a solver.
+    /// Presets are tuned for kinds of problems. `Config::auto` picks one by the structure of a problem.
+    #[derive(Debug, Clone)]
+    pub struct Config {
+        // how decision variables are selected
+        pub branching: std::sync::Arc<dyn BranchingHeuristic>,
+        // when a solver restarts
+        pub restart: std::sync::Arc<dyn RestartPolicy>,
+        // the limit of learnt clauses relative to the number of original clauses
+        pub learnt_size_factor: f64,
+        // the factor that increases the limit of learnt clauses
+        pub learnt_size_inc: f64,
+        // the initial polarity of decisions
+        pub polarity: bool,
+        // simplification passes and their budgets in order
+        pub simplifiers: Vec<(std::sync::Arc<dyn Simplifier>, usize)>,
+        // the activity decay factor of learnt clauses
+        pub clause_decay: f64,
+        // learnt clauses that are deleted by a reduction of the learnt clause database
+        pub deletion: std::sync::Arc<dyn DeletionPolicy>,
+        // how literals of learnt clauses are removed
+        pub minimization: Minimization,
+        // backtrack only one level instead of a backjump over more than this number of levels
+        pub chronological: Option<usize>,
+        // strengthen reasons that resolvents of conflict analysis subsume
+        pub on_the_fly_subsumption: bool,
+        // the fraction of decisions whose variables and polarities are random
+        pub random_decisions: f64,
+        // the seed of random decisions. The same seed reproduces the same search.
+        pub random_seed: u64,
+        // initialize activities and polarities by Jeroslow-Wang scores of original clauses
+        pub jeroslow_wang: bool,
+        // reset saved phases to the best, inverted or random phases every this number of conflicts
+        pub rephase: Option<u64>,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Config {
+                branching: std::sync::Arc::new(Vsids::default()),
+                restart: std::sync::Arc::new(GeometricRestart::new(100.0, 1.1)),
+                learnt_size_factor: 0.3,
+                learnt_size_inc: 1.1,
+                polarity: false,
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(AsymmetricElimination), 100),
+                ],
+                clause_decay: 0.999,
+                deletion: std::sync::Arc::new(SizePolicy),
+                minimization: Minimization::Recursive,
+                chronological: None,
+                on_the_fly_subsumption: false,
+                random_decisions: 0.0,
+                random_seed: 91648253,
+                jeroslow_wang: false,
+                rephase: None,
+            }
+        }
+    }
+
+    impl Config {
+        /// Fewer restarts and more learnt clauses to keep the search near a model.
+        pub fn sat() -> Config {
+            Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(300.0, 1.5)),
+                learnt_size_factor: 0.5,
+                ..Config::default()
+            }
+        }
+        /// Frequent restarts and slow decay to refute a problem by many conflicts.
+        pub fn unsat() -> Config {
+            Config {
+                branching: std::sync::Arc::new(Vsids::new(0.99)),
+                restart: std::sync::Arc::new(GeometricRestart::new(50.0, 1.05)),
+                ..Config::default()
+            }
+        }
+        /// Slow decay, many learnt clauses and Gauss-Jordan elimination of XOR constraints for XOR-heavy problems.
+        pub fn crypto() -> Config {
+            Config {
+                branching: std::sync::Arc::new(Vsids::new(0.99)),
+                restart: std::sync::Arc::new(GeometricRestart::new(200.0, 1.1)),
+                learnt_size_factor: 1.0,
+                simplifiers: vec![
+                    (std::sync::Arc::new(XorExtraction), 1000),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(AsymmetricElimination), 100),
+                ],
+                ..Config::default()
+            }
+        }
+        /// Restarts that grow fast and more learnt clauses for planning problems that mostly consist of binary clauses.
+        pub fn planning() -> Config {
+            Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(100.0, 1.2)),
+                learnt_size_factor: 0.5,
+                ..Config::default()
+            }
+        }
+        /// A preset by name: `default`, `sat`, `unsat`, `crypto` or `planning`.
+        pub fn preset(name: &str) -> Option<Config> {
+            match name {
+                "default" => Some(Config::default()),
+                "sat" => Some(Config::sat()),
+                "unsat" => Some(Config::unsat()),
+                "crypto" => Some(Config::crypto()),
+                "planning" => Some(Config::planning()),
+                _ => None,
+            }
+        }
+        /// Pick a preset by quick structural features of a problem.
+        /// - Many clauses that form XOR constraints: `crypto`
+        /// - Mostly binary clauses: `planning`
+        /// - Random-like 3-SAT: `sat` below the phase transition and `unsat` above it
+        pub fn auto<C: AsRef<[Lit]>>(clauses: &[C]) -> Config {
+            if clauses.is_empty() {
+                return Config::default();
+            }
+            let mut vars = HashSet::new();
+            let mut binary = 0;
+            let mut ternary = 0;
+            // A XOR of `k` variables is `2^(k-1)` clauses over the same variables.
+            let mut groups: HashMap<Vec<Var>, usize> = HashMap::new();
+            for clause in clauses.iter() {
+                let clause = clause.as_ref();
+                let mut vs: Vec<Var> = clause.iter().map(|lit| lit.var()).collect();
+                vs.sort();
+                vs.dedup();
+                vars.extend(vs.iter().copied());
+                match vs.len() {
+                    2 => binary += 1,
+                    3 => ternary += 1,
+                    _ => {}
+                }
+                if (3..=6).contains(&vs.len()) {
+                    *groups.entry(vs).or_insert(0) += 1;
+                }
+            }
+            let xor: usize = groups
+                .iter()
+                .filter(|(vs, &count)| count == 1 << (vs.len() - 1))
+                .map(|(_, &count)| count)
+                .sum();
+            let n = clauses.len() as f64;
+            let ratio = n / std::cmp::max(vars.len(), 1) as f64;
+            if xor as f64 >= 0.3 * n {
+                Config::crypto()
+            } else if binary as f64 >= 0.7 * n {
+                Config::planning()
+            } else if ternary as f64 >= 0.9 * n && ratio < 4.0 {
+                Config::sat()
+            } else if ternary as f64 >= 0.9 * n && ratio > 4.5 {
+                Config::unsat()
+            } else {
+                Config::default()
+            }
+        }
+    }
+
+    /// The reason, the decision level and the saved phase of a variable.
+    /// They are packed into one array because conflict analysis reads reasons and levels together
+    /// and backtracking writes all of them for each unassigned variable.
+    /// `assigns` stays a public array and activities belong to branching heuristics.
+    #[derive(Debug, Default, Clone, Copy)]
+    struct VarData {
+        // a clause that forced a variable to be assigned
+        reason: Option<CRef>,
+        // decision level(0: unassigned, 1: minimum level)
+        level: Idx,
+        // the saved phase that the next decision of a variable follows
+        phase: bool,
+    }
+
+    /// A clause that watches a literal.
+    /// If the `blocker`(another literal of the clause) is true, propagation skips the clause without reading its memory.
+    #[derive(Debug, Clone, Copy)]
+    struct Watcher {
+        cref: CRef,
+        blocker: Lit,
+    }
+
+    /// Saved phases are reset every `Config::rephase` conflicts like CaDiCaL.
+    /// The schedule is best, inverted, best, random, best and original.
+    /// The best phases are the assignments of the longest trail without a conflict since the last rephasing.
+    #[derive(Debug, Default, Clone)]
+    struct Rephasing {
+        best: Vec<Lit>,
+        // conflicts since the last rephasing
+        conflicts: u64,
+        // the number of rephasings
+        count: usize,
+    }
+
+    /// An at-most-k constraint that is propagated by counting its true literals.
+    #[derive(Debug, Clone)]
+    struct Card {
+        lits: Vec<Lit>,
+        k: usize,
+        // the number of true literals that have been propagated
+        count: usize,
+    }
+
+    /// XOR constraints over GF(2) as rows of a matrix for Gauss-Jordan elimination.
+    /// A row is a sum of added constraints, so every row stays implied however rows are added to each other.
+    /// The pivot of a row is a column that no other row has. Pivots are unassigned after each elimination.
+    #[derive(Debug, Default, Clone)]
+    struct Gauss {
+        // the variable of each column
+        vars: Vec<Var>,
+        // the column of each variable
+        columns: Vec<Option<usize>>,
+        // bits of the columns of each row and the parity of their sum
+        rows: Vec<(Vec<u64>, bool)>,
+        pivots: Vec<Option<usize>>,
+        // a variable of a column has been assigned since the last elimination
+        dirty: bool,
+    }
+    impl Gauss {
+        fn has(bits: &[u64], c: usize) -> bool {
+            bits[c / 64] >> (c % 64) & 1 == 1
+        }
+        fn add_row(to: &mut [u64], from: &[u64]) {
+            to.iter_mut().zip(from.iter()).for_each(|(t, f)| *t ^= f);
+        }
+        /// Columns of the set bits.
+        fn ones(bits: &[u64]) -> impl Iterator<Item = usize> + '_ {
+            bits.iter().enumerate().flat_map(|(w, &word)| {
+                let mut word = word;
+                std::iter::from_fn(move || {
+                    if word == 0 {
+                        return None;
+                    }
+                    let c = w * 64 + word.trailing_zeros() as usize;
+                    word &= word - 1;
+                    Some(c)
+                })
+            })
+        }
+        /// Add rows `vars[0] ^ vars[1] ^ ... = parity` of distinct variables and reduce all rows from scratch.
+        /// A row that the others imply is dropped. `false` means that the rows contradict each other.
+        fn add(&mut self, xors: Vec<(Vec<Var>, bool)>) -> bool {
+            for (vars, _) in xors.iter() {
+                for &v in vars.iter() {
+                    if self.columns[v].is_none() {
+                        self.columns[v] = Some(self.vars.len());
+                        self.vars.push(v);
+                    }
+                }
+            }
+            let words = self.vars.len().div_ceil(64);
+            let mut rows = std::mem::take(&mut self.rows);
+            rows.iter_mut().for_each(|(bits, _)| bits.resize(words, 0));
+            for (vars, parity) in xors {
+                let mut bits = vec![0; words];
+                for v in vars {
+                    let c = self.columns[v].unwrap();
+                    bits[c / 64] ^= 1 << (c % 64);
+                }
+                rows.push((bits, parity));
+            }
+            self.pivots.clear();
+            self.dirty = true;
+            for (mut bits, mut parity) in rows {
+                for (row, pivot) in self.rows.iter().zip(self.pivots.iter()) {
+                    if Gauss::has(&bits, pivot.unwrap()) {
+                        Gauss::add_row(&mut bits, &row.0);
+                        parity ^= row.1;
+                    }
+                }
+                let c = match Gauss::ones(&bits).next() {
+                    Some(c) => c,
+                    None if parity => return false,
+                    None => continue,
+                };
+                for row in self.rows.iter_mut() {
+                    if Gauss::has(&row.0, c) {
+                        Gauss::add_row(&mut row.0, &bits);
+                        row.1 ^= parity;
+                    }
+                }
+                self.rows.push((bits, parity));
+                self.pivots.push(Some(c));
+            }
+            true
+        }
+    }
+
+    /// Limits of a search of `Solver::solve_limited`. `None` is unlimited.
+    /// A search that reaches a limit returns `Indeterminate`. Learnt clauses are kept, so the next search resumes it.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Budget {
+        // the number of conflicts
+        pub conflicts: Option<u64>,
+        // the number of decisions except assumptions
+        pub decisions: Option<u64>,
+        // the number of propagated literals
+        pub propagations: Option<u64>,
+    }
+    impl Budget {
+        fn exhausted(&self, conflicts: u64, decisions: u64, propagations: u64) -> bool {
+            let over = |limit: Option<u64>, count: u64| limit.is_some_and(|limit| count >= limit);
+            over(self.conflicts, conflicts)
+                || over(self.decisions, decisions)
+                || over(self.propagations, propagations)
+        }
+    }
+
+    /// A clause of `Solver::add_removable_clause`. It is the activation literal of a clause.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct ClauseHandle(Lit);
+
+    /// Counters of the searches of a solver since it is created. `Display` shows them like MiniSat.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Statistics {
+        // the number of decisions except assumptions
+        pub decisions: u64,
+        // the number of propagated literals
+        pub propagations: u64,
+        // the number of conflicts
+        pub conflicts: u64,
+        // the number of restarts
+        pub restarts: u64,
+        // the number of clauses that are learnt by conflict analysis(including units)
+        pub learnts: u64,
+        // the number of learnt clauses that are deleted by reductions of the learnt clause database
+        pub deleted: u64,
+        // the maximum number of decisions on the trail
+        pub max_level: u64,
+    }
+    impl std::fmt::Display for Statistics {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            writeln!(f, "restarts              : {}", self.restarts)?;
+            writeln!(f, "conflicts             : {}", self.conflicts)?;
+            writeln!(f, "decisions             : {}", self.decisions)?;
+            writeln!(f, "propagations          : {}", self.propagations)?;
+            writeln!(f, "learnt clauses        : {}", self.learnts)?;
+            writeln!(f, "deleted clauses       : {}", self.deleted)?;
+            write!(f, "max decision level    : {}", self.max_level)
+        }
+    }
+
+    /// A handle that stops the search of a solver from another thread or a signal handler.
+    /// A search returns `Indeterminate` soon after `stop` is called and the request is cleared then.
+    #[derive(Debug, Clone)]
+    pub struct InterruptHandle(Arc<AtomicBool>);
+    impl InterruptHandle {
+        /// Stop the current or the next search.
+        pub fn stop(&self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// A callback of `Solver::set_terminate`.
+    struct Terminate(Box<dyn FnMut() -> bool>);
+    impl std::fmt::Debug for Terminate {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("Terminate")
+        }
+    }
+    // the number of iterations of a search between polls of a terminate callback(a power of two)
+    const TERMINATE_PERIOD: u64 = 1024;
+
+    #[derive(Debug)]
+    // A SAT Solver
+    pub struct Solver {
+        // the number of variables
+        n: usize,
+        // assignments for each variable
+        pub assigns: Vec<LitBool>,
+        // phases that are forced by `set_polarity`. Saved phases don't change them.
+        user_polarity: Vec<Option<bool>>,
+        // whether a variable can be decided
+        decision: Vec<bool>,
+        // all clauses
+        ca: ClauseAllocator,
+        // original clauses
+        clauses: Vec<CRef>,
+        // learnt clauses
+        learnts: Vec<CRef>,
+        // clauses that may be conflicted or propagated if a `lit` is false.
+        watchers: Vec<Vec<Watcher>>,
+        // binary clauses `(!p v q)` as `(q, clause)` in the list of `p`. They are propagated without their memory.
+        implications: Vec<Vec<(Lit, CRef)>>,
+        // empty watcher lists that are reused by new variables
+        spare_watchers: Vec<Vec<Watcher>>,
+        // the reason and the decision level of each variable
+        vardata: Vec<VarData>,
+        seen: Vec<bool>,
+        // variables that are assigned at higher levels than their reasons by chronological backtracking
+        elevated: Vec<bool>,
+        ccmin_stack: VecDeque<Lit>,
+        ccmin_clear: Vec<Lit>,
+        // assigned variables
+        que: VecDeque<Lit>,
+        // the head index of `que` points unprocessed elements
+        head: usize,
+        // the solver status. this value may be set by the functions `add_clause` and `solve`.
+        pub status: Option<Status>,
+        branching: Box<dyn BranchingHeuristic>,
+        skip_simplify: bool,
+        // proof logging if it is enabled
+        proof: Option<ProofLog>,
+        config: Config,
+        // clauses that are removed by model-affecting simplifications
+        reconstruction: Reconstruction,
+        // variables that are removed from the search by simplifications
+        eliminated: Vec<bool>,
+        // the number of `freeze` calls of each variable that aren't melted yet
+        frozen: Vec<u32>,
+        // assumptions that are refuted by the last `solve_with_assumptions`
+        failed: Vec<Lit>,
+        // activation literals of removable clauses. Every search assumes them.
+        activations: Vec<Lit>,
+        // native at-most-k constraints
+        cards: Vec<Card>,
+        // the constraints that have a `lit` for each literal
+        card_occurs: Vec<Vec<usize>>,
+        // the explanation clause of the last conflict of a native constraint
+        card_conflict: Option<CRef>,
+        // native XOR constraints
+        gauss: Gauss,
+        // the state of random decisions
+        rng: u64,
+        // the Jeroslow-Wang score of each literal(the sum of 2^-len of clauses that have it)
+        jw: Vec<f64>,
+        rephasing: Rephasing,
+        // the next literal that failed literal probing tries
+        probe_next: usize,
+        // the flag of `InterruptHandle::stop`
+        interrupt: Arc<AtomicBool>,
+        // a callback that stops a search if it returns true
+        terminate: Option<Terminate>,
+        stats: Statistics,
+    }
+
+    impl Default for Solver {
+        fn default() -> Self {
+            Solver::new(0, &[])
+        }
+    }
+
+    /// A copy of a solver with its learnt clauses. See `Solver::fork` for the parts that aren't copied.
+    impl Clone for Solver {
+        fn clone(&self) -> Self {
+            self.copy(true)
+        }
+    }
+
+    impl Solver {
+        /// Create a new `Solver` struct
+        ///
+        /// # Arguments
+        /// * `n` - The number of variable
+        /// * `clauses` - All clauses that solver solves
+        pub fn new(n: usize, clauses: &[Vec<Lit>]) -> Solver {
+            let mut solver = Solver {
+                n,
+                que: VecDeque::new(),
+                head: 0,
+                ca: ClauseAllocator::default(),
+                clauses: Vec::new(),
+                learnts: Vec::new(),
+                vardata: vec![VarData::default(); n],
+                seen: vec![false; n],
+                elevated: vec![false; n],
+                ccmin_stack: VecDeque::new(),
                 ccmin_clear: Vec::new(),
                 assigns: vec![LitBool::Undef; n],
-                polarity: vec![false; n],
-                order_heap: Heap::new(n, 1.0),
+                user_polarity: vec![None; n],
+                decision: vec![true; n],
+                branching: Config::default().branching.start(),
                 watchers: vec![vec![]; 2 * n],
+                implications: vec![vec![]; 2 * n],
+                spare_watchers: Vec::new(),
                 status: None,
                 skip_simplify: false,
+                proof: None,
+                config: Config::default(),
+                reconstruction: Reconstruction::default(),
+                eliminated: vec![false; n],
+                frozen: vec![0; n],
+                failed: Vec::new(),
+                activations: Vec::new(),
+                cards: Vec::new(),
+                card_occurs: vec![vec![]; 2 * n],
+                card_conflict: None,
+                gauss: Gauss {
+                    columns: vec![None; n],
+                    ..Gauss::default()
+                },
+                rng: Config::default().random_seed,
+                jw: vec![0.0; 2 * n],
+                rephasing: Rephasing::default(),
+                probe_next: 0,
+                interrupt: Arc::new(AtomicBool::new(false)),
+                terminate: None,
+                stats: Statistics::default(),
+            };
+            (0..n).for_each(|v| solver.branching.new_var(Var(v as u32)));
+            solver.add_clauses(clauses);
+            solver
+        }
+        /// Set parameters of a solver. Polarities of variables are reset to the polarity of `config`.
+        /// The branching heuristic starts over from `config.branching` and random decisions from `config.random_seed`.
+        pub fn set_config(&mut self, config: Config) {
+            self.vardata
+                .iter_mut()
+                .for_each(|d| d.phase = config.polarity);
+            self.rng = config.random_seed;
+            self.branching = config.branching.start();
+            (0..self.n).for_each(|v| self.branching.new_var(Var(v as u32)));
+            self.config = config;
+            self.jw.iter_mut().for_each(|s| *s = 0.0);
+            if self.config.jeroslow_wang {
+                for i in 0..self.clauses.len() {
+                    let cr = self.clauses[i];
+                    if self.ca.flags(cr) & DELETED == 0 {
+                        self.add_jeroslow_wang(cr);
+                    }
+                }
+            }
+        }
+        pub fn config(&self) -> &Config {
+            &self.config
+        }
+
+        /// Force the phase of decisions of a variable. `None` uses saved phases again.
+        /// Rephasing and random decisions don't change a forced phase. Variables are created if they don't exist.
+        pub fn set_polarity(&mut self, v: Var, polarity: Option<bool>) {
+            while v.0 as usize >= self.assigns.len() {
+                self.new_var();
+            }
+            self.user_polarity[v] = polarity;
+        }
+
+        /// Add a clause that can be removed by `remove_clause` later.
+        /// It is `clause v !a` for a new activation variable `a` that every search assumes.
+        /// The activation variable is a new variable, so variables of later clauses should be created by `new_var`.
+        pub fn add_removable_clause(&mut self, clause: &[Lit]) -> ClauseHandle {
+            clause.iter().for_each(|lit| {
+                while lit.var().0 as usize >= self.assigns.len() {
+                    self.new_var();
+                }
+            });
+            let a = Lit::new(self.new_var().0, true);
+            self.freeze(a.var());
+            let mut lits = clause.to_vec();
+            lits.push(!a);
+            self.add_clause(&lits);
+            self.activations.push(a);
+            ClauseHandle(a)
+        }
+
+        /// Remove a clause of `add_removable_clause`. The negation of its activation literal satisfies it permanently.
+        /// Removing a clause twice does nothing.
+        pub fn remove_clause(&mut self, handle: ClauseHandle) {
+            if let Some(i) = self.activations.iter().position(|&a| a == handle.0) {
+                self.activations.remove(i);
+                self.melt(handle.0.var());
+                self.add_clause(&[!handle.0]);
+            }
+        }
+
+        /// Freeze a variable so that simplifications don't remove it(variable elimination, pure literals and substitution).
+        /// A variable that is used by later clauses or assumptions should be frozen, or removed clauses are added again.
+        /// Freezing counts, so a variable is melted by as many `melt` calls. Variables are created if they don't exist.
+        pub fn freeze(&mut self, v: Var) {
+            while v.0 as usize >= self.assigns.len() {
+                self.new_var();
+            }
+            if self.eliminated[v] {
+                self.restore_eliminated();
+            }
+            self.frozen[v] += 1;
+        }
+
+        /// Undo a `freeze` of a variable.
+        pub fn melt(&mut self, v: Var) {
+            assert!(self.is_frozen(v), "a variable isn't frozen");
+            self.frozen[v] -= 1;
+        }
+
+        /// Whether a variable is frozen by `freeze`.
+        pub fn is_frozen(&self, v: Var) -> bool {
+            self.frozen
+                .get(v.0 as usize)
+                .is_some_and(|&count| count > 0)
+        }
+
+        /// Set whether a variable can be decided. A variable that isn't a decision variable is still propagated.
+        /// A solver answers `Sat` when all decision variables are assigned without a conflict,
+        /// so a variable that isn't implied by decision variables(like a Tseitin variable) is left `Undef` in a model.
+        pub fn set_decision(&mut self, v: Var, decision: bool) {
+            while v.0 as usize >= self.assigns.len() {
+                self.new_var();
+            }
+            if decision && !self.decision[v] && self.vardata[v].level == 0 {
+                // A skipped candidate is out of the branching heuristic until it is unassigned.
+                self.branching.unassign(v);
+            }
+            self.decision[v] = decision;
+        }
+
+        /// A copy of a solver without its learnt clauses. It is cheaper than `clone` after long searches,
+        /// and the copy can be searched under other assumptions or clauses independently.
+        /// A copy doesn't have the terminate callback and has its own interrupt flag.
+        /// A proof that is written to a file isn't copied, and a copy by `fork` never logs a proof.
+        pub fn fork(&self) -> Solver {
+            self.copy(false)
+        }
+
+        fn copy(&self, learnts: bool) -> Solver {
+            let proof = self
+                .proof
+                .as_ref()
+                .filter(|proof| learnts && proof.writer.is_none())
+                .map(|proof| ProofLog {
+                    proof: proof.proof.clone(),
+                    writer: None,
+                    next_id: proof.next_id,
+                    clause_id: proof.clause_id.clone(),
+                    unit_id: proof.unit_id.clone(),
+                });
+            let branching = self.branching.duplicate().unwrap_or_else(|| {
+                let mut branching = self.config.branching.start();
+                (0..self.n).for_each(|v| branching.new_var(Var(v as u32)));
+                branching
+            });
+            let mut solver = Solver {
+                n: self.n,
+                assigns: self.assigns.clone(),
+                user_polarity: self.user_polarity.clone(),
+                decision: self.decision.clone(),
+                ca: ClauseAllocator::default(),
+                clauses: Vec::new(),
+                learnts: Vec::new(),
+                watchers: vec![vec![]; self.watchers.len()],
+                implications: vec![vec![]; self.implications.len()],
+                spare_watchers: self.spare_watchers.clone(),
+                vardata: self.vardata.clone(),
+                seen: self.seen.clone(),
+                elevated: self.elevated.clone(),
+                ccmin_stack: VecDeque::new(),
+                ccmin_clear: Vec::new(),
+                que: self.que.clone(),
+                head: self.head,
+                status: self.status,
+                branching,
+                skip_simplify: self.skip_simplify,
+                proof,
+                config: self.config.clone(),
+                reconstruction: self.reconstruction.clone(),
+                eliminated: self.eliminated.clone(),
+                frozen: self.frozen.clone(),
+                failed: self.failed.clone(),
+                activations: self.activations.clone(),
+                cards: self.cards.clone(),
+                card_occurs: self.card_occurs.clone(),
+                card_conflict: None,
+                gauss: self.gauss.clone(),
+                rng: self.rng,
+                jw: self.jw.clone(),
+                rephasing: self.rephasing.clone(),
+                probe_next: self.probe_next,
+                interrupt: Arc::new(AtomicBool::new(false)),
+                terminate: None,
+                stats: self.stats,
+            };
+            if learnts {
+                solver.ca = self.ca.clone();
+                solver.clauses = self.clauses.clone();
+                solver.learnts = self.learnts.clone();
+                solver.watchers = self.watchers.clone();
+                solver.implications = self.implications.clone();
+                solver.card_conflict = self.card_conflict;
+                return solver;
+            }
+            // Reasons of assigned variables are copied even if they are learnt clauses.
+            let mut moved = HashMap::new();
+            let mut copy = |ca: &mut ClauseAllocator, cr: CRef| -> CRef {
+                *moved.entry(cr).or_insert_with(|| {
+                    let learnt = self.ca.is_learnt(cr);
+                    let new_cr = ca.alloc(self.ca.lits(cr), learnt);
+                    ca.data[ClauseAllocator::offset(new_cr) + 1] = Lit(self.ca.flags(cr));
+                    if learnt {
+                        ca.set_activity(new_cr, self.ca.activity(cr));
+                    }
+                    new_cr
+                })
             };
-            clauses.iter().for_each(|clause| {
-                if clause.len() == 1 {
-                    solver.enqueue(clause[0], None);
+            solver.ca.activity_inc = self.ca.activity_inc;
+            for &cr in self.clauses.iter() {
+                if self.ca.flags(cr) & DELETED != 0 {
+                    continue;
+                }
+                let new_cr = copy(&mut solver.ca, cr);
+                solver.clauses.push(new_cr);
+                solver.attach_clause(new_cr);
+            }
+            for lit in self.que.iter() {
+                if let Some(cr) = self.vardata[lit.var()].reason {
+                    solver.vardata[lit.var()].reason = Some(copy(&mut solver.ca, cr));
+                }
+            }
+            solver
+        }
+
+        /// A handle that stops a search of this solver. Handles can be cloned and sent to other threads.
+        pub fn interrupt_handle(&self) -> InterruptHandle {
+            InterruptHandle(self.interrupt.clone())
+        }
+
+        /// Set a callback that stops a search if it returns true. It is polled periodically during a search.
+        pub fn set_terminate<F: FnMut() -> bool + 'static>(&mut self, callback: F) {
+            self.terminate = Some(Terminate(Box::new(callback)));
+        }
+        // SplitMix64
+        fn next_random(&mut self) -> u64 {
+            self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = self.rng;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }
+        /// A random decision literal if a decision is random and its variable is free.
+        fn random_decision(&mut self) -> Option<Lit> {
+            if self.config.random_decisions <= 0.0 || self.n == 0 {
+                return None;
+            }
+            let r = (self.next_random() >> 11) as f64 / (1u64 << 53) as f64;
+            if r >= self.config.random_decisions {
+                return None;
+            }
+            let x = self.next_random();
+            let v = Var((x % self.n as u64) as u32);
+            if self.vardata[v].level != 0 || self.eliminated[v] || !self.decision[v] {
+                return None;
+            }
+            Some(Lit::new(v.0, self.user_polarity[v].unwrap_or(x >> 63 == 1)))
+        }
+        fn eval(&self, lit: Lit) -> LitBool {
+            eval(&self.assigns, lit)
+        }
+        /// Enqueue a variable to assign a `value` to a boolean `assign`
+        fn enqueue(&mut self, lit: Lit, reason: Option<CRef>) {
+            debug_assert!(self.vardata[lit.var()].level == 0);
+            self.assigns[lit.var()] = LitBool::from(lit.neg() as i8);
+
+            self.vardata[lit.var()].reason = reason;
+            self.vardata[lit.var()].level = if let Some(last) = self.que.back() {
+                self.vardata[last.var()].level
+            } else {
+                1
+            };
+            self.que.push_back(lit);
+            self.branching.assign(lit.var());
+
+            // A literal that is propagated at the root level is a unit clause of a proof.
+            if let (Some(proof), Some(cr)) = (self.proof.as_mut(), reason) {
+                if self.vardata[lit.var()].level == 1 {
+                    let clause = self.ca.lits(cr);
+                    let mut antecedents = vec![(proof.clause_id[&cr], clause.to_vec())];
+                    antecedents.extend(clause.iter().skip(1).map(|&p| proof.unit(p)));
+                    let id = proof.derive(&[lit], antecedents);
+                    proof.set_unit(lit.var(), id);
+                }
+            }
+        }
+
+        fn proof_log(&mut self) -> &mut ProofLog {
+            assert!(
+                self.clauses.is_empty() && self.que.is_empty(),
+                "a proof must be enabled before adding clauses"
+            );
+            self.proof.get_or_insert_with(ProofLog::default)
+        }
+
+        /// Build an in-memory resolution proof while searching.
+        /// It must be called before adding clauses.
+        pub fn enable_proof(&mut self) {
+            self.proof_log().proof = Some(Proof::default());
+        }
+
+        /// The resolution proof if it is enabled by `enable_proof`.
+        pub fn proof(&self) -> Option<&Proof> {
+            self.proof.as_ref().and_then(|log| log.proof.as_ref())
+        }
+
+        /// Write a proof to `writer` while searching.
+        /// It must be called before adding clauses.
+        /// A proof covers all `solve` calls of a solver. Clauses that are added between calls are original clauses
+        /// and an `Unsat` answer under assumptions adds the negation of the failed assumptions(see `Proof::failed`).
+        /// # Arguments
+        /// * `writer` - The output of a proof. It is flushed when the empty clause is derived.
+        /// * `format` - The format of a proof
+        pub fn set_proof_writer<W: Write + 'static>(&mut self, writer: W, format: ProofFormat) {
+            self.proof_log().writer = Some(ProofWriter::new(Box::new(writer), format));
+        }
+
+        /// Flush a proof file and report the first error of writing it.
+        /// Lines of an LRAT proof before the first search are written too.
+        pub fn flush_proof(&mut self) -> std::io::Result<()> {
+            match self.proof.as_mut().and_then(|log| log.writer.as_mut()) {
+                Some(writer) => {
+                    writer.start();
+                    match writer.error.take() {
+                        Some(e) => Err(e),
+                        None => writer.writer.flush(),
+                    }
+                }
+                None => Ok(()),
+            }
+        }
+
+        /// Derive the empty clause from a conflict at the root level.
+        fn prove_empty(&mut self, confl: CRef) {
+            if let Some(proof) = self.proof.as_mut() {
+                let clause = self.ca.lits(confl);
+                let mut antecedents = vec![(proof.clause_id[&confl], clause.to_vec())];
+                antecedents.extend(clause.iter().map(|&p| proof.unit(p)));
+                let id = proof.derive(&[], antecedents);
+                proof.empty(id);
+            }
+        }
+
+        /// Create a new space for one variable and return it.
+        /// Variables can be added at any time, also between `solve` calls.
+        pub fn new_var(&mut self) -> Var {
+            let v = Var(self.n as u32);
+            self.n += 1;
+            self.assigns.push(LitBool::Undef);
+            self.user_polarity.push(None);
+            self.decision.push(true);
+            self.vardata.push(VarData {
+                phase: self.config.polarity,
+                ..VarData::default()
+            });
+            self.branching.new_var(v);
+            self.seen.push(false);
+            self.elevated.push(false);
+            self.eliminated.push(false);
+            self.frozen.push(0);
+            // for literals
+            self.watchers
+                .push(self.spare_watchers.pop().unwrap_or_default());
+            self.watchers
+                .push(self.spare_watchers.pop().unwrap_or_default());
+            self.implications.push(Vec::new());
+            self.implications.push(Vec::new());
+            self.jw.push(0.0);
+            self.jw.push(0.0);
+            self.card_occurs.push(Vec::new());
+            self.card_occurs.push(Vec::new());
+            self.gauss.columns.push(None);
+            v
+        }
+
+        /// Create `n` new variables and return them.
+        pub fn new_vars(&mut self, n: usize) -> Vec<Var> {
+            (0..n).map(|_| self.new_var()).collect()
+        }
+
+        /// Watch the first two literals of a clause.
+        fn attach_clause(&mut self, cr: CRef) {
+            let clause = self.ca.lits(cr);
+            debug_assert!(clause.len() >= 2);
+            let (l1, l2) = (clause[0], clause[1]);
+            if clause.len() == 2 {
+                self.implications[!l1].push((l2, cr));
+                self.implications[!l2].push((l1, cr));
+            } else {
+                self.watchers[!l1].push(Watcher {
+                    cref: cr,
+                    blocker: l2,
+                });
+                self.watchers[!l2].push(Watcher {
+                    cref: cr,
+                    blocker: l1,
+                });
+            }
+        }
+
+        /// This method is only for internal usage and almost same as `add_clause`
+        /// But, this method doesn't grow the size of array.
+        fn add_clause_unchecked(&mut self, cr: CRef, learnt: bool) {
+            self.attach_clause(cr);
+            if learnt {
+                self.learnts.push(cr);
+            } else {
+                self.clauses.push(cr);
+                if self.config.jeroslow_wang {
+                    self.add_jeroslow_wang(cr);
+                }
+            }
+        }
+        /// Add the Jeroslow-Wang scores of an original clause. Short clauses weigh more.
+        /// A variable is scored by both of its literals and prefers the polarity of the higher one.
+        fn add_jeroslow_wang(&mut self, cr: CRef) {
+            let len = self.ca.len(cr);
+            let score = 0.5f64.powi(len.min(1024) as i32);
+            for i in 0..len {
+                let lit = self.ca.lits(cr)[i];
+                self.jw[lit] += score;
+                self.branching.add_score(lit.var(), score);
+                let (pos, neg) = (Lit::new(lit.var().0, true), Lit::new(lit.var().0, false));
+                if self.jw[pos] != self.jw[neg] {
+                    self.vardata[lit.var()].phase = self.jw[pos] > self.jw[neg];
+                }
+            }
+        }
+        /// Add a new clause to `clauses` and watch a clause.
+        /// If a variable is greater than the size of array, grow it.
+        /// # Arguments
+        /// * `clause` - a clause has one or some literal variables
+        pub fn add_clause(&mut self, clause: &[Lit]) {
+            // A solver may keep assignments of a previous `solve`.
+            // A new clause must be simplified by root level assignments only.
+            if self.status != Some(Status::Unsat) {
+                self.status = None;
+                self.pop_queue_until(1);
+            }
+            // grow the space of array variables.
+            clause.iter().for_each(|c| {
+                while c.var().0 as usize >= self.assigns.len() {
+                    self.new_var();
+                }
+            });
+            if clause.iter().any(|lit| self.eliminated[lit.var()]) {
+                self.restore_eliminated();
+            }
+            let original = clause.to_vec();
+            let mut node = self.proof.as_mut().map(|proof| proof.original(clause));
+            // An unsatisfiable solver stays unsatisfiable. A clause only takes its id in a proof,
+            // and the trail of the conflict isn't propagated again.
+            if self.status == Some(Status::Unsat) {
+                return;
+            }
+
+            // Simplify a clause
+            let mut clause = clause.to_vec();
+            clause.sort();
+            let mut len = 0;
+            let mut falsified = vec![];
+            for i in 0..clause.len() {
+                let mut remove = false;
+                if i >= 1 {
+                    // x0 v !x0 means a clause is already satisfied.
+                    // you don't need to add it.
+                    if clause[i] == !clause[i - 1] {
+                        return;
+                    }
+                    // x0 v x0 duplicated
+                    if clause[i] == clause[i - 1] {
+                        remove = true;
+                    }
+                }
+                let lit = clause[i];
+                //already assigned
+                match self.eval(lit) {
+                    LitBool::True => {
+                        // a clause is already satisfied. You don't need to add it.
+                        return;
+                    }
+                    LitBool::False => {
+                        // a literal is already false. You can remove it from a clause.
+                        remove = true;
+                        falsified.push(lit);
+                    }
+                    _ => {}
+                }
+
+                if !remove {
+                    clause[len] = lit;
+                    len += 1;
+                }
+            }
+            clause.truncate(len);
+
+            // Removed false literals are resolved with their unit clauses.
+            if let (Some(proof), Some(id)) = (self.proof.as_mut(), node) {
+                if !falsified.is_empty() {
+                    let mut antecedents = vec![(id, original)];
+                    antecedents.extend(falsified.iter().map(|&lit| proof.unit(lit)));
+                    node = Some(proof.derive(&clause, antecedents));
+                }
+            }
+
+            if clause.is_empty() {
+                // Empty clause
+                self.status = Some(Status::Unsat);
+                if let (Some(proof), Some(id)) = (self.proof.as_mut(), node) {
+                    proof.empty(id);
+                }
+            } else if clause.len() == 1 {
+                // Unit Clause
+                let c = clause[0];
+                self.enqueue(c, None);
+                if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                    proof.set_unit(c.var(), node);
+                }
+                // If the conflict happnes at the root level(decision level: 0), which means that a given problem is UNSATISFIABLE.
+                if let Some(confl) = self.propagate() {
+                    self.status = Some(Status::Unsat);
+                    self.prove_empty(confl);
+                }
+            } else {
+                debug_assert!(clause.len() >= 2);
+                let cr = self.ca.alloc(&clause, false);
+                if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                    proof.clause_id.insert(cr, node);
+                }
+                self.add_clause_unchecked(cr, false);
+            }
+        }
+
+        /// Add an at-most-k constraint `lits[0] + lits[1] + ... <= k` as a native constraint.
+        /// It is propagated by counting true literals instead of being encoded into clauses, so it stays as small as `lits`.
+        /// Propagations and conflicts are explained by clauses while they are needed by conflict analysis.
+        /// Duplicated literals count once. Proofs don't support native constraints.
+        pub fn add_at_most(&mut self, lits: &[Lit], k: usize) {
+            assert!(
+                self.proof.is_none(),
+                "a proof doesn't support native cardinality constraints"
+            );
+            if self.status != Some(Status::Unsat) {
+                self.status = None;
+                self.pop_queue_until(1);
+            }
+            lits.iter().for_each(|c| {
+                while c.var().0 as usize >= self.assigns.len() {
+                    self.new_var();
+                }
+            });
+            if lits.iter().any(|lit| self.eliminated[lit.var()]) {
+                self.restore_eliminated();
+            }
+            if self.status == Some(Status::Unsat) {
+                return;
+            }
+
+            // Simplify a constraint by root level assignments.
+            // `x` and `!x` are exactly one true literal.
+            let mut lits = lits.to_vec();
+            lits.sort();
+            lits.dedup();
+            let mut k = k as i64;
+            let mut rest = vec![];
+            let mut i = 0;
+            while i < lits.len() {
+                if i + 1 < lits.len() && lits[i + 1] == !lits[i] {
+                    k -= 1;
+                    i += 2;
+                    continue;
+                }
+                match self.eval(lits[i]) {
+                    LitBool::True => k -= 1,
+                    LitBool::False => {}
+                    LitBool::Undef => rest.push(lits[i]),
+                }
+                i += 1;
+            }
+            if k < 0 {
+                self.status = Some(Status::Unsat);
+                return;
+            }
+            let k = k as usize;
+            if k >= rest.len() {
+                return;
+            }
+            if k == 0 {
+                rest.iter().for_each(|&lit| self.enqueue(!lit, None));
+                if self.propagate().is_some() {
+                    self.status = Some(Status::Unsat);
+                }
+                return;
+            }
+            let c = self.cards.len();
+            rest.iter().for_each(|&lit| self.card_occurs[lit].push(c));
+            self.cards.push(Card {
+                lits: rest,
+                k,
+                count: 0,
+            });
+        }
+
+        /// Propagate the cardinality constraints of a true literal `p` that have `k` true literals.
+        /// The other literals are false. A constraint that has more true literals is a conflict.
+        fn propagate_cards(&mut self, p: Lit) -> Option<CRef> {
+            for i in 0..self.card_occurs[p].len() {
+                let c = self.card_occurs[p][i];
+                let card = &self.cards[c];
+                if card.count < card.k {
+                    continue;
+                }
+                // `p` is at the current level so that conflict analysis finds it first.
+                let mut trues = vec![!p];
+                trues.extend(
+                    card.lits
+                        .iter()
+                        .filter(|&&lit| lit != p && self.eval(lit) == LitBool::True)
+                        .map(|&lit| !lit),
+                );
+                if trues.len() > card.k {
+                    trues.truncate(card.k + 1);
+                    let cr = self.ca.alloc(&trues, false);
+                    self.ca.mark(cr, EXPLANATION);
+                    self.card_conflict = Some(cr);
+                    return Some(cr);
+                }
+                let forced: Vec<Lit> = card
+                    .lits
+                    .iter()
+                    .filter(|&&lit| self.eval(lit) == LitBool::Undef)
+                    .map(|&lit| !lit)
+                    .collect();
+                for lit in forced {
+                    // [!lit, !true1, !true2, ... !truek]
+                    trues.insert(0, lit);
+                    let cr = self.ca.alloc(&trues, false);
+                    trues.remove(0);
+                    self.ca.mark(cr, EXPLANATION);
+                    self.enqueue(lit, Some(cr));
+                }
+            }
+            None
+        }
+
+        /// Add XOR constraints as native constraints. The XOR of the literals of each of `xors` is true.
+        /// They are rows of a matrix that Gauss-Jordan elimination keeps reduced during the search,
+        /// so a literal that a sum of constraints implies is propagated and a sum that is false is a conflict.
+        /// Propagations and conflicts are explained by clauses while they are needed by conflict analysis.
+        /// A variable that appears twice cancels out. Proofs don't support native constraints.
+        pub fn add_xors<C: AsRef<[Lit]>>(&mut self, xors: &[C]) {
+            assert!(
+                self.proof.is_none(),
+                "a proof doesn't support native XOR constraints"
+            );
+            if self.status != Some(Status::Unsat) {
+                self.status = None;
+                self.pop_queue_until(1);
+            }
+            xors.iter()
+                .flat_map(|xor| xor.as_ref().iter())
+                .for_each(|c| {
+                    while c.var().0 as usize >= self.assigns.len() {
+                        self.new_var();
+                    }
+                });
+            if xors
+                .iter()
+                .any(|xor| xor.as_ref().iter().any(|lit| self.eliminated[lit.var()]))
+            {
+                self.restore_eliminated();
+            }
+            if self.status == Some(Status::Unsat) {
+                return;
+            }
+
+            let rows = xors
+                .iter()
+                .map(|xor| {
+                    let mut vars: Vec<Var> = xor.as_ref().iter().map(|lit| lit.var()).collect();
+                    vars.sort();
+                    let mut rest: Vec<Var> = vec![];
+                    for v in vars {
+                        if rest.last() == Some(&v) {
+                            rest.pop();
+                        } else {
+                            rest.push(v);
+                        }
+                    }
+                    // Each negative literal flips the parity of the sum of variables.
+                    let parity = xor.as_ref().iter().filter(|lit| lit.neg()).count() & 1 == 0;
+                    (rest, parity)
+                })
+                .collect();
+            if !self.gauss.add(rows) {
+                self.status = Some(Status::Unsat);
+            }
+        }
+
+        /// Add a XOR constraint `lits[0] ^ lits[1] ^ ... = true` as a native constraint like `add_xors`.
+        pub fn add_xor(&mut self, lits: &[Lit]) {
+            self.add_xors(&[lits]);
+        }
+
+        /// Gauss-Jordan elimination of XOR constraints over unassigned columns.
+        /// A row whose pivot is assigned takes another unassigned column as its pivot and the column is eliminated from the other rows.
+        /// Then a row whose only unassigned column is its pivot propagates it and a false row without unassigned columns is a conflict.
+        /// They are explained by the pivot and the false literals of the assigned columns of a row.
+        fn propagate_xors(&mut self) -> Option<CRef> {
+            let gauss = &mut self.gauss;
+            let words = gauss.vars.len().div_ceil(64);
+            let mut unassigned = vec![0u64; words];
+            let mut trues = vec![0u64; words];
+            for (c, &v) in gauss.vars.iter().enumerate() {
+                match self.assigns[v] {
+                    LitBool::Undef => unassigned[c / 64] |= 1 << (c % 64),
+                    LitBool::True => trues[c / 64] |= 1 << (c % 64),
+                    LitBool::False => {}
+                }
+            }
+            for i in 0..gauss.rows.len() {
+                if matches!(gauss.pivots[i], Some(c) if Gauss::has(&unassigned, c)) {
+                    continue;
+                }
+                let bits = &gauss.rows[i].0;
+                gauss.pivots[i] = (0..words)
+                    .find(|&w| bits[w] & unassigned[w] != 0)
+                    .map(|w| w * 64 + (bits[w] & unassigned[w]).trailing_zeros() as usize);
+                if let Some(c) = gauss.pivots[i] {
+                    let (bits, parity) = std::mem::take(&mut gauss.rows[i]);
+                    for (j, row) in gauss.rows.iter_mut().enumerate() {
+                        if j != i && Gauss::has(&row.0, c) {
+                            Gauss::add_row(&mut row.0, &bits);
+                            row.1 ^= parity;
+                        }
+                    }
+                    gauss.rows[i] = (bits, parity);
+                }
+            }
+
+            // A pivot is only in its row, so propagations of rows don't affect each other.
+            let mut found = vec![];
+            for (i, (bits, parity)) in gauss.rows.iter().enumerate() {
+                let mut free = 0;
+                let mut rest = *parity;
+                for w in 0..words {
+                    free += (bits[w] & unassigned[w]).count_ones();
+                    rest ^= (bits[w] & trues[w]).count_ones() & 1 == 1;
+                }
+                match free {
+                    0 if rest => {
+                        found = vec![(i, None)];
+                        break;
+                    }
+                    1 => {
+                        let v = gauss.vars[gauss.pivots[i].unwrap()];
+                        found.push((i, Some(Lit::new(v.0, rest))));
+                    }
+                    _ => {}
+                }
+            }
+            for (i, lit) in found {
+                let mut clause: Vec<Lit> = lit.into_iter().collect();
+                for c in Gauss::ones(&self.gauss.rows[i].0) {
+                    let v = self.gauss.vars[c];
+                    if Some(v) != lit.map(|lit| lit.var()) {
+                        clause.push(Lit::new(v.0, self.assigns[v] == LitBool::False));
+                    }
+                }
+                let cr = self.ca.alloc(&clause, false);
+                self.ca.mark(cr, EXPLANATION);
+                match lit {
+                    Some(lit) => self.enqueue(lit, Some(cr)),
+                    None => {
+                        self.card_conflict = Some(cr);
+                        return Some(cr);
+                    }
+                }
+            }
+            None
+        }
+
+        /// Check whether a clause is RUP(reverse unit propagation) with respect to the current clause database.
+        /// A clause is RUP if unit propagation of its negation reaches a conflict. A RUP clause is implied by the clauses.
+        /// Assignments of a previous `solve` are cleared like `add_clause`.
+        pub fn is_rup(&mut self, clause: &[Lit]) -> bool {
+            let negation: Vec<Lit> = clause.iter().map(|&lit| !lit).collect();
+            self.propagate_lits(&negation).is_none()
+        }
+
+        /// Assign `lits` and propagate them at the root level.
+        /// It returns the literals that are assigned or `None` if it reaches a conflict. Assignments are undone.
+        pub(crate) fn propagate_lits(&mut self, lits: &[Lit]) -> Option<Vec<Lit>> {
+            if self.status == Some(Status::Unsat) {
+                return None;
+            }
+            self.status = None;
+            self.pop_queue_until(1);
+            if let Some(confl) = self.propagate() {
+                self.status = Some(Status::Unsat);
+                self.prove_empty(confl);
+                return None;
+            }
+            let start = self.que.len();
+            let mut conflict = false;
+            for &lit in lits.iter() {
+                if lit.var().0 as usize >= self.assigns.len() {
+                    continue;
+                }
+                match self.eval(lit) {
+                    LitBool::True => {}
+                    LitBool::False => conflict = true,
+                    LitBool::Undef => {
+                        self.enqueue(lit, None);
+                        if self.que.len() == start + 1 {
+                            self.vardata[lit.var()].level += 1;
+                        }
+                        conflict = self.propagate().is_some();
+                    }
+                }
+                if conflict {
+                    break;
+                }
+            }
+            let assigned: Vec<Lit> = self.que.iter().skip(start).copied().collect();
+            // Checking must not change phases of the search.
+            let phases: Vec<(Var, bool)> = self
+                .que
+                .iter()
+                .skip(start)
+                .map(|lit| (lit.var(), self.vardata[lit.var()].phase))
+                .collect();
+            self.pop_queue_until(1);
+            phases
+                .into_iter()
+                .for_each(|(v, p)| self.vardata[v].phase = p);
+            if conflict {
+                None
+            } else {
+                Some(assigned)
+            }
+        }
+
+        /// The model of the last `solve` or `solve_with_assumptions` that returned `Sat`.
+        /// It is `None` if a solver hasn't found a model since clauses are added.
+        pub fn model(&self) -> Option<Model> {
+            if self.status == Some(Status::Sat) {
+                Some(Model {
+                    assigns: self.assigns.clone(),
+                })
+            } else {
+                None
+            }
+        }
+
+        /// The value of a literal in the current assignments. It is the value in a model after `Sat`.
+        pub fn value(&self, lit: Lit) -> LitBool {
+            if lit.var().0 as usize >= self.assigns.len() {
+                return LitBool::Undef;
+            }
+            self.eval(lit)
+        }
+
+        /// The value of a literal that is implied by clauses at the root level.
+        /// It is `Undef` unless a literal is fixed in all models.
+        pub fn fixed(&self, lit: Lit) -> LitBool {
+            if lit.var().0 as usize >= self.assigns.len() || self.vardata[lit.var()].level != 1 {
+                return LitBool::Undef;
+            }
+            self.eval(lit)
+        }
+
+        /// The assumptions that are refuted by the last `solve_with_assumptions` that returned `Unsat`.
+        /// Final conflict analysis follows the reasons of a false assumption back over the trail,
+        /// so the core has only the assumptions that its propagation uses. Clauses and the core are unsatisfiable.
+        /// It is empty if the refutation doesn't depend on assumptions. Activation literals of removable clauses aren't in it.
+        pub fn unsat_core(&self) -> &[Lit] {
+            &self.failed
+        }
+
+        /// The number of occurrences of each variable in original clauses.
+        pub(crate) fn occurrences(&self) -> Vec<usize> {
+            let mut occurrences = vec![0; self.assigns.len()];
+            self.clauses
+                .iter()
+                .flat_map(|&cr| self.ca.lits(cr).iter())
+                .for_each(|lit| occurrences[lit.var().0 as usize] += 1);
+            occurrences
+        }
+
+        /// Whether a solver writes a proof. Clauses that aren't derived in a proof mustn't be added then.
+        pub(crate) fn logs_proof(&self) -> bool {
+            self.proof.is_some()
+        }
+
+        /// Add many clauses at once. It is the same as calling `add_clause` for each clause but faster for large problems.
+        /// Clauses are simplified in one pass, the clause database grows once and watchers are reserved by counting.
+        /// # Arguments
+        /// * `clauses` - clauses that have one or some literal variables
+        pub fn add_clauses<C: AsRef<[Lit]>>(&mut self, clauses: &[C]) {
+            // Proof logging records clauses one by one.
+            if self.proof.is_some() || self.status == Some(Status::Unsat) {
+                clauses
+                    .iter()
+                    .for_each(|clause| self.add_clause(clause.as_ref()));
+                return;
+            }
+            self.status = None;
+            self.pop_queue_until(1);
+            let var_num = clauses
+                .iter()
+                .flat_map(|clause| clause.as_ref().iter())
+                .fold(0, |n, lit| std::cmp::max(n, lit.var().0 as usize + 1));
+            while self.assigns.len() < var_num {
+                self.new_var();
+            }
+            if clauses
+                .iter()
+                .any(|clause| clause.as_ref().iter().any(|lit| self.eliminated[lit.var()]))
+            {
+                self.restore_eliminated();
+            }
+
+            // Simplify clauses by root level assignments into one buffer.
+            // `ranges` are the ranges of clauses that have at least two literals.
+            let mut buffer: Vec<Lit> = Vec::new();
+            let mut ranges = Vec::new();
+            let mut clause = Vec::new();
+            'next_clause: for c in clauses.iter() {
+                clause.clear();
+                clause.extend_from_slice(c.as_ref());
+                clause.sort();
+                clause.dedup();
+                let start = buffer.len();
+                for (i, &lit) in clause.iter().enumerate() {
+                    // x0 v !x0 is already satisfied.
+                    if i >= 1 && lit == !clause[i - 1] {
+                        buffer.truncate(start);
+                        continue 'next_clause;
+                    }
+                    match self.eval(lit) {
+                        LitBool::True => {
+                            buffer.truncate(start);
+                            continue 'next_clause;
+                        }
+                        LitBool::False => {}
+                        LitBool::Undef => buffer.push(lit),
+                    }
+                }
+                match buffer.len() - start {
+                    0 => {
+                        // Empty clause
+                        self.status = Some(Status::Unsat);
+                        return;
+                    }
+                    1 => {
+                        // Unit Clause. It is propagated after all clauses are added.
+                        let lit = buffer.pop().unwrap();
+                        self.enqueue(lit, None);
+                    }
+                    _ => ranges.push((start, buffer.len())),
+                }
+            }
+
+            // Count watchers of each literal to reserve them once.
+            let mut counts = vec![(0, 0); self.watchers.len()];
+            for &(start, end) in ranges.iter() {
+                for &lit in buffer[start..start + 2].iter() {
+                    let count = &mut counts[(!lit).0 as usize];
+                    if end - start == 2 {
+                        count.1 += 1;
+                    } else {
+                        count.0 += 1;
+                    }
+                }
+            }
+            for (i, &(long, binary)) in counts.iter().enumerate() {
+                self.watchers[i].reserve(long);
+                self.implications[i].reserve(binary);
+            }
+            self.ca.data.reserve(buffer.len() + ranges.len() * HEADER);
+            self.clauses.reserve(ranges.len());
+            for &(start, end) in ranges.iter() {
+                let cr = self.ca.alloc(&buffer[start..end], false);
+                self.add_clause_unchecked(cr, false);
+            }
+
+            if self.propagate().is_some() {
+                // A conflict at the root level
+                self.status = Some(Status::Unsat);
+            }
+        }
+
+        /// Write the clause database in DIMACS CNF, e.g. to reproduce a search by another solver.
+        /// Root level assignments are unit clauses and an `Unsat` solver writes the empty clause.
+        /// Simplifications may have removed clauses, so the file is satisfiable iff the problem is but may have fewer models.
+        /// Activation literals of removable clauses are unit clauses like the assumptions of a search.
+        /// Learnt clauses are written after original clauses if `learnts` is true.
+        pub fn dump_cnf<W: Write>(&self, mut writer: W, learnts: bool) -> std::io::Result<()> {
+            if !self.cards.is_empty() || !self.gauss.rows.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "DIMACS doesn't support native constraints",
+                ));
+            }
+            let mut units: Vec<Lit> = self
+                .que
+                .iter()
+                .filter(|lit| self.vardata[lit.var()].level == 1)
+                .copied()
+                .collect();
+            units.extend(self.activations.iter().copied());
+            let learnts: &[CRef] = if learnts { &self.learnts } else { &[] };
+            let clauses: Vec<CRef> = self
+                .clauses
+                .iter()
+                .chain(learnts.iter())
+                .copied()
+                .filter(|&cr| self.ca.flags(cr) & DELETED == 0)
+                .collect();
+            let unsat = self.status == Some(Status::Unsat);
+            writeln!(
+                writer,
+                "p cnf {} {}",
+                self.n,
+                units.len() + clauses.len() + unsat as usize
+            )?;
+            if unsat {
+                writeln!(writer, "0")?;
+            }
+            for lit in units.iter() {
+                writeln!(writer, "{} 0", lit)?;
+            }
+            for &cr in clauses.iter() {
+                for lit in self.ca.lits(cr).iter() {
+                    write!(writer, "{} ", lit)?;
+                }
+                writeln!(writer, "0")?;
+            }
+            writer.flush()
+        }
+
+        /// Write the clause database in a compact binary format that `read_snapshot` loads much faster than DIMACS.
+        /// A snapshot has root level assignments, original clauses and clauses that are removed by simplifications.
+        /// Learnt clauses are saved with their LBDs and activities if `learnts` is true.
+        /// Parameters, proofs and assignments of a search aren't saved.
+        ///
+        /// The format is the magic `SCRWSNP1` followed by 32-bit little-endian words:
+        /// `[variables, unsat, units, eliminated, reconstruction, database(low), database(high)]`,
+        /// root level literals, eliminated variables, `[witness, length, literals...]` of each removed clause
+        /// and the clause database in the same layout as memory.
+        pub fn write_snapshot<W: Write>(
+            &self,
+            mut writer: W,
+            learnts: bool,
+        ) -> std::io::Result<()> {
+            if !self.cards.is_empty() || !self.gauss.rows.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "A snapshot doesn't support native constraints",
+                ));
+            }
+            if !self.activations.is_empty() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "A snapshot doesn't support removable clauses",
+                ));
+            }
+            let units: Vec<u32> = self
+                .que
+                .iter()
+                .filter(|lit| self.vardata[lit.var()].level == 1)
+                .map(|lit| lit.0)
+                .collect();
+            let eliminated: Vec<u32> = (0..self.n)
+                .filter(|&v| self.eliminated[v])
+                .map(|v| v as u32)
+                .collect();
+            let mut reconstruction = vec![];
+            for (witness, clause) in self.reconstruction.stack.iter() {
+                reconstruction.push(witness.0);
+                reconstruction.push(clause.len() as u32);
+                reconstruction.extend(clause.iter().map(|lit| lit.0));
+            }
+            // Deleted clauses and wasted words are dropped.
+            let mut database = vec![Lit(0)];
+            let learnts: &[CRef] = if learnts { &self.learnts } else { &[] };
+            for &cr in self.clauses.iter().chain(learnts.iter()) {
+                if self.ca.flags(cr) & DELETED != 0 {
+                    continue;
+                }
+                let offset = ClauseAllocator::offset(cr);
+                let end = offset + HEADER + self.ca.len(cr) + self.ca.is_learnt(cr) as usize;
+                database.extend_from_slice(&self.ca.data[offset..end]);
+            }
+            let header = [
+                self.n as u32,
+                (self.status == Some(Status::Unsat)) as u32,
+                units.len() as u32,
+                eliminated.len() as u32,
+                reconstruction.len() as u32,
+                database.len() as u32,
+                (database.len() as u64 >> 32) as u32,
+            ];
+            let words = header
+                .iter()
+                .chain(units.iter())
+                .chain(eliminated.iter())
+                .chain(reconstruction.iter())
+                .chain(database.iter().map(|lit| &lit.0));
+            let mut bytes = Vec::with_capacity(
+                SNAPSHOT_MAGIC.len()
+                    + 4 * (header.len()
+                        + units.len()
+                        + eliminated.len()
+                        + reconstruction.len()
+                        + database.len()),
+            );
+            bytes.extend_from_slice(SNAPSHOT_MAGIC);
+            words.for_each(|w| bytes.extend_from_slice(&w.to_le_bytes()));
+            writer.write_all(&bytes)?;
+            writer.flush()
+        }
+
+        /// Load a solver from a snapshot of `write_snapshot`.
+        /// The body of a snapshot is read at once and the clause database is used as it is.
+        /// A loaded solver has the default parameters.
+        pub fn read_snapshot<R: Read>(mut reader: R) -> std::io::Result<Solver> {
+            let invalid = |msg: &str| std::io::Error::new(std::io::ErrorKind::InvalidData, msg);
+            let mut magic = [0u8; 8];
+            reader.read_exact(&mut magic)?;
+            if &magic != SNAPSHOT_MAGIC {
+                return Err(invalid("Not a snapshot"));
+            }
+            let mut header = [0u8; 4 * 7];
+            reader.read_exact(&mut header)?;
+            let header: Vec<usize> = header
+                .chunks_exact(4)
+                .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+                .collect();
+            let n = header[0];
+            let database_len = (header[6] as u64) << 32 | header[5] as u64;
+            let len = (header[2] + header[3] + header[4]) as u64 + database_len;
+            let len = <usize as std::convert::TryFrom<u64>>::try_from(len)
+                .ok()
+                .and_then(|len| len.checked_mul(4))
+                .ok_or_else(|| invalid("A snapshot is too large"))?;
+            let mut body = vec![0u8; len];
+            reader.read_exact(&mut body)?;
+            let mut words = body
+                .chunks_exact(4)
+                .map(|b| Lit(u32::from_le_bytes([b[0], b[1], b[2], b[3]])));
+            let valid = |lit: &Lit| (lit.var().0 as usize) < n;
+
+            let mut solver = Solver::new(n, &[]);
+            let units: Vec<Lit> = words.by_ref().take(header[2]).collect();
+            for lit in words.by_ref().take(header[3]) {
+                *solver
+                    .eliminated
+                    .get_mut(lit.0 as usize)
+                    .ok_or_else(|| invalid("Invalid eliminated variable"))? = true;
+            }
+            let reconstruction: Vec<Lit> = words.by_ref().take(header[4]).collect();
+            let mut rest = &reconstruction[..];
+            while let [witness, len, tail @ ..] = rest {
+                let len = len.0 as usize;
+                if len > tail.len()
+                    || !tail[..len].contains(witness)
+                    || !tail[..len].iter().all(valid)
+                {
+                    return Err(invalid("Invalid removed clause"));
+                }
+                solver.reconstruction.push_clause(*witness, &tail[..len]);
+                rest = &tail[len..];
+            }
+            if !rest.is_empty() {
+                return Err(invalid("Invalid removed clause"));
+            }
+
+            solver.ca.data = words.collect();
+            let mut offset = 1;
+            while offset < solver.ca.data.len() {
+                let cr = CRef(NonZeroIdx::new(offset as Idx).ok_or_else(|| invalid("Too large"))?);
+                let len = solver.ca.data[offset].0 as usize;
+                let flags = solver
+                    .ca
+                    .data
+                    .get(offset + 1)
+                    .map_or(DELETED, |flags| flags.0);
+                let learnt = flags & LEARNT != 0;
+                let end = offset + HEADER + len + learnt as usize;
+                if len < 2
+                    || flags & (DELETED | RELOCATED) != 0
+                    || end > solver.ca.data.len()
+                    || !solver.ca.lits(cr).iter().all(valid)
+                {
+                    return Err(invalid("Invalid clause"));
+                }
+                solver.add_clause_unchecked(cr, learnt);
+                offset = end;
+            }
+
+            for lit in units {
+                if !valid(&lit) {
+                    return Err(invalid("Invalid unit"));
+                }
+                match solver.eval(lit) {
+                    LitBool::True => {}
+                    LitBool::False => solver.status = Some(Status::Unsat),
+                    LitBool::Undef => solver.enqueue(lit, None),
+                }
+            }
+            if header[1] != 0 || solver.propagate().is_some() {
+                solver.status = Some(Status::Unsat);
+            }
+            Ok(solver)
+        }
+
+        /// Propagate it by all enqueued values and check conflicts.
+        /// If a conflict is detected, this function returns a conflicted clause index.
+        /// `None` is no conflicts.
+        fn propagate(&mut self) -> Option<CRef> {
+            let mut conflict = None;
+            if let Some(cr) = self.card_conflict.take() {
+                self.ca.free(cr);
+            }
+            // XOR constraints are eliminated when clauses and cardinality constraints have nothing to propagate.
+            loop {
+                while self.head < self.que.len() && conflict.is_none() {
+                    let p = self.que[self.head];
+                    self.head += 1;
+                    self.stats.propagations += 1;
+                    debug_assert!(self.vardata[p.var()].level > 0);
+                    if !self.gauss.rows.is_empty() && self.gauss.columns[p.var()].is_some() {
+                        self.gauss.dirty = true;
+                    }
+                    // Counts are undone by `pop_queue_until` for each propagated literal.
+                    if !self.cards.is_empty() {
+                        for i in 0..self.card_occurs[p].len() {
+                            let c = self.card_occurs[p][i];
+                            self.cards[c].count += 1;
+                        }
+                    }
+
+                    // Binary clauses are propagated first.
+                    for i in 0..self.implications[p].len() {
+                        let (q, cr) = self.implications[p][i];
+                        match eval(&self.assigns, q) {
+                            LitBool::True => {}
+                            LitBool::False => {
+                                conflict = Some(cr);
+                                break;
+                            }
+                            LitBool::Undef => {
+                                // The first literal of a reason is the propagated literal.
+                                let clause = self.ca.lits_mut(cr);
+                                if clause[0] != q {
+                                    clause.swap(0, 1);
+                                }
+                                self.enqueue(q, Some(cr));
+                            }
+                        }
+                    }
+                    if conflict.is_some() {
+                        break;
+                    }
+
+                    // Take the watchers of `p` out so that other watchers can be updated.
+                    // `ws[..j]` are clauses that still watch `!p`.
+                    let mut ws = std::mem::take(&mut self.watchers[p]);
+                    // Root literals skip blockers so that every clause watching `!p` settles with a true `clause[0]`.
+                    // Then loading a snapshot and propagating its units again doesn't move watches.
+                    let blocking = self.vardata[p.var()].level > 1;
+                    let mut j = 0;
+                    let mut idx = 0;
+                    'next_clause: while idx < ws.len() {
+                        let w = ws[idx];
+                        idx += 1;
+                        if blocking && eval(&self.assigns, w.blocker) == LitBool::True {
+                            ws[j] = w;
+                            j += 1;
+                            continue 'next_clause;
+                        }
+                        let cr = w.cref;
+                        let clause = self.ca.lits_mut(cr);
+                        debug_assert!(clause[0] == !p || clause[1] == !p);
+
+                        // make sure that the clause[1] is the false literal.
+                        if clause[0] == !p {
+                            clause.swap(0, 1);
+                        }
+                        let first = clause[0];
+                        let w = Watcher {
+                            cref: cr,
+                            blocker: first,
+                        };
+                        // already satisfied
+                        if eval(&self.assigns, first) == LitBool::True {
+                            debug_assert!(first != clause[1]);
+                            ws[j] = w;
+                            j += 1;
+                            continue 'next_clause;
+                        }
+
+                        // Found a literal isn't false(true or undefined)
+                        if let Some(k) =
+                            find_lit(&self.assigns, &clause[2..], LitBool::False, false)
+                        {
+                            let lit = clause[k + 2];
+                            clause.swap(1, k + 2);
+                            self.watchers[!lit].push(w);
+                            continue 'next_clause;
+                        }
+                        ws[j] = w;
+                        j += 1;
+
+                        if eval(&self.assigns, first) == LitBool::False {
+                            // CONFLICT
+                            // a first literal(clause[0]) is false.
+                            // clause[1] is a false
+                            // clause[2..len] is a false
+                            conflict = Some(cr);
+                            // keep the rest of watchers
+                            while idx < ws.len() {
+                                ws[j] = ws[idx];
+                                j += 1;
+                                idx += 1;
+                            }
+                        } else {
+                            // UNIT PROPAGATION
+                            // a first literal(clause[0]) isn't assigned.
+                            // clause[1] is a false
+                            // clause[2..len] is a false
+
+                            debug_assert_eq!(self.vardata[first.var()].level, 0);
+                            self.enqueue(first, Some(cr));
+                        }
+                    }
+                    ws.truncate(j);
+                    self.watchers[p] = ws;
+                    if conflict.is_none() && !self.cards.is_empty() {
+                        conflict = self.propagate_cards(p);
+                    }
+                }
+                if conflict.is_some() || !self.gauss.dirty {
+                    break;
+                }
+                self.gauss.dirty = false;
+                conflict = self.propagate_xors();
+                if self.head == self.que.len() {
+                    break;
+                }
+            }
+
+            conflict
+        }
+        fn locked(&self, cr: CRef) -> bool {
+            let c = self.ca.lits(cr)[0];
+            if self.eval(c) == LitBool::True {
+                if let Some(reason) = self.vardata[c.var()].reason {
+                    return reason == cr;
+                }
+            }
+            false
+        }
+        fn unwatch_clause(&mut self, cr: CRef) {
+            if self.ca.len(cr) == 2 {
+                for idx in 0..2 {
+                    let p = !self.ca.lits(cr)[idx];
+                    let i = self.implications[p]
+                        .iter()
+                        .position(|&(_, c)| c == cr)
+                        .unwrap();
+                    self.implications[p].swap_remove(i);
+                }
+                return;
+            }
+            let mut cnt = 0;
+            for idx in 0..2 {
+                let p = !self.ca.lits(cr)[idx];
+                let n = self.watchers[p].len();
+                for i in 0..n {
+                    if self.watchers[p][i].cref == cr {
+                        self.watchers[p].swap(i, n - 1);
+                        self.watchers[p].pop();
+                        cnt += 1;
+                        break;
+                    }
+                }
+            }
+            debug_assert!(cnt == 2);
+        }
+        /// Unwatch and delete a clause from the clause database.
+        fn delete_clause(&mut self, cr: CRef) {
+            self.unwatch_clause(cr);
+            if let Some(proof) = self.proof.as_mut() {
+                proof.delete(cr, self.ca.lits(cr));
+            }
+            if self.locked(cr) {
+                let c = self.ca.lits(cr)[0];
+                self.vardata[c.var()].reason = None;
+            }
+            self.ca.free(cr);
+        }
+        /// Delete learnt clauses that `Config::deletion` selects.
+        fn reduce_learnts(&mut self) {
+            let ca = &self.ca;
+            let learnts: Vec<LearntClause> = self
+                .learnts
+                .iter()
+                .map(|&cr| LearntClause {
+                    len: ca.len(cr),
+                    lbd: ca.lbd(cr),
+                    activity: ca.activity(cr),
+                    used: ca.flags(cr) & USED != 0,
+                })
+                .collect();
+            let mut deleted = vec![false; learnts.len()];
+            self.config.deletion.select(&learnts, &mut deleted);
+
+            let mut new_size = 0;
+            for (i, &deleted) in deleted.iter().enumerate() {
+                let cr = self.learnts[i];
+                if deleted && self.ca.len(cr) > 2 && !self.locked(cr) {
+                    self.delete_clause(cr);
+                    self.stats.deleted += 1;
+                } else {
+                    self.ca.unmark(cr, USED);
+                    self.learnts[new_size] = cr;
+                    new_size += 1;
+                }
+            }
+
+            self.learnts.truncate(new_size);
+            self.check_garbage();
+        }
+
+        /// Compact the clause database if it has too many deleted clauses.
+        fn check_garbage(&mut self) {
+            if self.ca.wasted * 5 > self.ca.data.len() {
+                self.garbage_collect();
+            }
+        }
+
+        /// Move all alive clauses to a new clause database and update all references to them.
+        fn garbage_collect(&mut self) {
+            let mut to = ClauseAllocator::with_capacity(self.ca.data.len() - self.ca.wasted);
+            to.activity_inc = self.ca.activity_inc;
+            // The explanation of the last conflict isn't needed after conflict analysis.
+            self.card_conflict = None;
+            let mut clause_id = HashMap::new();
+            for cr in self.clauses.iter_mut().chain(self.learnts.iter_mut()) {
+                let old = *cr;
+                *cr = self.ca.relocate(*cr, &mut to);
+                if let Some(proof) = self.proof.as_ref() {
+                    clause_id.insert(*cr, proof.clause_id[&old]);
+                }
+            }
+            if let Some(proof) = self.proof.as_mut() {
+                proof.clause_id = clause_id;
+            }
+            for lit in self.que.iter() {
+                if let Some(cr) = self.vardata[lit.var()].reason {
+                    self.vardata[lit.var()].reason = Some(self.ca.relocate(cr, &mut to));
+                }
+            }
+            self.ca = to;
+            self.watchers.iter_mut().for_each(|ws| ws.clear());
+            self.implications.iter_mut().for_each(|ws| ws.clear());
+            for i in 0..self.clauses.len() {
+                self.attach_clause(self.clauses[i]);
+            }
+            for i in 0..self.learnts.len() {
+                self.attach_clause(self.learnts[i]);
+            }
+        }
+
+        /// Reset saved phases by the schedule of `Rephasing` at the root level.
+        fn rephase(&mut self) {
+            let original = self.config.polarity;
+            match self.rephasing.count % 6 {
+                1 => self.vardata.iter_mut().for_each(|d| d.phase = !original),
+                3 => {
+                    for v in 0..self.n {
+                        self.vardata[v].phase = self.next_random() >> 63 == 1;
+                    }
+                }
+                5 => self.vardata.iter_mut().for_each(|d| d.phase = original),
+                _ => {
+                    for lit in self.rephasing.best.iter() {
+                        self.vardata[lit.var()].phase = lit.pos();
+                    }
+                }
+            }
+            self.rephasing.best.clear();
+            self.rephasing.conflicts = 0;
+            self.rephasing.count += 1;
+        }
+
+        fn pop_queue_until(&mut self, backtrack_level: Idx) {
+            self.pop_queue_except(backtrack_level, None);
+        }
+
+        /// `pop_queue_until` that doesn't assign `except` again even if it is still implied.
+        fn pop_queue_except(&mut self, backtrack_level: Idx, except: Option<Var>) {
+            // elevated literals and their reasons that are unassigned
+            let mut reimplied = vec![];
+            while let Some(&p) = self.que.back() {
+                if self.vardata[p.var()].level > backtrack_level {
+                    if self.elevated[p.var()] && Some(p.var()) != except {
+                        self.elevated[p.var()] = false;
+                        reimplied.push((p, self.vardata[p.var()].reason.unwrap()));
+                    }
+                    if !self.cards.is_empty() || !self.gauss.rows.is_empty() {
+                        self.unassign_native(p);
+                    }
+                    self.branching.unassign(p.var());
+                    self.vardata[p.var()].phase = matches!(self.assigns[p.var()], LitBool::True);
+                    self.assigns[p.var()] = LitBool::Undef;
+                    self.vardata[p.var()].reason = None;
+                    self.vardata[p.var()].level = 0;
+                    self.que.pop_back();
                 } else {
-                    let cr = CRef::new(clause.clone());
-                    solver.add_clause_unchecked(cr, false);
+                    break;
                 }
-            });
-            solver
+            }
+            // Literals that are kept have been propagated unless they are still pending.
+            self.head = std::cmp::min(self.head, self.que.len());
+            // An elevated literal is still implied if the other literals of its reason are kept.
+            // It is assigned again at the current level, otherwise its propagation would be missed.
+            for (p, cr) in reimplied.into_iter().rev() {
+                let clause = self.ca.lits(cr);
+                debug_assert_eq!(clause[0], p);
+                if clause[1..].iter().all(|&q| self.eval(q) == LitBool::False) {
+                    let level = clause[1..]
+                        .iter()
+                        .map(|q| self.vardata[q.var()].level)
+                        .max()
+                        .unwrap();
+                    self.enqueue(p, Some(cr));
+                    self.elevated[p.var()] = self.vardata[p.var()].level > level;
+                }
+            }
         }
-        fn eval(&self, lit: Lit) -> LitBool {
-            LitBool::from(self.assigns[lit.var()] as i8 ^ lit.neg() as i8)
+
+        /// Undo the counts of cardinality constraints of the last literal `p` of the trail
+        /// and free the explanation of it by a native constraint.
+        fn unassign_native(&mut self, p: Lit) {
+            if self.que.len() <= self.head {
+                for i in 0..self.card_occurs[p].len() {
+                    let c = self.card_occurs[p][i];
+                    self.cards[c].count -= 1;
+                }
+            }
+            if let Some(cr) = self.vardata[p.var()].reason {
+                if self.ca.flags(cr) & EXPLANATION != 0 {
+                    self.ca.free(cr);
+                }
+            }
+        }
+
+        /// Whether a variable is in a native constraint. Simplifications that remove a variable skip it.
+        fn in_native(&self, v: Var) -> bool {
+            let (pos, neg) = (Lit::new(v.0, true), Lit::new(v.0, false));
+            !self.card_occurs[pos].is_empty()
+                || !self.card_occurs[neg].is_empty()
+                || self.gauss.columns[v].is_some()
+        }
+
+        /// Add clauses that are removed by simplifications again and put eliminated variables back to the search.
+        /// A clause or an assumption that has an eliminated variable needs them.
+        fn restore_eliminated(&mut self) {
+            // Variables may be on the trail of the conflict of an unsatisfiable solver.
+            if self.status == Some(Status::Unsat) {
+                return;
+            }
+            for v in 0..self.eliminated.len() {
+                if self.eliminated[v] {
+                    self.eliminated[v] = false;
+                    self.assigns[v] = LitBool::Undef;
+                    self.branching.unassign(Var(v as u32));
+                }
+            }
+            let stack = std::mem::take(&mut self.reconstruction.stack);
+            stack.iter().for_each(|(_, clause)| self.add_clause(clause));
         }
-        /// Enqueue a variable to assign a `value` to a boolean `assign`
-        fn enqueue(&mut self, lit: Lit, reason: Option<CRef>) {
-            debug_assert!(self.level[lit.var()] == 0);
-            self.assigns[lit.var()] = LitBool::from(lit.neg() as i8);
 
-            self.reason[lit.var()] = reason.map(|cr: CRef| Rc::downgrade(&cr.0));
-            self.level[lit.var()] = if let Some(last) = self.que.back() {
-                self.level[last.var()]
+        /// Remove clauses that are satisfied at the root level.
+        fn remove_satisfied(&mut self, learnt: bool) {
+            let mut cs = if learnt {
+                std::mem::take(&mut self.learnts)
             } else {
-                1
+                std::mem::take(&mut self.clauses)
             };
-            self.que.push_back(lit);
+            cs.retain(|&cr| {
+                let satisfied =
+                    find_lit(&self.assigns, self.ca.lits(cr), LitBool::True, true).is_some();
+                if satisfied {
+                    self.delete_clause(cr);
+                } else {
+                    self.strip_falsified(cr);
+                }
+                !satisfied
+            });
+            if learnt {
+                self.learnts = cs;
+            } else {
+                self.clauses = cs;
+            }
         }
 
-        // Create a new space for one variable.
-        pub fn new_var(&mut self) {
-            let v = Var(self.n as u32);
-            self.n += 1;
-            self.assigns.push(LitBool::Undef);
-            self.polarity.push(false);
-            self.reason.push(None);
-            self.level.push(0);
-            self.order_heap.push(v);
-            self.seen.push(false);
-            // for literals
-            self.watchers.push(Vec::new());
-            self.watchers.push(Vec::new());
+        /// Remove literals that are false at the root level from a clause that isn't satisfied.
+        /// All assignments must be propagated so that watched literals are unassigned.
+        fn strip_falsified(&mut self, cr: CRef) {
+            let lits = self.ca.lits(cr);
+            debug_assert!(lits[..2]
+                .iter()
+                .all(|&lit| self.eval(lit) == LitBool::Undef));
+            if find_lit(&self.assigns, &lits[2..], LitBool::False, true).is_none() {
+                return;
+            }
+            let before = lits.to_vec();
+            let mut falsified = vec![];
+            let mut len = 2;
+            for &lit in before[2..].iter() {
+                if self.eval(lit) == LitBool::False {
+                    falsified.push(lit);
+                } else {
+                    self.ca.lits_mut(cr)[len] = lit;
+                    len += 1;
+                }
+            }
+            // A binary clause moves to the implication lists.
+            if len == 2 {
+                self.unwatch_clause(cr);
+            }
+            self.ca.shrink(cr, len);
+            if len == 2 {
+                self.attach_clause(cr);
+            }
+
+            // A stripped clause is resolved with unit clauses of the removed literals.
+            if let Some(proof) = self.proof.as_mut() {
+                let id = proof.clause_id[&cr];
+                let mut antecedents = vec![(id, before.clone())];
+                antecedents.extend(falsified.iter().map(|&lit| proof.unit(lit)));
+                let new_id = proof.derive(self.ca.lits(cr), antecedents);
+                proof.delete(cr, &before);
+                proof.clause_id.insert(cr, new_id);
+            }
         }
 
-        /// This method is only for internal usage and almost same as `add_clause`
-        /// But, this method doesn't grow the size of array.
-        fn add_clause_unchecked(&mut self, cr: CRef, learnt: bool) {
-            let clause = cr.0.borrow();
-            debug_assert!(clause.len() >= 2);
-            //let cr = Rc::new(RefCell::new(clause));
+        /// Simplify the clause database at the root level.
+        /// Satisfied clauses are removed and false literals are removed from the others.
+        /// It returns `Unsat` if the propagation of root level assignments reaches a conflict and `Indeterminate` otherwise.
+        /// It is useful between batches of `add_clause`. Simplification passes of `Config::simplifiers` aren't run.
+        pub fn simplify(&mut self) -> Status {
+            if self.status == Some(Status::Unsat) {
+                return Status::Unsat;
+            }
+            self.status = None;
+            self.pop_queue_until(1);
+            if let Some(confl) = self.propagate() {
+                self.status = Some(Status::Unsat);
+                self.prove_empty(confl);
+                return Status::Unsat;
+            }
+            self.remove_satisfied(true);
+            self.remove_satisfied(false);
+            self.check_garbage();
+            Status::Indeterminate
+        }
+
+        /// Run the simplification passes of `Config::simplifiers`.
+        /// A pass that leaves a new unit clause ends them so that the search propagates it first.
+        fn run_simplifiers(&mut self) {
+            let simplifiers = self.config.simplifiers.clone();
+            for (simplifier, budget) in simplifiers.iter() {
+                simplifier.simplify(self, *budget);
+                if self.status == Some(Status::Unsat) || self.head < self.que.len() {
+                    break;
+                }
+            }
+            self.check_garbage();
+        }
+
+        /// Asymmetric literal elimination(ALE) and asymmetric tautology elimination(ATE) of original clauses,
+        /// or vivification of learnt clauses if `learnt` is true.
+        /// The negations of literals of a clause are assigned one by one and propagated without the clause.
+        /// - A literal that becomes false is implied by the others. It is removed.
+        /// - A literal that becomes true or a conflict means that the assigned literals are implied. The rest are removed.
+        ///   If no literal is removed, a clause is an asymmetric tautology and it is deleted.
+        ///
+        /// Each clause is probed once by at most `budget` propagations.
+        /// It stops at a new unit clause so that the search propagates it first.
+        fn asymmetric_eliminate(&mut self, budget: usize, learnt: bool) {
+            if budget == 0 {
+                return;
+            }
+            debug_assert_eq!(self.head, self.que.len());
+            let mut cs = std::mem::take(if learnt {
+                &mut self.learnts
+            } else {
+                &mut self.clauses
+            });
+            let mut j = 0;
+            for i in 0..cs.len() {
+                let cr = cs[i];
+                // A clause that has an assigned literal is left to `RemoveSatisfied`.
+                if self.ca.len(cr) < 3
+                    || self.ca.flags(cr) & PROBED != 0
+                    || self
+                        .ca
+                        .lits(cr)
+                        .iter()
+                        .any(|&lit| self.eval(lit) != LitBool::Undef)
+                {
+                    cs[j] = cr;
+                    j += 1;
+                    continue;
+                }
+                self.unwatch_clause(cr);
+                let lits = self.ca.lits(cr).to_vec();
+                let start = self.que.len();
+                let mut kept = vec![];
+                let mut implied = false;
+                let mut conflict = None;
+                for (k, &lit) in lits.iter().enumerate() {
+                    match self.eval(lit) {
+                        LitBool::False => {}
+                        LitBool::True => {
+                            kept.push(lit);
+                            implied = true;
+                            break;
+                        }
+                        LitBool::Undef => {
+                            kept.push(lit);
+                            if self.que.len() - start >= budget {
+                                kept.extend_from_slice(&lits[k + 1..]);
+                                break;
+                            }
+                            self.enqueue(!lit, None);
+                            if self.que.len() == start + 1 {
+                                self.vardata[lit.var()].level += 1;
+                            }
+                            conflict = self.propagate();
+                            if conflict.is_some() {
+                                implied = true;
+                                break;
+                            }
+                        }
+                    }
+                }
+                // Learnt clauses may be implied by the clause itself and they are deleted later,
+                // so an original clause is deleted only if it is implied by other original clauses.
+                let irredundant = conflict.iter().all(|&cr| !self.ca.is_learnt(cr))
+                    && self.que.iter().skip(start).all(|lit| {
+                        self.vardata[lit.var()]
+                            .reason
+                            .is_none_or(|cr| !self.ca.is_learnt(cr))
+                    });
+                let delete = implied && kept.len() == lits.len() && (learnt || irredundant);
+
+                // A new clause is derived from the original clause and the propagation.
+                let mut node = None;
+                if let Some(proof) = self.proof.as_mut() {
+                    if !delete && kept.len() < lits.len() {
+                        let (ca, vardata) = (&self.ca, &self.vardata);
+                        let mut reasons: Vec<CRef> = self
+                            .que
+                            .iter()
+                            .skip(start)
+                            .filter_map(|lit| vardata[lit.var()].reason)
+                            .collect();
+                        reasons.extend(conflict);
+                        let mut antecedents = vec![(proof.clause_id[&cr], lits.clone())];
+                        for &r in reasons.iter() {
+                            let clause = ca.lits(r);
+                            antecedents.push((proof.clause_id[&r], clause.to_vec()));
+                            for &lit in clause.iter() {
+                                if vardata[lit.var()].level == 1 {
+                                    antecedents.push(proof.unit(lit));
+                                }
+                            }
+                        }
+                        node = Some(proof.derive(&kept, antecedents));
+                    }
+                }
 
-            self.watchers[!clause[0]].push(Rc::downgrade(&cr.0));
-            self.watchers[!clause[1]].push(Rc::downgrade(&cr.0));
+                // Probing must not change phases of the search.
+                let phases: Vec<(Var, bool)> = self
+                    .que
+                    .iter()
+                    .skip(start)
+                    .map(|lit| (lit.var(), self.vardata[lit.var()].phase))
+                    .collect();
+                self.pop_queue_until(1);
+                phases
+                    .into_iter()
+                    .for_each(|(v, p)| self.vardata[v].phase = p);
+
+                if delete {
+                    if let Some(proof) = self.proof.as_mut() {
+                        proof.delete(cr, &lits);
+                    }
+                    self.ca.free(cr);
+                    continue;
+                }
+                if kept.len() < lits.len() {
+                    if let Some(proof) = self.proof.as_mut() {
+                        proof.delete(cr, &lits);
+                    }
+                }
+                if kept.len() == 1 {
+                    self.ca.free(cr);
+                    self.enqueue(kept[0], None);
+                    if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                        proof.set_unit(kept[0].var(), node);
+                    }
+                    let rest = cs.len() - i - 1;
+                    cs.copy_within(i + 1.., j);
+                    j += rest;
+                    break;
+                }
+                if kept.len() < lits.len() {
+                    self.ca.lits_mut(cr)[..kept.len()].copy_from_slice(&kept);
+                    self.ca.shrink(cr, kept.len());
+                    if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                        proof.clause_id.insert(cr, node);
+                    }
+                }
+                self.ca.mark(cr, PROBED);
+                self.attach_clause(cr);
+                cs[j] = cr;
+                j += 1;
+            }
+            cs.truncate(j);
             if learnt {
-                self.learnts.push(cr.clone());
+                self.learnts = cs;
             } else {
-                self.clauses.push(cr.clone());
+                self.clauses = cs;
             }
         }
-        /// Add a new clause to `clauses` and watch a clause.
-        /// If a variable is greater than the size of array, grow it.
-        /// # Arguments
-        /// * `clause` - a clause has one or some literal variables
-        pub fn add_clause(&mut self, clause: &[Lit]) {
-            // grow the space of array variables.
-            clause.iter().for_each(|c| {
-                while c.var().0 as usize >= self.assigns.len() {
-                    self.new_var();
+        /// Failed literal probing with hyper-binary resolution.
+        /// Each probe assigns a free literal `p` at the second level and propagates it.
+        /// - A conflict means that `p` fails. `!p` is a unit clause.
+        /// - A literal `q` that a long clause implies is implied by `p` alone. The binary clause `!p v q` is learnt
+        ///   so that binary clauses propagate it directly later(hyper-binary resolution).
+        ///
+        /// Roots of the binary implication graph are probed first, since their propagations cover their descendants.
+        /// At most `budget` literals are probed, going on from the last literal of the previous call.
+        /// It stops at a new unit clause so that the search propagates it first.
+        fn probe(&mut self, budget: usize) {
+            debug_assert_eq!(self.head, self.que.len());
+            let n = 2 * self.n;
+            let free = |solver: &Solver, p: Lit| {
+                solver.assigns[p.var()] == LitBool::Undef && !solver.eliminated[p.var()]
+            };
+            let mut candidates: Vec<Lit> = (0..n)
+                .map(|i| (self.probe_next + i) % n)
+                .map(|i| Lit::new((i / 2) as u32, i & 1 == 0))
+                .filter(|&p| free(self, p))
+                .take(budget)
+                .collect();
+            // Roots come first. The order of the rest is kept.
+            candidates.sort_by_key(|&p| !self.implications[!p].is_empty());
+
+            for (k, p) in candidates.into_iter().enumerate() {
+                self.probe_next = (self.probe_next + k + 1) % n.max(1);
+                if !free(self, p) {
+                    continue;
                 }
-            });
+                let start = self.que.len();
+                self.enqueue(p, None);
+                self.vardata[p.var()].level += 1;
+                let conflict = self.propagate();
 
-            // Simplify a clause
-            let mut clause = clause.to_vec();
-            clause.sort();
-            let mut len = 0;
-            for i in 0..clause.len() {
-                let mut remove = false;
-                if i >= 1 {
-                    // x0 v !x0 means a clause is already satisfied.
-                    // you don't need to add it.
-                    if clause[i] == !clause[i - 1] {
-                        return;
+                // Reasons of the propagation in order of the trail and new clauses that they derive.
+                let mut reasons: Vec<CRef> = vec![];
+                let mut derived: Vec<(Clause, Option<usize>)> = vec![];
+                for i in start + 1..self.que.len() {
+                    let q = self.que[i];
+                    let reason = self.vardata[q.var()].reason.unwrap();
+                    reasons.push(reason);
+                    if conflict.is_none() && self.ca.len(reason) > 2 {
+                        derived.push((vec![!p, q], None));
                     }
-                    // x0 v x0 duplicated
-                    if clause[i] == clause[i - 1] {
-                        remove = true;
+                }
+                if let Some(conflict) = conflict {
+                    reasons.push(conflict);
+                    derived.push((vec![!p], None));
+                }
+                if let Some(proof) = self.proof.as_mut() {
+                    let (ca, vardata, que) = (&self.ca, &self.vardata, &self.que);
+                    let mut antecedents = vec![];
+                    let mut used = 0;
+                    for (clause, node) in derived.iter_mut() {
+                        // The binary clause of `q` needs reasons up to `q`.
+                        let end = match clause.get(1) {
+                            Some(&q) => {
+                                que.iter().skip(start + 1).position(|&l| l == q).unwrap() + 1
+                            }
+                            None => reasons.len(),
+                        };
+                        for &r in reasons[used..end].iter() {
+                            let lits = ca.lits(r);
+                            antecedents.push((proof.clause_id[&r], lits.to_vec()));
+                            for &lit in lits.iter() {
+                                if vardata[lit.var()].level == 1 {
+                                    antecedents.push(proof.unit(lit));
+                                }
+                            }
+                        }
+                        used = end;
+                        *node = Some(proof.derive(clause, antecedents.clone()));
                     }
                 }
-                let lit = clause[i];
-                //already assigned
-                match self.eval(lit) {
-                    LitBool::True => {
-                        // a clause is already satisfied. You don't need to add it.
-                        return;
+
+                // Probing must not change phases of the search.
+                let phases: Vec<(Var, bool)> = self
+                    .que
+                    .iter()
+                    .skip(start)
+                    .map(|lit| (lit.var(), self.vardata[lit.var()].phase))
+                    .collect();
+                self.pop_queue_until(1);
+                phases
+                    .into_iter()
+                    .for_each(|(v, p)| self.vardata[v].phase = p);
+
+                if conflict.is_some() {
+                    let (_, node) = derived.pop().unwrap();
+                    self.enqueue(!p, None);
+                    if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                        proof.set_unit(p.var(), node);
                     }
-                    LitBool::False => {
-                        // a literal is already false. You can remove it from a clause.
-                        remove = true;
+                    return;
+                }
+                for (clause, node) in derived {
+                    let cr = self.ca.alloc(&clause, true);
+                    self.ca.set_lbd(cr, 2);
+                    if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                        proof.clause_id.insert(cr, node);
                     }
-                    _ => {}
+                    self.add_clause_unchecked(cr, true);
                 }
-
-                if !remove {
-                    clause[len] = lit;
-                    len += 1;
+            }
+        }
+        /// Find XOR constraints of 3 to 6 variables in original clauses and add them as native constraints.
+        /// `x1 ^ x2 ^ ... ^ xk = p` is encoded by `2^(k-1)` clauses over the same variables.
+        /// Each of them excludes one assignment of the other parity. Their clauses are kept.
+        /// Constraints are added while the matrix has at most `budget` rows. Proofs don't support native constraints.
+        fn extract_xors(&mut self, budget: usize) {
+            if self.proof.is_some() || self.status == Some(Status::Unsat) {
+                return;
+            }
+            // The clauses of `(vars, p)` and the signs of their literals in the order of `vars`.
+            let mut groups: HashMap<_, Vec<(u32, CRef)>> = HashMap::new();
+            for &cr in self.clauses.iter() {
+                if !(3..=6).contains(&self.ca.len(cr)) || self.ca.flags(cr) & XOR != 0 {
+                    continue;
+                }
+                let mut lits = self.ca.lits(cr).to_vec();
+                lits.sort();
+                if lits.windows(2).any(|w| w[0].var() == w[1].var()) {
+                    continue;
                 }
+                let vars: Vec<Var> = lits.iter().map(|lit| lit.var()).collect();
+                let signs = lits
+                    .iter()
+                    .enumerate()
+                    .fold(0, |signs, (i, lit)| signs | (lit.neg() as u32) << i);
+                // A clause excludes the assignment that makes its literals false. It has the parity of negative literals.
+                let parity = signs.count_ones() & 1 == 0;
+                groups.entry((vars, parity)).or_default().push((signs, cr));
             }
-            clause.truncate(len);
 
-            if clause.is_empty() {
-                // Empty clause
-                self.status = Some(Status::Unsat);
-            } else if clause.len() == 1 {
-                // Unit Clause
-                let c = clause[0];
-                // already assigned
-                if self.eval(c) == LitBool::False {
-                    self.status = Some(Status::Unsat);
+            // Groups are sorted so that the same constraints are added by `budget` every time.
+            let mut groups: Vec<_> = groups.into_iter().collect();
+            groups.sort();
+            let mut xors = vec![];
+            for ((vars, parity), mut clauses) in groups {
+                clauses.sort();
+                clauses.dedup_by_key(|(signs, _)| *signs);
+                if clauses.len() != 1 << (vars.len() - 1) {
+                    continue;
                 }
-                self.enqueue(c, None);
-                // If the conflict happnes at the root level(decision level: 0), which means that a given problem is UNSATISFIABLE.
-                if self.propagate().is_some() {
-                    self.status = Some(Status::Unsat);
+                if self.gauss.rows.len() + xors.len() >= budget {
+                    break;
                 }
-            } else {
-                debug_assert!(clause.len() >= 2);
-                let l1 = clause[0];
-                let l2 = clause[1];
-                let cr = CRef::new(clause);
+                clauses.iter().for_each(|&(_, cr)| self.ca.mark(cr, XOR));
+                let mut lits: Vec<Lit> = vars.iter().map(|v| Lit::new(v.0, true)).collect();
+                if !parity {
+                    lits[0] = !lits[0];
+                }
+                xors.push(lits);
+            }
+            if !xors.is_empty() {
+                self.add_xors(&xors);
+            }
+        }
 
-                self.watchers[!l1].push(Rc::downgrade(&cr.0));
-                self.watchers[!l2].push(Rc::downgrade(&cr.0));
-                self.clauses.push(cr);
+        /// The resolvent of `p` and `n` on `v` without false literals at the root level.
+        /// `None` means that it is a tautology or satisfied at the root level.
+        fn resolve(&self, p: CRef, n: CRef, v: Var) -> Option<Clause> {
+            let mut resolvent: Clause = self
+                .ca
+                .lits(p)
+                .iter()
+                .chain(self.ca.lits(n).iter())
+                .copied()
+                .filter(|lit| lit.var() != v && self.eval(*lit) != LitBool::False)
+                .collect();
+            resolvent.sort();
+            resolvent.dedup();
+            // `x` and `!x` are next to each other.
+            if resolvent.windows(2).any(|w| w[0] == !w[1])
+                || resolvent.iter().any(|&lit| self.eval(lit) == LitBool::True)
+            {
+                return None;
             }
+            Some(resolvent)
         }
 
-        /// Propagate it by all enqueued values and check conflicts.
-        /// If a conflict is detected, this function returns a conflicted clause index.
-        /// `None` is no conflicts.
-        fn propagate(&mut self) -> Option<CWRef> {
-            let mut conflict = None;
-            'conflict: while self.head < self.que.len() {
-                let p = self.que[self.head];
-                self.head += 1;
-                debug_assert!(self.level[p.var()] > 0);
+        /// Bounded variable elimination(BVE) of original clauses.
+        /// The clauses of a variable are replaced by their non-tautological resolvents on it
+        /// if the resolvents aren't more than the clauses. Learnt clauses of the variable are deleted.
+        /// Variables are tried in the order of the number of their resolvents and at most `budget` resolvents are tried.
+        /// It stops at a new unit clause so that the search propagates it first.
+        fn eliminate_variables(&mut self, budget: usize) {
+            if budget == 0 {
+                return;
+            }
+            debug_assert_eq!(self.head, self.que.len());
+            let mut occurs: Vec<Vec<CRef>> = vec![vec![]; 2 * self.n];
+            for &cr in self.clauses.iter() {
+                for &lit in self.ca.lits(cr).iter() {
+                    occurs[lit].push(cr);
+                }
+            }
+            let mut learnt_occurs: Vec<Vec<CRef>> = vec![vec![]; self.n];
+            for &cr in self.learnts.iter() {
+                for &lit in self.ca.lits(cr).iter() {
+                    learnt_occurs[lit.var()].push(cr);
+                }
+            }
+            let mut vars: Vec<Var> = (0..self.n)
+                .map(|v| Var(v as u32))
+                .filter(|&v| {
+                    self.vardata[v].level == 0
+                        && !self.eliminated[v]
+                        && !self.in_native(v)
+                        && self.frozen[v] == 0
+                })
+                .collect();
+            vars.sort_by_key(|&v| {
+                occurs[Lit::new(v.0, true)].len() * occurs[Lit::new(v.0, false)].len()
+            });
 
-                let mut idx = 0;
+            let mut tried = 0;
+            'next_var: for v in vars {
+                let (pos, neg) = (Lit::new(v.0, true), Lit::new(v.0, false));
+                for &lit in [pos, neg].iter() {
+                    let ca = &self.ca;
+                    occurs[lit].retain(|&cr| ca.flags(cr) & DELETED == 0);
+                }
+                let limit = occurs[pos].len() + occurs[neg].len();
+                if limit == 0 || tried + occurs[pos].len() * occurs[neg].len() > budget {
+                    continue;
+                }
+                let mut resolvents = vec![];
+                for &p in occurs[pos].iter() {
+                    for &n in occurs[neg].iter() {
+                        tried += 1;
+                        if let Some(resolvent) = self.resolve(p, n, v) {
+                            resolvents.push((resolvent, p, n));
+                            if resolvents.len() > limit {
+                                continue 'next_var;
+                            }
+                        }
+                    }
+                }
 
-                'next_clause: while idx < self.watchers[p].len() {
-                    let m = self.watchers[p].len();
-                    debug_assert!(idx < m);
-                    let cwr = self.watchers[p][idx].clone();
-                    debug_assert!(cwr.upgrade().is_some());
+                // Resolvents are derived before their antecedents are removed.
+                let mut unit = false;
+                for (resolvent, p, n) in resolvents {
+                    // A unit clause of a previous resolvent may assign literals.
+                    if resolvent.iter().any(|&lit| self.eval(lit) == LitBool::True) {
+                        continue;
+                    }
+                    let clause: Clause = resolvent
+                        .iter()
+                        .copied()
+                        .filter(|&lit| self.eval(lit) != LitBool::False)
+                        .collect();
+                    let mut node = None;
+                    if let Some(proof) = self.proof.as_mut() {
+                        let (ca, assigns) = (&self.ca, &self.assigns);
+                        let mut antecedents = vec![
+                            (proof.clause_id[&p], ca.lits(p).to_vec()),
+                            (proof.clause_id[&n], ca.lits(n).to_vec()),
+                        ];
+                        for &r in [p, n].iter() {
+                            for &lit in ca.lits(r).iter() {
+                                if eval(assigns, lit) == LitBool::False {
+                                    antecedents.push(proof.unit(lit));
+                                }
+                            }
+                        }
+                        node = Some(proof.derive(&clause, antecedents));
+                    }
+                    match clause.len() {
+                        0 => {
+                            self.status = Some(Status::Unsat);
+                            if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                                proof.empty(node);
+                            }
+                            break 'next_var;
+                        }
+                        1 => {
+                            self.enqueue(clause[0], None);
+                            if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                                proof.set_unit(clause[0].var(), node);
+                            }
+                            unit = true;
+                        }
+                        _ => {
+                            let cr = self.ca.alloc(&clause, false);
+                            if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                                proof.clause_id.insert(cr, node);
+                            }
+                            self.add_clause_unchecked(cr, false);
+                            clause.iter().for_each(|&lit| occurs[lit].push(cr));
+                        }
+                    }
+                }
 
-                    let cr = cwr.upgrade().unwrap();
-                    debug_assert!(Rc::strong_count(&cr) == 2);
-                    let mut clause = cr.borrow_mut();
+                // The clauses of `v` are removed and kept to extend models.
+                for &lit in [pos, neg].iter() {
+                    for cr in std::mem::take(&mut occurs[lit]) {
+                        self.reconstruction.push_clause(lit, self.ca.lits(cr));
+                        self.delete_clause(cr);
+                    }
+                }
+                for cr in std::mem::take(&mut learnt_occurs[v]) {
+                    if self.ca.flags(cr) & DELETED == 0 {
+                        self.delete_clause(cr);
+                    }
+                }
+                self.eliminated[v] = true;
+                if unit {
+                    break;
+                }
+            }
+            let ca = &self.ca;
+            self.clauses.retain(|&cr| ca.flags(cr) & DELETED == 0);
+            self.learnts.retain(|&cr| ca.flags(cr) & DELETED == 0);
+        }
 
-                    debug_assert!(clause[0] == !p || clause[1] == !p);
+        /// Eliminate variables that occur in only one polarity in original clauses.
+        /// Removing the clauses of a pure literal may make other literals pure, so they are eliminated until none is left.
+        /// Learnt clauses of eliminated variables are deleted.
+        fn eliminate_pure_literals(&mut self) {
+            debug_assert_eq!(self.head, self.que.len());
+            let mut occurs: Vec<Vec<CRef>> = vec![vec![]; 2 * self.n];
+            for &cr in self.clauses.iter() {
+                for &lit in self.ca.lits(cr).iter() {
+                    occurs[lit].push(cr);
+                }
+            }
+            // the number of clauses that have each literal and aren't removed
+            let mut counts: Vec<usize> = occurs.iter().map(|cs| cs.len()).collect();
+            let pure = |counts: &[usize], v: Var| {
+                let (pos, neg) = (Lit::new(v.0, true), Lit::new(v.0, false));
+                match (counts[pos.0 as usize], counts[neg.0 as usize]) {
+                    (0, 0) => None,
+                    (_, 0) => Some(pos),
+                    (0, _) => Some(neg),
+                    _ => None,
+                }
+            };
+            let mut queue: Vec<Var> = (0..self.n)
+                .map(|v| Var(v as u32))
+                .filter(|&v| pure(&counts, v).is_some())
+                .collect();
+            let mut changed = false;
+            while let Some(v) = queue.pop() {
+                let lit = match pure(&counts, v) {
+                    Some(lit)
+                        if self.vardata[v].level == 0
+                            && !self.eliminated[v]
+                            && !self.in_native(v)
+                            && self.frozen[v] == 0 =>
+                    {
+                        lit
+                    }
+                    _ => continue,
+                };
+                for cr in std::mem::take(&mut occurs[lit]) {
+                    if self.ca.flags(cr) & DELETED != 0 {
+                        continue;
+                    }
+                    for &l in self.ca.lits(cr).iter() {
+                        counts[l] -= 1;
+                        if counts[l] == 0 && pure(&counts, l.var()).is_some() {
+                            queue.push(l.var());
+                        }
+                    }
+                    self.reconstruction.push_clause(lit, self.ca.lits(cr));
+                    self.delete_clause(cr);
+                }
+                self.eliminated[v] = true;
+                changed = true;
+            }
+            if !changed {
+                return;
+            }
+            for i in 0..self.learnts.len() {
+                let cr = self.learnts[i];
+                if self
+                    .ca
+                    .lits(cr)
+                    .iter()
+                    .any(|lit| self.eliminated[lit.var()])
+                {
+                    self.delete_clause(cr);
+                }
+            }
+            let ca = &self.ca;
+            self.clauses.retain(|&cr| ca.flags(cr) & DELETED == 0);
+            self.learnts.retain(|&cr| ca.flags(cr) & DELETED == 0);
+        }
 
-                    // make sure that the clause[1] is the false literal.
-                    if clause[0] == !p {
-                        clause.swap(0, 1);
+        /// Strongly connected components of the binary implication graph of unassigned literals by Tarjan's algorithm.
+        /// `comp[lit]` is the component of `lit`. Components are numbered in reverse topological order.
+        fn implication_components(&self) -> Vec<Option<usize>> {
+            let free = |lit: Lit| self.eval(lit) == LitBool::Undef;
+            let mut index = vec![None; 2 * self.n];
+            let mut lowlink = vec![0; 2 * self.n];
+            let mut comp = vec![None; 2 * self.n];
+            let mut on_stack = vec![false; 2 * self.n];
+            let mut stack = vec![];
+            let mut count = 0;
+            let mut comps = 0;
+            for start in (0..2 * self.n).map(|i| Lit(i as u32)) {
+                if index[start].is_some() || !free(start) {
+                    continue;
+                }
+                // depth-first search by a stack of literals and the positions of their next edges
+                let mut calls = vec![(start, 0)];
+                index[start] = Some(count);
+                lowlink[start] = count;
+                count += 1;
+                stack.push(start);
+                on_stack[start] = true;
+                while let Some(&mut (lit, ref mut next)) = calls.last_mut() {
+                    if let Some(&(to, _)) = self.implications[lit].get(*next) {
+                        *next += 1;
+                        if !free(to) {
+                            continue;
+                        }
+                        match index[to] {
+                            None => {
+                                index[to] = Some(count);
+                                lowlink[to] = count;
+                                count += 1;
+                                stack.push(to);
+                                on_stack[to] = true;
+                                calls.push((to, 0));
+                            }
+                            Some(i) if on_stack[to] => {
+                                lowlink[lit] = lowlink[lit].min(i);
+                            }
+                            _ => {}
+                        }
+                        continue;
                     }
-                    let first = clause[0];
-                    // already satisfied
-                    if self.eval(first) == LitBool::True {
-                        debug_assert!(first != clause[1]);
-                        idx += 1;
-                        continue 'next_clause;
+                    calls.pop();
+                    if let Some(&(parent, _)) = calls.last() {
+                        lowlink[parent] = lowlink[parent].min(lowlink[lit]);
                     }
-
-                    for k in 2..clause.len() {
-                        let lit = clause[k];
-                        // Found a literal isn't false(true or undefined)
-                        if self.eval(lit) != LitBool::False {
-                            clause.swap(1, k);
-
-                            self.watchers[p].swap(idx, m - 1);
-                            self.watchers[p].pop();
-
-                            self.watchers[!clause[1]].push(cwr);
-                            // NOTE
-                            // Don't increase `idx` because you replace and the idx element with the last one.
-                            continue 'next_clause;
+                    if Some(lowlink[lit]) == index[lit] {
+                        while let Some(top) = stack.pop() {
+                            on_stack[top] = false;
+                            comp[top] = Some(comps);
+                            if top == lit {
+                                break;
+                            }
                         }
+                        comps += 1;
                     }
-                    //debug_assert_eq!(watcher[idx], cr);
-
-                    if self.eval(first) == LitBool::False {
-                        // CONFLICT
-                        // a first literal(clause[0]) is false.
-                        // clause[1] is a false
-                        // clause[2..len] is a false
-
-                        conflict = Some(cwr);
-                        break 'conflict;
-                    } else {
-                        // UNIT PROPAGATION
-                        // a first literal(clause[0]) isn't assigned.
-                        // clause[1] is a false
-                        // clause[2..len] is a false
-
-                        debug_assert_eq!(self.level[first.var()], 0);
-                        // NOTE
-                        // I don't know how to handle this borrowing problem. Please help me.
-                        // self.enqueue(var, sign, Some(cr));
-                        self.enqueue(first, Some(CRef(cr.clone())));
-                    }
-                    idx += 1;
                 }
             }
-
-            conflict
+            comp
         }
-        fn locked(&self, cwr: &CWRef) -> bool {
-            let c = cwr.upgrade().unwrap().borrow()[0];
-            if self.eval(c) == LitBool::True {
-                if let Some(reason) = self.reason[c.var()].as_ref() {
-                    return reason.ptr_eq(cwr);
+
+        /// Substitute equivalent literals by their representatives, the literals of the least variables of their components.
+        /// A component that has `x` and `!x` makes a problem unsatisfiable.
+        /// Variables of native constraints aren't substituted.
+        /// Rewritten clauses are derived from the binary clauses of their components.
+        fn substitute_equivalences(&mut self) {
+            debug_assert_eq!(self.head, self.que.len());
+            let comp = self.implication_components();
+            let mut members: HashMap<usize, Vec<Lit>> = HashMap::new();
+            for lit in (0..2 * self.n).map(|i| Lit(i as u32)) {
+                if let Some(c) = comp[lit] {
+                    members.entry(c).or_default().push(lit);
                 }
             }
-            false
-        }
-        fn unwatch_clause(&mut self, cwr: &CWRef) {
-            let clause = cwr.upgrade().unwrap();
-            let mut cnt = 0;
-            for idx in 0..2 {
-                let p = !clause.borrow()[idx];
-                let n = self.watchers[p].len();
-                for i in 0..n {
-                    if self.watchers[p][i].ptr_eq(&cwr) {
-                        self.watchers[p].swap(i, n - 1);
-                        self.watchers[p].pop();
-                        cnt += 1;
-                        break;
+            // binary clauses in each component for proofs
+            let mut binaries: HashMap<usize, Vec<CRef>> = HashMap::new();
+            if self.proof.is_some() {
+                for lit in (0..2 * self.n).map(|i| Lit(i as u32)) {
+                    for &(to, cr) in self.implications[lit].iter() {
+                        if comp[lit].is_some() && comp[lit] == comp[to] {
+                            binaries.entry(comp[lit].unwrap()).or_default().push(cr);
+                        }
                     }
                 }
             }
-            debug_assert!(cnt == 2);
-        }
-        fn reduce_learnts(&mut self) {
-            self.learnts.sort_by_key(|x| x.0.borrow_mut().len());
-            let mut new_size = self.learnts.len() / 2;
-            let m = new_size;
-            let n: usize = self.learnts.len();
-            for i in m..n {
-                let cr = self.learnts[i].clone();
-                let cwr = Rc::downgrade(&cr.0);
-                if cr.0.borrow().len() > 2 && !self.locked(&cwr) {
-                    self.unwatch_clause(&cwr);
-                } else {
-                    self.learnts[new_size] = cr;
-                    new_size += 1;
+            let antecedents_of = |proof: &ProofLog, ca: &ClauseAllocator, c: usize| {
+                binaries
+                    .get(&c)
+                    .into_iter()
+                    .flatten()
+                    .map(|&cr| (proof.clause_id[&cr], ca.lits(cr).to_vec()))
+                    .collect::<Vec<(usize, Clause)>>()
+            };
+
+            // A contradiction is found before any variable is substituted, so clauses are never half-rewritten.
+            // `x` and `!x` are next to each other in the sorted members.
+            for lits in members.values() {
+                if let Some(w) = lits.windows(2).find(|w| w[0] == !w[1]) {
+                    self.status = Some(Status::Unsat);
+                    if let Some(proof) = self.proof.as_mut() {
+                        let antecedents = antecedents_of(proof, &self.ca, comp[w[0]].unwrap());
+                        let unit = proof.derive(&[w[0]], antecedents.clone());
+                        let mut antecedents = antecedents;
+                        antecedents.push((unit, vec![w[0]]));
+                        let empty = proof.derive(&[], antecedents);
+                        proof.empty(empty);
+                    }
+                    return;
                 }
             }
 
-            self.learnts.truncate(new_size);
-        }
-
-        fn pop_queue_until(&mut self, backtrack_level: usize) {
-            while let Some(p) = self.que.back() {
-                if self.level[p.var()] > backtrack_level {
-                    if !self.order_heap.in_heap(p.var()) {
-                        self.order_heap.push(p.var());
+            let mut repr: Vec<Lit> = (0..2 * self.n).map(|i| Lit(i as u32)).collect();
+            let mut substituted = false;
+            for lits in members.values() {
+                if lits.len() < 2 {
+                    continue;
+                }
+                if lits.iter().any(|lit| self.in_native(lit.var())) {
+                    continue;
+                }
+                // A frozen variable is the representative. Two frozen variables are kept.
+                let frozen: Vec<Lit> = lits
+                    .iter()
+                    .copied()
+                    .filter(|lit| self.frozen[lit.var()] > 0)
+                    .collect();
+                if frozen.len() > 1 {
+                    continue;
+                }
+                let r = frozen
+                    .first()
+                    .copied()
+                    .unwrap_or_else(|| *lits.iter().min_by_key(|lit| lit.var()).unwrap());
+                for &lit in lits.iter() {
+                    repr[lit] = r;
+                    if lit != r && lit.pos() {
+                        self.reconstruction.push_equivalence(lit, r);
+                        self.eliminated[lit.var()] = true;
+                        substituted = true;
                     }
-                    self.polarity[p.var()] = match self.assigns[p.var()] {
-                        LitBool::True => true,
-                        _ => false,
-                    };
-                    self.assigns[p.var()] = LitBool::Undef;
-                    self.reason[p.var()] = None;
-                    self.level[p.var()] = 0;
-                    self.que.pop_back();
-                } else {
-                    break;
                 }
             }
-            if self.que.is_empty() {
-                self.head = 0;
-            } else {
-                self.head = self.que.len() - 1;
+            if !substituted {
+                return;
             }
-        }
 
-        fn simplify(&mut self) {
-            {
-                let n: usize = self.learnts.len();
-                let mut new_size = 0;
-                for i in 0..n {
-                    let cr = self.learnts[i].clone();
-                    let clause = cr.0.borrow();
-                    let mut satisfied = false;
-                    for &lit in clause.iter() {
-                        if self.eval(lit) == LitBool::True {
-                            let cwr = Rc::downgrade(&cr.0);
-                            self.unwatch_clause(&cwr);
-                            if self.locked(&cwr) {
-                                debug_assert!(self.reason[clause[0].var()].is_some());
-                                self.reason[clause[0].var()] = None;
-                            }
-                            satisfied = true;
-                            break;
+            // All clauses are derived before their antecedents are deleted.
+            let mut rewritten = vec![];
+            for &cr in self.clauses.iter().chain(self.learnts.iter()) {
+                let before = self.ca.lits(cr);
+                if before.iter().all(|&lit| repr[lit] == lit) {
+                    continue;
+                }
+                let mut after: Clause = before
+                    .iter()
+                    .map(|&lit| repr[lit])
+                    .filter(|&lit| self.eval(lit) != LitBool::False)
+                    .collect();
+                after.sort();
+                after.dedup();
+                let satisfied = after.windows(2).any(|w| w[0] == !w[1])
+                    || after.iter().any(|&lit| self.eval(lit) == LitBool::True);
+                let mut node = None;
+                if let (false, Some(proof)) = (satisfied, self.proof.as_mut()) {
+                    let mut antecedents = vec![];
+                    for &lit in before.iter() {
+                        if repr[lit] != lit {
+                            antecedents.extend(antecedents_of(proof, &self.ca, comp[lit].unwrap()));
+                        }
+                        if eval(&self.assigns, lit) == LitBool::False {
+                            antecedents.push(proof.unit(lit));
+                        }
+                    }
+                    antecedents.push((proof.clause_id[&cr], before.to_vec()));
+                    node = Some(proof.derive(&after, antecedents));
+                }
+                rewritten.push((cr, if satisfied { None } else { Some(after) }, node));
+            }
+
+            for (cr, after, node) in rewritten {
+                let after = match after {
+                    Some(after) => after,
+                    None => {
+                        self.delete_clause(cr);
+                        continue;
+                    }
+                };
+                let before = self.ca.lits(cr).to_vec();
+                self.unwatch_clause(cr);
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.delete(cr, &before);
+                }
+                if after.len() >= 2 {
+                    self.ca.lits_mut(cr)[..after.len()].copy_from_slice(&after);
+                    self.ca.shrink(cr, after.len());
+                    if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                        proof.clause_id.insert(cr, node);
+                    }
+                    self.attach_clause(cr);
+                    continue;
+                }
+                self.ca.free(cr);
+                // A unit clause may be assigned by another unit clause.
+                // All clauses are rewritten even after a conflict, so no clause has a substituted variable.
+                let lit = after[0];
+                match self.eval(lit) {
+                    LitBool::True => {}
+                    LitBool::False if self.status == Some(Status::Unsat) => {}
+                    LitBool::False => {
+                        self.status = Some(Status::Unsat);
+                        if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                            let antecedents = vec![(node, after.clone()), proof.unit(lit)];
+                            let empty = proof.derive(&[], antecedents);
+                            proof.empty(empty);
                         }
                     }
-                    drop(clause);
-                    if !satisfied {
-                        self.learnts[new_size] = cr;
-                        new_size += 1;
+                    LitBool::Undef => {
+                        self.enqueue(lit, None);
+                        if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                            proof.set_unit(lit.var(), node);
+                        }
                     }
                 }
-                self.learnts.truncate(new_size);
             }
-            {
-                let n: usize = self.clauses.len();
-                let mut new_size = 0;
-                for i in 0..n {
-                    let cr = self.clauses[i].clone();
-                    let clause = cr.0.borrow();
-                    let mut satisfied = false;
-                    for &lit in clause.iter() {
-                        if self.eval(lit) == LitBool::True {
-                            let cwr = Rc::downgrade(&cr.0);
-                            self.unwatch_clause(&cwr);
-                            if self.locked(&cwr) {
-                                debug_assert!(self.reason[clause[0].var()].is_some());
-                                self.reason[clause[0].var()] = None;
-                            }
-                            satisfied = true;
+            let ca = &self.ca;
+            self.clauses.retain(|&cr| ca.flags(cr) & DELETED == 0);
+            self.learnts.retain(|&cr| ca.flags(cr) & DELETED == 0);
+        }
+
+        /// Subsumption and self-subsuming resolution of clauses that have no assigned literals.
+        /// Each clause `c` looks for clauses `d` in the occurrences of its literal that occurs least.
+        /// - `c` subsumes `d`: `d` is removed.
+        /// - `c` subsumes `d` except one literal `x` which is `!x` in `d`: `!x` is removed from `d`.
+        ///
+        /// Signatures(bit sets of variables) skip most pairs without reading clauses.
+        /// It stops at a new unit clause so that the search propagates it first.
+        fn subsume(&mut self, budget: usize) {
+            if budget == 0 {
+                return;
+            }
+            debug_assert_eq!(self.head, self.que.len());
+            let signature = |lits: &[Lit]| {
+                lits.iter()
+                    .fold(0u64, |sig, lit| sig | 1 << (lit.var().0 % 64))
+            };
+            let mut occurs: Vec<Vec<CRef>> = vec![vec![]; 2 * self.n];
+            let mut signatures = HashMap::new();
+            let mut candidates = vec![];
+            for &cr in self.clauses.iter().chain(self.learnts.iter()) {
+                let lits = self.ca.lits(cr);
+                if lits.iter().any(|&lit| self.eval(lit) != LitBool::Undef) {
+                    continue;
+                }
+                lits.iter().for_each(|&lit| occurs[lit].push(cr));
+                signatures.insert(cr, signature(lits));
+                candidates.push(cr);
+            }
+            candidates.sort_by_key(|&cr| self.ca.len(cr));
+
+            let mut checked = 0;
+            let mut mark = vec![false; 2 * self.n];
+            'next_clause: for c in candidates {
+                if self.ca.flags(c) & DELETED != 0 {
+                    continue;
+                }
+                let lits = self.ca.lits(c).to_vec();
+                let sig = signatures[&c];
+                let best = *lits
+                    .iter()
+                    .min_by_key(|&&lit| occurs[lit].len() + occurs[!lit].len())
+                    .unwrap();
+                let ds: Vec<CRef> = occurs[best]
+                    .iter()
+                    .chain(occurs[!best].iter())
+                    .copied()
+                    .collect();
+                for d in ds {
+                    if checked >= budget {
+                        break 'next_clause;
+                    }
+                    if d == c
+                        || self.ca.flags(d) & DELETED != 0
+                        || self.ca.len(d) < lits.len()
+                        || sig & !signatures[&d] != 0
+                    {
+                        continue;
+                    }
+                    checked += 1;
+                    self.ca.lits(d).iter().for_each(|&lit| mark[lit] = true);
+                    let mut flipped = None;
+                    let mut subsumed = true;
+                    for &lit in lits.iter() {
+                        if mark[lit] {
+                            continue;
+                        }
+                        if mark[!lit] && flipped.is_none() {
+                            flipped = Some(!lit);
+                        } else {
+                            subsumed = false;
                             break;
                         }
                     }
-                    drop(clause);
-                    if !satisfied {
-                        self.clauses[new_size] = cr;
-                        new_size += 1;
+                    self.ca.lits(d).iter().for_each(|&lit| mark[lit] = false);
+                    if !subsumed {
+                        continue;
+                    }
+                    let before = self.ca.lits(d).to_vec();
+                    match flipped {
+                        None => {
+                            // A learnt clause that subsumes an original clause would be deleted later.
+                            if !self.ca.is_learnt(c) || self.ca.is_learnt(d) {
+                                self.delete_clause(d);
+                            }
+                        }
+                        Some(x) => {
+                            let after: Clause =
+                                before.iter().copied().filter(|&l| l != x).collect();
+                            let mut node = None;
+                            if let Some(proof) = self.proof.as_mut() {
+                                let antecedents = vec![
+                                    (proof.clause_id[&c], lits.clone()),
+                                    (proof.clause_id[&d], before.clone()),
+                                ];
+                                node = Some(proof.derive(&after, antecedents));
+                                proof.delete(d, &before);
+                            }
+                            self.unwatch_clause(d);
+                            if after.len() == 1 {
+                                self.ca.free(d);
+                                self.enqueue(after[0], None);
+                                if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                                    proof.set_unit(after[0].var(), node);
+                                }
+                                break 'next_clause;
+                            }
+                            self.ca.lits_mut(d)[..after.len()].copy_from_slice(&after);
+                            self.ca.shrink(d, after.len());
+                            if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                                proof.clause_id.insert(d, node);
+                            }
+                            self.attach_clause(d);
+                            signatures.insert(d, signature(&after));
+                        }
                     }
                 }
-                self.clauses.truncate(new_size);
             }
+            let ca = &self.ca;
+            self.clauses.retain(|&cr| ca.flags(cr) & DELETED == 0);
+            self.learnts.retain(|&cr| ca.flags(cr) & DELETED == 0);
         }
+
         fn lit_redundant(&mut self, lit: Lit) -> bool {
             // Check whether a literal can reach a decision variable or unit clause literal.
             // Self-subsume
@@ -678,16 +4873,15 @@ pub mod solver {
             let top = self.ccmin_clear.len();
             self.ccmin_stack.push_back(lit);
             while let Some(x) = self.ccmin_stack.pop_back() {
-                let cwr = self.reason[x.var()].as_ref().unwrap();
-                let cr = cwr.upgrade().unwrap();
-                let clause = cr.borrow();
+                let cr = self.vardata[x.var()].reason.unwrap();
+                let clause = self.ca.lits(cr);
                 debug_assert!(clause[0] == !x);
-                for c in clause.iter().skip(1) {
-                    if !self.seen[c.var()] && self.level[c.var()] > 1 {
-                        if self.reason[c.var()].is_some() {
+                for &c in clause.iter().skip(1) {
+                    if !self.seen[c.var()] && self.vardata[c.var()].level > 1 {
+                        if self.vardata[c.var()].reason.is_some() {
                             self.seen[c.var()] = true;
-                            self.ccmin_stack.push_back(*c);
-                            self.ccmin_clear.push(*c);
+                            self.ccmin_stack.push_back(c);
+                            self.ccmin_clear.push(c);
                         } else {
                             // A 'c' is a decision variable or unit clause literal.
                             // which means a "lit" isn't redundant
@@ -700,7 +4894,7 @@ pub mod solver {
                     }
                 }
             }
-            return true;
+            true
         }
         fn minimize_conflict_clause(&mut self, learnt_clause: &mut Clause) {
             debug_assert!(self.ccmin_stack.is_empty());
@@ -713,8 +4907,17 @@ pub mod solver {
                 let mut redundant = false;
 
                 // Traverse a conflict literal to check wheter a literal is redundant.
-                if self.reason[x].is_some() {
-                    redundant = self.lit_redundant(learnt_clause[i]);
+                if let Some(cr) = self.vardata[x].reason {
+                    redundant = match self.config.minimization {
+                        Minimization::None => false,
+                        Minimization::Local => self
+                            .ca
+                            .lits(cr)
+                            .iter()
+                            .skip(1)
+                            .all(|c| self.seen[c.var()] || self.vardata[c.var()].level <= 1),
+                        Minimization::Recursive => self.lit_redundant(learnt_clause[i]),
+                    };
                 }
 
                 if !redundant {
@@ -732,28 +4935,135 @@ pub mod solver {
             debug_assert!(self.ccmin_clear.is_empty());
             learnt_clause.truncate(new_size);
         }
+        /// Add a learnt clause to a proof.
+        /// Its antecedents are the resolved reasons and the reasons that removed literals by minimization.
+        fn prove_learnt(&mut self, resolved: &[CRef], before: &[Lit], learnt: &[Lit]) -> usize {
+            let proof = self.proof.as_mut().unwrap();
+            let ca = &self.ca;
+            let mut antecedents: Vec<(usize, Clause)> = resolved
+                .iter()
+                .map(|cr| (proof.clause_id[cr], ca.lits(*cr).to_vec()))
+                .collect();
+            let kept: HashSet<Var> = learnt.iter().map(|lit| lit.var()).collect();
+            let mut visited: HashSet<Var> = HashSet::new();
+            let mut stack: Vec<Lit> = before
+                .iter()
+                .filter(|lit| !kept.contains(&lit.var()))
+                .copied()
+                .collect();
+            while let Some(lit) = stack.pop() {
+                let v = lit.var();
+                if kept.contains(&v) || !visited.insert(v) {
+                    continue;
+                }
+                if self.vardata[v].level == 1 {
+                    antecedents.push(proof.unit(lit));
+                    continue;
+                }
+                let cr = self.vardata[v].reason.unwrap();
+                antecedents.push((proof.clause_id[&cr], ca.lits(cr).to_vec()));
+                stack.extend(ca.lits(cr).iter().skip(1));
+            }
+            proof.derive(learnt, antecedents)
+        }
+        /// Find the assumptions that make an assumption `lit` false by propagation.
+        /// It returns them including `lit` and the reasons of the propagation.
+        fn analyze_final(&self, lit: Lit) -> (Vec<Lit>, Vec<CRef>) {
+            debug_assert_eq!(self.eval(lit), LitBool::False);
+            let mut failed = vec![lit];
+            let mut reasons = vec![];
+            let mut visited: HashSet<Var> = HashSet::new();
+            let mut stack = vec![lit.var()];
+            while let Some(v) = stack.pop() {
+                if !visited.insert(v) || self.vardata[v].level == 1 {
+                    continue;
+                }
+                match self.vardata[v].reason {
+                    Some(cr) => {
+                        reasons.push(cr);
+                        stack.extend(self.ca.lits(cr).iter().skip(1).map(|lit| lit.var()));
+                    }
+                    // All decisions are assumptions while an assumption is decided.
+                    None => failed.push(Lit::new(v.0, self.assigns[v] == LitBool::True)),
+                }
+            }
+            (failed, reasons)
+        }
+
+        /// Add the negation of failed assumptions to a proof so that an `Unsat` answer under assumptions is checkable.
+        /// Reasons propagate the negation of `failed[0]` from the others.
+        fn prove_failed(&mut self, failed: &[Lit], reasons: &[CRef]) {
+            let proof = self.proof.as_mut().unwrap();
+            let clause: Clause = failed.iter().map(|&lit| !lit).collect();
+            let mut antecedents = vec![];
+            for &cr in reasons.iter() {
+                let lits = self.ca.lits(cr);
+                antecedents.push((proof.clause_id[&cr], lits.to_vec()));
+                for &lit in lits.iter() {
+                    if self.vardata[lit.var()].level == 1 {
+                        antecedents.push(proof.unit(lit));
+                    }
+                }
+            }
+            if self.vardata[failed[0].var()].level == 1 {
+                antecedents.push(proof.unit(failed[0]));
+            }
+            let id = proof.derive(&clause, antecedents);
+            proof.failed(id);
+        }
+
+        /// Remove the propagated literal from the reason `cr` of `v` that a resolvent of conflict analysis subsumes.
+        /// `v` loses its reason and it is unassigned by the backjump of conflict analysis.
+        fn strengthen_reason(&mut self, cr: CRef, v: Var, resolved: &[CRef]) {
+            debug_assert_eq!(self.ca.lits(cr)[0].var(), v);
+            let mut lits = self.ca.lits(cr)[1..].to_vec();
+            // Watch the literals of the highest levels.
+            let vardata = &self.vardata;
+            lits.sort_by_key(|lit| std::cmp::Reverse(vardata[lit.var()].level));
+            if let Some(proof) = self.proof.as_mut() {
+                let ca = &self.ca;
+                let antecedents = resolved
+                    .iter()
+                    .map(|r| (proof.clause_id[r], ca.lits(*r).to_vec()))
+                    .collect();
+                let id = proof.derive(&lits, antecedents);
+                proof.delete(cr, self.ca.lits(cr));
+                proof.clause_id.insert(cr, id);
+            }
+            self.unwatch_clause(cr);
+            self.ca.lits_mut(cr)[..lits.len()].copy_from_slice(&lits);
+            self.ca.shrink(cr, lits.len());
+            self.attach_clause(cr);
+            self.vardata[v].reason = None;
+            self.elevated[v] = false;
+        }
+
         /// Analyze a conflict clause and deduce a learnt clause to avoid a current conflict
-        fn analyze(&mut self, confl: CWRef) {
+        /// It returns the LBD of a learnt clause.
+        fn analyze(&mut self, confl: CRef) -> u32 {
             // seen must be clear
             debug_assert!(self.seen.iter().all(|&x| !x));
 
-            let current_level = self.level[self.que[self.que.len() - 1].var()];
+            let current_level = self.vardata[self.que[self.que.len() - 1].var()].level;
             let mut learnt_clause = vec![];
+            // reasons that are resolved with a conflict clause
+            let mut resolved = vec![confl];
+            if self.ca.is_learnt(confl) {
+                self.ca.bump_activity(confl, &self.learnts);
+            }
 
             let mut same_level_cnt = 0;
-            debug_assert!(confl.upgrade().is_some());
-            let clause = confl.upgrade().unwrap();
             // implication graph nodes that are start point from a conflict clause.
-            for p in clause.borrow().iter() {
+            for &p in self.ca.lits(confl).iter() {
                 let var = p.var();
-                debug_assert!(self.level[var] > 0);
-                self.order_heap.bump_activity(var);
+                debug_assert!(self.vardata[var].level > 0);
+                self.branching.bump(var);
                 // already checked
                 self.seen[var] = true;
 
-                //debug_assert!(self.level[var] <= current_level);
-                if self.level[var] < current_level {
-                    learnt_clause.push(*p);
+                //debug_assert!(self.vardata[var].level <= current_level);
+                if self.vardata[var].level < current_level {
+                    learnt_clause.push(p);
                 } else {
                     same_level_cnt += 1;
                 }
@@ -762,7 +5072,8 @@ pub mod solver {
             // Traverse an implication graph to 1-UIP(unique implication point)
             let first_uip = {
                 let mut p = None;
-                for &lit in self.que.iter().rev() {
+                for i in (0..self.que.len()).rev() {
+                    let lit = self.que[i];
                     let v = lit.var();
 
                     // Skip a variable that isn't checked.
@@ -770,8 +5081,8 @@ pub mod solver {
                         continue;
                     }
                     self.seen[v] = false;
-                    self.order_heap.bump_activity(v);
-                    debug_assert_eq!(self.level[v], current_level);
+                    self.branching.bump(v);
+                    debug_assert_eq!(self.vardata[v].level, current_level);
                     same_level_cnt -= 1;
                     // There is no variables that are at the conflict level
                     if same_level_cnt <= 0 {
@@ -779,24 +5090,39 @@ pub mod solver {
                         break;
                     }
 
-                    debug_assert!(self.reason[v].is_some());
-                    let reason = self.reason[v].as_ref().unwrap();
-                    debug_assert!(reason.upgrade().is_some());
-                    let clause = reason.upgrade().unwrap();
-                    for p in clause.borrow().iter().skip(1) {
+                    debug_assert!(self.vardata[v].reason.is_some());
+                    let reason = self.vardata[v].reason.unwrap();
+                    if self.proof.is_some() {
+                        resolved.push(reason);
+                    }
+                    if self.ca.is_learnt(reason) {
+                        self.ca.bump_activity(reason, &self.learnts);
+                    }
+                    for &p in self.ca.lits(reason).iter().skip(1) {
                         let var = p.var();
                         // already checked
                         if self.seen[var] {
                             continue;
                         }
                         self.seen[var] = true;
-                        debug_assert!(self.level[var] <= current_level);
-                        if self.level[var] < current_level {
-                            learnt_clause.push(*p);
+                        debug_assert!(self.vardata[var].level <= current_level);
+                        if self.vardata[var].level < current_level {
+                            learnt_clause.push(p);
                         } else {
                             same_level_cnt += 1;
                         }
                     }
+
+                    // A resolvent that is as small as the reason without `v` is the same clause.
+                    // Two literals at the current level are watched so that the backjump unassigns them.
+                    if self.config.on_the_fly_subsumption
+                        && same_level_cnt >= 2
+                        && self.ca.len(reason) >= 3
+                        && self.ca.flags(reason) & EXPLANATION == 0
+                        && learnt_clause.len() + same_level_cnt as usize + 1 == self.ca.len(reason)
+                    {
+                        self.strengthen_reason(reason, v, &resolved);
+                    }
                 }
                 p
             };
@@ -811,15 +5137,29 @@ pub mod solver {
 
             let analyze_clear = learnt_clause.clone();
             self.minimize_conflict_clause(&mut learnt_clause);
+            let node = if self.proof.is_some() {
+                Some(self.prove_learnt(&resolved, &analyze_clear, &learnt_clause))
+            } else {
+                None
+            };
+
+            // the number of decision levels of a learnt clause
+            let mut levels: Vec<Idx> = learnt_clause
+                .iter()
+                .map(|lit| self.vardata[lit.var()].level)
+                .collect();
+            levels.sort_unstable();
+            levels.dedup();
+            let lbd = levels.len();
 
             let backtrack_level = if learnt_clause.len() == 1 {
                 1
             } else {
                 let mut max_idx = 1;
-                let mut max_level = self.level[learnt_clause[max_idx].var()];
+                let mut max_level = self.vardata[learnt_clause[max_idx].var()].level;
                 for (i, lit) in learnt_clause.iter().enumerate().skip(2) {
-                    if self.level[lit.var()] > max_level {
-                        max_level = self.level[lit.var()];
+                    if self.vardata[lit.var()].level > max_level {
+                        max_level = self.vardata[lit.var()].level;
                         max_idx = i;
                     }
                 }
@@ -828,25 +5168,49 @@ pub mod solver {
                 max_level
             };
 
-            // Cancel decisions until the level is less than equal to the backtrack level
-            self.pop_queue_until(backtrack_level);
+            // A long backjump throws away many assignments that will be made again.
+            // Chronological backtracking assigns the first literal at a higher level than its reason instead.
+            let backtrack_level = match self.config.chronological {
+                Some(limit)
+                    if learnt_clause.len() > 1
+                        && (current_level - backtrack_level) as usize > limit =>
+                {
+                    current_level - 1
+                }
+                _ => backtrack_level,
+            };
+            // Cancel decisions until the level is less than equal to the backtrack level.
+            // The negation of a learnt literal may be still implied. Its reason becomes a conflict by propagation.
+            self.pop_queue_except(backtrack_level, Some(learnt_clause[0].var()));
 
             // propagate it by a new learnt clause
+            self.stats.learnts += 1;
             if learnt_clause.len() == 1 {
                 debug_assert_eq!(backtrack_level, 1);
                 self.skip_simplify = false;
                 self.enqueue(learnt_clause[0], None);
+                if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                    proof.set_unit(learnt_clause[0].var(), node);
+                }
             } else {
                 let first = learnt_clause[0];
-                let cr = CRef::new(learnt_clause);
-                self.enqueue(first, Some(cr.clone()));
+                let cr = self.ca.alloc(&learnt_clause, true);
+                self.ca.set_lbd(cr, lbd);
+                if let (Some(proof), Some(node)) = (self.proof.as_mut(), node) {
+                    proof.clause_id.insert(cr, node);
+                }
+                self.enqueue(first, Some(cr));
+                self.elevated[first.var()] =
+                    backtrack_level > self.vardata[learnt_clause[1].var()].level;
                 self.add_clause_unchecked(cr, true);
+                self.ca.bump_activity(cr, &self.learnts);
             }
 
             // Clear seen
             for lit in analyze_clear {
                 self.seen[lit.var()] = false;
             }
+            lbd as u32
         }
 
         /// Reserve the space of a clause database
@@ -861,23 +5225,99 @@ pub mod solver {
         pub fn reserve_variable(&mut self, var_num: usize) {
             self.que.reserve(var_num);
             self.clauses.reserve(var_num);
-            self.reason.reserve(var_num);
-            self.level.reserve(var_num);
+            self.vardata.reserve(var_num);
             self.assigns.reserve(var_num);
         }
 
         /// Solve a problem and return a enum `Status`.
         /// # Arguments
         /// * `time_limit` - The time limitation for searching.
-        /// Exceeding the time limit returns `Indeterminate`
+        ///   Exceeding the time limit returns `Indeterminate`
         pub fn solve(&mut self, time_limit: Option<Duration>) -> Status {
-            if let Some(status) = self.status.as_ref() {
-                return *status;
+            self.solve_with_assumptions(&[], time_limit)
+        }
+
+        /// Solve a problem under `assumptions` that are decided before any other variables.
+        /// Returning `Unsat` doesn't make a solver `Unsat` permanently if the conflict depends on `assumptions`.
+        /// Assumptions aren't added as unit clauses, so the next call can assume other literals.
+        /// # Arguments
+        /// * `assumptions` - Literals that are assumed to be true in this order
+        /// * `time_limit` - The time limitation for searching.
+        ///   Exceeding the time limit returns `Indeterminate`
+        pub fn solve_with_assumptions(
+            &mut self,
+            assumptions: &[Lit],
+            time_limit: Option<Duration>,
+        ) -> Status {
+            self.search(assumptions, time_limit, &Budget::default())
+        }
+
+        /// Solve a problem until it reaches a limit of `budget` and return a enum `Status`.
+        /// Exceeding a limit returns `Indeterminate`. The same budget stops a search at the same point,
+        /// so it is deterministic unlike a time limit. Calling it again resumes a search with learnt clauses.
+        pub fn solve_limited(&mut self, budget: &Budget) -> Status {
+            self.search(&[], None, budget)
+        }
+
+        /// Counters of the searches of a solver.
+        pub fn stats(&self) -> &Statistics {
+            &self.stats
+        }
+
+        /// Decide a literal at a new decision level.
+        fn decide(&mut self, lit: Lit) {
+            self.enqueue(lit, None);
+            let level = &mut self.vardata[lit.var()].level;
+            *level += 1;
+            self.stats.decisions += 1;
+            self.stats.max_level = self.stats.max_level.max(*level as u64 - 1);
+        }
+
+        /// The search of `solve_with_assumptions` and `solve_limited`.
+        fn search(
+            &mut self,
+            assumptions: &[Lit],
+            time_limit: Option<Duration>,
+            budget: &Budget,
+        ) -> Status {
+            // Unsat is permanent. Sat and Indeterminate can be searched again after adding clauses.
+            self.failed.clear();
+            if self.status == Some(Status::Unsat) {
+                return Status::Unsat;
+            }
+            // Original clauses of an LRAT proof end at the first search.
+            if let Some(writer) = self.proof.as_mut().and_then(|proof| proof.writer.as_mut()) {
+                writer.start();
             }
+            // Removable clauses are active while their activation literals are assumed first.
+            let assumptions: Vec<Lit> = self
+                .activations
+                .iter()
+                .chain(assumptions.iter())
+                .copied()
+                .collect();
+            // Assumed variables are frozen during the search so that simplifications between restarts keep them.
+            assumptions.iter().for_each(|lit| self.freeze(lit.var()));
+            // Assignments of a previous `solve` may be made under other assumptions.
+            self.pop_queue_until(1);
+            let status = self.search_assuming(&assumptions, time_limit, budget);
+            assumptions.iter().for_each(|lit| self.melt(lit.var()));
+            status
+        }
+
+        /// The CDCL loop of `search` under frozen assumptions.
+        fn search_assuming(
+            &mut self,
+            assumptions: &[Lit],
+            time_limit: Option<Duration>,
+            budget: &Budget,
+        ) -> Status {
             let start = Instant::now();
-            let mut max_learnt_clause = self.clauses.len() as f64 * 0.3;
-            let mut conflict_cnt = 0;
-            let mut restart_limit = 100.0;
+            let mut max_learnt_clause = self.clauses.len() as f64 * self.config.learnt_size_factor;
+            let mut restart = self.config.restart.start();
+            let mut restart_pending = false;
+            let mut iterations = 0u64;
+            let base = self.stats;
 
             loop {
                 if let Some(time_limit) = time_limit {
@@ -887,46 +5327,138 @@ pub mod solver {
                         return Status::Indeterminate;
                     }
                 }
+                if budget.exhausted(
+                    self.stats.conflicts - base.conflicts,
+                    self.stats.decisions - base.decisions,
+                    self.stats.propagations - base.propagations,
+                ) {
+                    self.status = Some(Status::Indeterminate);
+                    return Status::Indeterminate;
+                }
+                iterations += 1;
+                let terminated = iterations & (TERMINATE_PERIOD - 1) == 0
+                    && self
+                        .terminate
+                        .as_mut()
+                        .is_some_and(|terminate| (terminate.0)());
+                if self.interrupt.load(Ordering::Relaxed) || terminated {
+                    self.interrupt.store(false, Ordering::Relaxed);
+                    self.status = Some(Status::Indeterminate);
+                    return Status::Indeterminate;
+                }
                 if let Some(confl) = self.propagate() {
                     //Conflict
 
-                    let current_level = self.level[self.que.back().unwrap().var()];
+                    let current_level = self.vardata[self.que.back().unwrap().var()].level;
                     if current_level == 1 {
                         self.status = Some(Status::Unsat);
+                        self.prove_empty(confl);
                         return Status::Unsat;
                     }
-                    conflict_cnt += 1;
-                    self.analyze(confl);
-                    self.order_heap.decay_inc();
+                    self.stats.conflicts += 1;
+                    let trail_len = self.que.len();
+                    let lbd = self.analyze(confl);
+                    restart_pending |= restart.on_conflict(lbd, trail_len);
+                    self.rephasing.conflicts += 1;
+                    self.branching.decay();
+                    self.ca.activity_inc /= self.config.clause_decay;
                 } else {
                     // No Conflict
-                    if conflict_cnt as f64 >= restart_limit {
-                        restart_limit *= 1.1;
+                    if restart_pending {
                         self.pop_queue_until(1);
+                        // Literals that are implied again at the root level are propagated before simplification.
+                        if self.head < self.que.len() {
+                            continue;
+                        }
+                        restart_pending = false;
+                        restart.on_restart();
+                        self.stats.restarts += 1;
+                        if let Some(period) = self.config.rephase {
+                            if self.rephasing.conflicts >= period {
+                                self.rephase();
+                            }
+                        }
                         if !self.skip_simplify {
-                            self.simplify();
+                            self.run_simplifiers();
                             self.skip_simplify = true;
+                            if self.status == Some(Status::Unsat) {
+                                return Status::Unsat;
+                            }
+                            // New units of simplifiers are propagated at the root level.
+                            if self.head < self.que.len() {
+                                continue;
+                            }
                         }
                     }
 
                     if max_learnt_clause as usize <= self.learnts.len() {
                         self.reduce_learnts();
-                        max_learnt_clause *= 1.1;
+                        max_learnt_clause *= self.config.learnt_size_inc;
+                    }
+
+                    // Decide assumptions first. An assumption that is false is refuted by a problem.
+                    // An assumption that is already true needs no decision.
+                    let mut assumption = None;
+                    for &lit in assumptions.iter() {
+                        match self.eval(lit) {
+                            LitBool::True => continue,
+                            LitBool::False => {
+                                let (failed, reasons) = self.analyze_final(lit);
+                                if self.proof.is_some() {
+                                    self.prove_failed(&failed, &reasons);
+                                }
+                                // Activation literals aren't assumptions of a user.
+                                self.failed = failed
+                                    .into_iter()
+                                    .filter(|lit| !self.activations.contains(lit))
+                                    .collect();
+                                self.pop_queue_until(1);
+                                self.status = None;
+                                return Status::Unsat;
+                            }
+                            LitBool::Undef => {
+                                assumption = Some(lit);
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(lit) = assumption {
+                        self.enqueue(lit, None);
+                        self.vardata[lit.var()].level += 1;
+                        continue;
+                    }
+
+                    // A trail without a conflict is saved if it is the longest one.
+                    if self.config.rephase.is_some() && self.que.len() > self.rephasing.best.len() {
+                        self.rephasing.best.clear();
+                        self.rephasing.best.extend(self.que.iter());
+                    }
+
+                    if let Some(lit) = self.random_decision() {
+                        self.decide(lit);
+                        continue;
                     }
 
                     // Select a decision variable that isn't decided yet
                     loop {
-                        if let Some(v) = self.order_heap.pop() {
-                            if self.level[v] != 0 {
+                        if let Some(v) = self.branching.select() {
+                            if self.vardata[v].level != 0 || self.eliminated[v] || !self.decision[v]
+                            {
                                 continue;
                             }
 
-                            let lit = Lit::new(v.0, self.polarity[v]);
-                            self.enqueue(lit, None);
-                            self.level[lit.var()] += 1;
+                            let phase = self.user_polarity[v].unwrap_or(self.vardata[v].phase);
+                            let lit = Lit::new(v.0, phase);
+                            self.decide(lit);
                             break;
                         } else {
                             // all variables are selected. which means that a formula is satisfied
+                            // Eliminated variables are assigned to satisfy removed clauses.
+                            debug_assert!(self
+                                .reconstruction
+                                .vars()
+                                .all(|v| self.eliminated[v] || self.vardata[v].level != 1));
+                            self.reconstruction.extend(&mut self.assigns);
                             self.status = Some(Status::Sat);
                             return Status::Sat;
                         }
@@ -935,11 +5467,96 @@ pub mod solver {
             }
         }
     }
+
+    // Backing buffers of a solver that are kept by `SolverPool`.
+    #[derive(Debug, Default)]
+    struct Buffers {
+        arena: Vec<Lit>,
+        watchers: Vec<Vec<Watcher>>,
+        trail: VecDeque<Lit>,
+        clauses: Vec<CRef>,
+        learnts: Vec<CRef>,
+        assigns: Vec<LitBool>,
+        vardata: Vec<VarData>,
+    }
+
+    /// A pool of backing buffers(the clause arena, watcher lists and the trail) shared by many short-lived solvers.
+    /// A handle is cheap to clone and can be shared by threads.
+    /// A solver that is created by a pool should be returned by `recycle` so that the next solver reuses its memory.
+    ///
+    /// ```
+    /// use screwsat::solver::{Lit, SolverPool, Status};
+    /// let pool = SolverPool::default();
+    /// for _ in 0..3 {
+    ///     let mut solver = pool.new_solver(2, &[vec![Lit::from(1), Lit::from(2)]]);
+    ///     assert_eq!(solver.solve(None), Status::Sat);
+    ///     pool.recycle(solver);
+    /// }
+    /// ```
+    #[derive(Debug, Default, Clone)]
+    pub struct SolverPool {
+        buffers: std::sync::Arc<std::sync::Mutex<Vec<Buffers>>>,
+    }
+
+    impl SolverPool {
+        /// Create a new `Solver` like `Solver::new` on recycled buffers if there are.
+        pub fn new_solver(&self, n: usize, clauses: &[Vec<Lit>]) -> Solver {
+            let buffers = self.buffers.lock().unwrap().pop().unwrap_or_default();
+            let mut solver = Solver {
+                ca: ClauseAllocator::from_buffer(buffers.arena),
+                spare_watchers: buffers.watchers,
+                que: buffers.trail,
+                clauses: buffers.clauses,
+                learnts: buffers.learnts,
+                assigns: buffers.assigns,
+                vardata: buffers.vardata,
+                ..Solver::default()
+            };
+            for _ in 0..n {
+                solver.new_var();
+            }
+            solver.add_clauses(clauses);
+            solver
+        }
+
+        /// Return the buffers of a solver to a pool.
+        pub fn recycle(&self, solver: Solver) {
+            let Solver {
+                ca,
+                mut watchers,
+                mut spare_watchers,
+                mut que,
+                mut clauses,
+                mut learnts,
+                mut assigns,
+                mut vardata,
+                ..
+            } = solver;
+            spare_watchers.extend(watchers.drain(..).map(|mut ws| {
+                ws.clear();
+                ws
+            }));
+            que.clear();
+            clauses.clear();
+            learnts.clear();
+            assigns.clear();
+            vardata.clear();
+            self.buffers.lock().unwrap().push(Buffers {
+                arena: ca.data,
+                watchers: spare_watchers,
+                trail: que,
+                clauses,
+                learnts,
+                assigns,
+                vardata,
+            });
+        }
+    }
 }
 
 // This mod contains utility functions
 pub mod util {
-    use super::solver::{Clause, Lit};
+    use super::solver::{Clause, Lit, LitBool, Model, Status, Var};
     use std::io::BufRead;
 
     // CnfData is parsed form a input file
@@ -952,54 +5569,131 @@ pub mod util {
         // all problem clauses
         pub clauses: Vec<Clause>,
     }
-    /// Parse a DIMACAS cnf file
+
+    /// An error of a DIMACS CNF file. Lines are counted from 1.
+    #[derive(Debug)]
+    pub enum ParseError {
+        // an error of the reader
+        Io(std::io::Error),
+        // the second `p` line
+        DuplicateHeader { line: usize },
+        // a `p` line that isn't `p cnf <variables> <clauses>`
+        InvalidHeader { line: usize, header: String },
+        // a token that isn't an integer
+        InvalidLiteral { line: usize, token: String },
+        // a variable that is larger than the number of variables of the header
+        VariableOutOfRange { line: usize, var: usize },
+    }
+
+    impl std::fmt::Display for ParseError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                ParseError::Io(e) => write!(f, "{}", e),
+                ParseError::DuplicateHeader { line } => {
+                    write!(f, "Duplicate header at line {}", line)
+                }
+                ParseError::InvalidHeader { line, header } => {
+                    write!(f, "Invalid header at line {}: {}", line, header)
+                }
+                ParseError::InvalidLiteral { line, token } => {
+                    write!(f, "Invalid literal at line {}: {}", line, token)
+                }
+                ParseError::VariableOutOfRange { line, var } => {
+                    write!(f, "Variable {} at line {} exceeds the header", var, line)
+                }
+            }
+        }
+    }
+
+    impl std::error::Error for ParseError {}
+
+    // Errors of the reader keep their kinds and errors of the contents are `InvalidData`.
+    impl From<ParseError> for std::io::Error {
+        fn from(e: ParseError) -> std::io::Error {
+            match e {
+                ParseError::Io(e) => e,
+                e => std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()),
+            }
+        }
+    }
+
+    /// Parse the `p cnf <variables> <clauses>` line of a DIMACS file.
+    fn parse_header(line: &str) -> Option<(usize, usize)> {
+        let values: Vec<&str> = line.split_whitespace().collect();
+        match values[..] {
+            ["p", "cnf", var_num, cla_num] => Some((var_num.parse().ok()?, cla_num.parse().ok()?)),
+            _ => None,
+        }
+    }
+
+    /// Parse a DIMACS CNF file.
+    /// A clause is a sequence of literals that ends with `0`, so a clause can span lines and a line can have clauses.
+    /// A line that starts with `c` is a comment and a line that starts with `%` ends a file like SATLIB benchmarks.
+    /// The last clause may miss its `0`. An invalid header, a token that isn't an integer and
+    /// a variable that is larger than the number of variables of the header are `InvalidData` errors.
     /// # Arguments
-    /// * `input_file` - A path of an input file name
+    /// * `input` - A reader of a DIMACS CNF file
+    ///
+    /// ```text
     /// c Here is a comment.
-    /// c SATISFIABLE
     /// p cnf 5 3
     /// 1 -5 4 0
-    /// -1 5 3 4 0
-    /// -3 -4 0
+    /// -1 5
+    ///    3 4 0 -3 -4 0
+    /// ```
     pub fn parse_cnf<R: std::io::Read>(input: R) -> std::io::Result<CnfData> {
+        Ok(parse_cnf_data(input)?)
+    }
+
+    /// `parse_cnf` with a `ParseError`.
+    pub(crate) fn parse_cnf_data<R: std::io::Read>(input: R) -> Result<CnfData, ParseError> {
         let reader = std::io::BufReader::new(input);
         let mut var_num = None;
         let mut cla_num = None;
         let mut clauses = vec![];
-        for line in reader.lines() {
-            let line = line?;
+        let mut clause: Clause = vec![];
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(ParseError::Io)?;
             let line = line.trim();
-
-            if line.starts_with('c') {
-                //comment
+            if line.is_empty() || line.starts_with('c') {
                 continue;
             }
-            let values: Vec<&str> = line.split_whitespace().collect();
-            if values.is_empty() {
-                continue;
+            if line.starts_with('%') {
+                break;
             }
-            if values[0] == "p" {
-                //p cnf v_num c_num
-                if let Some(v) = values.get(2) {
-                    var_num = Some(v.parse::<usize>().unwrap());
-                };
-                if let Some(v) = values.get(3) {
-                    cla_num = Some(v.parse::<usize>().unwrap());
+            if line.starts_with('p') {
+                if var_num.is_some() {
+                    return Err(ParseError::DuplicateHeader { line: i + 1 });
                 }
+                let (v, c) = parse_header(line).ok_or_else(|| ParseError::InvalidHeader {
+                    line: i + 1,
+                    header: line.to_string(),
+                })?;
+                var_num = Some(v);
+                cla_num = Some(c);
                 continue;
             }
-
-            let values: Vec<_> = values
-                .into_iter()
-                .filter_map(|x| x.parse::<i32>().ok())
-                .take_while(|x| *x != 0)
-                .collect();
-
-            if values.is_empty() {
-                // skip an empty line
-                continue;
+            for token in line.split_whitespace() {
+                let x = token
+                    .parse::<i32>()
+                    .map_err(|_| ParseError::InvalidLiteral {
+                        line: i + 1,
+                        token: token.to_string(),
+                    })?;
+                if x == 0 {
+                    clauses.push(std::mem::take(&mut clause));
+                    continue;
+                }
+                if var_num.is_some_and(|n| x.unsigned_abs() as usize > n) {
+                    return Err(ParseError::VariableOutOfRange {
+                        line: i + 1,
+                        var: x.unsigned_abs() as usize,
+                    });
+                }
+                clause.push(Lit::from(x));
             }
-            let clause: Vec<Lit> = values.iter().map(|&x| Lit::from(x)).collect();
+        }
+        if !clause.is_empty() {
             clauses.push(clause);
         }
         Ok(CnfData {
@@ -1008,4 +5702,125 @@ pub mod util {
             clauses,
         })
     }
+
+    /// Write a DIMACS CNF file. The number of variables of the header is `var_num` or the largest variable of clauses.
+    pub fn write_cnf<W: std::io::Write>(cnf: &CnfData, mut writer: W) -> std::io::Result<()> {
+        let var_num = cnf.var_num.unwrap_or_else(|| {
+            cnf.clauses
+                .iter()
+                .flatten()
+                .map(|lit| lit.var().0 as usize + 1)
+                .max()
+                .unwrap_or(0)
+        });
+        writeln!(writer, "p cnf {} {}", var_num, cnf.clauses.len())?;
+        for clause in cnf.clauses.iter() {
+            for lit in clause.iter() {
+                write!(writer, "{} ", lit)?;
+            }
+            writeln!(writer, "0")?;
+        }
+        writer.flush()
+    }
+
+    /// Write the status line of the SAT competition: `s SATISFIABLE`, `s UNSATISFIABLE` or `s UNKNOWN`.
+    pub fn write_status<W: std::io::Write>(status: Status, mut writer: W) -> std::io::Result<()> {
+        let status = match status {
+            Status::Sat => "SATISFIABLE",
+            Status::Unsat => "UNSATISFIABLE",
+            Status::Indeterminate => "UNKNOWN",
+        };
+        writeln!(writer, "s {}", status)
+    }
+
+    /// Write a model in `v` lines of the SAT competition. Lines are wrapped at 80 characters and the last value is `0`.
+    /// Only the first `var_num` variables(the variables of a problem) are written,
+    /// so auxiliary variables of encodings aren't. A variable that isn't assigned is written as a negative literal.
+    pub fn write_model<W: std::io::Write>(
+        model: &Model,
+        var_num: usize,
+        mut writer: W,
+    ) -> std::io::Result<()> {
+        let mut line = "v".to_string();
+        let values = (0..var_num as u32)
+            .map(|v| Lit::new(v, model[Var(v)] == LitBool::True).to_string())
+            .chain(std::iter::once("0".to_string()));
+        for value in values {
+            if line.len() + 1 + value.len() > 80 {
+                writeln!(writer, "{}", line)?;
+                line = "v".to_string();
+            }
+            line.push(' ');
+            line.push_str(&value);
+        }
+        writeln!(writer, "{}", line)
+    }
+}
+
+// This mod reads and writes DIMACS CNF files with typed errors
+pub mod dimacs;
+
+// This mod enumerates all models of a problem(AllSAT)
+pub mod enumerate;
+
+// This mod samples models of a problem near-uniformly
+pub mod sampler;
+
+// This mod decompresses gzip files
+#[cfg(feature = "gzip")]
+pub mod gzip;
+
+// This mod contains a fixed-capacity solver that never allocates memory
+#[cfg(feature = "heapless")]
+pub mod heapless;
+
+// This mod contains a weighted MaxSAT solver
+pub mod maxsat;
+
+// This mod contains a pseudo-Boolean solver by cutting planes
+pub mod pb;
+
+// This mod explains infeasible problems by MUSes and MCSes
+pub mod mus;
+
+// This mod compiles a CNF formula into d-DNNF to answer queries quickly
+pub mod ddnnf;
+
+// This mod encodes Boolean formulas into clauses
+pub mod formula;
+
+// This mod reads the Boolean fragment of SMT-LIB2 scripts
+pub mod smtlib;
+
+// This mod reads ISCAS BENCH netlists
+pub mod bench;
+
+// This mod reads AIGER circuits
+pub mod aiger;
+
+// This mod serves incremental solver sessions over a line protocol
+pub mod server;
+
+// This mod resolves package/version selection problems
+pub mod resolver;
+
+// This mod encodes cardinality constraints into clauses
+pub mod cardinality;
+
+// This mod gathers encodings of constraints into clauses
+pub mod encodings {
+    /// Cardinality constraints by sequential counters and totalizers. It is `crate::cardinality`.
+    pub use crate::cardinality as card;
 }
+
+// This mod contains building blocks for scheduling and timetabling problems
+pub mod schedule;
+
+// This mod encodes exact cover problems
+pub mod exact_cover;
+
+// This mod breaks symmetries of clauses by lex-leader clauses
+pub mod symmetry;
+
+// This mod solves a problem by cube-and-conquer
+pub mod cube;
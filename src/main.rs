@@ -1,4 +1,4 @@
-use screwsat::{solver, util};
+use screwsat::{maxsat, mus, pb, server, smtlib, solver, util};
 use solver::LitBool;
 
 use std::{env, fs::File};
@@ -7,40 +7,221 @@ fn help(msg: Option<&str>) {
         println!("{}", msg);
     }
     println!("USAGE: screwsat [options] <input-file> [output-file]");
+    println!("       screwsat serve [--listen=<address>|--unix=<path>]");
+    println!("`-` reads an input from stdin. gzip inputs are decompressed(feature `gzip`). xz inputs aren't supported");
+    println!("Results are written in `s` and `v` lines to stdout, or as `SAT`, `UNSAT` or `UNKNOWN` and a model to an output file");
+    println!("OPTIONS:");
+    println!("    --maxsat    Solve a WCNF file as a weighted MaxSAT problem. It is default for `.wcnf` files");
+    println!("    --pb        Solve an OPB file by cutting planes. It is default for `.opb` files");
+    println!("    --mus       Find a group MUS of a GCNF file. It is default for `.gcnf` files");
+    println!("    --smtlib    Run an SMT-LIB2 script of Boolean constants. It is default for `.smt2` files");
+    println!("    --preset=<default|sat|unsat|crypto|planning|auto>  Parameters of a solver(default: default)");
+    println!("    --proof=<file>  Write a proof of UNSATISFIABLE to a file");
+    println!(
+        "    --proof-format=<drat|binary-drat|frat|frat-hints|lrat>  The format of a proof(default: drat)"
+    );
+    println!("    --explain   Explain why a small problem is UNSATISFIABLE by resolution steps in `c` lines");
+    println!("    --snapshot=<file>  Write a binary snapshot of a problem that loads fast. `.snap` files are read as snapshots");
+    println!("SERVE OPTIONS:");
+    println!(
+        "    --listen=<address>  Serve sessions to TCP connections one by one instead of stdin"
+    );
+    println!("    --unix=<path>  Serve sessions to connections of a Unix domain socket one by one");
 }
 
+// The maximum number of proof nodes that `--explain` writes.
+const EXPLAIN_LIMIT: usize = 500;
+
+/// Write the explanation of a proof in `c` lines.
+fn explain<W: std::io::Write>(proof: &solver::Proof, mut writer: W) -> std::io::Result<()> {
+    let used = proof.used().len();
+    if used > EXPLAIN_LIMIT {
+        writeln!(
+            writer,
+            "c The proof has {} clauses. It is too long to explain.",
+            used
+        )?;
+        return writer.flush();
+    }
+    let mut buf = vec![];
+    proof.write_explanation(&mut buf)?;
+    for line in String::from_utf8_lossy(&buf).lines() {
+        writeln!(writer, "c {}", line)?;
+    }
+    writer.flush()
+}
+
+/// Find a group MUS of a GCNF file and print groups of it in the `v` line.
+fn solve_mus<R: std::io::Read, W: std::io::Write>(input: R, mut writer: W) -> std::io::Result<()> {
+    let gcnf = mus::parse_gcnf(input)?;
+    match mus::find_mus(&gcnf.hards, &gcnf.groups, None) {
+        Some(groups) => {
+            writeln!(writer, "s UNSATISFIABLE")?;
+            write!(writer, "v")?;
+            for g in groups {
+                write!(writer, " {}", g + 1)?;
+            }
+            writeln!(writer, " 0")?;
+        }
+        None => writeln!(writer, "s SATISFIABLE")?,
+    }
+    writer.flush()
+}
+
+/// Serve solver sessions to stdin, TCP connections or Unix domain socket connections.
+/// Connections are served one by one and share sessions.
+fn serve(args: &[String]) -> std::io::Result<()> {
+    let mut server = server::Server::default();
+    match args {
+        [] => {
+            let stdin = std::io::stdin();
+            server.run(stdin.lock(), std::io::stdout())?;
+        }
+        [arg] if arg.starts_with("--listen=") => {
+            let listener = std::net::TcpListener::bind(&arg["--listen=".len()..])?;
+            for stream in listener.incoming() {
+                let stream = stream?;
+                if !server.run(std::io::BufReader::new(stream.try_clone()?), stream)? {
+                    break;
+                }
+            }
+        }
+        #[cfg(unix)]
+        [arg] if arg.starts_with("--unix=") => {
+            let listener = std::os::unix::net::UnixListener::bind(&arg["--unix=".len()..])?;
+            for stream in listener.incoming() {
+                let stream = stream?;
+                if !server.run(std::io::BufReader::new(stream.try_clone()?), stream)? {
+                    break;
+                }
+            }
+        }
+        _ => {
+            help(Some("Invalid options of serve"));
+            std::process::exit(1);
+        }
+    }
+    Ok(())
+}
+
+/// Solve a WCNF file and print the result in the MaxSAT Evaluation format.
+/// `o` lines are printed whenever a better solution is found.
+fn solve_maxsat<R: std::io::Read, W: std::io::Write>(
+    input: R,
+    mut writer: W,
+) -> std::io::Result<()> {
+    let wcnf = maxsat::parse_wcnf(input)?;
+    let mut solver = maxsat::MaxSatSolver::default();
+    wcnf.hards
+        .iter()
+        .for_each(|clause| solver.add_hard_clause(clause));
+    wcnf.softs
+        .iter()
+        .for_each(|(clause, weight)| solver.add_soft_clause(clause, *weight));
+    solver.preprocess(None);
+
+    let mut error = None;
+    let result = solver.solve_with_callback(None, |cost, _| {
+        if let Err(e) = writeln!(writer, "o {}", cost).and_then(|_| writer.flush()) {
+            error = Some(e);
+            return false;
+        }
+        true
+    });
+    if let Some(e) = error {
+        return Err(e);
+    }
+    match result.status {
+        maxsat::MaxSatStatus::Optimum => writeln!(writer, "s OPTIMUM FOUND")?,
+        maxsat::MaxSatStatus::Sat => writeln!(writer, "s SATISFIABLE")?,
+        maxsat::MaxSatStatus::Unsat => writeln!(writer, "s UNSATISFIABLE")?,
+        maxsat::MaxSatStatus::Indeterminate => writeln!(writer, "s UNKNOWN")?,
+    }
+    if let Some(model) = result.model {
+        // A value line is a string of 0 and 1 for variables of a problem.
+        let values: String = model[..wcnf.var_num]
+            .iter()
+            .map(|&b| if b == LitBool::True { '1' } else { '0' })
+            .collect();
+        writeln!(writer, "v {}", values)?;
+    }
+    writer.flush()
+}
+
+/// Solve an OPB file and print the result in the PB competition format.
+fn solve_pb<R: std::io::Read, W: std::io::Write>(input: R, mut writer: W) -> std::io::Result<()> {
+    let opb = pb::parse_opb(input)?;
+    let mut solver = pb::PbSolver::default();
+    for _ in 0..opb.var_num {
+        solver.new_var();
+    }
+    opb.constraints
+        .iter()
+        .for_each(|constraint| solver.add_constraint(constraint));
+    let (status, model) = match &opb.objective {
+        Some(objective) => {
+            let result = solver.minimize(objective, None);
+            if let Some(value) = result.value {
+                writeln!(writer, "o {}", value)?;
+            }
+            let status = match result.status {
+                maxsat::MaxSatStatus::Optimum => "OPTIMUM FOUND",
+                maxsat::MaxSatStatus::Sat => "SATISFIABLE",
+                maxsat::MaxSatStatus::Unsat => "UNSATISFIABLE",
+                maxsat::MaxSatStatus::Indeterminate => "UNKNOWN",
+            };
+            (status, result.model)
+        }
+        None => match solver.solve(None) {
+            solver::Status::Sat => ("SATISFIABLE", Some(solver.assigns.clone())),
+            solver::Status::Unsat => ("UNSATISFIABLE", None),
+            solver::Status::Indeterminate => ("UNKNOWN", None),
+        },
+    };
+    writeln!(writer, "s {}", status)?;
+    if let Some(model) = model {
+        write!(writer, "v")?;
+        for (v, &b) in model[..opb.var_num].iter().enumerate() {
+            let sign = if b == LitBool::True { "" } else { "-" };
+            write!(writer, " {}x{}", sign, v + 1)?;
+        }
+        writeln!(writer)?;
+    }
+    writer.flush()
+}
+
+/// Write the result of a CNF problem of `var_num` variables.
+/// The standard output has the `s` and `v` lines of the SAT competition.
+/// An output file has `SAT`, `UNSAT` or `UNKNOWN` and a model in one line like MiniSat.
 fn print_result<W: std::io::Write>(
-    solver: solver::Solver,
+    solver: &solver::Solver,
     status: solver::Status,
+    var_num: usize,
     mut writer: W,
     to_file: bool,
 ) -> std::io::Result<()> {
-    match status {
-        solver::Status::Sat => {
-            if to_file {
-                writeln!(writer, "SAT")?;
-            } else {
-                writeln!(writer, "s SATISFIABLE")?;
-            }
-            for (v, &b) in solver.assigns.iter().enumerate() {
-                let res = if b == LitBool::True {
-                    (v + 1) as i32
-                } else {
-                    -((v + 1) as i32)
-                };
-                write!(writer, "{} ", res)?;
+    let model = solver.model();
+    if !to_file {
+        util::write_status(status, &mut writer)?;
+        if let Some(model) = model.as_ref() {
+            util::write_model(model, var_num, &mut writer)?;
+        }
+        return writer.flush();
+    }
+    match model {
+        Some(model) => {
+            writeln!(writer, "SAT")?;
+            for v in 0..var_num as u32 {
+                let lit = solver::Lit::new(v, model[solver::Var(v)] == LitBool::True);
+                write!(writer, "{} ", lit)?;
             }
             writeln!(writer, "0")?;
         }
-        solver::Status::Unsat => {
-            if to_file {
-                writeln!(writer, "UNSAT")?;
-            } else {
-                writeln!(writer, "s UNSATISFIABLE")?;
-            }
+        None if status == solver::Status::Unsat => {
+            writeln!(writer, "UNSAT")?;
         }
-        solver::Status::Indeterminate => {
-            writeln!(writer, "s INDETERMINATE")?;
+        None => {
+            writeln!(writer, "UNKNOWN")?;
         }
     };
     writer.flush()?;
@@ -53,13 +234,70 @@ fn main() {
         help(Some("No input file"));
         std::process::exit(1);
     }
+    if args[1] == "serve" {
+        if let Err(e) = serve(&args[2..]) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
     let mut rest_args = vec![];
+    let mut maxsat = false;
+    let mut pseudo_boolean = false;
+    let mut group_mus = false;
+    let mut smt = false;
+    let mut proof_file = None;
+    let mut explanation = false;
+    let mut proof_format = solver::ProofFormat::Drat;
+    let mut preset = None;
+    let mut snapshot_file = None;
     args.iter().skip(1).for_each(|arg| {
-        if arg.starts_with('-') {
+        if arg.starts_with('-') && arg != "-" {
             if arg == "-h" || arg == "--help" {
                 help(None);
                 std::process::exit(0);
             }
+            if arg == "--maxsat" {
+                maxsat = true;
+            }
+            if arg == "--pb" {
+                pseudo_boolean = true;
+            }
+            if arg == "--mus" {
+                group_mus = true;
+            }
+            if arg == "--smtlib" {
+                smt = true;
+            }
+            if let Some(name) = arg.strip_prefix("--preset=") {
+                if name != "auto" && solver::Config::preset(name).is_none() {
+                    help(Some("Unknown preset"));
+                    std::process::exit(1);
+                }
+                preset = Some(name.to_string());
+            }
+            if arg == "--explain" {
+                explanation = true;
+            }
+            if let Some(file) = arg.strip_prefix("--proof=") {
+                proof_file = Some(file.to_string());
+            }
+            if let Some(file) = arg.strip_prefix("--snapshot=") {
+                snapshot_file = Some(file.to_string());
+            }
+            if let Some(format) = arg.strip_prefix("--proof-format=") {
+                proof_format = match format {
+                    "drat" => solver::ProofFormat::Drat,
+                    "binary-drat" => solver::ProofFormat::BinaryDrat,
+                    "frat" => solver::ProofFormat::Frat { hints: false },
+                    "frat-hints" => solver::ProofFormat::Frat { hints: true },
+                    "lrat" => solver::ProofFormat::Lrat,
+                    _ => {
+                        help(Some("Unknown proof format"));
+                        std::process::exit(1);
+                    }
+                };
+            }
         } else {
             rest_args.push(arg.clone());
         }
@@ -75,10 +313,110 @@ fn main() {
     }
     let input_file = &rest_args[0];
     let output_file = rest_args.get(1);
-    let input = std::fs::File::open(input_file).unwrap();
-    let mut solver = match util::parse_cnf(input) {
-        Ok(result) => {
+    let input: Box<dyn std::io::Read> = if input_file == "-" {
+        Box::new(std::io::stdin())
+    } else {
+        Box::new(std::fs::File::open(input_file).unwrap())
+    };
+    // gzip data is detected by its magic number. The extension before `.gz` decides the format.
+    let mut input = std::io::BufReader::new(input);
+    let (gzip, xz) = std::io::BufRead::fill_buf(&mut input)
+        .map(|buf| {
+            (
+                buf.starts_with(&[0x1f, 0x8b]),
+                buf.starts_with(b"\xfd7zXZ\0"),
+            )
+        })
+        .unwrap_or((false, false));
+    if xz {
+        help(Some(
+            "xz files aren't supported. Decompress them by `xz -d` first",
+        ));
+        std::process::exit(1);
+    }
+    let input_file = input_file.strip_suffix(".gz").unwrap_or(input_file);
+    let input: Box<dyn std::io::Read> = if gzip {
+        #[cfg(feature = "gzip")]
+        {
+            Box::new(screwsat::gzip::GzDecoder::new(input))
+        }
+        #[cfg(not(feature = "gzip"))]
+        {
+            help(Some("A gzip file needs the feature `gzip`"));
+            std::process::exit(1);
+        }
+    } else {
+        Box::new(input)
+    };
+    let (mut writer, to_file): (Box<dyn std::io::Write>, bool) =
+        if let Some(output_file) = output_file {
+            let f = File::create(output_file)
+                .unwrap_or_else(|_| panic!("Failed to open {}", output_file));
+            (Box::new(f), true)
+        } else {
+            (Box::new(std::io::stdout()), false)
+        };
+
+    if smt || input_file.ends_with(".smt2") {
+        if let Err(e) = smtlib::SmtLib::default().run(input, writer) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    if group_mus || input_file.ends_with(".gcnf") {
+        if let Err(e) = solve_mus(input, writer) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    if maxsat || input_file.ends_with(".wcnf") {
+        if let Err(e) = solve_maxsat(input, writer) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    if pseudo_boolean || input_file.ends_with(".opb") {
+        if let Err(e) = solve_pb(input, writer) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+        std::process::exit(0);
+    }
+
+    let loaded = if input_file.ends_with(".snap") {
+        if proof_file.is_some() || explanation {
+            help(Some("A proof can't be made from a snapshot"));
+            std::process::exit(1);
+        }
+        solver::Solver::read_snapshot(std::io::BufReader::new(input)).map(|mut solver| {
+            match preset.as_deref() {
+                Some("auto") | None => {}
+                Some(name) => solver.set_config(solver::Config::preset(name).unwrap()),
+            }
+            solver
+        })
+    } else {
+        util::parse_cnf(input).map(|result| {
             let mut solver = solver::Solver::default();
+            match preset.as_deref() {
+                Some("auto") => solver.set_config(solver::Config::auto(&result.clauses)),
+                Some(name) => solver.set_config(solver::Config::preset(name).unwrap()),
+                None => {}
+            }
+            if let Some(proof_file) = proof_file.as_ref() {
+                let f = File::create(proof_file)
+                    .unwrap_or_else(|_| panic!("Failed to open {}", proof_file));
+                solver.set_proof_writer(std::io::BufWriter::new(f), proof_format);
+            }
+            if explanation {
+                solver.enable_proof();
+            }
 
             if let Some(var_num) = result.var_num {
                 solver.reserve_variable(var_num);
@@ -89,29 +427,42 @@ fn main() {
             if let Some(cla_num) = result.cla_num {
                 solver.reserve_clause(cla_num);
             }
-            result.clauses.iter().for_each(|clause| {
-                solver.add_clause(clause);
-            });
+            solver.add_clauses(&result.clauses);
             solver
-        }
+        })
+    };
+    let mut solver = match loaded {
+        Ok(solver) => solver,
         Err(e) => {
             println!("{}", e);
             std::process::exit(1);
         }
     };
+    if let Some(snapshot_file) = snapshot_file.as_ref() {
+        let f = File::create(snapshot_file)
+            .unwrap_or_else(|_| panic!("Failed to open {}", snapshot_file));
+        if let Err(e) = solver.write_snapshot(std::io::BufWriter::new(f), false) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
 
+    // Variables that are created by solving are auxiliary.
+    let var_num = solver.assigns.len();
     let status = solver.solve(None);
-    let (writer, to_file): (Box<dyn std::io::Write>, bool) = if let Some(output_file) = output_file
-    {
-        let f =
-            File::create(output_file).unwrap_or_else(|_| panic!("Failed to open {}", output_file));
-        (Box::new(f), true)
-    } else {
-        (Box::new(std::io::stdout()), false)
-    };
-    if let Err(e) = print_result(solver, status, writer, to_file) {
+    if let Err(e) = solver.flush_proof() {
         println!("{}", e);
         std::process::exit(1);
     }
+    if let Err(e) = print_result(&solver, status, var_num, &mut writer, to_file) {
+        println!("{}", e);
+        std::process::exit(1);
+    }
+    if let Some(proof) = solver.proof().filter(|_| status == solver::Status::Unsat) {
+        if let Err(e) = explain(proof, &mut writer) {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
     std::process::exit(0);
 }
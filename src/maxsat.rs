@@ -0,0 +1,559 @@
+use crate::solver::{Clause, Lit, LitBool, Solver, Status, Var};
+use std::{
+    collections::{HashMap, HashSet},
+    io::BufRead,
+    time::{Duration, Instant},
+};
+
+/// The status of a MaxSAT problem that solver solved.
+/// - `Optimum` a solver found a model and proved that its cost is optimal.
+/// - `Sat` a solver found a model but stopped before proving optimality.
+/// - `Unsat` hard clauses are UNSATISFIABLE.
+/// - `Indeterminate` a solver stopped searching before finding any model.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub enum MaxSatStatus {
+    Optimum,
+    Sat,
+    Unsat,
+    Indeterminate,
+}
+
+/// The result of `MaxSatSolver::solve`.
+/// `cost` and `model` are the best known solution.
+#[derive(Debug, Clone)]
+pub struct MaxSatResult {
+    pub status: MaxSatStatus,
+    pub cost: Option<u64>,
+    pub model: Option<Vec<LitBool>>,
+}
+
+/// The result of `MaxSatSolver::minimize` and `MaxSatSolver::maximize`.
+/// `value` and `model` are the best known solution.
+#[derive(Debug, Clone)]
+pub struct OptimizeResult {
+    pub status: MaxSatStatus,
+    pub value: Option<i64>,
+    pub model: Option<Vec<LitBool>>,
+}
+
+/// The result of `MaxSatSolver::preprocess`.
+/// - `hardened` the number of soft literals that are implied by hard clauses. They are removed.
+/// - `groups` the number of intrinsic at-most-one groups of soft literals that are merged.
+/// - `lower_bound` a lower bound of the cost by disjoint cores.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PreprocessResult {
+    pub hardened: usize,
+    pub groups: usize,
+    pub lower_bound: u64,
+}
+
+fn normalize(clause: &[Lit]) -> Vec<Lit> {
+    let mut clause = clause.to_vec();
+    clause.sort();
+    clause.dedup();
+    clause
+}
+
+fn satisfied(model: &[LitBool], lit: Lit) -> bool {
+    match model[lit.var().0 as usize] {
+        LitBool::True => lit.pos(),
+        LitBool::False => lit.neg(),
+        LitBool::Undef => false,
+    }
+}
+
+/// A generalized totalizer encoding of a weighted sum of literals.
+/// `outputs` are pairs of a reachable sum and a literal that is true if the sum is at least that value.
+/// Sums greater than or equal to `cap` are merged into `cap`.
+struct Totalizer {
+    outputs: Vec<(u64, Lit)>,
+}
+
+impl Totalizer {
+    fn new_lit(solver: &mut Solver) -> Lit {
+        let v = solver.assigns.len() as u32;
+        solver.new_var();
+        Lit::new(v, true)
+    }
+    fn build(solver: &mut Solver, inputs: &[(Lit, u64)], cap: u64) -> Totalizer {
+        if inputs.len() == 1 {
+            let (lit, weight) = inputs[0];
+            return Totalizer {
+                outputs: vec![(std::cmp::min(weight, cap), lit)],
+            };
+        }
+        let (left, right) = inputs.split_at(inputs.len() / 2);
+        let left = Totalizer::build(solver, left, cap);
+        let right = Totalizer::build(solver, right, cap);
+
+        let mut sums: Vec<u64> = left
+            .outputs
+            .iter()
+            .chain(right.outputs.iter())
+            .map(|&(w, _)| w)
+            .collect();
+        for &(a, _) in left.outputs.iter() {
+            for &(b, _) in right.outputs.iter() {
+                sums.push(std::cmp::min(a + b, cap));
+            }
+        }
+        sums.sort_unstable();
+        sums.dedup();
+        let outputs: Vec<(u64, Lit)> = sums
+            .into_iter()
+            .map(|w| (w, Totalizer::new_lit(solver)))
+            .collect();
+        let output = |w: u64| outputs[outputs.binary_search_by_key(&w, |&(x, _)| x).unwrap()].1;
+
+        // An output is implied by inputs. The other direction is unnecessary to bound a sum from above.
+        for &(a, la) in left.outputs.iter().chain(right.outputs.iter()) {
+            solver.add_clause(&[!la, output(a)]);
+        }
+        for &(a, la) in left.outputs.iter() {
+            for &(b, lb) in right.outputs.iter() {
+                solver.add_clause(&[!la, !lb, output(std::cmp::min(a + b, cap))]);
+            }
+        }
+        Totalizer { outputs }
+    }
+    /// Assumptions that forbid sums that are greater than or equal to `bound`
+    fn restrict(&self, bound: u64) -> Vec<Lit> {
+        self.outputs
+            .iter()
+            .filter(|&&(w, _)| w >= bound)
+            .map(|&(_, lit)| !lit)
+            .collect()
+    }
+}
+
+/// A totalizer of soft literals whose cap is `cap`.
+struct Objective {
+    softs: Vec<(Lit, u64)>,
+    cap: u64,
+    totalizer: Totalizer,
+}
+
+/// A weighted MaxSAT solver.
+/// It minimizes the sum of weights of soft literals that are false subject to hard clauses.
+/// The search is anytime. It finds better models one by one(SAT-UNSAT linear search).
+/// A solver is incremental. Hard clauses and soft constraints can be added or removed between `solve`.
+/// Learnt clauses and an encoding of the objective are reused as long as they are valid.
+#[derive(Default)]
+pub struct MaxSatSolver {
+    solver: Solver,
+    // soft literals and their weights
+    softs: Vec<(Lit, u64)>,
+    // relaxation literals of soft clauses
+    relaxations: HashMap<Vec<Lit>, Lit>,
+    // the number of variables in a model. Encodings of objectives are excluded.
+    var_num: usize,
+    // the objective that was encoded by a previous `solve`
+    objective: Option<Objective>,
+    // a lower bound of the cost of the current soft literals
+    lower_bound: u64,
+}
+
+impl MaxSatSolver {
+    /// Add a hard clause that must be satisfied.
+    pub fn add_hard_clause(&mut self, clause: &[Lit]) {
+        clause.iter().for_each(|&lit| self.reserve(lit.var()));
+        self.solver.add_clause(clause);
+    }
+    /// Create a new variable.
+    /// Relaxation variables and encodings use variables after the ones in use.
+    /// So a variable that is introduced after `add_soft_clause` or `solve` must be created by this method.
+    pub fn new_var(&mut self) -> Var {
+        let v = Var(self.solver.assigns.len() as u32);
+        self.reserve(v);
+        v
+    }
+    fn reserve(&mut self, v: Var) {
+        while v.0 as usize >= self.solver.assigns.len() {
+            self.solver.new_var();
+        }
+        self.var_num = std::cmp::max(self.var_num, v.0 as usize + 1);
+    }
+    /// Add a soft literal. Its weight is the cost of a model in which `lit` is false.
+    pub fn add_soft_lit(&mut self, lit: Lit, weight: u64) {
+        self.reserve(lit.var());
+        if weight > 0 {
+            self.softs.push((lit, weight));
+        }
+    }
+    /// Remove all soft literals that are `lit`.
+    pub fn remove_soft_lit(&mut self, lit: Lit) {
+        self.softs.retain(|&(l, _)| l != lit);
+        self.lower_bound = 0;
+    }
+
+    /// Add a soft clause. Its weight is the cost of a model in which `clause` is falsified.
+    /// A soft clause is relaxed by a new variable `r` as a hard clause `clause v r` and a soft literal `¬r`.
+    pub fn add_soft_clause(&mut self, clause: &[Lit], weight: u64) {
+        if clause.len() == 1 {
+            self.add_soft_lit(clause[0], weight);
+            return;
+        }
+        let r = self.relaxation(clause);
+        self.add_soft_lit(!r, weight);
+    }
+    /// Remove all soft clauses that are `clause`.
+    /// A relaxed hard clause is kept, but it is satisfied by its relaxation variable for free.
+    pub fn remove_soft_clause(&mut self, clause: &[Lit]) {
+        if clause.len() == 1 {
+            self.remove_soft_lit(clause[0]);
+            return;
+        }
+        if let Some(&r) = self.relaxations.get(&normalize(clause)) {
+            self.remove_soft_lit(!r);
+        }
+    }
+    /// The relaxation literal of a clause. The same clause shares it.
+    fn relaxation(&mut self, clause: &[Lit]) -> Lit {
+        let key = normalize(clause);
+        if let Some(&r) = self.relaxations.get(&key) {
+            return r;
+        }
+        clause.iter().for_each(|&lit| self.reserve(lit.var()));
+        let r = Lit::new(self.new_var().0, true);
+        let mut relaxed = key.clone();
+        relaxed.push(r);
+        self.solver.add_clause(&relaxed);
+        self.relaxations.insert(key, r);
+        r
+    }
+
+    /// Find a model that minimizes the sum of weights of satisfied literals in `objective` subject to hard clauses.
+    /// Soft literals and soft clauses are ignored while minimizing.
+    /// # Arguments
+    /// * `objective` - Pairs of a literal and its weight. A weight can be negative.
+    /// * `time_limit` - The time limitation for searching.
+    pub fn minimize(
+        &mut self,
+        objective: &[(Lit, i64)],
+        time_limit: Option<Duration>,
+    ) -> OptimizeResult {
+        // w * lit is w + |w| * ¬lit if w is negative. So every term is a soft literal with a positive weight.
+        let mut offset = 0;
+        let mut softs = Vec::new();
+        for &(lit, weight) in objective.iter() {
+            self.reserve(lit.var());
+            if weight > 0 {
+                softs.push((!lit, weight as u64));
+            } else if weight < 0 {
+                offset += weight;
+                softs.push((lit, weight.unsigned_abs()));
+            }
+        }
+        let softs = std::mem::replace(&mut self.softs, softs);
+        let lower_bound = std::mem::take(&mut self.lower_bound);
+        let result = self.solve(time_limit);
+        self.softs = softs;
+        self.lower_bound = lower_bound;
+        OptimizeResult {
+            status: result.status,
+            value: result.cost.map(|cost| offset + cost as i64),
+            model: result.model,
+        }
+    }
+
+    /// Find a model that maximizes the sum of weights of satisfied literals in `objective` subject to hard clauses.
+    /// See `minimize`.
+    pub fn maximize(
+        &mut self,
+        objective: &[(Lit, i64)],
+        time_limit: Option<Duration>,
+    ) -> OptimizeResult {
+        let negated: Vec<(Lit, i64)> = objective.iter().map(|&(lit, w)| (lit, -w)).collect();
+        let result = self.minimize(&negated, time_limit);
+        OptimizeResult {
+            value: result.value.map(|value| -value),
+            ..result
+        }
+    }
+
+    fn cost(&self, model: &[LitBool]) -> u64 {
+        self.softs
+            .iter()
+            .filter(|&&(lit, _)| !satisfied(model, lit))
+            .map(|&(_, w)| w)
+            .sum()
+    }
+
+    /// Simplify soft literals and compute a lower bound of the cost before `solve`.
+    /// - Soft literals that are implied by hard clauses by unit propagation are always satisfied. They are removed.
+    /// - Soft literals that are pairwise implied(`a v b` by unit propagation) can't be falsified together.
+    ///   Such an at-most-one group `s1, ..., sk` whose smallest weight is `w` is merged into a new soft literal `b` of weight `w`
+    ///   by hard clauses `¬b v si`, and `w` is subtracted from the weight of each `si`.
+    /// - Disjoint cores of soft literals(sets of them that can't be satisfied together) are found greedily.
+    ///   The sum of the smallest weights of the cores is a lower bound. `solve` stops as soon as it finds a model of that cost.
+    ///
+    /// Soft literals that are merged or removed can't be removed by `remove_soft_lit` anymore.
+    /// # Arguments
+    /// * `time_limit` - The time limitation for finding cores.
+    pub fn preprocess(&mut self, time_limit: Option<Duration>) -> PreprocessResult {
+        let start = Instant::now();
+        let mut result = PreprocessResult::default();
+
+        // Hardening
+        let len = self.softs.len();
+        let solver = &mut self.solver;
+        self.softs.retain(|&(lit, _)| !solver.is_rup(&[lit]));
+        result.hardened = len - self.softs.len();
+
+        // Intrinsic at-most-one groups. A falsified soft literal implies the others.
+        let mut weights: HashMap<Lit, u64> = HashMap::new();
+        self.softs
+            .iter()
+            .for_each(|&(lit, w)| *weights.entry(lit).or_default() += w);
+        let mut lits: Vec<Lit> = weights.keys().copied().collect();
+        lits.sort_by_key(|lit| (std::cmp::Reverse(weights[lit]), *lit));
+        let mut grouped: HashSet<Lit> = HashSet::new();
+        for &lit in lits.iter() {
+            if grouped.contains(&lit) {
+                continue;
+            }
+            let implied = match self.solver.propagate_lits(&[!lit]) {
+                Some(implied) => implied,
+                // A soft literal that is implied. It is removed by hardening next time.
+                None => continue,
+            };
+            let mut group = vec![lit];
+            for &other in implied.iter() {
+                if other == lit || !weights.contains_key(&other) || grouped.contains(&other) {
+                    continue;
+                }
+                // `other` must exclude every member of a group.
+                if group
+                    .iter()
+                    .skip(1)
+                    .all(|&member| self.solver.is_rup(&[member, other]))
+                {
+                    group.push(other);
+                }
+            }
+            if group.len() < 2 {
+                continue;
+            }
+            grouped.extend(group.iter().copied());
+            let w = group.iter().map(|member| weights[member]).min().unwrap();
+            let b = Totalizer::new_lit(&mut self.solver);
+            group
+                .iter()
+                .for_each(|&member| self.solver.add_clause(&[!b, member]));
+            for &member in group.iter() {
+                let weight = weights.get_mut(&member).unwrap();
+                *weight -= w;
+            }
+            weights.insert(b, w);
+            result.groups += 1;
+        }
+        if result.groups > 0 {
+            let mut softs: Vec<(Lit, u64)> = weights.into_iter().filter(|&(_, w)| w > 0).collect();
+            softs.sort();
+            self.softs = softs;
+        }
+
+        // Disjoint cores
+        let mut weights: HashMap<Lit, u64> = HashMap::new();
+        self.softs
+            .iter()
+            .for_each(|&(lit, w)| *weights.entry(lit).or_default() += w);
+        // Heavy soft literals are assumed first so that cores tend to have them.
+        let mut assumptions: Vec<Lit> = weights.keys().copied().collect();
+        assumptions.sort_by_key(|lit| (std::cmp::Reverse(weights[lit]), *lit));
+        loop {
+            let remaining = time_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+            if remaining == Some(Duration::from_secs(0)) {
+                break;
+            }
+            if self.solver.solve_with_assumptions(&assumptions, remaining) != Status::Unsat {
+                break;
+            }
+            let mut core = self.solver.unsat_core().to_vec();
+            if core.is_empty() {
+                // Hard clauses are unsatisfiable.
+                break;
+            }
+            // Trim a core by solving under the core again while it shrinks.
+            loop {
+                let remaining = time_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+                let ordered: Vec<Lit> = assumptions
+                    .iter()
+                    .filter(|lit| core.contains(lit))
+                    .copied()
+                    .collect();
+                if self.solver.solve_with_assumptions(&ordered, remaining) != Status::Unsat
+                    || self.solver.unsat_core().len() >= core.len()
+                {
+                    break;
+                }
+                core = self.solver.unsat_core().to_vec();
+            }
+            result.lower_bound += core.iter().map(|lit| weights[lit]).min().unwrap();
+            assumptions.retain(|lit| !core.contains(lit));
+        }
+        self.lower_bound = result.lower_bound;
+        result
+    }
+
+    /// Solve a problem and return the best known solution.
+    /// # Arguments
+    /// * `time_limit` - The time limitation for searching.
+    pub fn solve(&mut self, time_limit: Option<Duration>) -> MaxSatResult {
+        self.solve_with_callback(time_limit, |_, _| true)
+    }
+
+    /// Solve a problem and report every improved solution to `on_improve`.
+    /// # Arguments
+    /// * `time_limit` - The time limitation for searching.
+    ///   Exceeding the time limit returns the best known solution with `MaxSatStatus::Sat`.
+    /// * `on_improve` - A callback that is called with the cost and the model of an improved solution.
+    ///   Returning `false` stops searching.
+    pub fn solve_with_callback<F>(
+        &mut self,
+        time_limit: Option<Duration>,
+        mut on_improve: F,
+    ) -> MaxSatResult
+    where
+        F: FnMut(u64, &[LitBool]) -> bool,
+    {
+        let start = Instant::now();
+        let var_num = self.var_num;
+        let mut best: Option<(u64, Vec<LitBool>)> = None;
+        let mut assumptions = vec![];
+
+        let status = loop {
+            let remaining = time_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+            match self.solver.solve_with_assumptions(&assumptions, remaining) {
+                Status::Sat => {
+                    let model = self.solver.assigns[..var_num].to_vec();
+                    // Merged soft literals are after the variables of a model.
+                    let cost = self.cost(&self.solver.assigns);
+                    let proceed = on_improve(cost, &model);
+                    best = Some((cost, model));
+                    if cost <= self.lower_bound {
+                        break MaxSatStatus::Optimum;
+                    }
+                    if !proceed {
+                        break MaxSatStatus::Sat;
+                    }
+                    // Search a model whose cost is less than `cost`.
+                    // A totalizer of a previous `solve` is reused if the soft literals are same and its cap is enough.
+                    let reusable = self.objective.as_ref().is_some_and(|objective| {
+                        objective.softs == self.softs && objective.cap >= cost
+                    });
+                    if !reusable {
+                        let inputs: Vec<(Lit, u64)> =
+                            self.softs.iter().map(|&(lit, w)| (!lit, w)).collect();
+                        self.objective = Some(Objective {
+                            softs: self.softs.clone(),
+                            cap: cost,
+                            totalizer: Totalizer::build(&mut self.solver, &inputs, cost),
+                        });
+                    }
+                    assumptions = self.objective.as_ref().unwrap().totalizer.restrict(cost);
+                }
+                Status::Unsat => {
+                    break if best.is_some() {
+                        MaxSatStatus::Optimum
+                    } else {
+                        MaxSatStatus::Unsat
+                    };
+                }
+                Status::Indeterminate => {
+                    break if best.is_some() {
+                        MaxSatStatus::Sat
+                    } else {
+                        MaxSatStatus::Indeterminate
+                    };
+                }
+            }
+        };
+        let (cost, model) = match best {
+            Some((cost, model)) => (Some(cost), Some(model)),
+            None => (None, None),
+        };
+        MaxSatResult {
+            status,
+            cost,
+            model,
+        }
+    }
+}
+
+// WcnfData is parsed from a WCNF file
+#[derive(Debug, Default)]
+pub struct WcnfData {
+    // the number of variable
+    pub var_num: usize,
+    // clauses that must be satisfied
+    pub hards: Vec<Clause>,
+    // soft clauses and their weights
+    pub softs: Vec<(Clause, u64)>,
+}
+
+/// Parse a WCNF file of the MaxSAT Evaluation format.
+/// A hard clause starts with `h` and a soft clause starts with its weight.
+/// The old format that has a header `p wcnf <variables> <clauses> [<top>]` is also parsed.
+/// A clause of the old format whose weight is at least `top` is hard.
+///
+/// ```text
+/// c Here is a comment.
+/// h 1 2 0
+/// h -1 -2 0
+/// 3 1 0
+/// 5 2 0
+/// ```
+pub fn parse_wcnf<R: std::io::Read>(input: R) -> std::io::Result<WcnfData> {
+    let reader = std::io::BufReader::new(input);
+    let mut wcnf = WcnfData::default();
+    let mut top = None;
+    for line in reader.lines() {
+        let line = line?;
+        let values: Vec<&str> = line.split_whitespace().collect();
+        if values.is_empty() || values[0].starts_with('c') {
+            // comment or empty line
+            continue;
+        }
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            )
+        };
+        if values[0] == "p" {
+            let (var_num, weight) = match values[..] {
+                ["p", "wcnf", var_num, _] => (var_num, None),
+                ["p", "wcnf", var_num, _, weight] => (var_num, Some(weight)),
+                _ => return Err(invalid()),
+            };
+            let var_num = var_num.parse::<usize>().map_err(|_| invalid())?;
+            wcnf.var_num = std::cmp::max(wcnf.var_num, var_num);
+            top = match weight {
+                Some(weight) => Some(weight.parse::<u64>().map_err(|_| invalid())?),
+                None => None,
+            };
+            continue;
+        }
+        let clause = values[1..]
+            .iter()
+            .map(|x| x.parse::<i32>().map_err(|_| invalid()))
+            .take_while(|x| !matches!(x, Ok(0)))
+            .map(|x| x.map(Lit::from))
+            .collect::<std::io::Result<Clause>>()?;
+        wcnf.var_num = clause.iter().fold(wcnf.var_num, |n, lit| {
+            std::cmp::max(n, lit.var().0 as usize + 1)
+        });
+        if values[0] == "h" {
+            wcnf.hards.push(clause);
+        } else {
+            let weight = values[0].parse::<u64>().map_err(|_| invalid())?;
+            if top.is_some_and(|top| weight >= top) {
+                wcnf.hards.push(clause);
+            } else {
+                wcnf.softs.push((clause, weight));
+            }
+        }
+    }
+    Ok(wcnf)
+}
@@ -0,0 +1,377 @@
+use crate::maxsat::{MaxSatSolver, MaxSatStatus};
+use crate::solver::{Clause, Lit, LitBool, Solver, Status};
+use std::{
+    io::{BufRead, Write},
+    time::{Duration, Instant},
+};
+
+/// An explanation of an infeasible problem over constraint groups.
+/// - `Mus` a minimal unsatisfiable subset. Removing any group makes it satisfiable.
+/// - `Mcs` a minimal correction set. Removing it makes the others satisfiable and any smaller one doesn't.
+///
+/// Groups are represented by their indices.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Explanation {
+    Mus(Vec<usize>),
+    Mcs(Vec<usize>),
+}
+
+/// A solver that checks whether hard clauses and a subset of groups are satisfiable.
+/// Each group is enabled by a selector literal under assumptions.
+struct Oracle {
+    solver: Solver,
+    groups: Vec<Vec<Clause>>,
+    selectors: Vec<Lit>,
+    deadline: Option<Instant>,
+}
+
+impl Oracle {
+    fn new(hards: &[Clause], groups: &[Vec<Clause>], time_limit: Option<Duration>) -> Oracle {
+        let mut solver = Solver::default();
+        let var_num = hards
+            .iter()
+            .chain(groups.iter().flatten())
+            .flatten()
+            .fold(0, |n, lit| std::cmp::max(n, lit.var().0 as usize + 1));
+        while solver.assigns.len() < var_num {
+            solver.new_var();
+        }
+        hards.iter().for_each(|clause| solver.add_clause(clause));
+        let selectors: Vec<Lit> = (0..groups.len())
+            .map(|i| Lit::new((var_num + i) as u32, true))
+            .collect();
+        for (group, &s) in groups.iter().zip(selectors.iter()) {
+            for clause in group.iter() {
+                let mut clause = clause.clone();
+                clause.push(!s);
+                solver.add_clause(&clause);
+            }
+        }
+        Oracle {
+            solver,
+            groups: groups.to_vec(),
+            selectors,
+            deadline: time_limit.map(|limit| Instant::now() + limit),
+        }
+    }
+
+    /// Check whether hard clauses and `set` are satisfiable.
+    fn check(&mut self, set: &[usize]) -> Status {
+        let assumptions: Vec<Lit> = set.iter().map(|&i| self.selectors[i]).collect();
+        let remaining = self
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        if remaining == Some(Duration::from_secs(0)) {
+            return Status::Indeterminate;
+        }
+        self.solver.solve_with_assumptions(&assumptions, remaining)
+    }
+
+    /// Whether the last model satisfies all clauses of a group.
+    fn satisfies(&self, group: usize) -> bool {
+        self.groups[group].iter().all(|clause| {
+            clause.iter().any(|lit| {
+                (self.solver.assigns[lit.var().0 as usize] == LitBool::True) == lit.pos()
+            })
+        })
+    }
+
+    /// Grow a satisfiable set to a maximal satisfiable subset.
+    /// The oracle must have a model of `set`.
+    fn grow(&mut self, mut set: Vec<usize>) -> Option<Vec<usize>> {
+        let mut in_set = vec![false; self.groups.len()];
+        set.iter().for_each(|&i| in_set[i] = true);
+        let rest: Vec<usize> = (0..self.groups.len()).filter(|&i| !in_set[i]).collect();
+        for i in rest {
+            // A group that is satisfied by the current model can be added for free.
+            if !self.satisfies(i) {
+                set.push(i);
+                match self.check(&set) {
+                    Status::Sat => {}
+                    Status::Unsat => {
+                        set.pop();
+                        // Restore a model of `set`.
+                        if self.check(&set) != Status::Sat {
+                            return None;
+                        }
+                        continue;
+                    }
+                    Status::Indeterminate => return None,
+                }
+            } else {
+                set.push(i);
+            }
+        }
+        set.sort_unstable();
+        Some(set)
+    }
+
+    /// Shrink an unsatisfiable set to a minimal unsatisfiable subset by deletion.
+    fn shrink(&mut self, mut set: Vec<usize>) -> Option<Vec<usize>> {
+        let mut i = 0;
+        while i < set.len() {
+            let removed = set.remove(i);
+            match self.check(&set) {
+                Status::Unsat => {}
+                Status::Sat => {
+                    set.insert(i, removed);
+                    i += 1;
+                }
+                Status::Indeterminate => return None,
+            }
+        }
+        Some(set)
+    }
+}
+
+/// Find a MUS. Groups are removed one by one while they are unsatisfiable without them.
+/// It returns `None` if all groups are satisfiable together or the time limit is exceeded.
+/// # Arguments
+/// * `hards` - Clauses that are always enabled
+/// * `groups` - Constraint groups. A MUS is a set of indices of them.
+/// * `time_limit` - The time limitation for searching.
+pub fn find_mus(
+    hards: &[Clause],
+    groups: &[Vec<Clause>],
+    time_limit: Option<Duration>,
+) -> Option<Vec<usize>> {
+    let all: Vec<usize> = (0..groups.len()).collect();
+    find_mus_in(hards, groups, &all, time_limit)
+}
+
+/// Find a MUS inside `core`, an unsatisfiable subset of groups such as an unsatisfiable core of assumptions.
+/// Only groups in `core` are removed one by one, so a small core makes it fast.
+/// It returns `None` if `core` is satisfiable or the time limit is exceeded.
+/// # Arguments
+/// * `hards` - Clauses that are always enabled
+/// * `groups` - Constraint groups. A MUS is a set of indices of them.
+/// * `core` - Indices of groups that are unsatisfiable together
+/// * `time_limit` - The time limitation for searching.
+pub fn find_mus_in(
+    hards: &[Clause],
+    groups: &[Vec<Clause>],
+    core: &[usize],
+    time_limit: Option<Duration>,
+) -> Option<Vec<usize>> {
+    let mut oracle = Oracle::new(hards, groups, time_limit);
+    if oracle.check(core) != Status::Unsat {
+        return None;
+    }
+    oracle.shrink(core.to_vec())
+}
+
+/// An iterator over distinct MUSes and MCSes of a problem(MARCO).
+/// A map solver keeps subsets of groups that are not explored yet.
+/// A satisfiable seed is grown to an MSS and its complement is an MCS.
+/// An unsatisfiable seed is shrunk to a MUS.
+/// The iteration ends when all subsets are explored or the time limit is exceeded.
+pub struct Marco {
+    oracle: Oracle,
+    map: Solver,
+    done: bool,
+}
+
+impl Marco {
+    /// Create a new `Marco` iterator
+    /// # Arguments
+    /// * `hards` - Clauses that are always enabled
+    /// * `groups` - Constraint groups. A MUS and an MCS are sets of indices of them.
+    /// * `time_limit` - The time limitation for the whole enumeration.
+    pub fn new(hards: &[Clause], groups: &[Vec<Clause>], time_limit: Option<Duration>) -> Marco {
+        let mut map = Solver::default();
+        while map.assigns.len() < groups.len() {
+            map.new_var();
+        }
+        Marco {
+            oracle: Oracle::new(hards, groups, time_limit),
+            map,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for Marco {
+    type Item = Explanation;
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.oracle.groups.len();
+        if self.done {
+            return None;
+        }
+        // The enumeration ends unless a new explanation is found.
+        self.done = true;
+        if self.map.solve(None) == Status::Sat {
+            let seed: Vec<usize> = (0..n)
+                .filter(|&i| self.map.assigns[i] == LitBool::True)
+                .collect();
+            match self.oracle.check(&seed) {
+                Status::Sat => {
+                    let mss = self.oracle.grow(seed)?;
+                    let mcs: Vec<usize> =
+                        (0..n).filter(|i| mss.binary_search(i).is_err()).collect();
+                    // Block subsets of the MSS.
+                    let block: Clause = mcs.iter().map(|&i| Lit::new(i as u32, true)).collect();
+                    self.map.add_clause(&block);
+                    // All groups are satisfiable together if `mcs` is empty.
+                    if !mcs.is_empty() {
+                        self.done = false;
+                        return Some(Explanation::Mcs(mcs));
+                    }
+                }
+                Status::Unsat => {
+                    let mus = self.oracle.shrink(seed)?;
+                    // Block supersets of the MUS.
+                    let block: Clause = mus.iter().map(|&i| Lit::new(i as u32, false)).collect();
+                    self.map.add_clause(&block);
+                    self.done = false;
+                    return Some(Explanation::Mus(mus));
+                }
+                Status::Indeterminate => {}
+            }
+        }
+        None
+    }
+}
+
+/// Find a MUS that has the minimum number of groups.
+/// A hitting set of MCSes that has the minimum size is a smallest MUS if it is unsatisfiable(implicit hitting set).
+/// Otherwise it is grown to an MSS, and the complement MCS is added.
+/// It returns `None` if all groups are satisfiable together or the time limit is exceeded.
+/// # Arguments
+/// * `hards` - Clauses that are always enabled
+/// * `groups` - Constraint groups. A MUS is a set of indices of them.
+/// * `time_limit` - The time limitation for searching.
+pub fn smallest_mus(
+    hards: &[Clause],
+    groups: &[Vec<Clause>],
+    time_limit: Option<Duration>,
+) -> Option<Vec<usize>> {
+    let mut oracle = Oracle::new(hards, groups, time_limit);
+    // x_i is true if a hitting set has the group i.
+    let mut hitting_set = MaxSatSolver::default();
+    (0..groups.len()).for_each(|i| hitting_set.add_soft_lit(Lit::new(i as u32, false), 1));
+    loop {
+        let remaining = oracle
+            .deadline
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+        let result = hitting_set.solve(remaining);
+        if result.status != MaxSatStatus::Optimum {
+            return None;
+        }
+        let model = result.model.unwrap();
+        let set: Vec<usize> = (0..groups.len())
+            .filter(|&i| model[i] == LitBool::True)
+            .collect();
+        match oracle.check(&set) {
+            Status::Unsat => return Some(set),
+            Status::Sat => {
+                let mss = oracle.grow(set)?;
+                let mcs: Clause = (0..groups.len())
+                    .filter(|i| mss.binary_search(i).is_err())
+                    .map(|i| Lit::new(i as u32, true))
+                    .collect();
+                if mcs.is_empty() {
+                    return None;
+                }
+                // Every MUS has at least one group of an MCS.
+                hitting_set.add_hard_clause(&mcs);
+            }
+            Status::Indeterminate => return None,
+        }
+    }
+}
+
+// GcnfData is parsed from a GCNF(group oriented CNF) file
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct GcnfData {
+    // the number of variable
+    pub var_num: usize,
+    // clauses of the group 0 that are always enabled
+    pub hards: Vec<Clause>,
+    // clauses of the groups 1, 2, ...
+    pub groups: Vec<Vec<Clause>>,
+}
+
+/// Parse a GCNF file. A clause starts with its group `{g}`.
+/// The group 0 is hard clauses. Groups that have no clauses are empty.
+///
+/// ```text
+/// c Here is a comment.
+/// p gcnf 2 4 3
+/// {0} 1 2 0
+/// {1} -1 0
+/// {2} -2 0
+/// {3} 1 -2 0
+/// ```
+pub fn parse_gcnf<R: std::io::Read>(input: R) -> std::io::Result<GcnfData> {
+    let reader = std::io::BufReader::new(input);
+    let mut gcnf = GcnfData::default();
+    for line in reader.lines() {
+        let line = line?;
+        let values: Vec<&str> = line.split_whitespace().collect();
+        if values.is_empty() || values[0].starts_with('c') {
+            // comment or empty line
+            continue;
+        }
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid line: {}", line),
+            )
+        };
+        if values[0] == "p" {
+            // p gcnf v_num c_num g_num
+            if let Some(g) = values.get(4) {
+                let g: usize = g.parse().map_err(|_| invalid())?;
+                gcnf.groups
+                    .resize(std::cmp::max(gcnf.groups.len(), g), vec![]);
+            }
+            continue;
+        }
+        let group: usize = values[0]
+            .strip_prefix('{')
+            .and_then(|g| g.strip_suffix('}'))
+            .and_then(|g| g.parse().ok())
+            .ok_or_else(invalid)?;
+        let clause = values[1..]
+            .iter()
+            .map(|x| x.parse::<i32>().map_err(|_| invalid()))
+            .take_while(|x| !matches!(x, Ok(0)))
+            .map(|x| x.map(Lit::from))
+            .collect::<std::io::Result<Clause>>()?;
+        gcnf.var_num = clause.iter().fold(gcnf.var_num, |n, lit| {
+            std::cmp::max(n, lit.var().0 as usize + 1)
+        });
+        if group == 0 {
+            gcnf.hards.push(clause);
+        } else {
+            if gcnf.groups.len() < group {
+                gcnf.groups.resize(group, vec![]);
+            }
+            gcnf.groups[group - 1].push(clause);
+        }
+    }
+    Ok(gcnf)
+}
+
+/// Write a GCNF file.
+pub fn write_gcnf<W: Write>(gcnf: &GcnfData, mut writer: W) -> std::io::Result<()> {
+    let cla_num = gcnf.hards.len() + gcnf.groups.iter().map(|g| g.len()).sum::<usize>();
+    writeln!(
+        writer,
+        "p gcnf {} {} {}",
+        gcnf.var_num,
+        cla_num,
+        gcnf.groups.len()
+    )?;
+    let groups = std::iter::once(&gcnf.hards).chain(gcnf.groups.iter());
+    for (g, clauses) in groups.enumerate() {
+        for clause in clauses.iter() {
+            write!(writer, "{{{}}}", g)?;
+            for lit in clause.iter() {
+                write!(writer, " {}", lit)?;
+            }
+            writeln!(writer, " 0")?;
+        }
+    }
+    writer.flush()
+}
@@ -0,0 +1,685 @@
+use crate::maxsat::{MaxSatStatus, OptimizeResult};
+use crate::solver::{BranchingHeuristic, Config, Lit, LitBool, RestartPolicy, Status, Var};
+use std::{
+    io::Read,
+    time::{Duration, Instant},
+};
+
+/// The relation of a pseudo-Boolean constraint `sum coef * lit <relation> degree`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Relation {
+    AtLeast,
+    AtMost,
+    Equal,
+}
+
+/// A linear pseudo-Boolean constraint over literals. Coefficients can be negative.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct PbConstraint {
+    pub terms: Vec<(Lit, i64)>,
+    pub relation: Relation,
+    pub degree: i64,
+}
+
+/// A normalized constraint `sum coef * lit >= degree`. Coefficients are positive and at most `degree`.
+/// Terms are sorted by their coefficients in descending order so that propagation stops at the first small one.
+#[derive(Debug, Clone)]
+struct Constraint {
+    terms: Vec<(Lit, i64)>,
+    degree: i64,
+    // the sum of coefficients of literals that aren't false minus `degree`
+    slack: i64,
+}
+
+// Learnt constraints are divided while their degrees are larger than it so that coefficients don't overflow.
+const DEGREE_LIMIT: i128 = 1 << 40;
+
+/// A normalized constraint of conflict analysis over dense arrays of variables.
+#[derive(Debug, Default)]
+struct Working {
+    coefs: Vec<i128>,
+    lits: Vec<Lit>,
+    // variables that have nonzero coefficients. Coefficients can be zero by cancellation until saturation.
+    vars: Vec<usize>,
+    seen: Vec<bool>,
+    degree: i128,
+}
+
+impl Working {
+    fn clear(&mut self) {
+        for &v in self.vars.iter() {
+            self.coefs[v] = 0;
+            self.seen[v] = false;
+        }
+        self.vars.clear();
+        self.degree = 0;
+    }
+    fn grow(&mut self, n: usize) {
+        self.coefs.resize(n, 0);
+        self.lits.resize(n, Lit::default());
+        self.seen.resize(n, false);
+    }
+    fn coef(&self, lit: Lit) -> i128 {
+        let v = lit.var().0 as usize;
+        if self.coefs[v] > 0 && self.lits[v] == lit {
+            self.coefs[v]
+        } else {
+            0
+        }
+    }
+    /// Add `coef * lit`. `a * x + b * !x` is `(a - b) * x + b`.
+    fn add(&mut self, lit: Lit, coef: i128) {
+        let v = lit.var().0 as usize;
+        if self.coefs[v] == 0 {
+            if !self.seen[v] {
+                self.seen[v] = true;
+                self.vars.push(v);
+            }
+            self.coefs[v] = coef;
+            self.lits[v] = lit;
+        } else if self.lits[v] == lit {
+            self.coefs[v] += coef;
+        } else {
+            let cancel = std::cmp::min(coef, self.coefs[v]);
+            self.degree -= cancel;
+            if coef > self.coefs[v] {
+                self.lits[v] = lit;
+            }
+            self.coefs[v] = (self.coefs[v] - coef).abs();
+        }
+    }
+    /// Remove zero coefficients and saturate coefficients to the degree.
+    fn saturate(&mut self) {
+        let (coefs, seen, degree) = (&mut self.coefs, &mut self.seen, self.degree);
+        self.vars.retain(|&v| {
+            coefs[v] = std::cmp::min(coefs[v], degree);
+            seen[v] = coefs[v] > 0;
+            seen[v]
+        });
+    }
+    /// Weaken literals that aren't falsified and whose coefficients aren't divisible by `d`, then divide by `d` rounding up.
+    /// A conflicting constraint is still conflicting and a literal whose coefficient is `d` gets the coefficient 1.
+    fn divide<F: Fn(Lit) -> bool>(&mut self, d: i128, falsified: F) {
+        if d <= 1 {
+            return;
+        }
+        for &v in self.vars.iter() {
+            if self.coefs[v] % d != 0 && !falsified(self.lits[v]) {
+                self.degree -= self.coefs[v];
+                self.coefs[v] = 0;
+            }
+        }
+        for &v in self.vars.iter() {
+            self.coefs[v] = (self.coefs[v] + d - 1) / d;
+        }
+        self.degree = (self.degree + d - 1) / d;
+        self.saturate();
+    }
+}
+
+/// A pseudo-Boolean solver that propagates linear constraints natively and learns constraints by cutting planes.
+///
+/// Conflict analysis follows RoundingSat. The reason of a propagated literal is weakened and divided
+/// by the coefficient of the literal, then it is added to a conflicting constraint to cancel the literal.
+/// Counting arguments such as the pigeonhole principle have short proofs by cutting planes
+/// while they are exponentially long by resolution.
+/// Learnt constraints are kept for the following searches.
+#[derive(Debug)]
+pub struct PbSolver {
+    // assignments for each variable
+    pub assigns: Vec<LitBool>,
+    level: Vec<usize>,
+    reason: Vec<Option<usize>>,
+    polarity: Vec<bool>,
+    trail: Vec<Lit>,
+    // the start of each decision level in `trail`
+    trail_lim: Vec<usize>,
+    head: usize,
+    constraints: Vec<Constraint>,
+    // pairs of a constraint and a coefficient of each literal
+    occurs: Vec<Vec<(usize, i64)>>,
+    branching: Box<dyn BranchingHeuristic>,
+    conflict: Working,
+    reason_buf: Working,
+    // `Unsat` is permanent
+    unsat: bool,
+}
+
+impl Default for PbSolver {
+    fn default() -> Self {
+        PbSolver {
+            assigns: Vec::new(),
+            level: Vec::new(),
+            reason: Vec::new(),
+            polarity: Vec::new(),
+            trail: Vec::new(),
+            trail_lim: Vec::new(),
+            head: 0,
+            constraints: Vec::new(),
+            occurs: Vec::new(),
+            branching: Config::default().branching.start(),
+            conflict: Working::default(),
+            reason_buf: Working::default(),
+            unsat: false,
+        }
+    }
+}
+
+impl PbSolver {
+    /// Create a new variable and return it.
+    pub fn new_var(&mut self) -> Var {
+        let v = Var(self.assigns.len() as u32);
+        self.assigns.push(LitBool::Undef);
+        self.level.push(0);
+        self.reason.push(None);
+        self.polarity.push(false);
+        self.occurs.push(Vec::new());
+        self.occurs.push(Vec::new());
+        self.branching.new_var(v);
+        self.conflict.grow(self.assigns.len());
+        self.reason_buf.grow(self.assigns.len());
+        v
+    }
+
+    fn reserve(&mut self, v: Var) {
+        while v.0 as usize >= self.assigns.len() {
+            self.new_var();
+        }
+    }
+
+    fn value(&self, lit: Lit) -> LitBool {
+        match self.assigns[lit.var().0 as usize] {
+            LitBool::True if lit.pos() => LitBool::True,
+            LitBool::False if lit.neg() => LitBool::True,
+            LitBool::Undef => LitBool::Undef,
+            _ => LitBool::False,
+        }
+    }
+
+    fn decision_level(&self) -> usize {
+        self.trail_lim.len()
+    }
+
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) {
+        let v = lit.var().0 as usize;
+        debug_assert_eq!(self.assigns[v], LitBool::Undef);
+        self.assigns[v] = if lit.pos() {
+            LitBool::True
+        } else {
+            LitBool::False
+        };
+        self.level[v] = self.decision_level();
+        self.reason[v] = reason;
+        self.trail.push(lit);
+        self.branching.assign(lit.var());
+        // Slacks are updated on assignments so that they are always exact.
+        for i in 0..self.occurs[!lit].len() {
+            let (c, coef) = self.occurs[!lit][i];
+            self.constraints[c].slack -= coef;
+        }
+    }
+
+    fn pop(&mut self) {
+        let lit = self.trail.pop().unwrap();
+        let v = lit.var().0 as usize;
+        self.polarity[v] = lit.pos();
+        self.assigns[v] = LitBool::Undef;
+        self.reason[v] = None;
+        self.branching.unassign(lit.var());
+        for i in 0..self.occurs[!lit].len() {
+            let (c, coef) = self.occurs[!lit][i];
+            self.constraints[c].slack += coef;
+        }
+        self.head = std::cmp::min(self.head, self.trail.len());
+    }
+
+    fn backtrack(&mut self, level: usize) {
+        if self.decision_level() > level {
+            while self.trail.len() > self.trail_lim[level] {
+                self.pop();
+            }
+            self.trail_lim.truncate(level);
+        }
+    }
+
+    /// Propagate a constraint. Literals whose coefficients are larger than the slack must be true.
+    /// It returns the constraint if it is conflicting.
+    fn propagate_constraint(&mut self, c: usize) -> Option<usize> {
+        let slack = self.constraints[c].slack;
+        if slack < 0 {
+            return Some(c);
+        }
+        for i in 0..self.constraints[c].terms.len() {
+            let (lit, coef) = self.constraints[c].terms[i];
+            if coef <= slack {
+                break;
+            }
+            if self.value(lit) == LitBool::Undef {
+                self.enqueue(lit, Some(c));
+            }
+        }
+        None
+    }
+
+    fn propagate(&mut self) -> Option<usize> {
+        while self.head < self.trail.len() {
+            let p = self.trail[self.head];
+            self.head += 1;
+            // constraints whose literal `!p` becomes false
+            for i in 0..self.occurs[!p].len() {
+                let c = self.occurs[!p][i].0;
+                if let Some(confl) = self.propagate_constraint(c) {
+                    return Some(confl);
+                }
+            }
+        }
+        None
+    }
+
+    /// Add a normalized constraint and propagate it. It returns a conflicting constraint if a conflict is found.
+    fn attach(&mut self, mut terms: Vec<(Lit, i64)>, degree: i64) -> Option<usize> {
+        terms.sort_by_key(|&(_, coef)| std::cmp::Reverse(coef));
+        let slack = terms
+            .iter()
+            .filter(|(lit, _)| self.value(*lit) != LitBool::False)
+            .map(|(_, coef)| coef)
+            .sum::<i64>()
+            - degree;
+        let c = self.constraints.len();
+        terms
+            .iter()
+            .for_each(|&(lit, coef)| self.occurs[lit].push((c, coef)));
+        self.constraints.push(Constraint {
+            terms,
+            degree,
+            slack,
+        });
+        self.propagate_constraint(c).or_else(|| self.propagate())
+    }
+
+    /// Add a constraint `sum coef * lit >= degree`.
+    /// A constraint is normalized to positive coefficients and simplified by root level assignments.
+    pub fn add_at_least(&mut self, terms: &[(Lit, i64)], degree: i64) {
+        if self.unsat {
+            return;
+        }
+        self.backtrack(0);
+        terms.iter().for_each(|&(lit, _)| self.reserve(lit.var()));
+        let mut w = std::mem::take(&mut self.conflict);
+        w.clear();
+        w.degree = degree as i128;
+        for &(lit, coef) in terms.iter() {
+            // coef * lit is coef + |coef| * !lit if coef is negative.
+            if coef < 0 {
+                w.degree -= coef as i128;
+                w.add(!lit, -(coef as i128));
+            } else if coef > 0 {
+                w.add(lit, coef as i128);
+            }
+        }
+        let mut normalized = vec![];
+        for &v in w.vars.iter() {
+            let (lit, coef) = (w.lits[v], w.coefs[v]);
+            match self.value(lit) {
+                LitBool::True => w.degree -= coef,
+                LitBool::False => {}
+                LitBool::Undef if coef > 0 => normalized.push((lit, coef)),
+                LitBool::Undef => {}
+            }
+        }
+        let degree = w.degree;
+        w.clear();
+        self.conflict = w;
+        if degree <= 0 {
+            return;
+        }
+        let sum: i128 = normalized.iter().map(|(_, coef)| coef).sum();
+        assert!(
+            sum <= i64::MAX as i128,
+            "coefficients of a constraint overflow"
+        );
+        // Coefficients larger than the degree are the same as the degree.
+        let normalized = normalized
+            .into_iter()
+            .map(|(lit, coef)| (lit, std::cmp::min(coef, degree) as i64))
+            .collect();
+        if sum < degree || self.attach(normalized, degree as i64).is_some() {
+            self.unsat = true;
+        }
+    }
+
+    /// Add a constraint `sum coef * lit <= degree`.
+    pub fn add_at_most(&mut self, terms: &[(Lit, i64)], degree: i64) {
+        let negated: Vec<(Lit, i64)> = terms.iter().map(|&(lit, coef)| (lit, -coef)).collect();
+        self.add_at_least(&negated, -degree);
+    }
+
+    /// Add a clause as a constraint `sum lit >= 1`.
+    pub fn add_clause(&mut self, clause: &[Lit]) {
+        let terms: Vec<(Lit, i64)> = clause.iter().map(|&lit| (lit, 1)).collect();
+        self.add_at_least(&terms, 1);
+    }
+
+    pub fn add_constraint(&mut self, constraint: &PbConstraint) {
+        let PbConstraint {
+            terms,
+            relation,
+            degree,
+        } = constraint;
+        match relation {
+            Relation::AtLeast => self.add_at_least(terms, *degree),
+            Relation::AtMost => self.add_at_most(terms, *degree),
+            Relation::Equal => {
+                self.add_at_least(terms, *degree);
+                self.add_at_most(terms, *degree);
+            }
+        }
+    }
+
+    /// The slack of the conflicting constraint under assignments up to `level`.
+    fn slack_at(&self, level: usize) -> i128 {
+        let w = &self.conflict;
+        w.vars
+            .iter()
+            .filter(|&&v| !(self.value(w.lits[v]) == LitBool::False && self.level[v] <= level))
+            .map(|&v| w.coefs[v])
+            .sum::<i128>()
+            - w.degree
+    }
+
+    /// The lowest level where the conflicting constraint propagates a literal if it isn't conflicting below the current level.
+    fn asserting_level(&self) -> Option<usize> {
+        let w = &self.conflict;
+        let mut levels: Vec<usize> = w
+            .vars
+            .iter()
+            .filter(|&&v| self.assigns[v] != LitBool::Undef)
+            .map(|&v| self.level[v])
+            .filter(|&level| level < self.decision_level())
+            .collect();
+        levels.push(0);
+        levels.sort_unstable();
+        levels.dedup();
+        levels.into_iter().find(|&level| {
+            let slack = self.slack_at(level);
+            w.vars.iter().any(|&v| {
+                let unassigned = self.assigns[v] == LitBool::Undef || self.level[v] > level;
+                unassigned && w.coefs[v] > slack
+            })
+        })
+    }
+
+    /// Learn a constraint from a conflicting constraint by cutting planes.
+    /// It returns the level to backjump or `None` if a conflict is at the root level.
+    fn analyze(&mut self, confl: usize) -> Option<usize> {
+        let mut w = std::mem::take(&mut self.conflict);
+        w.clear();
+        w.degree = self.constraints[confl].degree as i128;
+        for &(lit, coef) in self.constraints[confl].terms.iter() {
+            w.add(lit, coef as i128);
+            self.branching.bump(lit.var());
+        }
+        self.conflict = w;
+        loop {
+            let level = self.decision_level();
+            if level == 0 {
+                return None;
+            }
+            if self.conflict.degree > DEGREE_LIMIT {
+                let d = self.conflict.degree / DEGREE_LIMIT + 1;
+                let mut w = std::mem::take(&mut self.conflict);
+                w.divide(d, |lit| self.value(lit) == LitBool::False);
+                self.conflict = w;
+            }
+            // A learnt constraint can be conflicting at a lower level.
+            if self.slack_at(level - 1) < 0 {
+                self.backtrack(level - 1);
+                continue;
+            }
+            if let Some(level) = self.asserting_level() {
+                return Some(level);
+            }
+
+            // The last literal of the trail is propagated because a decision makes a constraint asserting.
+            let lit = *self.trail.last().unwrap();
+            let c = self.conflict.coef(!lit);
+            if c > 0 {
+                let r = self.reason[lit.var().0 as usize].expect("a decision isn't asserting");
+                let mut reason = std::mem::take(&mut self.reason_buf);
+                reason.clear();
+                reason.degree = self.constraints[r].degree as i128;
+                for &(l, coef) in self.constraints[r].terms.iter() {
+                    reason.add(l, coef as i128);
+                    self.branching.bump(l.var());
+                }
+                reason.divide(reason.coef(lit), |l| self.value(l) == LitBool::False);
+                let w = &mut self.conflict;
+                for &v in reason.vars.iter() {
+                    w.add(reason.lits[v], reason.coefs[v] * c);
+                }
+                w.degree += reason.degree * c;
+                w.saturate();
+                self.reason_buf = reason;
+            }
+            self.pop();
+            if self.trail_lim.last() == Some(&self.trail.len()) {
+                self.trail_lim.pop();
+            }
+        }
+    }
+
+    /// Solve constraints.
+    /// # Arguments
+    /// * `time_limit` - The time limitation for searching.
+    pub fn solve(&mut self, time_limit: Option<Duration>) -> Status {
+        if self.unsat {
+            return Status::Unsat;
+        }
+        self.backtrack(0);
+        if self.propagate().is_some() {
+            self.unsat = true;
+            return Status::Unsat;
+        }
+        let start = Instant::now();
+        let mut restart: Box<dyn RestartPolicy> = Config::default().restart.start();
+        // a learnt constraint can be conflicting after a backjump
+        let mut pending = None;
+        loop {
+            if let Some(confl) = pending.take().or_else(|| self.propagate()) {
+                let level = match self.analyze(confl) {
+                    Some(level) => level,
+                    None => {
+                        self.unsat = true;
+                        return Status::Unsat;
+                    }
+                };
+                let w = &self.conflict;
+                let mut levels: Vec<usize> = w
+                    .vars
+                    .iter()
+                    .filter(|&&v| self.value(w.lits[v]) == LitBool::False)
+                    .map(|&v| self.level[v])
+                    .collect();
+                levels.sort_unstable();
+                levels.dedup();
+                let terms: Vec<(Lit, i64)> = w
+                    .vars
+                    .iter()
+                    .map(|&v| (w.lits[v], w.coefs[v] as i64))
+                    .collect();
+                let degree = w.degree as i64;
+                self.branching.decay();
+                self.backtrack(level);
+                pending = self.attach(terms, degree);
+                if pending.is_some() {
+                    continue;
+                }
+                if restart.on_conflict(levels.len() as u32, self.trail.len()) {
+                    restart.on_restart();
+                    self.backtrack(0);
+                }
+                if let Some(time_limit) = time_limit {
+                    if start.elapsed() > time_limit {
+                        self.backtrack(0);
+                        return Status::Indeterminate;
+                    }
+                }
+            } else {
+                let v = loop {
+                    match self.branching.select() {
+                        Some(v) if self.assigns[v.0 as usize] != LitBool::Undef => continue,
+                        v => break v,
+                    }
+                };
+                match v {
+                    Some(v) => {
+                        self.trail_lim.push(self.trail.len());
+                        let lit = Lit::new(v.0, self.polarity[v.0 as usize]);
+                        self.enqueue(lit, None);
+                    }
+                    // all variables are assigned without conflicts, which means that all constraints are satisfied.
+                    None => return Status::Sat,
+                }
+            }
+        }
+    }
+
+    /// Find a model that minimizes `sum weight * lit` subject to constraints.
+    /// Each model adds a constraint that the objective is smaller than its value, so the constraints stay in a solver.
+    /// # Arguments
+    /// * `objective` - Pairs of a literal and its weight. A weight can be negative.
+    /// * `time_limit` - The time limitation for searching.
+    pub fn minimize(
+        &mut self,
+        objective: &[(Lit, i64)],
+        time_limit: Option<Duration>,
+    ) -> OptimizeResult {
+        objective
+            .iter()
+            .for_each(|&(lit, _)| self.reserve(lit.var()));
+        let start = Instant::now();
+        let mut best: Option<(i64, Vec<LitBool>)> = None;
+        loop {
+            let rest = time_limit.map(|limit| limit.saturating_sub(start.elapsed()));
+            let status = self.solve(rest);
+            match status {
+                Status::Sat => {
+                    let value = objective
+                        .iter()
+                        .filter(|(lit, _)| self.value(*lit) == LitBool::True)
+                        .map(|(_, weight)| weight)
+                        .sum::<i64>();
+                    best = Some((value, self.assigns.clone()));
+                    self.add_at_most(objective, value - 1);
+                }
+                Status::Unsat | Status::Indeterminate => {
+                    let status = match (status, best.is_some()) {
+                        (Status::Unsat, true) => MaxSatStatus::Optimum,
+                        (Status::Unsat, false) => MaxSatStatus::Unsat,
+                        (_, true) => MaxSatStatus::Sat,
+                        (_, false) => MaxSatStatus::Indeterminate,
+                    };
+                    let (value, model) = best.map_or((None, None), |(v, m)| (Some(v), Some(m)));
+                    return OptimizeResult {
+                        status,
+                        value,
+                        model,
+                    };
+                }
+            }
+        }
+    }
+}
+
+// OpbData is parsed from an OPB file
+#[derive(Debug, Default)]
+pub struct OpbData {
+    // the number of variable
+    pub var_num: usize,
+    // the objective to minimize
+    pub objective: Option<Vec<(Lit, i64)>>,
+    pub constraints: Vec<PbConstraint>,
+}
+
+/// Parse a linear OPB file of the pseudo-Boolean competition format.
+/// A variable is `x<number>` and a negated literal is `~x<number>`.
+///
+/// ```text
+/// * Here is a comment.
+/// min: +1 x1 +2 x2 ;
+/// +1 x1 +1 x2 +1 ~x3 >= 2 ;
+/// +3 x1 -2 x3 = 1 ;
+/// ```
+pub fn parse_opb<R: Read>(mut input: R) -> std::io::Result<OpbData> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let text: String = text
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('*'))
+        .collect::<Vec<&str>>()
+        .join("\n");
+    let mut opb = OpbData::default();
+    for statement in text.split(';') {
+        let statement = statement.trim();
+        if statement.is_empty() {
+            continue;
+        }
+        let invalid = || {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Invalid constraint: {}", statement),
+            )
+        };
+        let mut tokens: Vec<&str> = statement.split_whitespace().collect();
+        let objective = tokens[0] == "min:";
+        if objective {
+            tokens.remove(0);
+        }
+        let mut terms = vec![];
+        let mut i = 0;
+        while i + 1 < tokens.len() && !matches!(tokens[i], ">=" | "<=" | "=") {
+            let coef = tokens[i]
+                .trim_start_matches('+')
+                .parse::<i64>()
+                .map_err(|_| invalid())?;
+            let (negated, name) = match tokens[i + 1].strip_prefix('~') {
+                Some(name) => (true, name),
+                None => (false, tokens[i + 1]),
+            };
+            let index = name
+                .strip_prefix('x')
+                .and_then(|x| x.parse::<u32>().ok())
+                .filter(|&x| x > 0)
+                .ok_or_else(invalid)?;
+            opb.var_num = std::cmp::max(opb.var_num, index as usize);
+            terms.push((Lit::new(index - 1, !negated), coef));
+            i += 2;
+        }
+        if objective {
+            if i != tokens.len() {
+                return Err(invalid());
+            }
+            opb.objective = Some(terms);
+            continue;
+        }
+        let relation = match tokens.get(i).copied() {
+            Some(">=") => Relation::AtLeast,
+            Some("<=") => Relation::AtMost,
+            Some("=") => Relation::Equal,
+            _ => return Err(invalid()),
+        };
+        if i + 2 != tokens.len() {
+            return Err(invalid());
+        }
+        let degree = tokens[i + 1]
+            .trim_start_matches('+')
+            .parse::<i64>()
+            .map_err(|_| invalid())?;
+        opb.constraints.push(PbConstraint {
+            terms,
+            relation,
+            degree,
+        });
+    }
+    Ok(opb)
+}
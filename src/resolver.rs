@@ -0,0 +1,177 @@
+use crate::mus;
+use crate::solver::{Clause, Lit, LitBool, Solver, Status};
+use std::time::Duration;
+
+/// A constraint of a package/version selection problem.
+/// A version is an index of versions of a package.
+/// - `Require` one of `versions` of `package` must be selected.
+/// - `Depend` `version` of `package` requires one of `versions` of `dependency`.
+/// - `Conflict` two versions of packages can't be selected together.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
+pub enum Constraint {
+    Require {
+        package: usize,
+        versions: Vec<usize>,
+    },
+    Depend {
+        package: usize,
+        version: usize,
+        dependency: usize,
+        versions: Vec<usize>,
+    },
+    Conflict {
+        a: (usize, usize),
+        b: (usize, usize),
+    },
+}
+
+/// The result of `Resolver::resolve`.
+/// - `Selected` the selected version of each package. `None` is an optional package that isn't needed.
+/// - `Conflict` a minimal set of constraints that can't be satisfied together.
+/// - `Indeterminate` a resolver stopped searching.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum Resolution {
+    Selected(Vec<Option<usize>>),
+    Conflict(Vec<Constraint>),
+    Indeterminate,
+}
+
+/// A package/version resolver on top of the incremental solver.
+/// Exactly one version of each package is selected. An optional package has at most one.
+/// Each constraint is enabled by its own selector literal so that a conflict can be explained by constraints.
+#[derive(Debug, Default)]
+pub struct Resolver {
+    solver: Solver,
+    // a literal of each version of each package
+    versions: Vec<Vec<Lit>>,
+    // exactly-one clauses of packages
+    hards: Vec<Clause>,
+    constraints: Vec<Constraint>,
+    // clauses of each constraint
+    groups: Vec<Vec<Clause>>,
+    selectors: Vec<Lit>,
+}
+
+impl Resolver {
+    fn new_lit(&mut self) -> Lit {
+        Lit::new(self.solver.new_var().0, true)
+    }
+
+    /// Add a package that has `versions` versions and return its index.
+    /// One of them is always selected.
+    pub fn add_package(&mut self, versions: usize) -> usize {
+        let package = self.add_optional_package(versions);
+        let clause = self.versions[package].clone();
+        self.solver.add_clause(&clause);
+        self.hards.push(clause);
+        package
+    }
+
+    /// Add a package that is selected only if a constraint needs it and return its index.
+    pub fn add_optional_package(&mut self, versions: usize) -> usize {
+        let lits: Vec<Lit> = (0..versions).map(|_| self.new_lit()).collect();
+        for i in 0..lits.len() {
+            for j in i + 1..lits.len() {
+                let clause = vec![!lits[i], !lits[j]];
+                self.solver.add_clause(&clause);
+                self.hards.push(clause);
+            }
+        }
+        self.versions.push(lits);
+        self.versions.len() - 1
+    }
+
+    /// One of `versions` of `package` must be selected.
+    pub fn require(&mut self, package: usize, versions: &[usize]) {
+        let clause = versions
+            .iter()
+            .map(|&v| self.versions[package][v])
+            .collect();
+        self.add_constraint(
+            Constraint::Require {
+                package,
+                versions: versions.to_vec(),
+            },
+            vec![clause],
+        );
+    }
+
+    /// `version` of `package` requires one of `versions` of `dependency`.
+    pub fn depend(
+        &mut self,
+        package: usize,
+        version: usize,
+        dependency: usize,
+        versions: &[usize],
+    ) {
+        let mut clause: Clause = versions
+            .iter()
+            .map(|&v| self.versions[dependency][v])
+            .collect();
+        clause.push(!self.versions[package][version]);
+        self.add_constraint(
+            Constraint::Depend {
+                package,
+                version,
+                dependency,
+                versions: versions.to_vec(),
+            },
+            vec![clause],
+        );
+    }
+
+    /// `a` and `b` can't be selected together. They are pairs of a package and its version.
+    pub fn conflict(&mut self, a: (usize, usize), b: (usize, usize)) {
+        let clause = vec![!self.versions[a.0][a.1], !self.versions[b.0][b.1]];
+        self.add_constraint(Constraint::Conflict { a, b }, vec![clause]);
+    }
+
+    fn add_constraint(&mut self, constraint: Constraint, clauses: Vec<Clause>) {
+        let s = self.new_lit();
+        for clause in clauses.iter() {
+            let mut clause = clause.clone();
+            clause.push(!s);
+            self.solver.add_clause(&clause);
+        }
+        self.constraints.push(constraint);
+        self.groups.push(clauses);
+        self.selectors.push(s);
+    }
+
+    /// Select versions that satisfy all constraints or explain why they are unsatisfiable.
+    /// # Arguments
+    /// * `time_limit` - The time limitation for searching.
+    pub fn resolve(&mut self, time_limit: Option<Duration>) -> Resolution {
+        match self
+            .solver
+            .solve_with_assumptions(&self.selectors, time_limit)
+        {
+            Status::Sat => Resolution::Selected(
+                self.versions
+                    .iter()
+                    .map(|lits| {
+                        lits.iter().position(|lit| {
+                            self.solver.assigns[lit.var().0 as usize] == LitBool::True
+                        })
+                    })
+                    .collect(),
+            ),
+            Status::Unsat => {
+                // A MUS is in the constraints of the core.
+                let core: Vec<usize> = self
+                    .solver
+                    .unsat_core()
+                    .iter()
+                    .filter_map(|lit| self.selectors.iter().position(|s| s == lit))
+                    .collect();
+                match mus::find_mus_in(&self.hards, &self.groups, &core, time_limit) {
+                    Some(mus) => Resolution::Conflict(
+                        mus.iter().map(|&i| self.constraints[i].clone()).collect(),
+                    ),
+                    None => Resolution::Indeterminate,
+                }
+            }
+            Status::Indeterminate => Resolution::Indeterminate,
+        }
+    }
+}
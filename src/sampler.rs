@@ -0,0 +1,202 @@
+use crate::solver::{Clause, Lit, LitBool, Solver, SolverPool, Status, Var};
+
+#[derive(Debug)]
+enum State {
+    Start,
+    // all projected models of a problem that has fewer than `hi_thresh` of them
+    Exact(Vec<Vec<LitBool>>),
+    // the estimated number of XOR constraints that makes a cell small enough
+    Hashing(usize),
+}
+
+/// A near-uniform sampler of models(UniGen).
+///
+/// Models are hashed into cells by random XOR constraints over a sampling set.
+/// A cell that has a number of models between two thresholds is enumerated and one of its models is drawn.
+/// For a tolerance `epsilon`(> 1.71), the probability of each model `y` that is projected on the sampling set satisfies
+/// `1 / ((1 + epsilon) * n) <= Pr[y] <= (1 + epsilon) / n` where `n` is the number of projected models.
+/// A problem that has few models is sampled exactly uniformly.
+#[derive(Debug)]
+pub struct Sampler {
+    var_num: usize,
+    clauses: Vec<Clause>,
+    // variables that samples are uniform over
+    vars: Vec<Var>,
+    lo_thresh: usize,
+    hi_thresh: usize,
+    pivot: f64,
+    state: State,
+    rng: u64,
+    pool: SolverPool,
+}
+
+impl Sampler {
+    /// Create a new `Sampler`
+    /// # Arguments
+    /// * `var_num` - The number of variable. Samples have `var_num` assignments.
+    /// * `clauses` - All clauses of a problem
+    /// * `vars` - A sampling set. Models that differ only outside of it are the same model.
+    /// * `epsilon` - The tolerance of uniformity. It must be larger than 1.71.
+    /// * `seed` - The seed of random XOR constraints
+    pub fn new(
+        var_num: usize,
+        clauses: &[Clause],
+        vars: &[Var],
+        epsilon: f64,
+        seed: u64,
+    ) -> Sampler {
+        assert!(epsilon > 1.71, "epsilon must be larger than 1.71");
+        // Find kappa in (0, 1) such that epsilon = (1 + kappa) * (2.23 + 0.48 / (1 - kappa)^2) - 1 by bisection.
+        let tolerance = |kappa: f64| (1.0 + kappa) * (2.23 + 0.48 / (1.0 - kappa).powi(2)) - 1.0;
+        let (mut low, mut high) = (0.0, 1.0);
+        for _ in 0..100 {
+            let mid = (low + high) / 2.0;
+            if tolerance(mid) < epsilon {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        let kappa = low;
+        let pivot = (3.0 * 1f64.exp().sqrt() * (1.0 + 1.0 / kappa).powi(2)).ceil();
+        let var_num = clauses
+            .iter()
+            .flat_map(|clause| clause.iter())
+            .fold(var_num, |n, lit| std::cmp::max(n, lit.var().0 as usize + 1));
+        Sampler {
+            var_num,
+            clauses: clauses.to_vec(),
+            vars: vars.to_vec(),
+            lo_thresh: (pivot / (2f64.sqrt() * (1.0 + kappa))) as usize,
+            hi_thresh: (1.0 + 2f64.sqrt() * (1.0 + kappa) * pivot) as usize,
+            pivot,
+            state: State::Start,
+            rng: seed,
+            pool: SolverPool::default(),
+        }
+    }
+
+    // SplitMix64
+    fn next_u64(&mut self) -> u64 {
+        self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.rng;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Enumerate at most `limit` models that are distinct on the sampling set under `xors` random XOR constraints.
+    fn cell(&mut self, xors: usize, limit: usize) -> Vec<Vec<LitBool>> {
+        let mut solver = self.pool.new_solver(self.var_num, &self.clauses);
+        for _ in 0..xors {
+            let mut lits = vec![];
+            for i in 0..self.vars.len() {
+                if self.next_u64() & 1 == 1 {
+                    lits.push(Lit::new(self.vars[i].0, true));
+                }
+            }
+            let parity = self.next_u64() & 1 == 1;
+            add_xor(&mut solver, &lits, parity);
+        }
+        let mut models = vec![];
+        while models.len() < limit && solver.solve(None) == Status::Sat {
+            let model: Vec<LitBool> = solver.assigns[..self.var_num].to_vec();
+            // Block a found model on the sampling set.
+            let block: Clause = self
+                .vars
+                .iter()
+                .map(|v| Lit::new(v.0, model[v.0 as usize] != LitBool::True))
+                .collect();
+            models.push(model);
+            solver.add_clause(&block);
+        }
+        self.pool.recycle(solver);
+        models
+    }
+
+    /// Estimate the number of XOR constraints by the smallest number that makes a cell smaller than `hi_thresh`.
+    fn estimate(&mut self) -> usize {
+        let mut xors = 1;
+        let models = loop {
+            let models = self.cell(xors, self.hi_thresh).len();
+            if models < self.hi_thresh || xors >= self.vars.len() {
+                break models;
+            }
+            xors += 1;
+        };
+        // The number of models is estimated as `models * 2^xors`.
+        let count = std::cmp::max(models, 1) as f64 * 2f64.powi(xors as i32);
+        std::cmp::max(
+            (count.log2() + 1.8f64.log2() - self.pivot.log2()).ceil() as i64,
+            1,
+        ) as usize
+    }
+
+    /// Draw a sample. It returns `None` if a try fails or a problem is unsatisfiable.
+    fn sample(&mut self) -> Option<Vec<LitBool>> {
+        if let State::Start = self.state {
+            let models = self.cell(0, self.hi_thresh);
+            self.state = if models.len() < self.hi_thresh {
+                State::Exact(models)
+            } else {
+                State::Hashing(self.estimate())
+            };
+        }
+        let r = self.next_u64();
+        let q = match &self.state {
+            State::Exact(models) if models.is_empty() => return None,
+            State::Exact(models) => {
+                return Some(models[(r % models.len() as u64) as usize].clone())
+            }
+            State::Hashing(q) => *q,
+            State::Start => unreachable!(),
+        };
+        for xors in q.saturating_sub(3).max(1)..=q {
+            let mut models = self.cell(xors, self.hi_thresh + 1);
+            if self.lo_thresh <= models.len() && models.len() <= self.hi_thresh {
+                let i = (self.next_u64() % models.len() as u64) as usize;
+                return Some(models.swap_remove(i));
+            }
+        }
+        None
+    }
+}
+
+impl Iterator for Sampler {
+    type Item = Vec<LitBool>;
+    /// A sample of a model. It never ends unless a problem is unsatisfiable.
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(model) = self.sample() {
+                return Some(model);
+            }
+            // Failed tries are repeated with new XOR constraints.
+            if let State::Exact(_) = self.state {
+                return None;
+            }
+        }
+    }
+}
+
+/// Add `lits[0] ^ lits[1] ^ ... = parity` by a chain of binary XORs on new variables.
+fn add_xor(solver: &mut Solver, lits: &[Lit], parity: bool) {
+    let mut acc = match lits.first() {
+        Some(&lit) => lit,
+        None => {
+            if parity {
+                solver.add_clause(&[]);
+            }
+            return;
+        }
+    };
+    for &b in lits.iter().skip(1) {
+        let x = Lit::new(solver.assigns.len() as u32, true);
+        solver.new_var();
+        solver.add_clause(&[!x, acc, b]);
+        solver.add_clause(&[!x, !acc, !b]);
+        solver.add_clause(&[x, !acc, b]);
+        solver.add_clause(&[x, acc, !b]);
+        acc = x;
+    }
+    solver.add_clause(&[if parity { acc } else { !acc }]);
+}
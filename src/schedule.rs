@@ -0,0 +1,49 @@
+use crate::cardinality;
+use crate::solver::{Lit, LitBool, Solver};
+
+/// Create a tasks×slots grid of variables. `grid[t][s]` is true if a task `t` is assigned to a slot `s`.
+pub fn grid(solver: &mut Solver, tasks: usize, slots: usize) -> Vec<Vec<Lit>> {
+    (0..tasks)
+        .map(|_| {
+            (0..slots)
+                .map(|_| Lit::new(solver.new_var().0, true))
+                .collect()
+        })
+        .collect()
+}
+
+/// Each task is assigned to exactly one slot.
+pub fn one_slot_per_task(solver: &mut Solver, grid: &[Vec<Lit>]) {
+    grid.iter()
+        .for_each(|slots| cardinality::exactly_one(solver, slots));
+}
+
+/// Conflicting tasks `a` and `b` don't share a slot.
+pub fn separate(solver: &mut Solver, grid: &[Vec<Lit>], a: usize, b: usize) {
+    for (&x, &y) in grid[a].iter().zip(grid[b].iter()) {
+        solver.add_clause(&[!x, !y]);
+    }
+}
+
+/// At most `capacity` tasks are assigned to a slot `slot`.
+pub fn capacity(solver: &mut Solver, grid: &[Vec<Lit>], slot: usize, capacity: usize) {
+    let lits: Vec<Lit> = grid.iter().map(|slots| slots[slot]).collect();
+    cardinality::at_most_k(solver, &lits, capacity);
+}
+
+/// A task `task` is never assigned to a slot `slot`.
+pub fn forbid(solver: &mut Solver, grid: &[Vec<Lit>], task: usize, slot: usize) {
+    solver.add_clause(&[!grid[task][slot]]);
+}
+
+/// Decode a model of the last `solve` into the slot of each task.
+/// `None` is a task that isn't assigned to any slot.
+pub fn decode(solver: &Solver, grid: &[Vec<Lit>]) -> Vec<Option<usize>> {
+    grid.iter()
+        .map(|slots| {
+            slots
+                .iter()
+                .position(|lit| solver.assigns[lit.var().0 as usize] == LitBool::True)
+        })
+        .collect()
+}
@@ -0,0 +1,203 @@
+use crate::solver::{Lit, LitBool, Solver, Status};
+use crate::util;
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, Write},
+    time::Duration,
+};
+
+/// A server of named incremental solver sessions over a line protocol.
+///
+/// Each line of an input is a command and each command is answered by exactly one line.
+/// Literals are DIMACS integers and a trailing `0` of literals is optional.
+/// A failed command is answered by `error <message>` and doesn't stop a server.
+///
+/// ```text
+/// new <session>                       create an empty session            -> ok
+/// load <session> <file>               create a session from a DIMACS file or a snapshot(`.snap`) -> ok
+/// add <session> <lits> 0              add a clause                       -> ok
+/// solve <session> [timeout=<secs>] [<lits> 0]  solve under assumptions  -> sat | unsat | unknown
+/// model <session>                     the model of the last `sat`        -> v <lits> 0
+/// failed <session>                    refuted assumptions of the last `unsat` -> f <lits> 0
+/// drop <session>                      remove a session                   -> ok
+/// list                                names of sessions                  -> sessions <names>
+/// quit                                close an input                     -> bye
+/// shutdown                            close an input and stop a server   -> bye
+/// ```
+#[derive(Debug, Default)]
+pub struct Server {
+    sessions: BTreeMap<String, Session>,
+}
+
+#[derive(Debug, Default)]
+struct Session {
+    solver: Solver,
+    // the result of the last `solve`
+    status: Option<Status>,
+}
+
+impl Server {
+    /// Answer all commands of `input` and write responses to `writer`. Each response is flushed.
+    /// Sessions are kept for the next input. It returns `false` if an input shuts down a server.
+    pub fn run<R: BufRead, W: Write>(&mut self, input: R, mut writer: W) -> std::io::Result<bool> {
+        for line in input.lines() {
+            let line = line?;
+            let response = match self.execute(&line) {
+                Ok(Some(response)) => response,
+                Ok(None) => continue,
+                Err(msg) => format!("error {}", msg),
+            };
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+            if response == "bye" {
+                return Ok(line.split_whitespace().next() != Some("shutdown"));
+            }
+        }
+        Ok(true)
+    }
+
+    /// Execute a command line and return its response. Empty lines and comments(`c ...`) have no responses.
+    pub fn execute(&mut self, line: &str) -> Result<Option<String>, String> {
+        let mut args = line.split_whitespace();
+        let command = match args.next() {
+            Some("c") | None => return Ok(None),
+            Some(command) => command,
+        };
+        let args: Vec<&str> = args.collect();
+        let response = match (command, args.as_slice()) {
+            ("new", [name]) => {
+                self.create(name, Solver::default())?;
+                "ok".to_string()
+            }
+            ("load", [name, file]) => {
+                if self.sessions.contains_key(*name) {
+                    return Err(format!("Session already exists: {}", name));
+                }
+                let input = std::fs::File::open(file).map_err(|e| e.to_string())?;
+                let solver = if file.ends_with(".snap") {
+                    Solver::read_snapshot(std::io::BufReader::new(input))
+                } else {
+                    util::parse_cnf(input).map(|cnf| {
+                        let mut solver = Solver::default();
+                        solver.add_clauses(&cnf.clauses);
+                        solver
+                    })
+                };
+                self.create(name, solver.map_err(|e| e.to_string())?)?;
+                "ok".to_string()
+            }
+            ("add", [name, lits @ ..]) => {
+                let clause = parse_lits(lits)?;
+                let session = self.session(name)?;
+                session.solver.add_clause(&clause);
+                session.status = None;
+                "ok".to_string()
+            }
+            ("solve", [name, args @ ..]) => {
+                let (time_limit, lits) = match args.first().and_then(|a| a.strip_prefix("timeout="))
+                {
+                    Some(secs) => {
+                        let secs: f64 = secs
+                            .parse()
+                            .ok()
+                            .filter(|secs: &f64| secs.is_finite() && *secs >= 0.0)
+                            .ok_or_else(|| format!("Invalid timeout: {}", secs))?;
+                        (Some(Duration::from_secs_f64(secs)), &args[1..])
+                    }
+                    None => (None, args),
+                };
+                let assumptions = parse_lits(lits)?;
+                let session = self.session(name)?;
+                let status = session
+                    .solver
+                    .solve_with_assumptions(&assumptions, time_limit);
+                session.status = Some(status);
+                match status {
+                    Status::Sat => "sat",
+                    Status::Unsat => "unsat",
+                    Status::Indeterminate => "unknown",
+                }
+                .to_string()
+            }
+            ("model", [name]) => {
+                let session = self.session(name)?;
+                if session.status != Some(Status::Sat) {
+                    return Err("A model is not available".to_string());
+                }
+                let lits = session
+                    .solver
+                    .assigns
+                    .iter()
+                    .enumerate()
+                    .map(|(v, &value)| Lit::new(v as u32, value == LitBool::True));
+                format!("v{}", format_lits(lits))
+            }
+            ("failed", [name]) => {
+                let session = self.session(name)?;
+                if session.status != Some(Status::Unsat) {
+                    return Err("Failed assumptions are not available".to_string());
+                }
+                let lits = session.solver.unsat_core().iter().copied();
+                format!("f{}", format_lits(lits))
+            }
+            ("drop", [name]) => {
+                self.sessions
+                    .remove(*name)
+                    .ok_or_else(|| format!("No session: {}", name))?;
+                "ok".to_string()
+            }
+            ("list", []) => {
+                let mut response = "sessions".to_string();
+                self.sessions.keys().for_each(|name| {
+                    response.push(' ');
+                    response.push_str(name);
+                });
+                response
+            }
+            ("quit", []) | ("shutdown", []) => "bye".to_string(),
+            _ => return Err(format!("Invalid command: {}", line.trim())),
+        };
+        Ok(Some(response))
+    }
+
+    fn create(&mut self, name: &str, solver: Solver) -> Result<(), String> {
+        if self.sessions.contains_key(name) {
+            return Err(format!("Session already exists: {}", name));
+        }
+        let session = Session {
+            solver,
+            status: None,
+        };
+        self.sessions.insert(name.to_string(), session);
+        Ok(())
+    }
+
+    fn session(&mut self, name: &str) -> Result<&mut Session, String> {
+        self.sessions
+            .get_mut(name)
+            .ok_or_else(|| format!("No session: {}", name))
+    }
+}
+
+/// Parse DIMACS literals. A trailing `0` is ignored.
+fn parse_lits(args: &[&str]) -> Result<Vec<Lit>, String> {
+    let args = match args.split_last() {
+        Some((&"0", rest)) => rest,
+        _ => args,
+    };
+    args.iter()
+        .map(|arg| match arg.parse::<i32>() {
+            Ok(x) if x != 0 && x != i32::MIN => Ok(Lit::from(x)),
+            _ => Err(format!("Invalid literal: {}", arg)),
+        })
+        .collect()
+}
+
+fn format_lits<I: Iterator<Item = Lit>>(lits: I) -> String {
+    let mut line = String::new();
+    for lit in lits {
+        line.push_str(&format!(" {}", lit));
+    }
+    line.push_str(" 0");
+    line
+}
@@ -0,0 +1,337 @@
+use crate::formula::{self, Formula};
+use crate::solver::{Lit, LitBool, Solver, Status};
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+/// An S-expression of SMT-LIB2.
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+fn invalid(msg: String) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
+/// Split an input into S-expressions.
+/// Comments are skipped. Quoted symbols `|...|` are unquoted.
+fn parse_sexprs(input: &str) -> std::io::Result<Vec<SExpr>> {
+    let mut stack: Vec<Vec<SExpr>> = vec![vec![]];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                // comment
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '(' => stack.push(vec![]),
+            ')' => {
+                let list = stack.pop().unwrap();
+                match stack.last_mut() {
+                    Some(top) => top.push(SExpr::List(list)),
+                    None => return Err(invalid("Unexpected )".to_string())),
+                }
+            }
+            '|' => {
+                let mut symbol = String::new();
+                loop {
+                    match chars.next() {
+                        Some('|') => break,
+                        Some(c) => symbol.push(c),
+                        None => return Err(invalid("Unterminated symbol".to_string())),
+                    }
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(symbol));
+            }
+            '"' => {
+                // `""` is an escaped double quote in a string literal.
+                let mut string = String::from('"');
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.next_if_eq(&'"').is_some() => string.push('"'),
+                        Some('"') => break,
+                        Some(c) => string.push(c),
+                        None => return Err(invalid("Unterminated string".to_string())),
+                    }
+                }
+                string.push('"');
+                stack.last_mut().unwrap().push(SExpr::Atom(string));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut atom = String::from(c);
+                while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && !"();|\"".contains(c))
+                {
+                    atom.push(c);
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(atom));
+            }
+        }
+    }
+    if stack.len() != 1 {
+        return Err(invalid("Missing )".to_string()));
+    }
+    Ok(stack.pop().unwrap())
+}
+
+/// Quote a symbol if it isn't a simple symbol.
+fn quote(symbol: &str) -> String {
+    if !symbol.is_empty()
+        && !symbol.starts_with(|c: char| c.is_ascii_digit())
+        && symbol
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "~!@$%^&*_-+=<>.?/".contains(c))
+    {
+        symbol.to_string()
+    } else {
+        format!("|{}|", symbol)
+    }
+}
+
+/// An interpreter of the Boolean fragment of SMT-LIB2 scripts.
+///
+/// Supported commands are `declare-const`, `declare-fun` and `define-fun` of `Bool` constants,
+/// `assert`, `check-sat`, `get-model`, `echo` and `exit`.
+/// `set-logic`, `set-info` and `set-option` are accepted and ignored.
+///
+/// Terms are built from `true`, `false`, `not`, `and`, `or`, `=>`, `xor`, `=`, `distinct`, `ite`, `let` and `!` annotations.
+/// They are compiled into clauses by the Tseitin encoding of `formula`.
+///
+/// ```text
+/// (set-logic QF_UF)
+/// (declare-const p Bool)
+/// (declare-const q Bool)
+/// (assert (and (or p q) (not (and p q))))
+/// (assert (=> p q))
+/// (check-sat)
+/// (get-model)
+/// ```
+#[derive(Debug, Default)]
+pub struct SmtLib {
+    solver: Solver,
+    // Terms that are asserted or shared share their subterms.
+    encoder: formula::Encoder,
+    // declared constants in declaration order
+    names: Vec<String>,
+    consts: HashMap<String, Formula>,
+    // the result of the last `check-sat`
+    status: Option<Status>,
+}
+
+impl SmtLib {
+    /// Execute all commands of a script and write their responses to `writer`.
+    pub fn run<R: Read, W: Write>(&mut self, mut input: R, mut writer: W) -> std::io::Result<()> {
+        let mut script = String::new();
+        input.read_to_string(&mut script)?;
+        for command in parse_sexprs(&script)? {
+            if !self.execute(&command, &mut writer)? {
+                break;
+            }
+        }
+        writer.flush()
+    }
+
+    /// The value of a declared constant in a model of the last `check-sat`.
+    pub fn value(&self, name: &str) -> Option<bool> {
+        if self.status != Some(Status::Sat) {
+            return None;
+        }
+        let f = self.consts.get(name)?;
+        Some(f.eval(&|lit| self.lit_value(lit)))
+    }
+
+    fn lit_value(&self, lit: Lit) -> bool {
+        // variables that a solver doesn't know are unconstrained.
+        match self.solver.assigns.get(lit.var().0 as usize) {
+            Some(LitBool::True) => lit.pos(),
+            Some(LitBool::False) => lit.neg(),
+            _ => lit.neg(),
+        }
+    }
+
+    /// Execute a command. Returns `false` if a script exits.
+    fn execute<W: Write>(&mut self, command: &SExpr, writer: &mut W) -> std::io::Result<bool> {
+        let args = match command {
+            SExpr::List(args) if !args.is_empty() => args,
+            _ => return Err(invalid(format!("Invalid command: {:?}", command))),
+        };
+        let name = match &args[0] {
+            SExpr::Atom(name) => name.as_str(),
+            _ => return Err(invalid(format!("Invalid command: {:?}", command))),
+        };
+        match (name, &args[1..]) {
+            ("set-logic", _) | ("set-info", _) | ("set-option", _) => {}
+            ("declare-const", [SExpr::Atom(symbol), sort]) => {
+                self.declare(symbol, sort)?;
+            }
+            ("declare-fun", [SExpr::Atom(symbol), SExpr::List(params), sort])
+                if params.is_empty() =>
+            {
+                self.declare(symbol, sort)?;
+            }
+            ("define-fun", [SExpr::Atom(symbol), SExpr::List(params), sort, body])
+                if params.is_empty() =>
+            {
+                check_bool(sort)?;
+                let f = self.term(body, &mut vec![])?;
+                let f = self.share(f);
+                self.consts.insert(symbol.clone(), f);
+            }
+            ("assert", [term]) => {
+                let f = self.term(term, &mut vec![])?;
+                self.encoder.add_formula(&mut self.solver, &f);
+                self.status = None;
+            }
+            ("check-sat", []) => {
+                let status = self.solver.solve(None);
+                self.status = Some(status);
+                match status {
+                    Status::Sat => writeln!(writer, "sat")?,
+                    Status::Unsat => writeln!(writer, "unsat")?,
+                    Status::Indeterminate => writeln!(writer, "unknown")?,
+                }
+            }
+            ("get-model", []) => {
+                if self.status != Some(Status::Sat) {
+                    return Err(invalid("A model is not available".to_string()));
+                }
+                writeln!(writer, "(")?;
+                for name in self.names.iter() {
+                    let value = self.value(name).unwrap();
+                    writeln!(writer, "  (define-fun {} () Bool {})", quote(name), value)?;
+                }
+                writeln!(writer, ")")?;
+            }
+            ("echo", [SExpr::Atom(string)]) => writeln!(writer, "{}", string)?,
+            ("exit", []) => return Ok(false),
+            _ => return Err(invalid(format!("Unsupported command: {:?}", command))),
+        }
+        Ok(true)
+    }
+
+    fn declare(&mut self, symbol: &str, sort: &SExpr) -> std::io::Result<()> {
+        check_bool(sort)?;
+        if self.consts.contains_key(symbol) {
+            return Err(invalid(format!("Already declared: {}", symbol)));
+        }
+        let v = self.solver.new_var();
+        self.names.push(symbol.to_string());
+        self.consts
+            .insert(symbol.to_string(), Formula::Lit(Lit::new(v.0, true)));
+        Ok(())
+    }
+
+    /// Replace a compound formula with a literal that is equivalent to it.
+    /// `let` and `define-fun` terms are shared by their literals instead of copying formulas.
+    fn share(&mut self, f: Formula) -> Formula {
+        match f {
+            Formula::Const(_) | Formula::Lit(_) => f,
+            _ => Formula::Lit(self.encoder.encode(&mut self.solver, &f)),
+        }
+    }
+
+    /// Convert a term into a formula. `scope` is a stack of `let` bindings.
+    fn term(
+        &mut self,
+        term: &SExpr,
+        scope: &mut Vec<(String, Formula)>,
+    ) -> std::io::Result<Formula> {
+        let args = match term {
+            SExpr::Atom(symbol) => {
+                return match symbol.as_str() {
+                    "true" => Ok(Formula::Const(true)),
+                    "false" => Ok(Formula::Const(false)),
+                    _ => {
+                        let bound = scope.iter().rev().find(|(name, _)| name == symbol);
+                        bound
+                            .map(|(_, f)| f)
+                            .or_else(|| self.consts.get(symbol))
+                            .cloned()
+                            .ok_or_else(|| invalid(format!("Unknown symbol: {}", symbol)))
+                    }
+                };
+            }
+            SExpr::List(args) if !args.is_empty() => args,
+            _ => return Err(invalid("Invalid term: ()".to_string())),
+        };
+        let op = match &args[0] {
+            SExpr::Atom(op) => op.as_str(),
+            _ => return Err(invalid(format!("Invalid term: {:?}", term))),
+        };
+        match (op, &args[1..]) {
+            ("let", [SExpr::List(bindings), body]) => {
+                // all bindings of a `let` are evaluated in the outer scope.
+                let mut bound = vec![];
+                for binding in bindings.iter() {
+                    match binding {
+                        SExpr::List(binding) => match binding.as_slice() {
+                            [SExpr::Atom(name), t] => {
+                                let f = self.term(t, scope)?;
+                                bound.push((name.clone(), self.share(f)));
+                            }
+                            _ => return Err(invalid(format!("Invalid binding: {:?}", binding))),
+                        },
+                        _ => return Err(invalid(format!("Invalid binding: {:?}", binding))),
+                    }
+                }
+                let len = scope.len();
+                scope.extend(bound);
+                let f = self.term(body, scope);
+                scope.truncate(len);
+                return f;
+            }
+            ("!", [t, ..]) => return self.term(t, scope),
+            _ => {}
+        }
+
+        let mut fs = vec![];
+        for arg in args[1..].iter() {
+            fs.push(self.term(arg, scope)?);
+        }
+        let f = match (op, fs.len()) {
+            ("not", 1) => !fs.pop().unwrap(),
+            ("and", _) => Formula::And(fs),
+            ("or", _) => Formula::Or(fs),
+            // right associative
+            ("=>", n) if n >= 2 => {
+                let last = fs.pop().unwrap();
+                fs.into_iter()
+                    .rev()
+                    .fold(last, |b, a| Formula::implies(a, b))
+            }
+            // left associative
+            ("xor", n) if n >= 2 => {
+                let mut fs = fs.into_iter();
+                let first = fs.next().unwrap();
+                fs.fold(first, Formula::xor)
+            }
+            // chainable
+            ("=", n) if n >= 2 => Formula::And(
+                fs.windows(2)
+                    .map(|w| Formula::iff(w[0].clone(), w[1].clone()))
+                    .collect(),
+            ),
+            // pairwise distinct. More than two Booleans can't be distinct.
+            ("distinct", 2) => Formula::xor(fs[0].clone(), fs[1].clone()),
+            ("distinct", n) if n > 2 => Formula::Const(false),
+            ("ite", 3) => {
+                let e = fs.pop().unwrap();
+                let t = fs.pop().unwrap();
+                let c = fs.pop().unwrap();
+                Formula::ite(c, t, e)
+            }
+            _ => return Err(invalid(format!("Unsupported term: {:?}", term))),
+        };
+        Ok(f)
+    }
+}
+
+fn check_bool(sort: &SExpr) -> std::io::Result<()> {
+    match sort {
+        SExpr::Atom(sort) if sort == "Bool" => Ok(()),
+        _ => Err(invalid(format!("Unsupported sort: {:?}", sort))),
+    }
+}
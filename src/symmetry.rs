@@ -0,0 +1,236 @@
+use crate::solver::{Lit, Solver};
+use std::collections::{BTreeMap, HashSet};
+
+fn fresh_lit(solver: &mut Solver) -> Lit {
+    Lit::new(solver.new_var().0, true)
+}
+
+// the vertex of a literal
+fn index(lit: Lit) -> usize {
+    2 * lit.var().0 as usize + lit.neg() as usize
+}
+
+/// The graph of a formula. Literals are vertices by `index` and clauses follow them.
+/// A literal is adjacent to its negation and its clauses, so an automorphism maps a literal and its negation together.
+struct Graph {
+    adj: Vec<Vec<usize>>,
+    literals: usize,
+}
+
+/// Split colors of vertices by the colors of their neighbors until they don't change.
+/// `a` and `b` are refined together and their new colors are shared, so the same colors mean the same structure.
+/// `false` means that a color has different numbers of vertices in `a` and `b`.
+fn refine(graph: &Graph, a: &mut [usize], b: &mut [usize]) -> bool {
+    let mut colors = a.iter().collect::<HashSet<_>>().len();
+    loop {
+        let signature = |coloring: &[usize], v: usize| {
+            let mut neighbors: Vec<usize> = graph.adj[v].iter().map(|&u| coloring[u]).collect();
+            neighbors.sort_unstable();
+            (coloring[v], neighbors)
+        };
+        let sa: Vec<_> = (0..a.len()).map(|v| signature(a, v)).collect();
+        let sb: Vec<_> = (0..b.len()).map(|v| signature(b, v)).collect();
+        let mut ids = BTreeMap::new();
+        for s in sa.iter().chain(sb.iter()) {
+            ids.entry(s).or_insert(0usize);
+        }
+        for (i, id) in ids.values_mut().enumerate() {
+            *id = i;
+        }
+        let mut counts = vec![0i64; ids.len()];
+        for v in 0..a.len() {
+            a[v] = ids[&sa[v]];
+            b[v] = ids[&sb[v]];
+            counts[a[v]] += 1;
+            counts[b[v]] -= 1;
+        }
+        if counts.iter().any(|&c| c != 0) {
+            return false;
+        }
+        // Each color is in both of them.
+        if ids.len() == colors {
+            return true;
+        }
+        colors = ids.len();
+    }
+}
+
+/// Search a map from `a` to `b` that preserves colors by individualizing a vertex of the first cell that isn't a singleton.
+/// The same vertex is tried first, so a map fixes as many vertices as it can.
+/// It returns the image of each vertex and spends one of `budget` for each node of the search.
+fn search(
+    graph: &Graph,
+    mut a: Vec<usize>,
+    mut b: Vec<usize>,
+    budget: &mut usize,
+) -> Option<Vec<usize>> {
+    if *budget == 0 {
+        return None;
+    }
+    *budget -= 1;
+    if !refine(graph, &mut a, &mut b) {
+        return None;
+    }
+    let mut cells: BTreeMap<usize, (Vec<usize>, Vec<usize>)> = BTreeMap::new();
+    for v in 0..a.len() {
+        cells.entry(a[v]).or_default().0.push(v);
+        cells.entry(b[v]).or_default().1.push(v);
+    }
+    let cell = cells.values().find(|(xs, _)| xs.len() > 1);
+    let (xs, ys) = match cell {
+        Some(cell) => cell,
+        None => {
+            let mut image = vec![0; a.len()];
+            for (xs, ys) in cells.values() {
+                image[xs[0]] = ys[0];
+            }
+            return Some(image);
+        }
+    };
+    let u = xs[0];
+    let mut candidates = ys.clone();
+    if let Some(i) = candidates.iter().position(|&v| v == u) {
+        candidates.swap(0, i);
+    }
+    let fresh = a.len();
+    for v in candidates {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        a[u] = fresh;
+        b[v] = fresh;
+        if let Some(image) = search(graph, a, b, budget) {
+            return Some(image);
+        }
+    }
+    None
+}
+
+fn find(parent: &mut [usize], x: usize) -> usize {
+    if parent[x] != x {
+        let root = find(parent, parent[x]);
+        parent[x] = root;
+    }
+    parent[x]
+}
+
+/// Find symmetries of `clauses`: permutations of literals that map the clauses onto themselves.
+/// `perm[v]` is the image of the positive literal of a variable `v` and the image of the negative literal is its negation.
+/// A symmetry may map a literal to a negative literal.
+///
+/// The graph of clauses is colored by refinement. Then a literal is mapped to each literal of its color
+/// that isn't in its orbit of the found symmetries yet, and the rest of a map is searched by individualization.
+/// The symmetries generate a part of the automorphism group. At most `budget` nodes are searched in total.
+pub fn find_symmetries(clauses: &[Vec<Lit>], budget: usize) -> Vec<Vec<Lit>> {
+    let var_num = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.var().0 as usize + 1)
+        .max()
+        .unwrap_or(0);
+    let mut set = HashSet::new();
+    for clause in clauses.iter() {
+        let mut clause = clause.clone();
+        clause.sort();
+        clause.dedup();
+        // A tautology is satisfied by every assignment.
+        if !clause.windows(2).any(|w| w[0] == !w[1]) {
+            set.insert(clause);
+        }
+    }
+    let mut list: Vec<&Vec<Lit>> = set.iter().collect();
+    list.sort();
+
+    let literals = 2 * var_num;
+    let mut adj: Vec<Vec<usize>> = (0..literals).map(|l| vec![l ^ 1]).collect();
+    for (i, clause) in list.iter().enumerate() {
+        let c = literals + i;
+        adj.push(clause.iter().map(|&lit| index(lit)).collect());
+        clause.iter().for_each(|&lit| adj[index(lit)].push(c));
+    }
+    let graph = Graph { adj, literals };
+    // Literals are colored 0 and clauses are colored 1.
+    let mut coloring: Vec<usize> = (0..graph.adj.len())
+        .map(|v| (v >= graph.literals) as usize)
+        .collect();
+    let mut same = coloring.clone();
+    refine(&graph, &mut coloring, &mut same);
+
+    let lit = |l: usize| Lit::new((l / 2) as u32, l & 1 == 0);
+    let mut budget = budget;
+    let mut orbit: Vec<usize> = (0..literals).collect();
+    let mut symmetries = vec![];
+    for u in 0..literals {
+        for v in u + 1..literals {
+            if coloring[u] != coloring[v] || find(&mut orbit, u) == find(&mut orbit, v) {
+                continue;
+            }
+            let (mut a, mut b) = (coloring.clone(), coloring.clone());
+            a[u] = graph.adj.len();
+            b[v] = graph.adj.len();
+            let image = match search(&graph, a, b, &mut budget) {
+                Some(image) => image,
+                None => continue,
+            };
+            // A map that preserves colors of refinement is an automorphism. Check it anyway.
+            let symmetric = list.iter().all(|clause| {
+                let mut mapped: Vec<Lit> = clause.iter().map(|&l| lit(image[index(l)])).collect();
+                mapped.sort();
+                set.contains(&mapped)
+            });
+            if !symmetric {
+                continue;
+            }
+            for (l, &m) in image.iter().enumerate().take(literals) {
+                let (x, y) = (find(&mut orbit, l), find(&mut orbit, m));
+                orbit[x] = y;
+            }
+            symmetries.push((0..var_num).map(|v| lit(image[2 * v])).collect());
+        }
+    }
+    symmetries
+}
+
+/// Add lex-leader clauses of a symmetry `perm` in the form of `find_symmetries`.
+/// An assignment must not be greater than its image in the order of variables(`false < true`).
+/// Each orbit of assignments keeps its least assignment, so it preserves the satisfiability of symmetric clauses.
+/// `e[i]` means that the first `i` moved variables are equal to their images. They are fresh variables.
+pub fn add_lex_leader(solver: &mut Solver, perm: &[Lit]) {
+    while perm.len() > solver.assigns.len() {
+        solver.new_var();
+    }
+    let moved: Vec<u32> = (0..perm.len() as u32)
+        .filter(|&v| perm[v as usize] != Lit::new(v, true))
+        .collect();
+    // `None` is the empty prefix that is always equal.
+    let mut equal: Option<Lit> = None;
+    for (i, &v) in moved.iter().enumerate() {
+        let (x, y) = (Lit::new(v, true), perm[v as usize]);
+        let with = |lits: &[Lit]| -> Vec<Lit> {
+            equal
+                .map(|e| !e)
+                .into_iter()
+                .chain(lits.iter().copied())
+                .collect()
+        };
+        // x <= y
+        solver.add_clause(&with(&[!x, y]));
+        // `x` and `!x` are never equal.
+        if y == !x || i + 1 == moved.len() {
+            break;
+        }
+        let e = fresh_lit(solver);
+        solver.add_clause(&with(&[!x, !y, e]));
+        solver.add_clause(&with(&[x, y, e]));
+        equal = Some(e);
+    }
+}
+
+/// Add lex-leader clauses of symmetries of `clauses` that `find_symmetries` finds in `budget`.
+/// The clauses themselves aren't added. Each model of the new clauses is a model of `clauses`,
+/// but clauses that are added later must respect the symmetries. It returns the number of symmetries.
+pub fn break_symmetries(solver: &mut Solver, clauses: &[Vec<Lit>], budget: usize) -> usize {
+    let symmetries = find_symmetries(clauses, budget);
+    symmetries
+        .iter()
+        .for_each(|perm| add_lex_leader(solver, perm));
+    symmetries.len()
+}
@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::aiger::*;
+    use screwsat::solver::*;
+
+    // The value of a literal of `aiger` under values of inputs.
+    fn value(aiger: &AigerData, inputs: &[bool], lit: Lit) -> bool {
+        let mut solver = Solver::new(aiger.var_num, &aiger.clauses);
+        for (&x, &b) in aiger.inputs.iter().zip(inputs.iter()) {
+            solver.add_clause(&[if b { x } else { !x }]);
+        }
+        assert_eq!(solver.solve(None), Status::Sat);
+        let b = (solver.assigns[lit.var().0 as usize] == LitBool::True) == lit.pos();
+        // The value is forced by inputs.
+        solver.add_clause(&[if b { !lit } else { lit }]);
+        assert_eq!(solver.solve(None), Status::Unsat);
+        b
+    }
+
+    #[test]
+    fn test_ascii() {
+        let input = "aag 7 2 1 2 4 1
+2
+4
+6 11 0
+1
+15
+6
+8 2 4
+10 3 5
+12 8 2
+14 13 11
+i0 a
+i1 b
+l0 state
+o0 true
+o1 out
+b0 bad
+c
+Here is a comment.
+";
+        let aiger = parse_aiger(input.as_bytes()).unwrap();
+        assert_eq!(aiger.var_num, 8);
+        assert_eq!(aiger.inputs, vec![Lit::new(1, true), Lit::new(2, true)]);
+        assert_eq!(aiger.latches, vec![(Lit::new(3, true), Lit::new(5, false))]);
+        assert_eq!(aiger.resets, vec![Some(false)]);
+        assert_eq!(aiger.outputs, vec![Lit::new(0, false), Lit::new(7, false)]);
+        assert_eq!(aiger.bad, vec![Lit::new(3, true)]);
+        assert_eq!(aiger.names["b"], aiger.inputs[1]);
+        assert_eq!(aiger.names["state"], aiger.latches[0].0);
+        assert_eq!(aiger.names["out"], aiger.outputs[1]);
+        for bits in 0..4 {
+            let (a, b) = (bits & 1 == 1, bits & 2 == 2);
+            assert!(value(&aiger, &[a, b], aiger.outputs[0]));
+            assert_eq!(value(&aiger, &[a, b], aiger.outputs[1]), a == b);
+            assert_eq!(value(&aiger, &[a, b], aiger.latches[0].1), a || b);
+        }
+    }
+
+    #[test]
+    fn test_binary() {
+        // a chain of `x_i = x_{i-1} & a` from `x_0 = b`
+        let n = 100;
+        let mut ascii = format!("aag {} 2 0 1 {}\n2\n4\n{}\n", n + 2, n, 2 * (n + 2));
+        let mut binary = format!("aig {} 2 0 1 {}\n{}\n", n + 2, n, 2 * (n + 2)).into_bytes();
+        for i in 0..n {
+            let lhs = 2 * (i + 3);
+            let rhs0 = lhs - 2;
+            ascii.push_str(&format!("{} {} 2\n", lhs, rhs0));
+            for mut delta in [lhs - rhs0, rhs0 - 2] {
+                while delta > 0x7f {
+                    binary.push((delta & 0x7f) as u8 | 0x80);
+                    delta >>= 7;
+                }
+                binary.push(delta as u8);
+            }
+        }
+        binary.extend_from_slice(b"i0 a\no0 all\n");
+        let a = parse_aiger(ascii.as_bytes()).unwrap();
+        let b = parse_aiger(&binary[..]).unwrap();
+        assert_eq!(a.clauses, b.clauses);
+        assert_eq!(a.inputs, b.inputs);
+        assert_eq!(a.outputs, b.outputs);
+        assert_eq!(b.names["all"], b.outputs[0]);
+        for bits in 0..4 {
+            let (x, y) = (bits & 1 == 1, bits & 2 == 2);
+            assert_eq!(value(&b, &[x, y], b.outputs[0]), x && y);
+        }
+    }
+
+    #[test]
+    fn test_latches_and_constraints() {
+        let input = "aag 3 1 2 0 0 0 1\n2\n4 2 1\n6 7 6\n4\n";
+        let aiger = parse_aiger(input.as_bytes()).unwrap();
+        assert_eq!(aiger.resets, vec![Some(true), None]);
+        assert_eq!(aiger.latches[1], (Lit::new(3, true), Lit::new(3, false)));
+        assert_eq!(aiger.constraints, vec![Lit::new(2, true)]);
+
+        let binary = b"aig 3 1 2 0 0\n2 1\n7 6\n";
+        let aiger = parse_aiger(&binary[..]).unwrap();
+        assert_eq!(aiger.latches[0], (Lit::new(2, true), Lit::new(1, true)));
+        assert_eq!(aiger.resets, vec![Some(true), None]);
+    }
+
+    #[test]
+    fn test_errors() {
+        for input in [
+            "aig 1 1 0 0\n",
+            "aag x 1 0 0 0\n",
+            "aag 1 1 0 1 0\n3\n2\n",
+            "aag 1 1 0 1 0\n2\n4\n",
+            "aag 2 1 0 1 0\n2\n4\n",
+            "aag 2 1 0 0 1\n2\n2 2 2\n",
+            "aag 1 1 0 0 0 0 0 1 0\n2\n",
+            "aag 1 1 0 0 0\n2\nx0 name\n",
+            "aig 2 1 0 0 1\n\x05\x00",
+        ]
+        .iter()
+        {
+            let err = parse_aiger(input.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{}", input);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::bench::*;
+    use screwsat::solver::*;
+
+    // Check that inputs force outputs to their expected values.
+    fn check<F: Fn(&[bool]) -> Vec<bool>>(bench: &BenchData, expected: F) {
+        let n = bench.inputs.len();
+        for bits in 0..1u32 << n {
+            let values: Vec<bool> = (0..n).map(|i| bits >> i & 1 == 1).collect();
+            let mut solver = Solver::new(bench.var_num, &bench.clauses);
+            for (&(_, v), &b) in bench.inputs.iter().zip(values.iter()) {
+                solver.add_clause(&[Lit::new(v.0, b)]);
+            }
+            assert_eq!(solver.solve(None), Status::Sat);
+            for (&(_, v), b) in bench.outputs.iter().zip(expected(&values)) {
+                assert_eq!(solver.assigns[v.0 as usize] == LitBool::True, b);
+                let mut solver = Solver::new(bench.var_num, &bench.clauses);
+                for (&(_, v), &b) in bench.inputs.iter().zip(values.iter()) {
+                    solver.add_clause(&[Lit::new(v.0, b)]);
+                }
+                solver.add_clause(&[Lit::new(v.0, !b)]);
+                assert_eq!(solver.solve(None), Status::Unsat);
+            }
+        }
+    }
+
+    #[test]
+    fn test_c17() {
+        let input = "# c17
+INPUT(1)
+INPUT(2)
+INPUT(3)
+INPUT(6)
+INPUT(7)
+
+OUTPUT(22)
+OUTPUT(23)
+
+10 = NAND(1, 3)
+11 = NAND(3, 6)
+16 = NAND(2, 11)
+19 = NAND(11, 7)
+22 = NAND(10, 16)
+23 = NAND(16, 19)
+";
+        let bench = parse_bench(input.as_bytes()).unwrap();
+        assert_eq!(bench.inputs.len(), 5);
+        assert_eq!(bench.outputs[1].0, "23");
+        assert_eq!(bench.names["11"], Var(8));
+        check(&bench, |x| {
+            let nand = |a: bool, b: bool| !(a && b);
+            let (g10, g11) = (nand(x[0], x[2]), nand(x[2], x[3]));
+            let (g16, g19) = (nand(x[1], g11), nand(g11, x[4]));
+            vec![nand(g10, g16), nand(g16, g19)]
+        });
+    }
+
+    #[test]
+    fn test_gates() {
+        let input = "INPUT(a)
+INPUT(b)
+INPUT(c)
+OUTPUT(x)
+OUTPUT(y)
+OUTPUT(z)
+OUTPUT(w)
+OUTPUT(q)
+x = xor(a, b, c)
+y = XNOR(a, b)
+z = NOR(a, w)
+w = buff(n)
+n = NOT(c)
+o = OR(a, b, c)
+q = DFF(o)
+";
+        let bench = parse_bench(input.as_bytes()).unwrap();
+        assert_eq!(bench.latches, vec![(bench.names["q"], bench.names["o"])]);
+        // A current state of a flip-flop is free.
+        let inputs: Vec<_> = bench
+            .inputs
+            .iter()
+            .cloned()
+            .chain(std::iter::once(("q".to_string(), bench.names["q"])))
+            .collect();
+        let with_state = BenchData {
+            inputs,
+            ..bench.clone()
+        };
+        check(&with_state, |x| {
+            vec![x[0] ^ x[1] ^ x[2], x[0] == x[1], !x[0] && x[2], !x[2], x[3]]
+        });
+
+        assert!(parse_bench("OUTPUT(a)".as_bytes()).is_err());
+        assert!(parse_bench("INPUT(a)\nINPUT(a)".as_bytes()).is_err());
+        assert!(parse_bench("INPUT(a)\nb = NOT(a, a)".as_bytes()).is_err());
+        assert!(parse_bench("INPUT(a)\nb = MUX(a)".as_bytes()).is_err());
+    }
+
+    // Output values of a circuit for input values by name
+    fn simulate(bench: &BenchData, inputs: &[(String, bool)]) -> Vec<bool> {
+        let mut solver = Solver::new(bench.var_num, &bench.clauses);
+        for (name, b) in inputs.iter() {
+            solver.add_clause(&[Lit::new(bench.names[name].0, *b)]);
+        }
+        assert_eq!(solver.solve(None), Status::Sat);
+        bench
+            .outputs
+            .iter()
+            .map(|&(_, v)| solver.assigns[v.0 as usize] == LitBool::True)
+            .collect()
+    }
+
+    #[test]
+    fn test_equivalence() {
+        let c17 = "INPUT(1)
+INPUT(2)
+INPUT(3)
+INPUT(6)
+INPUT(7)
+OUTPUT(22)
+OUTPUT(23)
+10 = NAND(1, 3)
+11 = NAND(3, 6)
+16 = NAND(2, 11)
+19 = NAND(11, 7)
+22 = NAND(10, 16)
+23 = NAND(16, 19)
+";
+        let a = parse_bench(c17.as_bytes()).unwrap();
+        // NAND(10, 16) = AND(1, 3) v NOT(16)
+        let rewritten = c17.replace(
+            "22 = NAND(10, 16)",
+            "a = AND(1, 3)\nn = NOT(16)\n22 = OR(a, n)",
+        );
+        let b = parse_bench(rewritten.as_bytes()).unwrap();
+        for &per_output in [false, true].iter() {
+            assert_eq!(
+                check_equivalence(&a, &b, per_output, None).unwrap(),
+                Equivalence::Equivalent
+            );
+            let buggy = parse_bench(
+                rewritten
+                    .replace("22 = OR(a, n)", "22 = OR(a, 16)")
+                    .as_bytes(),
+            )
+            .unwrap();
+            match check_equivalence(&a, &buggy, per_output, None).unwrap() {
+                Equivalence::Different { inputs, outputs } => {
+                    assert_eq!(outputs, vec!["22".to_string()]);
+                    let (x, y) = (simulate(&a, &inputs), simulate(&buggy, &inputs));
+                    assert_ne!(x[0], y[0]);
+                    assert_eq!(x[1], y[1]);
+                }
+                result => panic!("{:?}", result),
+            }
+        }
+        let renamed = parse_bench(c17.replace("23", "24").as_bytes()).unwrap();
+        assert!(check_equivalence(&a, &renamed, false, None).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::encodings::card::*;
+    use screwsat::solver::*;
+
+    // Check that `encode` accepts exactly assignments of `n` variables that satisfy `expected`.
+    fn check<E: Fn(&mut Solver, &[Lit]), F: Fn(usize) -> bool>(n: u32, encode: E, expected: F) {
+        let lits: Vec<Lit> = (0..n).map(|v| Lit::new(v, v % 2 == 1)).collect();
+        for bits in 0..1u32 << n {
+            let mut solver = Solver::default();
+            encode(&mut solver, &lits);
+            (0..n).for_each(|v| solver.add_clause(&[Lit::new(v, bits >> v & 1 == 1)]));
+            let count = lits
+                .iter()
+                .filter(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                .count();
+            assert_eq!(solver.solve(None) == Status::Sat, expected(count));
+        }
+    }
+
+    #[test]
+    fn test_cardinality() {
+        for n in 1..9 {
+            check(n, at_most_one, |c| c <= 1);
+            check(n, exactly_one, |c| c == 1);
+            for k in 0..=n as usize {
+                check(n, |solver, lits| at_most_k(solver, lits, k), |c| c <= k);
+                check(n, |solver, lits| at_least_k(solver, lits, k), |c| c >= k);
+                check(n, |solver, lits| solver.add_at_most_k(lits, k), |c| c <= k);
+                check(n, |solver, lits| solver.add_at_least_k(lits, k), |c| c >= k);
+                check(n, |solver, lits| solver.add_exactly_k(lits, k), |c| c == k);
+            }
+            check(
+                n,
+                |solver, lits| solver.add_at_least_k(lits, n as usize + 1),
+                |_| false,
+            );
+        }
+    }
+
+    #[test]
+    fn test_totalizer() {
+        let n = 7;
+        let lits: Vec<Lit> = (0..n).map(|v| Lit::new(v, v % 3 != 0)).collect();
+        for cap in 0..=n as usize + 1 {
+            for bits in 0..1u32 << n {
+                let mut solver = Solver::default();
+                let outputs = totalizer(&mut solver, &lits, cap);
+                assert_eq!(outputs.len(), std::cmp::min(n as usize, cap));
+                (0..n).for_each(|v| solver.add_clause(&[Lit::new(v, bits >> v & 1 == 1)]));
+                let count = lits
+                    .iter()
+                    .filter(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                    .count();
+                assert_eq!(solver.solve(None), Status::Sat);
+                // Outputs are forced by inputs.
+                for (j, &out) in outputs.iter().enumerate() {
+                    let expected = count > j;
+                    assert_eq!(
+                        solver.assigns[out.var().0 as usize] == LitBool::True,
+                        expected == out.pos()
+                    );
+                    let status =
+                        solver.solve_with_assumptions(&[if expected { !out } else { out }], None);
+                    assert_eq!(status, Status::Unsat);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::cube::*;
+    use screwsat::solver::*;
+
+    fn random_problem(seed: u64, var_num: u32, cla_num: usize) -> Vec<Vec<Lit>> {
+        let mut x = seed;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..cla_num)
+            .map(|_| {
+                (0..3)
+                    .map(|_| Lit::new((next() % var_num as u64) as u32, next() % 2 == 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn pigeonhole(pigeons: u32, holes: u32) -> Vec<Vec<Lit>> {
+        let x = |p: u32, h: u32| Lit::new(p * holes + h, true);
+        let mut clauses: Vec<Vec<Lit>> = (0..pigeons)
+            .map(|p| (0..holes).map(|h| x(p, h)).collect())
+            .collect();
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![!x(p, h), !x(q, h)]);
+                }
+            }
+        }
+        clauses
+    }
+
+    fn satisfied(assigns: &[LitBool], clauses: &[Vec<Lit>]) -> bool {
+        clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|lit| (assigns[lit.var().0 as usize] == LitBool::True) == lit.pos())
+        })
+    }
+
+    #[test]
+    fn test_cube_and_conquer() {
+        let configs = [
+            CubeConfig::default(),
+            CubeConfig {
+                depth: 4,
+                candidates: 5,
+                ..CubeConfig::default()
+            },
+            CubeConfig {
+                threads: 4,
+                ..CubeConfig::default()
+            },
+        ];
+        for config in configs.iter() {
+            let (mut sat, mut unsat) = (0, 0);
+            for seed in 1..40 {
+                let clauses = random_problem(seed * 7919, 60, 256);
+                let expected = Solver::new(60, &clauses).solve(None);
+                let mut solver = Solver::new(60, &clauses);
+                let status = solver.solve_cube_and_conquer(config);
+                assert_eq!(status, expected);
+                if status == Status::Sat {
+                    assert!(satisfied(&solver.assigns, &clauses));
+                    sat += 1;
+                } else {
+                    unsat += 1;
+                }
+                // A solver can be used incrementally after cube-and-conquer.
+                solver.add_clause(&[clauses[0][0]]);
+                let mut expected = Solver::new(60, &clauses);
+                expected.add_clause(&[clauses[0][0]]);
+                assert_eq!(solver.solve(None), expected.solve(None));
+            }
+            assert!(sat > 0 && unsat > 0);
+        }
+    }
+
+    #[test]
+    fn test_cube_and_conquer_unsat() {
+        for threads in [1, 4].iter() {
+            let config = CubeConfig {
+                threads: *threads,
+                ..CubeConfig::default()
+            };
+            let mut solver = Solver::new(0, &pigeonhole(7, 6));
+            assert_eq!(solver.solve_cube_and_conquer(&config), Status::Unsat);
+            // Unsat is permanent.
+            assert_eq!(solver.solve(None), Status::Unsat);
+
+            let clauses = pigeonhole(6, 6);
+            let mut solver = Solver::new(0, &clauses);
+            assert_eq!(solver.solve_cube_and_conquer(&config), Status::Sat);
+            assert!(satisfied(&solver.assigns, &clauses));
+        }
+        // Contradictory units are refuted before splitting.
+        let mut solver = Solver::new(2, &[vec![Lit::from(1)], vec![Lit::from(-1)]]);
+        assert_eq!(
+            solver.solve_cube_and_conquer(&CubeConfig::default()),
+            Status::Unsat
+        );
+        // Native constraints can't be copied to threads, so the solver solves cubes by itself.
+        let mut solver = Solver::default();
+        solver.add_xor(&[Lit::from(1), Lit::from(2)]);
+        solver.add_xor(&[Lit::from(2), Lit::from(3)]);
+        solver.add_clause(&[Lit::from(1), Lit::from(3)]);
+        solver.add_clause(&[Lit::from(-1), Lit::from(-3)]);
+        let config = CubeConfig {
+            threads: 4,
+            ..CubeConfig::default()
+        };
+        assert_eq!(solver.solve_cube_and_conquer(&config), Status::Unsat);
+    }
+}
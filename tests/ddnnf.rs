@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::ddnnf::*;
+    use screwsat::solver::*;
+    use std::collections::HashSet;
+
+    fn random_problem(seed: u64, var_num: u32, cla_num: usize) -> Vec<Vec<Lit>> {
+        let mut x = seed;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..cla_num)
+            .map(|_| {
+                (0..(next() % 3 + 1))
+                    .map(|_| Lit::new((next() % var_num as u64) as u32, next() % 2 == 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn brute_force_models(var_num: u32, clauses: &[Vec<Lit>]) -> HashSet<Vec<bool>> {
+        (0..1u32 << var_num)
+            .filter(|bits| {
+                clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                })
+            })
+            .map(|bits| (0..var_num).map(|v| bits >> v & 1 == 1).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_ddnnf() {
+        for seed in 1..200 {
+            let var_num = 9;
+            let clauses = random_problem(seed * 7919, var_num, 8 + (seed % 10) as usize);
+            let ddnnf = DDnnf::compile(var_num as usize, &clauses);
+            let models = brute_force_models(var_num, &clauses);
+            assert_eq!(ddnnf.count(), models.len() as u128);
+
+            let enumerated: HashSet<Vec<bool>> = ddnnf
+                .models()
+                .map(|model| model.iter().map(|&b| b == LitBool::True).collect())
+                .collect();
+            assert_eq!(enumerated, models);
+
+            // Conditioning
+            let lits = [Lit::from(1), Lit::from(-2)];
+            let conditioned = ddnnf.condition(&lits);
+            let expected: HashSet<Vec<bool>> = models
+                .iter()
+                .filter(|model| model[0] && !model[1])
+                .cloned()
+                .collect();
+            assert_eq!(conditioned.count(), expected.len() as u128);
+            let enumerated: HashSet<Vec<bool>> = conditioned
+                .models()
+                .map(|model| model.iter().map(|&b| b == LitBool::True).collect())
+                .collect();
+            assert_eq!(enumerated, expected);
+        }
+    }
+
+    #[test]
+    fn test_weighted_count() {
+        // Exact integer weights of literals
+        let weight = |lit: Lit| lit.var().0 as u128 * 2 + if lit.pos() { 3 } else { 1 };
+        for seed in 1..100 {
+            let var_num = 9;
+            let clauses = random_problem(seed * 7919, var_num, 8 + (seed % 10) as usize);
+            let ddnnf = DDnnf::compile(var_num as usize, &clauses);
+            let models = brute_force_models(var_num, &clauses);
+            let model_weight = |model: &Vec<bool>| -> u128 {
+                (0..var_num)
+                    .map(|v| weight(Lit::new(v, model[v as usize])))
+                    .product()
+            };
+            assert_eq!(
+                ddnnf.weighted_count(weight),
+                models.iter().map(model_weight).sum::<u128>()
+            );
+            let conditioned = ddnnf.condition(&[Lit::from(-3)]);
+            assert_eq!(
+                conditioned.weighted_count(weight),
+                models
+                    .iter()
+                    .filter(|model| !model[2])
+                    .map(model_weight)
+                    .sum::<u128>()
+            );
+        }
+        // Probabilities of independent variables sum to 1.
+        let ddnnf = DDnnf::compile(3, &[vec![Lit::from(1), Lit::from(-2)]]);
+        let p = |lit: Lit| if lit.pos() { 0.25 } else { 0.75 };
+        assert!((DDnnf::compile(3, &[]).weighted_count(p) - 1.0f64).abs() < 1e-12);
+        // Pr[x1 v !x2] = 1 - Pr[!x1 & x2]
+        assert!((ddnnf.weighted_count(p) - (1.0 - 0.75 * 0.25)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_ddnnf_trivial() {
+        // No clauses
+        assert_eq!(DDnnf::compile(3, &[]).count(), 8);
+        // Empty clause
+        assert_eq!(DDnnf::compile(3, &[vec![]]).count(), 0);
+        // Contradicted conditions
+        let ddnnf = DDnnf::compile(2, &[vec![Lit::from(1), Lit::from(2)]]);
+        assert_eq!(ddnnf.count(), 3);
+        assert_eq!(ddnnf.condition(&[Lit::from(1), Lit::from(-1)]).count(), 0);
+        assert_eq!(ddnnf.condition(&[Lit::from(-1)]).count(), 1);
+    }
+}
@@ -0,0 +1,71 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::dimacs::*;
+    use screwsat::solver::*;
+
+    #[test]
+    fn test_parse_cnf() {
+        let input = "c comment\n p  cnf 3 2 \n1\t-3 0 -1\n 2\n   3 0\n";
+        let cnf = parse_cnf(input.as_bytes()).unwrap();
+        assert_eq!((cnf.var_num, cnf.cla_num), (Some(3), Some(2)));
+        assert_eq!(
+            cnf.clauses,
+            vec![
+                vec![Lit::from(1), Lit::from(-3)],
+                vec![Lit::from(-1), Lit::from(2), Lit::from(3)]
+            ]
+        );
+        assert_eq!(Solver::new(3, &cnf.clauses).solve(None), Status::Sat);
+    }
+
+    #[test]
+    fn test_write_dimacs() {
+        let cnf = parse_cnf("c comment\np cnf 4 2\n1 -3 0\n-1\n2 0\n".as_bytes()).unwrap();
+        let mut buf = vec![];
+        cnf.write_dimacs(&mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "p cnf 4 2\n1 -3 0\n-1 2 0\n"
+        );
+        // A written formula is parsed again.
+        let mut buf = vec![];
+        let formula = CnfFormula {
+            var_num: None,
+            cla_num: None,
+            clauses: vec![vec![Lit::from(2), Lit::from(-5)], vec![]],
+        };
+        formula.write_dimacs(&mut buf).unwrap();
+        let again = parse_cnf(&buf[..]).unwrap();
+        assert_eq!((again.var_num, again.cla_num), (Some(5), Some(2)));
+        assert_eq!(again.clauses, formula.clauses);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |input: &str| parse_cnf(input.as_bytes()).unwrap_err();
+        assert!(matches!(
+            err("p cnf 2 1\np cnf 2 1\n"),
+            ParseError::DuplicateHeader { line: 2 }
+        ));
+        assert!(matches!(
+            err("c\np cnf x 1\n"),
+            ParseError::InvalidHeader { line: 2, ref header } if header == "p cnf x 1"
+        ));
+        assert!(matches!(
+            err("p cnf 2 1\n1 a 0\n"),
+            ParseError::InvalidLiteral { line: 2, ref token } if token == "a"
+        ));
+        assert!(matches!(
+            err("p cnf 2 1\n1 -3 0\n"),
+            ParseError::VariableOutOfRange { line: 2, var: 3 }
+        ));
+        // Invalid UTF-8 is an error of a reader. Errors of contents are `InvalidData` as `std::io::Error`.
+        assert!(matches!(
+            parse_cnf(&b"1 2 0\n\xff\n"[..]).unwrap_err(),
+            ParseError::Io(ref e) if e.kind() == std::io::ErrorKind::InvalidData
+        ));
+        let e: std::io::Error = err("p cnf 2 1\n1 3 0\n").into();
+        assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(e.to_string(), "Variable 3 at line 2 exceeds the header");
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::enumerate;
+    use screwsat::solver::*;
+    use std::collections::HashSet;
+
+    // (x1 v x2 v ¬x3) ∧ (¬x1 v x4) ∧ (x2 v x3 v ¬x4) ∧ (¬x2 v ¬x5 v x6) ∧ (x5 v ¬x6)
+    fn problem() -> Vec<Vec<Lit>> {
+        vec![
+            vec![Lit::from(1), Lit::from(2), Lit::from(-3)],
+            vec![Lit::from(-1), Lit::from(4)],
+            vec![Lit::from(2), Lit::from(3), Lit::from(-4)],
+            vec![Lit::from(-2), Lit::from(-5), Lit::from(6)],
+            vec![Lit::from(5), Lit::from(-6)],
+        ]
+    }
+
+    fn brute_force_count(var_num: usize, clauses: &[Vec<Lit>]) -> usize {
+        (0..1u32 << var_num)
+            .filter(|bits| {
+                clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                })
+            })
+            .count()
+    }
+
+    #[test]
+    fn test_models() {
+        let clauses = problem();
+        let mut solver = Solver::default();
+        clauses.iter().for_each(|clause| solver.add_clause(clause));
+
+        let models: Vec<_> = enumerate::Models::new(solver).collect();
+        assert_eq!(models.len(), brute_force_count(6, &clauses));
+        let distinct: HashSet<_> = models.iter().collect();
+        assert_eq!(distinct.len(), models.len());
+    }
+
+    #[test]
+    fn test_parallel_models() {
+        let clauses = problem();
+        let models: Vec<_> = enumerate::parallel(6, &clauses, 4).collect();
+        assert_eq!(models.len(), brute_force_count(6, &clauses));
+        let distinct: HashSet<_> = models.iter().collect();
+        assert_eq!(distinct.len(), models.len());
+
+        // Unsatisfiable
+        let clauses = vec![vec![Lit::from(1)], vec![Lit::from(-1)]];
+        assert_eq!(enumerate::parallel(1, &clauses, 4).count(), 0);
+    }
+}
@@ -0,0 +1,74 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::exact_cover::*;
+    use screwsat::solver::*;
+
+    // Count solutions by blocking selected rows.
+    fn count(rows: &[Vec<usize>], primary: usize) -> usize {
+        let mut solver = Solver::default();
+        let lits = encode(&mut solver, rows, primary);
+        let mut count = 0;
+        while solver.solve(None) == Status::Sat {
+            let selected = decode(&solver, &lits);
+            let mut covered: Vec<usize> = selected.iter().flat_map(|&r| rows[r].clone()).collect();
+            covered.sort_unstable();
+            let len = covered.len();
+            covered.dedup();
+            assert_eq!(covered.len(), len);
+            assert!((0..primary).all(|c| covered.contains(&c)));
+            count += 1;
+            let blocking: Vec<Lit> = lits
+                .iter()
+                .map(|&lit| {
+                    if solver.assigns[lit.var().0 as usize] == LitBool::True {
+                        !lit
+                    } else {
+                        lit
+                    }
+                })
+                .collect();
+            solver.add_clause(&blocking);
+        }
+        count
+    }
+
+    // Dominoes on a `h`×`w` board.
+    fn dominoes(h: usize, w: usize) -> Vec<Vec<usize>> {
+        let mut rows = vec![];
+        for y in 0..h {
+            for x in 0..w {
+                if x + 1 < w {
+                    rows.push(vec![y * w + x, y * w + x + 1]);
+                }
+                if y + 1 < h {
+                    rows.push(vec![y * w + x, (y + 1) * w + x]);
+                }
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_exact_cover() {
+        // Knuth's example of Dancing Links
+        let rows = vec![
+            vec![2, 4, 5],
+            vec![0, 3, 6],
+            vec![1, 2, 5],
+            vec![0, 3],
+            vec![1, 6],
+            vec![3, 4, 6],
+        ];
+        let mut solver = Solver::default();
+        let lits = encode(&mut solver, &rows, 7);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(decode(&solver, &lits), vec![0, 3, 4]);
+        assert_eq!(count(&rows, 7), 1);
+
+        assert_eq!(count(&dominoes(2, 4), 8), 5);
+        assert_eq!(count(&dominoes(3, 4), 12), 11);
+        assert_eq!(count(&dominoes(3, 3), 9), 0);
+        // The last cell of a 3×3 board is secondary.
+        assert_eq!(count(&dominoes(3, 3), 8), 4);
+    }
+}
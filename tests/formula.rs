@@ -0,0 +1,197 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::formula::*;
+    use screwsat::solver::*;
+
+    struct Rng(u64);
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+    }
+
+    fn random_formula(rng: &mut Rng, var_num: u32, depth: u32) -> Formula {
+        if depth == 0 || rng.next() % 5 == 4 {
+            return match rng.next() % 8 {
+                0 => Formula::Const(rng.next() & 1 == 1),
+                _ => Formula::Lit(Lit::new(
+                    (rng.next() % var_num as u64) as u32,
+                    rng.next() & 1 == 1,
+                )),
+            };
+        }
+        let sub = |rng: &mut Rng| random_formula(rng, var_num, depth - 1);
+        match rng.next() % 6 {
+            0 => !sub(rng),
+            1 => Formula::And((0..rng.next() % 4).map(|_| sub(rng)).collect()),
+            2 => Formula::Or((0..rng.next() % 4).map(|_| sub(rng)).collect()),
+            3 => Formula::xor(sub(rng), sub(rng)),
+            4 => Formula::iff(sub(rng), sub(rng)),
+            _ => Formula::ite(sub(rng), sub(rng), sub(rng)),
+        }
+    }
+
+    #[test]
+    fn test_formula() {
+        let var_num = 4;
+        let mut rng = Rng(7);
+        for _ in 0..300 {
+            let f = random_formula(&mut rng, var_num, 4);
+            for bits in 0..1u32 << var_num {
+                let value = |lit: Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                let units: Vec<Lit> = (0..var_num)
+                    .map(|v| Lit::new(v, bits >> v & 1 == 1))
+                    .collect();
+                let expected = f.eval(&value);
+
+                // A constraint is satisfiable iff a formula is true.
+                let mut solver = Solver::default();
+                units.iter().for_each(|&lit| solver.add_clause(&[lit]));
+                add_formula(&mut solver, &f);
+                let status = solver.solve(None);
+                assert_eq!(status == Status::Sat, expected, "{:?} {:b}", f, bits);
+
+                // An encoded literal is equivalent to a formula.
+                // Fresh variables never overlap with variables of a formula.
+                let mut solver = Solver::default();
+                let lit = encode(&mut solver, &f);
+                units.iter().for_each(|&lit| solver.add_clause(&[lit]));
+                solver.add_clause(&[if expected { !lit } else { lit }]);
+                assert_eq!(solver.solve(None), Status::Unsat, "{:?} {:b}", f, bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_plaisted_greenbaum() {
+        let var_num = 4;
+        let mut rng = Rng(13);
+        for _ in 0..300 {
+            let f = random_formula(&mut rng, var_num, 4);
+            for bits in 0..1u32 << var_num {
+                let value = |lit: Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                let units: Vec<Lit> = (0..var_num)
+                    .map(|v| Lit::new(v, bits >> v & 1 == 1))
+                    .collect();
+                let expected = f.eval(&value);
+
+                let mut solver = Solver::default();
+                units.iter().for_each(|&lit| solver.add_clause(&[lit]));
+                Encoder::plaisted_greenbaum().add_formula(&mut solver, &f);
+                let status = solver.solve(None);
+                assert_eq!(status == Status::Sat, expected, "{:?} {:b}", f, bits);
+
+                // An encoded literal can be true iff a formula is true.
+                let mut encoder = Encoder::plaisted_greenbaum();
+                let mut solver = Solver::default();
+                units.iter().for_each(|&lit| solver.add_clause(&[lit]));
+                let lit = encoder.encode(&mut solver, &f);
+                // Shared subformulas that occur negatively get the other clauses.
+                let not = encoder.encode(&mut solver, &!f.clone());
+                let status = solver.solve_with_assumptions(&[lit], None);
+                assert_eq!(status == Status::Sat, expected, "{:?} {:b}", f, bits);
+                let status = solver.solve_with_assumptions(&[not], None);
+                assert_eq!(status == Status::Sat, !expected, "{:?} {:b}", f, bits);
+            }
+        }
+
+        // Only one of the implications of each gate is needed by a constraint.
+        let lit = |x: i32| Formula::Lit(Lit::from(x));
+        let f = Formula::And(vec![
+            Formula::Or(vec![Formula::And(vec![lit(1), lit(2)]), lit(3)]),
+            Formula::Or(vec![Formula::And(vec![lit(-1), lit(4)]), lit(-3)]),
+        ]);
+        let mut tseitin = screwsat::util::CnfData {
+            var_num: None,
+            cla_num: None,
+            clauses: vec![],
+        };
+        add_formula(&mut tseitin, &f);
+        let mut pg = screwsat::util::CnfData {
+            var_num: None,
+            cla_num: None,
+            clauses: vec![],
+        };
+        Encoder::plaisted_greenbaum().add_formula(&mut pg, &f);
+        assert_eq!(tseitin.clauses.len(), 8);
+        assert_eq!(pg.clauses.len(), 6);
+        assert_eq!(pg.var_num, Some(6));
+    }
+
+    #[test]
+    fn test_cnf_target() {
+        let var_num = 4;
+        let mut rng = Rng(17);
+        for _ in 0..100 {
+            let f = random_formula(&mut rng, var_num, 4);
+            let mut cnf = screwsat::util::CnfData {
+                var_num: Some(var_num as usize),
+                cla_num: None,
+                clauses: vec![],
+            };
+            let lit = encode(&mut cnf, &f);
+            // Fresh variables follow the variables of a header.
+            assert!(cnf.var_num.unwrap() >= var_num as usize);
+            assert!(lit.var().0 < cnf.var_num.unwrap() as u32);
+            for bits in 0..1u32 << var_num {
+                let value = |lit: Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                let mut solver = Solver::new(cnf.var_num.unwrap(), &cnf.clauses);
+                (0..var_num).for_each(|v| solver.add_clause(&[Lit::new(v, bits >> v & 1 == 1)]));
+                let expected = f.eval(&value);
+                solver.add_clause(&[if expected { !lit } else { lit }]);
+                assert_eq!(solver.solve(None), Status::Unsat, "{:?} {:b}", f, bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_structural_hashing() {
+        let lit = |x: i32| Formula::Lit(Lit::from(x));
+        let shared = Formula::xor(lit(1), Formula::And(vec![lit(2), lit(3)]));
+        let mut solver = Solver::default();
+        let mut encoder = Encoder::default();
+        let a = encoder.encode(&mut solver, &shared);
+        let var_num = solver.assigns.len();
+        // The same structure, commuted operands and negated inputs reuse the gates.
+        let b = encoder.encode(&mut solver, &shared.clone());
+        let c = encoder.encode(
+            &mut solver,
+            &Formula::xor(Formula::And(vec![lit(3), lit(2)]), lit(1)),
+        );
+        let d = encoder.encode(
+            &mut solver,
+            &Formula::xor(!lit(1), Formula::And(vec![lit(2), lit(3)])),
+        );
+        assert_eq!(a, b);
+        assert_eq!(a, c);
+        assert_eq!(!a, d);
+        assert_eq!(solver.assigns.len(), var_num);
+
+        // Random formulas that share subformulas are still equivalent to their literals.
+        let var_num = 4;
+        let mut rng = Rng(11);
+        for _ in 0..100 {
+            let f = random_formula(&mut rng, var_num, 3);
+            let g = Formula::Or(vec![f.clone(), random_formula(&mut rng, var_num, 2)]);
+            for bits in 0..1u32 << var_num {
+                let value = |lit: Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                for h in 0..2 {
+                    let mut solver = Solver::default();
+                    let mut encoder = Encoder::default();
+                    let lits = [
+                        encoder.encode(&mut solver, &f),
+                        encoder.encode(&mut solver, &g),
+                    ];
+                    (0..var_num)
+                        .for_each(|v| solver.add_clause(&[Lit::new(v, bits >> v & 1 == 1)]));
+                    let expected = [&f, &g][h].eval(&value);
+                    solver.add_clause(&[if expected { !lits[h] } else { lits[h] }]);
+                    assert_eq!(solver.solve(None), Status::Unsat, "{:?} {:b}", g, bits);
+                }
+            }
+        }
+    }
+}
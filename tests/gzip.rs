@@ -0,0 +1,104 @@
+#![cfg(feature = "gzip")]
+#[cfg(test)]
+mod tests {
+    use screwsat::gzip::GzDecoder;
+    use screwsat::solver::*;
+    use screwsat::util;
+    use std::io::Read;
+
+    const PIGEONHOLE: &str = "c pigeons\np cnf 6 9\n1 2 0\n3 4 0\n5 6 0\n-1 -3 0\n-1 -5 0\n-3 -5 0\n-2 -4 0\n-2 -6 0\n-4 -6 0\n";
+
+    // `PIGEONHOLE` in a stored block
+    const STORED: [u8; 109] = [
+        31, 139, 8, 0, 0, 0, 0, 0, 4, 3, 1, 86, 0, 169, 255, 99, 32, 112, 105, 103, 101, 111, 110,
+        115, 10, 112, 32, 99, 110, 102, 32, 54, 32, 57, 10, 49, 32, 50, 32, 48, 10, 51, 32, 52, 32,
+        48, 10, 53, 32, 54, 32, 48, 10, 45, 49, 32, 45, 51, 32, 48, 10, 45, 49, 32, 45, 53, 32, 48,
+        10, 45, 51, 32, 45, 53, 32, 48, 10, 45, 50, 32, 45, 52, 32, 48, 10, 45, 50, 32, 45, 54, 32,
+        48, 10, 45, 52, 32, 45, 54, 32, 48, 10, 46, 131, 74, 141, 86, 0, 0, 0,
+    ];
+
+    // `PIGEONHOLE` in a block of fixed Huffman codes
+    const FIXED: [u8; 77] = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 75, 86, 40, 200, 76, 79, 205, 207, 43, 230, 42, 80, 72,
+        206, 75, 83, 48, 83, 176, 228, 50, 84, 48, 82, 48, 224, 50, 86, 48, 1, 146, 166, 64, 17, 3,
+        46, 93, 67, 5, 93, 99, 40, 109, 10, 162, 141, 161, 180, 145, 130, 174, 9, 148, 6, 171, 51,
+        129, 208, 0, 46, 131, 74, 141, 86, 0, 0, 0,
+    ];
+
+    // a chain of 20 variables in a block of dynamic Huffman codes
+    const DYNAMIC: [u8; 108] = [
+        31, 139, 8, 0, 0, 0, 0, 0, 2, 3, 21, 204, 187, 13, 195, 48, 0, 3, 209, 222, 83, 112, 129,
+        3, 68, 249, 171, 121, 2, 164, 52, 178, 127, 21, 178, 57, 178, 122, 63, 125, 222, 175, 230,
+        144, 215, 102, 49, 53, 182, 41, 246, 204, 46, 142, 204, 33, 206, 204, 41, 174, 204, 37,
+        238, 204, 45, 158, 204, 35, 86, 102, 9, 143, 108, 130, 221, 19, 201, 165, 18, 92, 44, 193,
+        229, 18, 92, 48, 193, 37, 19, 92, 52, 193, 101, 19, 92, 56, 97, 86, 254, 3, 2, 8, 109, 156,
+        166, 0, 0, 0,
+    ];
+
+    fn decompress(bytes: &[u8]) -> std::io::Result<String> {
+        let mut text = String::new();
+        GzDecoder::new(bytes).read_to_string(&mut text)?;
+        Ok(text)
+    }
+
+    #[test]
+    fn test_gzip() {
+        for bytes in [&STORED[..], &FIXED[..]].iter() {
+            assert_eq!(decompress(bytes).unwrap(), PIGEONHOLE);
+            let cnf = util::parse_cnf(GzDecoder::new(*bytes)).unwrap();
+            assert_eq!(Solver::new(6, &cnf.clauses).solve(None), Status::Unsat);
+        }
+        let cnf = util::parse_cnf(GzDecoder::new(&DYNAMIC[..])).unwrap();
+        assert_eq!((cnf.var_num, cnf.clauses.len()), (Some(20), 19));
+        assert!(cnf.clauses.iter().enumerate().all(|(i, clause)| {
+            *clause == vec![Lit::new(i as u32, true), Lit::new(i as u32 + 1, false)]
+        }));
+
+        // Concatenated members are one stream.
+        let concatenated: Vec<u8> = STORED.iter().chain(FIXED.iter()).copied().collect();
+        assert_eq!(decompress(&concatenated).unwrap(), PIGEONHOLE.repeat(2));
+    }
+
+    #[test]
+    fn test_gzip_files() {
+        // Files that are compressed by the `gzip` command(`-9` and `-1`) are the same as the tracked cnf files.
+        for (gz, cnf, expected) in [
+            ("php_7_6.cnf.gz", "unsat/php_7_6.cnf", Status::Unsat),
+            (
+                "rand3_40_172_3.cnf.gz",
+                "sat/rand3_40_172_3.cnf",
+                Status::Sat,
+            ),
+        ]
+        .iter()
+        {
+            let file = std::fs::File::open(format!("tests/cnf/gzip/{}", gz)).unwrap();
+            let mut text = String::new();
+            GzDecoder::new(file).read_to_string(&mut text).unwrap();
+            assert_eq!(
+                text,
+                std::fs::read_to_string(format!("tests/cnf/{}", cnf)).unwrap()
+            );
+            let file = std::fs::File::open(format!("tests/cnf/gzip/{}", gz)).unwrap();
+            let cnf = util::parse_cnf(GzDecoder::new(file)).unwrap();
+            let mut solver = Solver::new(cnf.var_num.unwrap(), &cnf.clauses);
+            assert_eq!(solver.solve(None), *expected, "{}", gz);
+        }
+    }
+
+    #[test]
+    fn test_gzip_errors() {
+        let mut corrupted = FIXED;
+        corrupted[FIXED.len() - 6] ^= 1;
+        let truncated = &DYNAMIC[..60];
+        let kinds = [
+            (&corrupted[..], std::io::ErrorKind::InvalidData),
+            (truncated, std::io::ErrorKind::UnexpectedEof),
+            (PIGEONHOLE.as_bytes(), std::io::ErrorKind::InvalidData),
+            (&[][..], std::io::ErrorKind::InvalidData),
+        ];
+        for (bytes, kind) in kinds.iter() {
+            assert_eq!(decompress(bytes).unwrap_err().kind(), *kind);
+        }
+    }
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "heapless")]
+#[cfg(test)]
+mod tests {
+    use screwsat::heapless::HeaplessSolver;
+    use screwsat::solver::*;
+
+    // A tiny xorshift generator to build random 3-SAT problems without dependencies.
+    fn random_problem(seed: u64, var_num: u32, cla_num: usize) -> Vec<Vec<Lit>> {
+        let mut x = seed;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..cla_num)
+            .map(|_| {
+                (0..3)
+                    .map(|_| Lit::new((next() % var_num as u64) as u32, next() % 2 == 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn sat_model_check(clauses: &[Vec<Lit>], assigns: &[LitBool]) -> bool {
+        clauses.iter().all(|clause| {
+            clause.iter().any(|lit| {
+                assigns[lit.var().0 as usize]
+                    == if lit.pos() {
+                        LitBool::True
+                    } else {
+                        LitBool::False
+                    }
+            })
+        })
+    }
+
+    #[test]
+    fn test_heapless_solve() {
+        for seed in 1..50 {
+            let clauses = random_problem(seed, 40, 170);
+            let mut solver = Solver::default();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let expected = solver.solve(None);
+
+            // The clause database is small to exercise the deletion of learnt clauses.
+            let mut heapless = Box::new(HeaplessSolver::<40, 220, 1000>::new());
+            clauses
+                .iter()
+                .for_each(|clause| assert!(heapless.add_clause(clause)));
+            let status = heapless.solve(None);
+            assert_eq!(status, expected);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, heapless.assigns()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_heapless_capacity() {
+        let mut solver = HeaplessSolver::<2, 1, 2>::new();
+        assert!(solver.add_clause(&[Lit::from(1), Lit::from(2)]));
+        // No space for literals
+        assert!(!solver.add_clause(&[Lit::from(-1), Lit::from(-2)]));
+        // Out of variable
+        assert!(!solver.add_clause(&[Lit::from(3)]));
+        assert_eq!(solver.solve(None), Status::Sat);
+    }
+}
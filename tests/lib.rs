@@ -10,17 +10,13 @@ mod tests {
             let mut satisfied = false;
             for lit in clause {
                 match assigns[lit.var().0 as usize] {
-                    LitBool::True => {
-                        if lit.pos() {
-                            satisfied = true;
-                            break;
-                        }
+                    LitBool::True if lit.pos() => {
+                        satisfied = true;
+                        break;
                     }
-                    LitBool::False => {
-                        if lit.neg() {
-                            satisfied = true;
-                            break;
-                        }
+                    LitBool::False if lit.neg() => {
+                        satisfied = true;
+                        break;
                     }
                     _ => {}
                 };
@@ -65,13 +61,15 @@ mod tests {
         }
         Ok(())
     }
-    fn test_all_files(which: &str) {
+    // Solve cnf files in `dir/sat` or `dir/unsat` and return the number of solved files.
+    fn test_all_files(dir: &str, which: &str) -> usize {
         let expected = if which == "sat" {
             Status::Sat
         } else {
             Status::Unsat
         };
-        let entries = WalkDir::new(format!("cnf/{}/", which));
+        let entries = WalkDir::new(format!("{}/{}/", dir, which));
+        let mut solved = 0;
         for entry in entries
             .into_iter()
             .filter_map(|e| e.ok())
@@ -80,6 +78,14 @@ mod tests {
             let path_str = entry.path().to_str().unwrap();
 
             if path_str.ends_with(".cnf") {
+                // The problems are stored by git-lfs. Skip a pointer file that isn't pulled yet.
+                if std::fs::read_to_string(path_str)
+                    .map(|s| s.starts_with("version https://git-lfs"))
+                    .unwrap_or(false)
+                {
+                    eprintln!("Skip!!(NOT PULLED BY GIT-LFS): {}", path_str);
+                    continue;
+                }
                 //parse cnf file
                 let input = std::fs::File::open(path_str).unwrap();
                 let cnf = util::parse_cnf(input).unwrap();
@@ -98,27 +104,2045 @@ mod tests {
                     continue;
                 }
                 if status != expected {
-                    eprintln!(
+                    panic!(
                         "cnf: {}, Result: {:?} Expected: {:?}",
                         path_str, status, expected
                     );
-                    assert!(false);
                 }
+                if status == Status::Sat && !sat_model_check(&cnf.clauses, &solver.assigns) {
+                    panic!(
+                        "Assignments are wrong!! cnf: {}, Result: {:?} Expected: {:?}",
+                        path_str, status, expected
+                    );
+                }
+                solved += 1;
+            }
+        }
+        solved
+    }
+    #[test]
+    fn test_solve() {
+        // Small problems in `tests/cnf` aren't stored by git-lfs, so all of them are solved.
+        assert_eq!(test_all_files("tests/cnf", "sat"), 4);
+        assert_eq!(test_all_files("tests/cnf", "unsat"), 5);
+        test_all_files("cnf", "sat");
+        test_all_files("cnf", "unsat");
+    }
+
+    // A tiny xorshift generator to build random 3-SAT problems without dependencies.
+    fn random_problem(seed: u64, var_num: u32, cla_num: usize) -> Vec<Vec<Lit>> {
+        let mut x = seed;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..cla_num)
+            .map(|_| {
+                (0..3)
+                    .map(|_| Lit::new((next() % var_num as u64) as u32, next() % 2 == 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_random_small() {
+        // `cnf` files may not be pulled. Check random problems by brute force.
+        for seed in 1..300 {
+            let var_num = 12;
+            let clauses = random_problem(seed, var_num, 55);
+            let expected = (0..1u32 << var_num).any(|bits| {
+                clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                })
+            });
+            let mut solver = Solver::default();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let status = solver.solve(None);
+            if expected {
+                assert_eq!(status, Status::Sat, "seed: {}", seed);
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            } else {
+                assert_eq!(status, Status::Unsat, "seed: {}", seed);
+            }
+        }
+    }
+    #[test]
+    fn test_binary_clauses() {
+        // Binary clauses are propagated by implication lists. Mix them with longer clauses.
+        for seed in 1..300 {
+            let var_num = 12;
+            let mut clauses = random_problem(seed, var_num, 40);
+            clauses.iter_mut().step_by(2).for_each(|clause| {
+                clause.pop();
+            });
+            let expected = (0..1u32 << var_num).any(|bits| {
+                clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                })
+            });
+            let mut solver = Solver::default();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let status = solver.solve(None);
+            assert_eq!(status == Status::Sat, expected, "seed: {}", seed);
+            if expected {
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            }
+        }
+    }
+    #[test]
+    fn test_reconstruction() {
+        // Eliminate variables by resolution and substitute x1 by x0. Extended models satisfy the original clauses.
+        for seed in 1..200 {
+            let var_num = 10;
+            let mut original = random_problem(seed, var_num, 30);
+            // Resolution on a tautology isn't sound.
+            original.retain(|clause| !clause.iter().any(|&lit| clause.contains(&!lit)));
+            // x0 <-> x1
+            original.push(vec![Lit::new(0, true), Lit::new(1, false)]);
+            original.push(vec![Lit::new(0, false), Lit::new(1, true)]);
+            let mut clauses = original.clone();
+            let mut reconstruction = Reconstruction::default();
+            for v in 2..5 {
+                let (pos, neg): (Vec<_>, Vec<_>) = clauses
+                    .iter()
+                    .filter(|clause| clause.iter().any(|lit| lit.var() == Var(v)))
+                    .cloned()
+                    .partition(|clause| clause.contains(&Lit::new(v, true)));
+                let mut resolvents = vec![];
+                for p in pos.iter() {
+                    for n in neg.iter() {
+                        let mut r: Vec<Lit> = p
+                            .iter()
+                            .chain(n.iter())
+                            .cloned()
+                            .filter(|lit| lit.var() != Var(v))
+                            .collect();
+                        r.sort();
+                        r.dedup();
+                        if !r.windows(2).any(|w| w[0] == !w[1]) {
+                            resolvents.push(r);
+                        }
+                    }
+                }
+                for clause in pos.iter().chain(neg.iter()) {
+                    let witness = *clause.iter().find(|lit| lit.var() == Var(v)).unwrap();
+                    reconstruction.push_clause(witness, clause);
+                }
+                clauses.retain(|clause| clause.iter().all(|lit| lit.var() != Var(v)));
+                clauses.extend(resolvents);
+            }
+            let (x0, x1) = (Lit::new(0, true), Lit::new(1, true));
+            let mut substituted: Vec<Vec<Lit>> = clauses
+                .iter()
+                .map(|clause| {
+                    clause
+                        .iter()
+                        .map(|&lit| {
+                            if lit.var() == x1.var() {
+                                if lit.pos() {
+                                    x0
+                                } else {
+                                    !x0
+                                }
+                            } else {
+                                lit
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            reconstruction.push_equivalence(x1, x0);
+            substituted.push(vec![
+                Lit::new(var_num - 1, true),
+                Lit::new(var_num - 1, false),
+            ]);
+            assert!(!reconstruction.is_empty());
+
+            let mut solver = Solver::default();
+            substituted
+                .iter()
+                .for_each(|clause| solver.add_clause(clause));
+            if solver.solve(None) == Status::Sat {
+                let mut model = solver.assigns.clone();
+                model.resize(var_num as usize, LitBool::Undef);
+                reconstruction.extend(&mut model);
+                assert!(sat_model_check(&original, &model), "seed: {}", seed);
+                assert_eq!(model[0], model[1]);
+            } else {
+                let mut solver = Solver::default();
+                original.iter().for_each(|clause| solver.add_clause(clause));
+                assert_eq!(solver.solve(None), Status::Unsat, "seed: {}", seed);
+            }
+        }
+    }
+    #[test]
+    fn test_add_clauses() {
+        for seed in 1..300 {
+            let var_num = 12;
+            let mut clauses = random_problem(seed, var_num, 50);
+            // units, a duplicated literal and a tautology
+            clauses.push(vec![Lit::new(seed as u32 % var_num, true)]);
+            clauses.push(vec![Lit::new(3, false), Lit::new(3, false)]);
+            clauses.push(vec![Lit::new(5, false), Lit::new(5, true)]);
+            let expected = (0..1u32 << var_num).any(|bits| {
+                clauses.iter().all(|clause| {
+                    clause
+                        .iter()
+                        .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                })
+            });
+            // Add clauses in bulk after some clauses are added and solved.
+            let mut solver = Solver::default();
+            clauses[..10]
+                .iter()
+                .for_each(|clause| solver.add_clause(clause));
+            solver.solve(None);
+            solver.add_clauses(&clauses[10..]);
+            let status = solver.solve(None);
+            if expected {
+                assert_eq!(status, Status::Sat, "seed: {}", seed);
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            } else {
+                assert_eq!(status, Status::Unsat, "seed: {}", seed);
+            }
+        }
+    }
+    #[test]
+    fn test_incremental_clauses() {
+        // Clauses are added between `solve` calls. Units make later clauses unit or falsified at the root level.
+        for seed in 1..200 {
+            let var_num = 12;
+            let mut clauses = random_problem(seed, var_num - 2, 50);
+            clauses.insert(10, vec![Lit::new(seed as u32 % 10, true)]);
+            clauses.insert(
+                20,
+                vec![Lit::new(seed as u32 % 10, false), Lit::new(10, true)],
+            );
+            // New variables grow a solver.
+            clauses.push(vec![Lit::new(10, false), Lit::new(11, true)]);
+            let mut solver = Solver::default();
+            for len in (5..=clauses.len()).step_by(5) {
+                clauses[len - 5..len]
+                    .iter()
+                    .for_each(|clause| solver.add_clause(clause));
+                let expected = (0..1u32 << var_num).any(|bits| {
+                    clauses[..len].iter().all(|clause| {
+                        clause
+                            .iter()
+                            .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                    })
+                });
+                let status = solver.solve(None);
+                assert_eq!(status == Status::Sat, expected, "seed: {}", seed);
+                if expected {
+                    assert!(sat_model_check(&clauses[..len], &solver.assigns));
+                }
+            }
+        }
+        // A clause that is falsified by units makes a solver unsatisfiable.
+        let mut solver = Solver::default();
+        solver.add_clause(&[Lit::from(1)]);
+        solver.add_clause(&[Lit::from(-1), Lit::from(2)]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        solver.add_clause(&[Lit::from(-2), Lit::from(-1)]);
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+    #[test]
+    fn test_solve_with_assumptions() {
+        let satisfiable = |clauses: &[Vec<Lit>], assumptions: &[Lit]| {
+            (0..1u32 << 12).any(|bits| {
+                let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                assumptions.iter().all(value) && clauses.iter().all(|c| c.iter().any(value))
+            })
+        };
+        for seed in 1..200 {
+            let clauses = random_problem(seed, 12, 45);
+            let mut solver = Solver::default();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            for i in 0..5u64 {
+                let assumptions: Vec<Lit> = (0..i)
+                    .map(|j| Lit::new(((seed + 5 * j) % 12) as u32, (seed + i + j) % 3 == 0))
+                    .collect();
+                let expected = satisfiable(&clauses, &assumptions);
+                let status = solver.solve_with_assumptions(&assumptions, None);
+                assert_eq!(status == Status::Sat, expected, "seed: {}", seed);
+                if expected {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                    assert!(sat_model_check(
+                        &assumptions.iter().map(|&lit| vec![lit]).collect::<Vec<_>>(),
+                        &solver.assigns
+                    ));
+                }
+            }
+            // Assumptions aren't kept after a call.
+            assert_eq!(
+                solver.solve(None) == Status::Sat,
+                satisfiable(&clauses, &[])
+            );
+        }
+        // Contradictory assumptions and an assumption of a new variable
+        let mut solver = Solver::default();
+        solver.add_clause(&[Lit::from(1), Lit::from(2)]);
+        let status = solver.solve_with_assumptions(&[Lit::from(-1), Lit::from(1)], None);
+        assert_eq!(status, Status::Unsat);
+        let status = solver.solve_with_assumptions(&[Lit::from(-1), Lit::from(3)], None);
+        assert_eq!(status, Status::Sat);
+        assert_eq!(
+            solver.assigns,
+            vec![LitBool::False, LitBool::True, LitBool::True]
+        );
+        assert_eq!(solver.solve(None), Status::Sat);
+    }
+    #[test]
+    fn test_unsat_core() {
+        let satisfiable = |clauses: &[Vec<Lit>], assumptions: &[Lit]| {
+            (0..1u32 << 12).any(|bits| {
+                let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                assumptions.iter().all(value) && clauses.iter().all(|c| c.iter().any(value))
+            })
+        };
+        let mut refuted = 0;
+        for seed in 1..200 {
+            let clauses = random_problem(seed, 12, 40);
+            let mut solver = Solver::default();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let assumptions: Vec<Lit> = (0..6)
+                .map(|j| Lit::new(2 * j as u32 + seed as u32 % 2, (seed >> j) % 2 == 0))
+                .collect();
+            if solver.solve_with_assumptions(&assumptions, None) != Status::Unsat {
+                continue;
+            }
+            let core = solver.unsat_core().to_vec();
+            assert!(core.iter().all(|lit| assumptions.contains(lit)));
+            assert!(!satisfiable(&clauses, &core));
+            // An empty core means that the conflict doesn't depend on assumptions.
+            assert!(!core.is_empty() || !satisfiable(&clauses, &[]));
+            refuted += 1;
+        }
+        assert!(refuted > 10);
+        // x1 -> x2 refutes x1 and -x2 without x3.
+        let mut solver = Solver::default();
+        solver.add_clause(&[Lit::from(-1), Lit::from(2)]);
+        let status =
+            solver.solve_with_assumptions(&[Lit::from(3), Lit::from(1), Lit::from(-2)], None);
+        assert_eq!(status, Status::Unsat);
+        let mut core = solver.unsat_core().to_vec();
+        core.sort();
+        let mut expected = vec![Lit::from(1), Lit::from(-2)];
+        expected.sort();
+        assert_eq!(core, expected);
+    }
+    #[test]
+    fn test_config() {
+        for name in ["default", "sat", "unsat", "crypto", "planning"].iter() {
+            let config = Config::preset(name).unwrap();
+            for seed in 1..50 {
+                let clauses = random_problem(seed, 12, 55);
+                let mut reference = Solver::default();
+                clauses
+                    .iter()
+                    .for_each(|clause| reference.add_clause(clause));
+                let mut solver = Solver::default();
+                solver.set_config(config.clone());
+                clauses.iter().for_each(|clause| solver.add_clause(clause));
+                assert_eq!(
+                    solver.solve(None),
+                    reference.solve(None),
+                    "{} {}",
+                    name,
+                    seed
+                );
+            }
+        }
+        assert!(Config::preset("unknown").is_none());
+
+        // Presets are compared by their parameters and by the restarts of their restart policies.
+        let same = |a: &Config, b: &Config| {
+            let restarts = |config: &Config| {
+                let mut policy = config.restart.start();
+                (0..10000).filter(|_| policy.on_conflict(2, 10)).count()
+            };
+            let budgets =
+                |config: &Config| config.simplifiers.iter().map(|s| s.1).collect::<Vec<_>>();
+            a.learnt_size_factor == b.learnt_size_factor
+                && a.learnt_size_inc == b.learnt_size_inc
+                && a.polarity == b.polarity
+                && budgets(a) == budgets(b)
+                && a.clause_decay == b.clause_decay
+                && a.minimization == b.minimization
+                && a.chronological == b.chronological
+                && a.on_the_fly_subsumption == b.on_the_fly_subsumption
+                && a.random_decisions == b.random_decisions
+                && a.random_seed == b.random_seed
+                && a.jeroslow_wang == b.jeroslow_wang
+                && a.rephase == b.rephase
+                && restarts(a) == restarts(b)
+        };
+        let presets = [
+            Config::default(),
+            Config::sat(),
+            Config::unsat(),
+            Config::crypto(),
+            Config::planning(),
+        ];
+        for (i, a) in presets.iter().enumerate() {
+            for (j, b) in presets.iter().enumerate() {
+                assert_eq!(same(a, b), i == j, "{} {}", i, j);
+            }
+        }
+        // A used restart policy doesn't change a preset.
+        let sat = Config::sat();
+        let mut solver = Solver::default();
+        solver.set_config(sat.clone());
+        solver.add_clauses(&random_problem(1, 100, 430));
+        solver.solve(None);
+        assert!(same(&sat, &Config::sat()));
+
+        assert!(same(
+            &Config::auto(&random_problem(1, 100, 300)),
+            &Config::sat()
+        ));
+        assert!(same(
+            &Config::auto(&random_problem(1, 100, 500)),
+            &Config::unsat()
+        ));
+        assert!(same(
+            &Config::auto(&random_problem(1, 100, 430)),
+            &Config::default()
+        ));
+        let binary: Vec<Vec<Lit>> = random_problem(1, 100, 300)
+            .into_iter()
+            .map(|clause| clause[..2].to_vec())
+            .collect();
+        assert!(same(&Config::auto(&binary), &Config::planning()));
+        // x0 ^ x1 ^ x2 = 1
+        let xor: Vec<Vec<Lit>> = (0..8u32)
+            .filter(|bits| bits.count_ones() % 2 == 0)
+            .map(|bits| (0..3).map(|v| Lit::new(v, bits >> v & 1 == 0)).collect())
+            .collect();
+        assert!(same(&Config::auto(&xor), &Config::crypto()));
+    }
+    #[test]
+    fn test_solver_pool() {
+        let pool = SolverPool::default();
+        for seed in 1..200 {
+            // Different sizes of problems reuse the same buffers.
+            let var_num = 8 + (seed % 5) as u32;
+            let clauses = random_problem(seed, var_num, 40);
+            let mut reference = Solver::new(var_num as usize, &clauses);
+            let mut solver = pool.new_solver(var_num as usize, &clauses);
+            let status = solver.solve(None);
+            assert_eq!(status, reference.solve(None), "seed: {}", seed);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            }
+            pool.recycle(solver);
+        }
+    }
+    #[test]
+    fn test_asymmetric_budget() {
+        // Asymmetric literal elimination must not change results whether it is cut off or disabled.
+        for budget in [0, 1, 3, 100].iter() {
+            for seed in 1..100 {
+                let clauses = random_problem(seed, 30, 128);
+                let mut reference = Solver::new(30, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    simplifiers: vec![
+                        (std::sync::Arc::new(RemoveSatisfied), 0),
+                        (std::sync::Arc::new(AsymmetricElimination), *budget),
+                    ],
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{} {}", budget, seed);
                 if status == Status::Sat {
-                    if !sat_model_check(&cnf.clauses, &solver.assigns) {
-                        eprintln!(
-                            "Assignments are wrong!! cnf: {}, Result: {:?} Expected: {:?}",
-                            path_str, status, expected
-                        );
-                        assert!(false);
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_simplification_units() {
+        // Units of simplifications at restarts are propagated at the root level before the next decision.
+        // Debug builds check that clauses aren't strengthened by literals of higher levels.
+        for seed in 1..300 {
+            let clauses = random_problem(seed, 15, 60);
+            let mut reference = Solver::new(15, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            let status = solver.solve(None);
+            assert_eq!(status, reference.solve(None), "seed: {}", seed);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            }
+        }
+    }
+    #[test]
+    fn test_incremental_asymmetric_elimination() {
+        // Learnt clauses of earlier calls don't make asymmetric literal elimination delete original clauses.
+        let parse = |cs: &[&str]| -> Vec<Vec<Lit>> {
+            cs.iter()
+                .map(|c| {
+                    c.split_whitespace()
+                        .map(|x| Lit::from(x.parse::<i32>().unwrap()))
+                        .collect()
+                })
+                .collect()
+        };
+        let satisfiable = |clauses: &[Vec<Lit>], assumptions: &[Lit]| {
+            (0..1u32 << 14).any(|bits| {
+                let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                assumptions.iter().all(value) && clauses.iter().all(|c| c.iter().any(value))
+            })
+        };
+        let configs = [
+            Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                learnt_size_factor: 0.05,
+                ..Config::default()
+            },
+            Config {
+                simplifiers: vec![
+                    (std::sync::Arc::new(AsymmetricElimination), 50),
+                    (std::sync::Arc::new(VariableElimination), 100),
+                ],
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                learnt_size_factor: 0.05,
+                ..Config::default()
+            },
+        ];
+        let check = |solver: &mut Solver, clauses: &[Vec<Lit>], assumptions: &[Lit]| {
+            let expected = satisfiable(clauses, assumptions);
+            let status = solver.solve_with_assumptions(assumptions, None);
+            assert_eq!(status == Status::Sat, expected, "{:?}", assumptions);
+            if expected {
+                assert!(sat_model_check(clauses, &solver.assigns));
+                assert!(assumptions
+                    .iter()
+                    .all(
+                        |lit| (solver.assigns[lit.var().0 as usize] == LitBool::True) == lit.pos()
+                    ));
+            }
+        };
+        for config in configs.iter().skip(1) {
+            let batches = [
+                (
+                    parse(&[
+                        "-6 -3 -3 12",
+                        "7 13",
+                        "-5 -10 9 -5",
+                        "4 6 -2",
+                        "-7 -4 2 -13",
+                        "-9 4 7",
+                        "-6 10",
+                    ]),
+                    vec![dimacs_lits(&[11, 6, 13]), dimacs_lits(&[5, 1, -4])],
+                ),
+                (
+                    parse(&[
+                        "-5 8 12",
+                        "-7 1 2 2",
+                        "-5 13 -1 -4",
+                        "4 8 2 2",
+                        "-2 -5 -5",
+                        "-7 2 5",
+                        "5 -4 -4 -2",
+                    ]),
+                    vec![dimacs_lits(&[-12, -6, 7])],
+                ),
+                (parse(&["-12 3", "-6 -8", "-13 5 2 -10"]), vec![]),
+                (parse(&["8 -3", "-13 6", "10 6 2", "-10 4"]), vec![vec![]]),
+            ];
+            let mut solver = Solver::default();
+            solver.set_config(config.clone());
+            let mut clauses = vec![];
+            for (batch, calls) in batches.iter() {
+                solver.add_clauses(batch);
+                clauses.extend(batch.iter().cloned());
+                for assumptions in calls.iter() {
+                    check(&mut solver, &clauses, assumptions);
+                }
+            }
+
+            // Batches of random clauses are solved under a few assumptions.
+            for seed in 1..150 {
+                let problem = random_problem(seed, 14, 64);
+                let mut solver = Solver::default();
+                solver.set_config(config.clone());
+                for (i, batch) in problem.chunks(8).enumerate() {
+                    solver.add_clauses(batch);
+                    let clauses = &problem[..8 * i + batch.len()];
+                    for j in 0..3u64 {
+                        let assumptions: Vec<Lit> = (0..j)
+                            .map(|k| {
+                                Lit::new(
+                                    ((seed + 5 * k + i as u64) % 14) as u32,
+                                    (seed + j + k) % 2 == 0,
+                                )
+                            })
+                            .collect();
+                        check(&mut solver, clauses, &assumptions);
                     }
                 }
             }
         }
     }
+    #[derive(Debug, Default)]
+    struct CountingPolicy(std::sync::atomic::AtomicUsize);
+    impl DeletionPolicy for CountingPolicy {
+        fn select(&self, learnts: &[LearntClause], deleted: &mut [bool]) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            assert!(learnts.iter().all(|c| c.len >= 2 && c.lbd >= 1));
+            // Delete all clauses. Binary clauses and reasons must survive.
+            deleted.iter_mut().for_each(|d| *d = true);
+        }
+    }
     #[test]
-    fn test_solve() {
-        test_all_files("sat");
-        test_all_files("unsat");
+    fn test_deletion_policy() {
+        let counting = std::sync::Arc::new(CountingPolicy::default());
+        let policies: Vec<std::sync::Arc<dyn DeletionPolicy>> = vec![
+            std::sync::Arc::new(SizePolicy),
+            std::sync::Arc::new(ActivityPolicy),
+            std::sync::Arc::new(LbdPolicy::default()),
+            std::sync::Arc::new(TieredPolicy::default()),
+            std::sync::Arc::new(SizeBoundPolicy { max_len: 8 }),
+            counting.clone(),
+        ];
+        for policy in policies.iter() {
+            for seed in 1..20 {
+                let clauses = random_problem(seed, 60, 258);
+                let mut reference = Solver::new(60, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    deletion: policy.clone(),
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{:?} {}", policy, seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+        assert!(counting.0.load(std::sync::atomic::Ordering::Relaxed) > 0);
+
+        // core, used tier2, unused tier2 and local clauses
+        let learnts: Vec<LearntClause> = [(2, false), (5, true), (5, false), (9, true)]
+            .iter()
+            .map(|&(lbd, used)| LearntClause {
+                len: 10,
+                lbd,
+                activity: 0.0,
+                used,
+            })
+            .collect();
+        let mut deleted = vec![false; learnts.len()];
+        TieredPolicy::default().select(&learnts, &mut deleted);
+        assert_eq!(deleted, vec![false, false, false, true]);
+    }
+    #[test]
+    fn test_minimization() {
+        for &minimization in [
+            Minimization::None,
+            Minimization::Local,
+            Minimization::Recursive,
+        ]
+        .iter()
+        {
+            for seed in 1..20 {
+                let clauses = random_problem(seed, 60, 258);
+                let mut reference = Solver::new(60, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    minimization,
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{:?} {}", minimization, seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+        assert_ne!(Config::default().minimization, Minimization::Local);
+    }
+    #[test]
+    fn test_chronological_backtracking() {
+        for &limit in [0, 5].iter() {
+            for seed in 1..20 {
+                let clauses = random_problem(seed, 100, 426);
+                let mut reference = Solver::new(100, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    chronological: Some(limit),
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{} {}", limit, seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_random_decisions() {
+        let config = |seed| Config {
+            random_decisions: 0.2,
+            random_seed: seed,
+            ..Config::default()
+        };
+        for seed in 1..20 {
+            let clauses = random_problem(seed, 60, 250);
+            let mut reference = Solver::new(60, &clauses);
+            let expected = reference.solve(None);
+            let mut models = vec![];
+            for &random_seed in [seed, seed, seed + 100].iter() {
+                let mut solver = Solver::default();
+                solver.set_config(config(random_seed));
+                solver.add_clauses(&clauses);
+                assert_eq!(solver.solve(None), expected, "{}", seed);
+                if expected == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+                models.push(solver.assigns);
+            }
+            // The same seed reproduces the same search.
+            assert_eq!(models[0], models[1]);
+        }
+        assert_ne!(config(1).random_seed, config(2).random_seed);
+    }
+    #[test]
+    fn test_jeroslow_wang() {
+        let config = Config {
+            jeroslow_wang: true,
+            ..Config::default()
+        };
+        for seed in 1..20 {
+            let clauses = random_problem(seed, 60, 255);
+            let mut reference = Solver::new(60, &clauses);
+            let expected = reference.solve(None);
+            // Scores are computed by `set_config` for existing clauses and by `add_clauses` for new clauses.
+            let mut before = Solver::default();
+            before.set_config(config.clone());
+            before.add_clauses(&clauses);
+            let mut after = Solver::new(60, &clauses);
+            after.set_config(config.clone());
+            for solver in [before, after].iter_mut() {
+                assert_eq!(solver.solve(None), expected, "{}", seed);
+                if expected == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+        // Every variable prefers the polarity that satisfies more short clauses.
+        let mut solver = Solver::default();
+        solver.set_config(config);
+        solver.add_clauses(&[
+            vec![Lit::from(1), Lit::from(2)],
+            vec![Lit::from(1), Lit::from(3)],
+            vec![Lit::from(-1), Lit::from(4)],
+        ]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(solver.assigns, vec![LitBool::True; 4]);
+    }
+    #[test]
+    fn test_rephase() {
+        for seed in 1..20 {
+            let clauses = random_problem(seed, 100, 426);
+            let mut reference = Solver::new(100, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                rephase: Some(20),
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            let status = solver.solve(None);
+            assert_eq!(status, reference.solve(None), "{}", seed);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            }
+        }
+    }
+    #[test]
+    fn test_variable_elimination() {
+        for seed in 1..30 {
+            let mut clauses = random_problem(seed, 80, 300);
+            let more = random_problem(seed + 1000, 80, 40);
+            let mut reference = Solver::new(80, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(VariableElimination), 10000),
+                ],
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            // Clauses that have eliminated variables put them back.
+            for added in [vec![], more].iter() {
+                reference.add_clauses(added);
+                solver.add_clauses(added);
+                clauses.extend(added.iter().cloned());
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns), "{}", seed);
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_add_clause_after_unsat() {
+        // Clauses added to an unsatisfiable solver don't put eliminated variables back.
+        let satisfiable = |clauses: &[Vec<Lit>]| {
+            (0..1u32 << 12).any(|bits| {
+                let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                clauses.iter().all(|c| c.iter().any(value))
+            })
+        };
+        let mut unsat = 0;
+        for seed in 1..300 {
+            let problem = random_problem(seed, 12, 60);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(VariableElimination), 10000),
+                ],
+                ..Config::default()
+            });
+            for (i, chunk) in problem.chunks(10).enumerate() {
+                let clauses = &problem[..10 * i + chunk.len()];
+                solver.add_clauses(chunk);
+                let status = solver.solve(None);
+                assert_eq!(
+                    status == Status::Sat,
+                    satisfiable(clauses),
+                    "seed: {}",
+                    seed
+                );
+                if status == Status::Unsat {
+                    // Clauses with eliminated variables don't change an unsatisfiable solver.
+                    let snapshot = |solver: &Solver| {
+                        let mut snapshot = vec![];
+                        solver.write_snapshot(&mut snapshot, false).unwrap();
+                        snapshot
+                    };
+                    let before = snapshot(&solver);
+                    solver
+                        .add_clause(&(0..12).map(|v| Lit::new(v, v % 2 == 0)).collect::<Vec<_>>());
+                    solver.add_clause(&[Lit::new((seed % 12) as u32, true)]);
+                    assert!(before == snapshot(&solver), "seed: {}", seed);
+                    unsat += 1;
+                }
+                let assumptions = [Lit::new((seed % 12) as u32, i % 2 == 0)];
+                if solver.solve_with_assumptions(&assumptions, None) == Status::Sat {
+                    assert!(sat_model_check(clauses, &solver.assigns), "seed: {}", seed);
+                }
+            }
+        }
+        assert!(unsat > 0);
+    }
+    #[test]
+    fn test_freeze() {
+        // Eliminated variables in a snapshot are after its header and root level literals.
+        let eliminated = |solver: &Solver| {
+            let mut snapshot = vec![];
+            solver.write_snapshot(&mut snapshot, false).unwrap();
+            let words: Vec<u32> = snapshot[8..]
+                .chunks(4)
+                .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+                .collect();
+            let (units, eliminated) = (words[2] as usize, words[3] as usize);
+            words[7 + units..7 + units + eliminated].to_vec()
+        };
+        let mut removed = 0;
+        for seed in 1..30 {
+            let mut clauses = random_problem(seed, 60, 200);
+            for w in random_problem(seed + 500, 60, 12).iter() {
+                clauses.push(vec![!w[0], w[1]]);
+                clauses.push(vec![w[0], !w[1]]);
+            }
+            // Later clauses have only frozen variables.
+            let more = random_problem(seed + 1000, 20, 30);
+            let mut reference = Solver::new(60, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(VariableElimination), 10000),
+                ],
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            (0..20).for_each(|v| solver.freeze(Var(v)));
+            assert!(solver.is_frozen(Var(19)) && !solver.is_frozen(Var(20)));
+            for added in [vec![], more].iter() {
+                reference.add_clauses(added);
+                solver.add_clauses(added);
+                clauses.extend(added.iter().cloned());
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns), "{}", seed);
+                }
+                let eliminated = eliminated(&solver);
+                assert!(eliminated.iter().all(|&v| v >= 20), "{}", seed);
+                removed += eliminated.len();
+            }
+        }
+        assert!(removed > 0);
+        // Freezing counts.
+        let mut solver = Solver::default();
+        solver.freeze(Var(3));
+        solver.freeze(Var(3));
+        solver.melt(Var(3));
+        assert!(solver.is_frozen(Var(3)));
+        solver.melt(Var(3));
+        assert!(!solver.is_frozen(Var(3)));
+
+        // Assumed variables are frozen during a search, so elimination between restarts doesn't flip them.
+        let mut solver = Solver::default();
+        solver.set_config(Config {
+            simplifiers: vec![(std::sync::Arc::new(VariableElimination), 100)],
+            branching: std::sync::Arc::new(Vsids::default()),
+            jeroslow_wang: true,
+            restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+            ..Config::default()
+        });
+        for batch in [
+            &[
+                &[-14, -9, -6][..],
+                &[13, 10],
+                &[-10, 14, 14, -2],
+                &[8, -2, 1],
+                &[5, 9],
+                &[2, 6],
+            ][..],
+            &[&[5, 9], &[12, -3], &[-12, -3]],
+            &[&[-6, 9, -4, 1], &[3, 6]],
+            &[&[-9, 2]],
+        ] {
+            batch
+                .iter()
+                .for_each(|c| solver.add_clause(&dimacs_lits(c)));
+            assert_eq!(solver.solve(None), Status::Sat);
+        }
+        assert_eq!(
+            solver.solve_with_assumptions(&[Lit::from(-13)], None),
+            Status::Sat
+        );
+        assert_eq!(solver.assigns[12], LitBool::False);
+        assert!(!solver.is_frozen(Var(12)));
+
+        // Pure literal elimination between restarts doesn't flip assumed variables either.
+        for seed in 1..200 {
+            let clauses = random_problem(seed, 12, 48);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                simplifiers: vec![
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                ],
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                ..Config::default()
+            });
+            for len in (8..=clauses.len()).step_by(8) {
+                solver.add_clauses(&clauses[len - 8..len]);
+                let assumptions = [Lit::new((seed + len as u64) as u32 % 12, seed % 2 == 0)];
+                let expected = (0..1u32 << 12).any(|bits| {
+                    let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                    value(&assumptions[0]) && clauses[..len].iter().all(|c| c.iter().any(value))
+                });
+                let status = solver.solve_with_assumptions(&assumptions, None);
+                assert_eq!(status == Status::Sat, expected, "{}", seed);
+                if expected {
+                    assert!(
+                        sat_model_check(&clauses[..len], &solver.assigns),
+                        "{}",
+                        seed
+                    );
+                    assert!(sat_model_check(&[assumptions.to_vec()], &solver.assigns));
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_equivalent_literal_substitution() {
+        let mut unsat = 0;
+        for seed in 1..60 {
+            // Random 3-SAT with chains of equivalent literals.
+            let mut clauses = random_problem(seed, 60, 200);
+            for w in random_problem(seed + 500, 60, 12).iter() {
+                clauses.push(vec![!w[0], w[1]]);
+                clauses.push(vec![w[0], !w[1]]);
+            }
+            let more = random_problem(seed + 1000, 60, 20);
+            let mut reference = Solver::new(60, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                ],
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            for added in [vec![], more].iter() {
+                reference.add_clauses(added);
+                solver.add_clauses(added);
+                clauses.extend(added.iter().cloned());
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns), "{}", seed);
+                } else {
+                    unsat += 1;
+                }
+            }
+        }
+        assert!(unsat > 0);
+    }
+    #[test]
+    fn test_equivalent_literal_substitution_incremental() {
+        // Equivalent literal substitution may find the problem unsatisfiable in the middle of rewriting.
+        // Solving and adding clauses afterwards must still work.
+        let satisfiable = |clauses: &[Vec<Lit>], assumptions: &[Lit]| {
+            (0..1u32 << 12).any(|bits| {
+                let value = |lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                assumptions.iter().all(value) && clauses.iter().all(|c| c.iter().any(value))
+            })
+        };
+        for seed in 1500..1700 {
+            let problem = random_problem(seed, 12, 60);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(2.0, 1.05)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                ],
+                ..Config::default()
+            });
+            for (i, chunk) in problem.chunks(10).enumerate() {
+                solver.add_clauses(chunk);
+                let clauses = &problem[..10 * i + chunk.len()];
+                let status = solver.solve(None);
+                assert_eq!(
+                    status == Status::Sat,
+                    satisfiable(clauses, &[]),
+                    "seed: {}",
+                    seed
+                );
+                let assumptions = [Lit::new((seed % 12) as u32, i % 2 == 0)];
+                let status = solver.solve_with_assumptions(&assumptions, None);
+                assert_eq!(
+                    status == Status::Sat,
+                    satisfiable(clauses, &assumptions),
+                    "seed: {}",
+                    seed
+                );
+                if status == Status::Sat {
+                    assert!(sat_model_check(clauses, &solver.assigns), "seed: {}", seed);
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_failed_literal_probing() {
+        let mut unsat = 0;
+        for seed in 1..60 {
+            let mut clauses = random_problem(seed, 60, 250);
+            let more = random_problem(seed + 1000, 60, 10);
+            let mut reference = Solver::new(60, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(FailedLiteralProbing), 60),
+                ],
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            for added in [vec![], more].iter() {
+                reference.add_clauses(added);
+                solver.add_clauses(added);
+                clauses.extend(added.iter().cloned());
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns), "{}", seed);
+                } else {
+                    unsat += 1;
+                }
+            }
+        }
+        assert!(unsat > 0);
+    }
+    #[test]
+    fn test_on_the_fly_subsumption() {
+        for seed in 1..20 {
+            let clauses = random_problem(seed, 100, 426);
+            let mut reference = Solver::new(100, &clauses);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                on_the_fly_subsumption: true,
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            let status = solver.solve(None);
+            assert_eq!(status, reference.solve(None), "{}", seed);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, &solver.assigns));
+            }
+        }
+    }
+    // Restart at every conflict and count restarts.
+    #[derive(Debug, Default, Clone)]
+    struct EagerRestart(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+    impl RestartPolicy for EagerRestart {
+        fn on_conflict(&mut self, lbd: u32, trail_len: usize) -> bool {
+            assert!(lbd >= 1 && trail_len >= 1);
+            true
+        }
+        fn on_restart(&mut self) {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        }
+        fn start(&self) -> Box<dyn RestartPolicy> {
+            Box::new(self.clone())
+        }
+    }
+    #[test]
+    fn test_restart_policy() {
+        let eager = EagerRestart::default();
+        let policies: Vec<std::sync::Arc<dyn RestartPolicy>> = vec![
+            std::sync::Arc::new(GeometricRestart::new(10.0, 1.2)),
+            std::sync::Arc::new(LubyRestart::new(4)),
+            std::sync::Arc::new(GlucoseRestart::new(5, 0.8)),
+            std::sync::Arc::new(eager.clone()),
+        ];
+        for policy in policies.iter() {
+            for seed in 1..20 {
+                let clauses = random_problem(seed, 60, 258);
+                let mut reference = Solver::new(60, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    restart: policy.clone(),
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{:?} {}", policy, seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+        assert!(eager.0.load(std::sync::atomic::Ordering::Relaxed) > 0);
+    }
+    // Decide variables in index order.
+    #[derive(Debug, Default)]
+    struct InOrder {
+        candidates: std::collections::BTreeSet<Var>,
+    }
+    impl BranchingHeuristic for InOrder {
+        fn new_var(&mut self, v: Var) {
+            self.candidates.insert(v);
+        }
+        fn unassign(&mut self, v: Var) {
+            self.candidates.insert(v);
+        }
+        fn bump(&mut self, _v: Var) {}
+        fn decay(&mut self) {}
+        fn select(&mut self) -> Option<Var> {
+            let v = *self.candidates.iter().next()?;
+            self.candidates.remove(&v);
+            Some(v)
+        }
+        fn start(&self) -> Box<dyn BranchingHeuristic> {
+            Box::new(InOrder::default())
+        }
+    }
+    #[test]
+    fn test_branching_heuristic() {
+        let heuristics: Vec<std::sync::Arc<dyn BranchingHeuristic>> = vec![
+            std::sync::Arc::new(Vsids::default()),
+            std::sync::Arc::new(Vmtf::default()),
+            std::sync::Arc::new(Lrb::default()),
+            std::sync::Arc::new(Alternating::new(
+                Box::new(Lrb::default()),
+                Box::new(Vsids::default()),
+                7,
+            )),
+            std::sync::Arc::new(InOrder::default()),
+        ];
+        for heuristic in heuristics.iter() {
+            for seed in 1..20 {
+                let clauses = random_problem(seed, 60, 258);
+                let mut reference = Solver::new(60, &clauses);
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    branching: heuristic.clone(),
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, reference.solve(None), "{:?} {}", heuristic, seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+        }
+    }
+    // Add original clauses again. They are implied, so results must not change.
+    #[derive(Debug)]
+    struct AddAgain {
+        clauses: Vec<Vec<Lit>>,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+    impl Simplifier for AddAgain {
+        fn simplify(&self, solver: &mut Solver, budget: usize) {
+            self.calls
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            self.clauses
+                .iter()
+                .take(budget)
+                .for_each(|clause| solver.add_clause(clause));
+        }
+    }
+    #[test]
+    fn test_simplifiers() {
+        let mut calls = 0;
+        for seed in 1..40 {
+            let clauses = random_problem(seed, 60, 258);
+            let again = std::sync::Arc::new(AddAgain {
+                clauses: clauses.clone(),
+                calls: Default::default(),
+            });
+            let pipelines: Vec<Vec<(std::sync::Arc<dyn Simplifier>, usize)>> = vec![
+                vec![],
+                vec![(std::sync::Arc::new(AsymmetricElimination), 10)],
+                vec![
+                    (std::sync::Arc::new(AsymmetricElimination), 3),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(AsymmetricElimination), 100),
+                ],
+                vec![
+                    (again.clone(), 20),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                ],
+                vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(Vivification), 100),
+                ],
+                vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(VariableElimination), 1000),
+                    (std::sync::Arc::new(AsymmetricElimination), 100),
+                ],
+                vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(Subsumption), 10000),
+                ],
+                vec![
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                ],
+                vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                    (std::sync::Arc::new(AsymmetricElimination), 100),
+                ],
+                vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(FailedLiteralProbing), 30),
+                ],
+            ];
+            let mut reference = Solver::new(60, &clauses);
+            let expected = reference.solve(None);
+            for simplifiers in pipelines {
+                let mut solver = Solver::default();
+                solver.set_config(Config {
+                    simplifiers,
+                    ..Config::default()
+                });
+                solver.add_clauses(&clauses);
+                let status = solver.solve(None);
+                assert_eq!(status, expected, "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                }
+            }
+            calls += again.calls.load(std::sync::atomic::Ordering::Relaxed);
+        }
+        assert!(calls > 0);
+    }
+    #[test]
+    fn test_lit() {
+        let lit = Lit::from(-3);
+        assert_eq!(lit.var(), Var(2));
+        assert!(lit.neg() && (!lit).pos());
+        assert_eq!(!!lit, lit);
+        assert_eq!(Lit::new(2, true), !lit);
+        // Literals of a variable are next to each other.
+        assert!(Lit::from(2) < Lit::from(-2) && Lit::from(-2) < Lit::from(3));
+        assert_eq!(format!("{} {} {}", lit, !lit, lit.var()), "-3 3 3");
+        assert_eq!(Lit::from(1).to_string(), "1");
+    }
+    #[test]
+    fn test_new_var() {
+        let mut solver = Solver::new(2, &[vec![Lit::from(1), Lit::from(2)]]);
+        let vars = solver.new_vars(2);
+        assert_eq!(vars, vec![Var(2), Var(3)]);
+        let lit = |v: Var| Lit::new(v.0, true);
+        solver.add_clause(&[!lit(vars[0]), !lit(vars[1])]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        // Variables are added between `solve` calls.
+        let v = solver.new_var();
+        assert_eq!(v, Var(4));
+        assert_eq!(solver.assigns.len(), 5);
+        solver.add_clause(&[lit(v)]);
+        solver.add_clause(&[!lit(v), lit(vars[0])]);
+        solver.add_clause(&[!lit(v), lit(vars[1])]);
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+    #[test]
+    fn test_model() {
+        let clauses = vec![
+            vec![Lit::from(1), Lit::from(2)],
+            vec![Lit::from(-1)],
+            vec![Lit::from(-2), Lit::from(-3)],
+        ];
+        let mut solver = Solver::new(3, &clauses);
+        assert!(solver.model().is_none());
+        assert_eq!(solver.solve(None), Status::Sat);
+        let model = solver.model().unwrap();
+        assert_eq!(model[Var(0)], LitBool::False);
+        assert_eq!(model[Lit::from(-1)], LitBool::True);
+        assert_eq!(model[Lit::from(2)], LitBool::True);
+        assert_eq!(model[Lit::from(-3)], LitBool::True);
+        assert_eq!(model[Var(7)], LitBool::Undef);
+        let lits: Vec<Lit> = model.lits().collect();
+        assert_eq!(lits, vec![Lit::from(-1), Lit::from(2), Lit::from(-3)]);
+        // A new clause invalidates a model.
+        solver.add_clause(&[Lit::from(3)]);
+        assert!(solver.model().is_none());
+        assert_eq!(solver.solve(None), Status::Unsat);
+        assert!(solver.model().is_none());
+    }
+    #[test]
+    fn test_value() {
+        // x1 is fixed by a unit and x2 is implied by it. x3 is free.
+        let clauses = vec![
+            vec![Lit::from(1)],
+            vec![Lit::from(-1), Lit::from(-2)],
+            vec![Lit::from(2), Lit::from(3), Lit::from(4)],
+        ];
+        let mut solver = Solver::new(4, &clauses);
+        assert_eq!(solver.fixed(Lit::from(1)), LitBool::True);
+        assert_eq!(solver.fixed(Lit::from(2)), LitBool::False);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(solver.fixed(Lit::from(-2)), LitBool::True);
+        assert_eq!(solver.fixed(Lit::from(3)), LitBool::Undef);
+        assert_eq!(solver.fixed(Lit::from(9)), LitBool::Undef);
+        assert_eq!(solver.value(Lit::from(-1)), LitBool::False);
+        assert_eq!(solver.value(Lit::from(9)), LitBool::Undef);
+        let model = solver.model().unwrap();
+        for v in 1..=4 {
+            assert_eq!(solver.value(Lit::from(v)), model[Lit::from(v)]);
+        }
+        // A unit that is added later fixes the literals that it implies.
+        solver.add_clause(&[Lit::from(-3)]);
+        assert_eq!(solver.fixed(Lit::from(4)), LitBool::True);
+    }
+    #[test]
+    fn test_public_simplify() {
+        let size = |solver: &Solver| {
+            let mut snapshot = vec![];
+            solver.write_snapshot(&mut snapshot, false).unwrap();
+            snapshot.len()
+        };
+        // x1 satisfies the first clause and removes -x1 from the second one.
+        let mut solver = Solver::new(
+            4,
+            &[
+                vec![Lit::from(1), Lit::from(2), Lit::from(3)],
+                vec![Lit::from(-1), Lit::from(3), Lit::from(4)],
+                vec![Lit::from(-2), Lit::from(-3), Lit::from(4)],
+            ],
+        );
+        let before = size(&solver);
+        solver.add_clause(&[Lit::from(1)]);
+        assert_eq!(solver.simplify(), Status::Indeterminate);
+        assert!(size(&solver) < before);
+        assert_eq!(solver.solve(None), Status::Sat);
+        solver.add_clause(&[Lit::from(-3)]);
+        solver.add_clause(&[Lit::from(-4)]);
+        assert_eq!(solver.simplify(), Status::Unsat);
+        assert_eq!(solver.solve(None), Status::Unsat);
+
+        // Simplification between batches keeps the answers.
+        for seed in 1..100 {
+            let var_num = 12;
+            let clauses = random_problem(seed, var_num, 50);
+            let mut solver = Solver::default();
+            for (i, chunk) in clauses.chunks(10).enumerate() {
+                chunk.iter().for_each(|clause| solver.add_clause(clause));
+                solver.add_clause(&[Lit::new((seed as u32 + i as u32) % var_num, i % 2 == 0)]);
+                solver.simplify();
+            }
+            let mut expected = Solver::default();
+            clauses
+                .iter()
+                .for_each(|clause| expected.add_clause(clause));
+            for i in 0..5 {
+                expected.add_clause(&[Lit::new((seed as u32 + i as u32) % var_num, i % 2 == 0)]);
+            }
+            assert_eq!(solver.solve(None), expected.solve(None), "seed: {}", seed);
+        }
+    }
+    #[test]
+    fn test_interrupt() {
+        // The pigeonhole problem of 11 pigeons in 10 holes is too hard to be solved in a test.
+        let x = |p: u32, h: u32| Lit::new(p * 10 + h, true);
+        let mut clauses: Vec<Vec<Lit>> = (0..11)
+            .map(|p| (0..10).map(|h| x(p, h)).collect())
+            .collect();
+        for h in 0..10 {
+            for p in 0..11 {
+                for q in p + 1..11 {
+                    clauses.push(vec![!x(p, h), !x(q, h)]);
+                }
+            }
+        }
+        let mut solver = Solver::new(110, &clauses);
+        let handle = solver.interrupt_handle();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            handle.stop();
+        });
+        assert_eq!(solver.solve(None), Status::Indeterminate);
+        stopper.join().unwrap();
+
+        // A callback stops a search too.
+        let polls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let counter = polls.clone();
+        solver.set_terminate(move || {
+            counter.set(counter.get() + 1);
+            counter.get() >= 3
+        });
+        assert_eq!(solver.solve(None), Status::Indeterminate);
+        assert_eq!(polls.get(), 3);
+
+        // A stop before a search stops it once.
+        let mut solver = Solver::new(2, &[vec![Lit::from(1), Lit::from(2)]]);
+        solver.interrupt_handle().stop();
+        assert_eq!(solver.solve(None), Status::Indeterminate);
+        assert_eq!(solver.solve(None), Status::Sat);
+    }
+    #[test]
+    fn test_solve_limited() {
+        let mut resumed = 0;
+        for seed in 1..10 {
+            let clauses = random_problem(seed, 70, 300);
+            let expected = Solver::new(70, &clauses).solve(None);
+            // A search is resumed by small budgets of conflicts. The same budgets stop at the same points.
+            let rounds = |budget: &Budget| {
+                let mut solver = Solver::new(70, &clauses);
+                let mut rounds = 0;
+                loop {
+                    let status = solver.solve_limited(budget);
+                    rounds += 1;
+                    if status != Status::Indeterminate {
+                        assert_eq!(status, expected, "seed: {}", seed);
+                        if status == Status::Sat {
+                            assert!(sat_model_check(&clauses, &solver.assigns));
+                        }
+                        return rounds;
+                    }
+                }
+            };
+            let budget = Budget {
+                conflicts: Some(20),
+                ..Budget::default()
+            };
+            let r = rounds(&budget);
+            assert_eq!(r, rounds(&budget));
+            resumed += (r > 1) as usize;
+            rounds(&Budget {
+                decisions: Some(50),
+                ..Budget::default()
+            });
+            rounds(&Budget {
+                propagations: Some(2000),
+                ..Budget::default()
+            });
+        }
+        assert!(resumed > 0);
+        // No decision is allowed.
+        let mut solver = Solver::new(3, &[vec![Lit::from(1), Lit::from(2)]]);
+        let budget = Budget {
+            decisions: Some(0),
+            ..Budget::default()
+        };
+        assert_eq!(solver.solve_limited(&budget), Status::Indeterminate);
+        assert_eq!(solver.solve_limited(&Budget::default()), Status::Sat);
+    }
+    #[test]
+    fn test_stats() {
+        let mut solver = Solver::default();
+        solver.set_config(Config {
+            restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+            learnt_size_factor: 0.01,
+            ..Config::default()
+        });
+        let clauses = random_problem(3, 70, 320);
+        clauses.iter().for_each(|clause| solver.add_clause(clause));
+        assert_eq!(*solver.stats(), Statistics::default());
+        solver.solve(None);
+        let stats = *solver.stats();
+        assert!(stats.conflicts > 0 && stats.restarts > 0 && stats.deleted > 0);
+        assert!(stats.learnts <= stats.conflicts);
+        assert!(stats.decisions >= stats.max_level && stats.max_level > 0);
+        assert!(stats.propagations >= stats.decisions);
+        // Counters accumulate over searches.
+        solver.add_clause(&[clauses[0][0]]);
+        solver.solve(None);
+        assert!(solver.stats().propagations > stats.propagations);
+        let table = solver.stats().to_string();
+        assert!(table.starts_with("restarts              : "));
+        assert!(table.contains(&format!(
+            "deleted clauses       : {}",
+            solver.stats().deleted
+        )));
+        assert_eq!(table.lines().count(), 7);
+    }
+    #[test]
+    fn test_polarity_and_decision() {
+        // Decisions along the phases of a model never conflict.
+        for seed in 1..50 {
+            let clauses = random_problem(seed, 50, 180);
+            let mut solver = Solver::new(50, &clauses);
+            if solver.solve(None) != Status::Sat {
+                continue;
+            }
+            let model = solver.assigns.clone();
+            let mut solver = Solver::new(50, &clauses);
+            for v in 0..50 {
+                solver.set_polarity(Var(v), Some(model[v as usize] == LitBool::True));
+            }
+            assert_eq!(solver.solve(None), Status::Sat);
+            assert_eq!(solver.stats().conflicts, 0);
+            assert_eq!(solver.assigns, model);
+        }
+
+        // x3 <-> (x1 & x2) is implied by x1 and x2. x4 is in no clause.
+        let mut solver = Solver::new(
+            4,
+            &[
+                vec![Lit::from(-3), Lit::from(1)],
+                vec![Lit::from(-3), Lit::from(2)],
+                vec![Lit::from(3), Lit::from(-1), Lit::from(-2)],
+            ],
+        );
+        solver.set_decision(Var(2), false);
+        solver.set_decision(Var(3), false);
+        solver.set_polarity(Var(0), Some(true));
+        solver.set_polarity(Var(1), Some(true));
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(
+            solver.assigns,
+            vec![LitBool::True, LitBool::True, LitBool::True, LitBool::Undef]
+        );
+        solver.set_decision(Var(3), true);
+        solver.set_polarity(Var(0), None);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_ne!(solver.assigns[3], LitBool::Undef);
+    }
+    fn dimacs_lits(xs: &[i32]) -> Vec<Lit> {
+        xs.iter().map(|&x| Lit::from(x)).collect()
+    }
+
+    #[test]
+    fn test_parse_cnf() {
+        let input = "c Here is a comment.
+c p cnf 1 1
+p  cnf 5\t4
+
+1 -5 4 0
+-1 5
+   3 4 0 -3 -4 0
+-2
+%
+0
+";
+        let cnf = util::parse_cnf(input.as_bytes()).unwrap();
+        assert_eq!(cnf.var_num, Some(5));
+        assert_eq!(cnf.cla_num, Some(4));
+        assert_eq!(
+            cnf.clauses,
+            vec![
+                dimacs_lits(&[1, -5, 4]),
+                dimacs_lits(&[-1, 5, 3, 4]),
+                dimacs_lits(&[-3, -4]),
+                dimacs_lits(&[-2])
+            ]
+        );
+
+        // A file may have no header. A lone `0` is the empty clause.
+        let cnf = util::parse_cnf("1 2 0\n0\n".as_bytes()).unwrap();
+        assert_eq!(cnf.var_num, None);
+        assert_eq!(cnf.clauses, vec![dimacs_lits(&[1, 2]), vec![]]);
+        let mut solver = Solver::default();
+        cnf.clauses
+            .iter()
+            .for_each(|clause| solver.add_clause(clause));
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+
+    #[test]
+    fn test_parse_cnf_errors() {
+        for input in [
+            "p cnf 2\n1 2 0\n",
+            "p cnf x 1\n1 2 0\n",
+            "p wcnf 2 1\n1 2 0\n",
+            "p cnf 2 1\np cnf 2 1\n1 2 0\n",
+            "p cnf 2 1\n1 a 0\n",
+            "p cnf 2 1\n1 3 0\n",
+        ]
+        .iter()
+        {
+            let err = util::parse_cnf(input.as_bytes()).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{}", input);
+        }
+    }
+    #[test]
+    fn test_dump_cnf() {
+        let cnf = util::CnfData {
+            var_num: None,
+            cla_num: None,
+            clauses: vec![dimacs_lits(&[1, -3]), dimacs_lits(&[-2]), vec![]],
+        };
+        let mut buf = vec![];
+        util::write_cnf(&cnf, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "p cnf 3 3\n1 -3 0\n-2 0\n0\n"
+        );
+
+        let (mut sat, mut unsat) = (0, 0);
+        for seed in 1..40 {
+            let clauses = random_problem(seed, 50, 210);
+            let mut solver = Solver::new(50, &clauses);
+            solver.add_removable_clause(&[Lit::new(0, true), Lit::new(1, true)]);
+            let status = solver.solve(None);
+            for learnts in [false, true].iter() {
+                let mut buf = vec![];
+                solver.dump_cnf(&mut buf, *learnts).unwrap();
+                let dumped = util::parse_cnf(&buf[..]).unwrap();
+                assert_eq!(dumped.var_num, Some(solver.assigns.len()));
+                assert_eq!(dumped.cla_num, Some(dumped.clauses.len()));
+                let mut copy = Solver::new(solver.assigns.len(), &dumped.clauses);
+                assert_eq!(copy.solve(None), status, "{}", seed);
+            }
+            if status == Status::Sat {
+                sat += 1;
+            } else {
+                unsat += 1;
+            }
+        }
+        assert!(sat > 0 && unsat > 0);
+    }
+
+    #[test]
+    fn test_competition_output() {
+        let mut buf = vec![];
+        util::write_status(Status::Sat, &mut buf).unwrap();
+        util::write_status(Status::Unsat, &mut buf).unwrap();
+        util::write_status(Status::Indeterminate, &mut buf).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "s SATISFIABLE\ns UNSATISFIABLE\ns UNKNOWN\n"
+        );
+
+        // Auxiliary variables after `var_num` aren't written.
+        let mut solver = Solver::new(3, &[vec![Lit::from(1)], vec![Lit::from(-2)]]);
+        solver.add_exactly_k(&[Lit::from(1), Lit::from(2), Lit::from(3)], 1);
+        assert!(solver.assigns.len() > 3);
+        assert_eq!(solver.solve(None), Status::Sat);
+        let mut buf = vec![];
+        util::write_model(&solver.model().unwrap(), 3, &mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), "v 1 -2 -3 0\n");
+
+        let clauses = random_problem(1, 100, 300);
+        let mut solver = Solver::new(100, &clauses);
+        assert_eq!(solver.solve(None), Status::Sat);
+        let mut buf = vec![];
+        util::write_model(&solver.model().unwrap(), 100, &mut buf).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        let mut values = vec![];
+        for line in output.lines() {
+            assert!(line.starts_with("v ") && line.len() <= 80);
+            values.extend(line[2..].split(' ').map(|x| x.parse::<i32>().unwrap()));
+        }
+        assert_eq!(values.pop(), Some(0));
+        let model: Vec<LitBool> = values
+            .iter()
+            .map(|&x| if x > 0 { LitBool::True } else { LitBool::False })
+            .collect();
+        assert_eq!(model.len(), 100);
+        assert!(sat_model_check(&clauses, &model));
+    }
+
+    #[test]
+    fn test_clone_and_fork() {
+        let (mut sat, mut unsat) = (0, 0);
+        for seed in 1..40 {
+            let clauses = random_problem(seed, 60, 250);
+            let mut solver = Solver::new(60, &clauses);
+            solver.solve(None);
+            let handle = solver.add_removable_clause(&[Lit::new(0, true), Lit::new(1, true)]);
+            let status = solver.solve(None);
+            let copies = vec![solver.clone(), solver.fork()];
+            for (i, mut copy) in copies.into_iter().enumerate() {
+                assert_eq!(copy.solve(None), status);
+                copy.remove_clause(handle);
+                let mut added = vec![vec![Lit::new(0, false)], vec![Lit::new(1, false)]];
+                copy.add_clauses(&added);
+                added.extend(clauses.iter().cloned());
+                let expected = Solver::new(60, &added).solve(None);
+                assert_eq!(copy.solve(None), expected, "{} {}", seed, i);
+                if expected == Status::Sat {
+                    assert!(sat_model_check(&added, &copy.assigns));
+                    sat += 1;
+                } else {
+                    unsat += 1;
+                }
+            }
+            // Copies don't change the original solver.
+            let mut original = clauses.clone();
+            original.push(vec![Lit::new(0, true), Lit::new(1, true)]);
+            assert_eq!(solver.solve(None), status);
+            assert_eq!(Solver::new(60, &original).solve(None), status);
+            if status == Status::Sat {
+                assert!(sat_model_check(&original, &solver.assigns));
+            }
+        }
+        assert!(sat > 0 && unsat > 0);
+    }
+
+    #[test]
+    fn test_removable_clauses() {
+        let (mut sat, mut unsat) = (0, 0);
+        for seed in 1..40 {
+            let base = random_problem(seed, 40, 120);
+            let removable = random_problem(seed + 1000, 40, 60);
+            let mut solver = Solver::new(40, &base);
+            let handles: Vec<ClauseHandle> = removable
+                .iter()
+                .map(|clause| solver.add_removable_clause(clause))
+                .collect();
+            let mut active: Vec<bool> = vec![true; removable.len()];
+            for round in 0..4 {
+                let clauses: Vec<Vec<Lit>> = base
+                    .iter()
+                    .chain(
+                        removable
+                            .iter()
+                            .zip(active.iter())
+                            .filter(|x| *x.1)
+                            .map(|x| x.0),
+                    )
+                    .cloned()
+                    .collect();
+                let status = solver.solve(None);
+                assert_eq!(status, Solver::new(40, &clauses).solve(None), "{}", seed);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &solver.assigns));
+                    sat += 1;
+                } else {
+                    unsat += 1;
+                }
+                let assumptions = [Lit::new(round, true), Lit::new(round + 1, false)];
+                if solver.solve_with_assumptions(&assumptions, None) == Status::Unsat {
+                    assert!(solver
+                        .unsat_core()
+                        .iter()
+                        .all(|lit| assumptions.contains(lit)));
+                }
+                // Remove a quarter of the clauses. Removing a clause again does nothing.
+                solver.remove_clause(handles[0]);
+                for i in (round as usize..removable.len()).step_by(4) {
+                    solver.remove_clause(handles[i]);
+                    active[i] = false;
+                }
+            }
+        }
+        assert!(sat > 0 && unsat > 0);
+    }
+    #[test]
+    fn test_is_rup() {
+        for seed in 1..40 {
+            let clauses = random_problem(seed, 30, 110);
+            let mut solver = Solver::new(30, &clauses);
+            // Resolvents of two clauses are RUP.
+            for (a, b) in clauses.iter().zip(clauses.iter().skip(1)) {
+                if let Some(&lit) = a.iter().find(|&&lit| b.contains(&!lit)) {
+                    let mut resolvent: Vec<Lit> =
+                        a.iter().filter(|&&x| x != lit).copied().collect();
+                    resolvent.extend(b.iter().filter(|&&x| x != !lit));
+                    assert!(solver.is_rup(&resolvent), "{} {:?}", seed, resolvent);
+                }
+            }
+            match solver.solve(None) {
+                Status::Sat => {
+                    // A clause that a model falsifies isn't implied.
+                    let model = solver.assigns.clone();
+                    let falsified: Vec<Lit> = (0..3)
+                        .map(|v| Lit::new(v, model[v as usize] == LitBool::False))
+                        .collect();
+                    assert!(!solver.is_rup(&falsified), "{}", seed);
+                }
+                Status::Unsat => assert!(solver.is_rup(&[])),
+                Status::Indeterminate => unreachable!(),
+            }
+        }
+        let mut solver = Solver::new(2, &[vec![Lit::from(1), Lit::from(2)]]);
+        assert!(solver.is_rup(&[Lit::from(1), Lit::from(2), Lit::from(-1)]));
+        assert!(!solver.is_rup(&[Lit::from(1)]));
+        assert!(!solver.is_rup(&[]));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        for seed in 1..40 {
+            let clauses = random_problem(seed, 30, 125);
+            let mut solver = Solver::new(30, &clauses);
+            let expected = solver.solve(Some(std::time::Duration::from_secs(5)));
+            for &learnts in [false, true].iter() {
+                let mut bytes = vec![];
+                solver.write_snapshot(&mut bytes, learnts).unwrap();
+                let mut loaded = Solver::read_snapshot(&bytes[..]).unwrap();
+                let status = loaded.solve(None);
+                assert_eq!(status, expected, "{} {}", seed, learnts);
+                if status == Status::Sat {
+                    assert!(sat_model_check(&clauses, &loaded.assigns), "{}", seed);
+                }
+                // A snapshot of a loaded solver is the same snapshot.
+                let mut again = vec![];
+                Solver::read_snapshot(&bytes[..])
+                    .unwrap()
+                    .write_snapshot(&mut again, learnts)
+                    .unwrap();
+                assert!(bytes == again, "{} {}", seed, learnts);
+            }
+        }
+
+        let mut bytes = vec![];
+        Solver::new(2, &[vec![Lit::from(1)], vec![Lit::from(-1), Lit::from(2)]])
+            .write_snapshot(&mut bytes, false)
+            .unwrap();
+        let solver = Solver::read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(solver.assigns, vec![LitBool::True, LitBool::True]);
+        // Truncated or broken snapshots are errors.
+        assert!(Solver::read_snapshot(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Solver::read_snapshot(&b"p cnf 1 1\n1 0\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_at_most() {
+        // Compare native at-most-k constraints with brute force.
+        for seed in 1..300 {
+            let var_num = 12;
+            let clauses = random_problem(seed, var_num, 30);
+            // Literals of constraints are chosen from random clauses.
+            let cards: Vec<(Vec<Lit>, usize)> = random_problem(seed + 1000, var_num, 8)
+                .chunks(2)
+                .enumerate()
+                .map(|(i, c)| (c.concat(), i % 3 + 1))
+                .collect();
+            let satisfied = |bits: u32, lits: &[Lit]| -> Vec<Lit> {
+                lits.iter()
+                    .filter(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                    .copied()
+                    .collect()
+            };
+            let models: Vec<u32> = (0..1u32 << var_num)
+                .filter(|&bits| {
+                    clauses
+                        .iter()
+                        .all(|clause| !satisfied(bits, clause).is_empty())
+                        && cards.iter().all(|(lits, k)| {
+                            let mut trues = satisfied(bits, lits);
+                            trues.sort();
+                            trues.dedup();
+                            trues.len() <= *k
+                        })
+                })
+                .collect();
+            let mut solver = Solver::new(var_num as usize, &clauses);
+            cards
+                .iter()
+                .for_each(|(lits, k)| solver.add_at_most(lits, *k));
+            // Enumerate all models by blocking clauses.
+            let mut found = vec![];
+            while solver.solve(None) == Status::Sat {
+                let bits = (0..var_num).fold(0, |bits, v| {
+                    bits | ((solver.assigns[v as usize] == LitBool::True) as u32) << v
+                });
+                assert!(models.contains(&bits), "seed: {}", seed);
+                found.push(bits);
+                let block: Vec<Lit> = (0..var_num)
+                    .map(|v| Lit::new(v, bits >> v & 1 == 0))
+                    .collect();
+                solver.add_clause(&block);
+            }
+            assert_eq!(found.len(), models.len(), "seed: {}", seed);
+        }
+
+        // x1 + x2 + x3 + !x1 <= 1 means x2 = x3 = false.
+        let mut solver = Solver::default();
+        solver.add_at_most(
+            &[Lit::from(1), Lit::from(2), Lit::from(3), Lit::from(-1)],
+            1,
+        );
+        solver.add_clause(&[Lit::from(2), Lit::from(3), Lit::from(4)]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(
+            solver.assigns[1..4],
+            [LitBool::False, LitBool::False, LitBool::True]
+        );
+        // Pigeonhole: three pigeons in two holes.
+        let mut solver = Solver::default();
+        let p = |i: i32, h: i32| Lit::from(i * 2 + h + 1);
+        (0..3).for_each(|i| solver.add_clause(&[p(i, 0), p(i, 1)]));
+        (0..2).for_each(|h| solver.add_at_most(&[p(0, h), p(1, h), p(2, h)], 1));
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+    #[test]
+    fn test_xor_constraints() {
+        for seed in 1..150 {
+            let var_num = 10;
+            let clauses = random_problem(seed, var_num, 20);
+            // Each of them is a XOR of up to 3 variables. A duplicated variable cancels out.
+            let xors = random_problem(seed + 1000, var_num, 5);
+            let parity = |bits: u32, lits: &[Lit]| {
+                lits.iter()
+                    .filter(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                    .count()
+                    % 2
+                    == 1
+            };
+            let models: Vec<u32> = (0..1u32 << var_num)
+                .filter(|&bits| {
+                    clauses.iter().all(|clause| {
+                        clause
+                            .iter()
+                            .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+                    }) && xors.iter().all(|xor| parity(bits, xor))
+                })
+                .collect();
+            let mut solver = Solver::new(var_num as usize, &clauses);
+            solver.add_xors(&xors[..2]);
+            xors[2..].iter().for_each(|xor| solver.add_xor(xor));
+            // Enumerate all models by blocking clauses.
+            let mut found = vec![];
+            while solver.solve(None) == Status::Sat {
+                let bits = (0..var_num).fold(0, |bits, v| {
+                    bits | ((solver.assigns[v as usize] == LitBool::True) as u32) << v
+                });
+                assert!(models.contains(&bits), "seed: {}", seed);
+                found.push(bits);
+                let block: Vec<Lit> = (0..var_num)
+                    .map(|v| Lit::new(v, bits >> v & 1 == 0))
+                    .collect();
+                solver.add_clause(&block);
+            }
+            assert_eq!(found.len(), models.len(), "seed: {}", seed);
+        }
+
+        // x1 ^ x2 = 1 and x2 ^ x3 = 1 imply x1 ^ x3 = 0 by their sum.
+        let mut solver = Solver::default();
+        solver.add_xor(&[Lit::from(1), Lit::from(2)]);
+        solver.add_xor(&[Lit::from(2), Lit::from(3)]);
+        solver.add_xor(&[Lit::from(1), Lit::from(-3)]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        assert_eq!(solver.assigns[0], solver.assigns[2]);
+        solver.add_xor(&[Lit::from(1), Lit::from(3)]);
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+    #[test]
+    fn test_xor_extraction() {
+        // Random 3-XOR systems in CNF. Each XOR is 4 clauses. More equations than variables are mostly unsatisfiable.
+        let mut unsat = 0;
+        for seed in 1..40 {
+            let var_num = 40;
+            let mut clauses = vec![];
+            for (i, xor) in random_problem(seed, var_num, 44).iter().enumerate() {
+                let mut vars: Vec<u32> = xor.iter().map(|lit| lit.var().0).collect();
+                vars.sort();
+                vars.dedup();
+                for signs in 0..1u32 << vars.len() {
+                    // A XOR of parity `i % 2` excludes assignments of the other parity.
+                    if (signs.count_ones() as usize + i) % 2 == 1 {
+                        clauses.push(
+                            vars.iter()
+                                .enumerate()
+                                .map(|(k, &v)| Lit::new(v, signs >> k & 1 == 0))
+                                .collect(),
+                        );
+                    }
+                }
+            }
+            clauses.extend(random_problem(seed + 1000, var_num, 20));
+            let mut reference = Solver::new(var_num as usize, &clauses);
+            let expected = reference.solve(None);
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(XorExtraction), 1000),
+                ],
+                ..Config::default()
+            });
+            solver.add_clauses(&clauses);
+            let status = solver.solve(None);
+            assert_eq!(status, expected, "{}", seed);
+            if status == Status::Sat {
+                assert!(sat_model_check(&clauses, &solver.assigns), "{}", seed);
+            } else {
+                unsat += 1;
+            }
+        }
+        assert!(unsat > 0);
     }
 }
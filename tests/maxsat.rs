@@ -0,0 +1,404 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::maxsat::*;
+    use screwsat::solver::*;
+
+    // Hard: (x1 v x2) ∧ (¬x1 v ¬x3) ∧ (¬x2 v ¬x4) ∧ (x3 v x4 v x5)
+    // Soft: x1(3), x2(2), x3(4), x4(1), ¬x5(5)
+    fn problem() -> (Vec<Vec<Lit>>, Vec<(Lit, u64)>) {
+        let hards = vec![
+            vec![Lit::from(1), Lit::from(2)],
+            vec![Lit::from(-1), Lit::from(-3)],
+            vec![Lit::from(-2), Lit::from(-4)],
+            vec![Lit::from(3), Lit::from(4), Lit::from(5)],
+        ];
+        let softs = vec![
+            (Lit::from(1), 3),
+            (Lit::from(2), 2),
+            (Lit::from(3), 4),
+            (Lit::from(4), 1),
+            (Lit::from(-5), 5),
+        ];
+        (hards, softs)
+    }
+
+    fn brute_force_cost(var_num: usize, hards: &[Vec<Lit>], softs: &[(Lit, u64)]) -> Option<u64> {
+        let value = |bits: u32, lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+        (0..1u32 << var_num)
+            .filter(|&bits| {
+                hards
+                    .iter()
+                    .all(|clause| clause.iter().any(|lit| value(bits, lit)))
+            })
+            .map(|bits| {
+                softs
+                    .iter()
+                    .filter(|(lit, _)| !value(bits, lit))
+                    .map(|&(_, w)| w)
+                    .sum()
+            })
+            .min()
+    }
+
+    #[test]
+    fn test_maxsat_anytime() {
+        let (hards, softs) = problem();
+        let mut solver = MaxSatSolver::default();
+        hards
+            .iter()
+            .for_each(|clause| solver.add_hard_clause(clause));
+        softs
+            .iter()
+            .for_each(|&(lit, w)| solver.add_soft_lit(lit, w));
+
+        let mut costs = vec![];
+        let result = solver.solve_with_callback(None, |cost, model| {
+            assert_eq!(model.len(), 5);
+            costs.push(cost);
+            true
+        });
+        assert_eq!(result.status, MaxSatStatus::Optimum);
+        assert_eq!(result.cost, brute_force_cost(5, &hards, &softs));
+        assert_eq!(costs.last().copied(), result.cost);
+        assert!(costs.windows(2).all(|w| w[0] > w[1]));
+
+        // Stop at the first model
+        let mut solver = MaxSatSolver::default();
+        hards
+            .iter()
+            .for_each(|clause| solver.add_hard_clause(clause));
+        softs
+            .iter()
+            .for_each(|&(lit, w)| solver.add_soft_lit(lit, w));
+        let result = solver.solve_with_callback(None, |_, _| false);
+        assert!(result.model.is_some());
+        assert!(result.status == MaxSatStatus::Sat || result.status == MaxSatStatus::Optimum);
+    }
+
+    #[test]
+    fn test_maxsat_random() {
+        let mut x: u64 = 88172645463325252;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        for _ in 0..30 {
+            let var_num = 10;
+            let hards: Vec<Vec<Lit>> = (0..25)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| Lit::new((next() % var_num) as u32, next() % 2 == 0))
+                        .collect()
+                })
+                .collect();
+            let softs: Vec<(Lit, u64)> = (0..var_num)
+                .map(|v| (Lit::new(v as u32, next() % 2 == 0), next() % 7 + 1))
+                .collect();
+            let mut solver = MaxSatSolver::default();
+            hards
+                .iter()
+                .for_each(|clause| solver.add_hard_clause(clause));
+            softs
+                .iter()
+                .for_each(|&(lit, w)| solver.add_soft_lit(lit, w));
+            let result = solver.solve(None);
+            let expected = brute_force_cost(var_num as usize, &hards, &softs);
+            assert_eq!(result.cost, expected);
+            if expected.is_some() {
+                assert_eq!(result.status, MaxSatStatus::Optimum);
+            } else {
+                assert_eq!(result.status, MaxSatStatus::Unsat);
+            }
+        }
+    }
+
+    #[test]
+    fn test_preprocess() {
+        // At most one of x1, x2 and x3 is false and at least one of them is false.
+        let mut solver = MaxSatSolver::default();
+        solver.add_hard_clause(&[Lit::from(1), Lit::from(2)]);
+        solver.add_hard_clause(&[Lit::from(1), Lit::from(3)]);
+        solver.add_hard_clause(&[Lit::from(2), Lit::from(3)]);
+        solver.add_hard_clause(&[Lit::from(-1), Lit::from(-2), Lit::from(-3)]);
+        solver.add_hard_clause(&[Lit::from(4)]);
+        solver.add_soft_lit(Lit::from(1), 3);
+        solver.add_soft_lit(Lit::from(2), 5);
+        solver.add_soft_lit(Lit::from(3), 4);
+        solver.add_soft_lit(Lit::from(4), 2);
+        let result = solver.preprocess(None);
+        assert_eq!(
+            result,
+            PreprocessResult {
+                hardened: 1,
+                groups: 1,
+                lower_bound: 3
+            }
+        );
+        let result = solver.solve(None);
+        assert_eq!(result.status, MaxSatStatus::Optimum);
+        assert_eq!(result.cost, Some(3));
+
+        // Random problems
+        let mut x: u64 = 88172645463325252;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        for _ in 0..50 {
+            let var_num = 10;
+            let hards: Vec<Vec<Lit>> = (0..15)
+                .map(|_| {
+                    (0..(next() % 2 + 2))
+                        .map(|_| Lit::new((next() % var_num) as u32, next() % 2 == 0))
+                        .collect()
+                })
+                .collect();
+            let softs: Vec<(Lit, u64)> = (0..var_num)
+                .map(|v| (Lit::new(v as u32, next() % 2 == 0), next() % 7 + 1))
+                .collect();
+            let mut solver = MaxSatSolver::default();
+            hards
+                .iter()
+                .for_each(|clause| solver.add_hard_clause(clause));
+            softs
+                .iter()
+                .for_each(|&(lit, w)| solver.add_soft_lit(lit, w));
+            let expected = brute_force_cost(var_num as usize, &hards, &softs);
+            let bound = solver.preprocess(None).lower_bound;
+            let result = solver.solve(None);
+            assert_eq!(result.cost, expected);
+            if let Some(cost) = expected {
+                assert!(bound <= cost);
+                assert_eq!(result.status, MaxSatStatus::Optimum);
+                // The model is optimal for the original soft literals.
+                let model = result.model.unwrap();
+                let original: u64 = softs
+                    .iter()
+                    .filter(|&&(lit, _)| {
+                        model[lit.var().0 as usize]
+                            != if lit.pos() {
+                                LitBool::True
+                            } else {
+                                LitBool::False
+                            }
+                    })
+                    .map(|&(_, w)| w)
+                    .sum();
+                assert_eq!(original, cost);
+            } else {
+                assert_eq!(result.status, MaxSatStatus::Unsat);
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize_maximize() {
+        let mut x: u64 = 88172645463325252;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        for _ in 0..30 {
+            let var_num = 8;
+            let hards: Vec<Vec<Lit>> = (0..15)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| Lit::new((next() % var_num) as u32, next() % 2 == 0))
+                        .collect()
+                })
+                .collect();
+            let objective: Vec<(Lit, i64)> = (0..var_num)
+                .map(|v| (Lit::new(v as u32, next() % 2 == 0), next() as i64 % 7))
+                .collect();
+
+            let value = |bits: u32, lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+            let values: Vec<i64> = (0..1u32 << var_num)
+                .filter(|&bits| {
+                    hards
+                        .iter()
+                        .all(|clause| clause.iter().any(|lit| value(bits, lit)))
+                })
+                .map(|bits| {
+                    objective
+                        .iter()
+                        .filter(|(lit, _)| value(bits, lit))
+                        .map(|&(_, w)| w)
+                        .sum()
+                })
+                .collect();
+
+            let solver = || {
+                let mut solver = MaxSatSolver::default();
+                hards
+                    .iter()
+                    .for_each(|clause| solver.add_hard_clause(clause));
+                solver
+            };
+            let min = solver().minimize(&objective, None);
+            let max = solver().maximize(&objective, None);
+            assert_eq!(min.value, values.iter().min().copied());
+            assert_eq!(max.value, values.iter().max().copied());
+            if let Some(model) = min.model {
+                let value: i64 = objective
+                    .iter()
+                    .filter(|(lit, _)| (model[lit.var().0 as usize] == LitBool::True) == lit.pos())
+                    .map(|&(_, w)| w)
+                    .sum();
+                assert_eq!(min.value, Some(value));
+            }
+        }
+    }
+
+    #[test]
+    fn test_soft_clauses() {
+        // Soft: (x1 v x2)(4), (¬x1)(3), (¬x2)(3), (x1 v ¬x2 v x3)(2), (¬x3)(1)
+        let softs = [
+            (vec![Lit::from(1), Lit::from(2)], 4),
+            (vec![Lit::from(-1)], 3),
+            (vec![Lit::from(-2)], 3),
+            (vec![Lit::from(1), Lit::from(-2), Lit::from(3)], 2),
+            (vec![Lit::from(-3)], 1),
+        ];
+        let mut solver = MaxSatSolver::default();
+        softs
+            .iter()
+            .for_each(|(clause, w)| solver.add_soft_clause(clause, *w));
+        let result = solver.solve(None);
+        assert_eq!(result.status, MaxSatStatus::Optimum);
+
+        let model = result.model.unwrap();
+        let value = |lit: &Lit| match model[lit.var().0 as usize] {
+            LitBool::True => lit.pos(),
+            LitBool::False => lit.neg(),
+            LitBool::Undef => false,
+        };
+        let cost: u64 = softs
+            .iter()
+            .filter(|(clause, _)| !clause.iter().any(value))
+            .map(|(_, w)| w)
+            .sum();
+        // x1 = true, x2 = false, x3 = false violates (¬x1) only.
+        assert_eq!(result.cost, Some(3));
+        assert_eq!(cost, 3);
+        let v = solver.new_var();
+        assert!(v.0 as usize >= model.len());
+    }
+
+    #[test]
+    fn test_maxsat_incremental() {
+        let mut x: u64 = 88172645463325252;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        let var_num = 8;
+        let mut random_clause = |len: u64| -> Vec<Lit> {
+            (0..len)
+                .map(|_| Lit::new((next() % var_num) as u32, next() % 2 == 0))
+                .collect()
+        };
+        for _ in 0..10 {
+            let mut solver = MaxSatSolver::default();
+            let mut hards: Vec<Vec<Lit>> = (0..8).map(|_| random_clause(3)).collect();
+            hards
+                .iter()
+                .for_each(|clause| solver.add_hard_clause(clause));
+            let mut softs: Vec<(Vec<Lit>, u64)> = vec![];
+            for step in 0..12 {
+                match step % 3 {
+                    0 | 1 => {
+                        let clause = random_clause(step % 3 + 1);
+                        let weight = step % 4 + 1;
+                        solver.add_soft_clause(&clause, weight);
+                        softs.push((clause, weight));
+                    }
+                    _ => {
+                        let clause = random_clause(3);
+                        solver.add_hard_clause(&clause);
+                        hards.push(clause);
+                        if !softs.is_empty() {
+                            let (clause, _) = softs.swap_remove(0);
+                            solver.remove_soft_clause(&clause);
+                            let normalize = |c: &[Lit]| {
+                                let mut c = c.to_vec();
+                                c.sort();
+                                c.dedup();
+                                c
+                            };
+                            softs.retain(|(c, _)| normalize(c) != normalize(&clause));
+                        }
+                    }
+                }
+
+                // A soft clause is false if all literals are false.
+                let value = |bits: u32, lit: &Lit| (bits >> lit.var().0 & 1 == 1) == lit.pos();
+                let expected = (0..1u32 << var_num)
+                    .filter(|&bits| {
+                        hards
+                            .iter()
+                            .all(|clause| clause.iter().any(|lit| value(bits, lit)))
+                    })
+                    .map(|bits| {
+                        softs
+                            .iter()
+                            .filter(|(clause, _)| !clause.iter().any(|lit| value(bits, lit)))
+                            .map(|&(_, w)| w)
+                            .sum::<u64>()
+                    })
+                    .min();
+                let result = solver.solve(None);
+                assert_eq!(result.cost, expected);
+                if expected.is_none() {
+                    assert_eq!(result.status, MaxSatStatus::Unsat);
+                    break;
+                }
+                assert_eq!(result.status, MaxSatStatus::Optimum);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_wcnf() {
+        let input = "c comment\nh 1 -2 0\n3 2 0\n5 -1 3 0\n";
+        let wcnf = parse_wcnf(input.as_bytes()).unwrap();
+        assert_eq!(wcnf.var_num, 3);
+        assert_eq!(wcnf.hards, vec![vec![Lit::from(1), Lit::from(-2)]]);
+        assert_eq!(
+            wcnf.softs,
+            vec![
+                (vec![Lit::from(2)], 3),
+                (vec![Lit::from(-1), Lit::from(3)], 5)
+            ]
+        );
+        assert!(parse_wcnf("x 1 0\n".as_bytes()).is_err());
+
+        let input = "c old format\np wcnf 4 3 10\n10 1 -2 0\n3 2 0\n5 -1 3 0\n";
+        let old = parse_wcnf(input.as_bytes()).unwrap();
+        assert_eq!(old.var_num, 4);
+        assert_eq!(old.hards, wcnf.hards);
+        assert_eq!(old.softs, wcnf.softs);
+        let unweighted = parse_wcnf("p wcnf 2 1\n10 1 -2 0\n".as_bytes()).unwrap();
+        assert!(unweighted.hards.is_empty());
+        assert_eq!(unweighted.softs.len(), 1);
+        assert!(parse_wcnf("p wcnf 2 1 x\n1 1 0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_maxsat_unsat() {
+        let mut solver = MaxSatSolver::default();
+        solver.add_hard_clause(&[Lit::from(1)]);
+        solver.add_hard_clause(&[Lit::from(-1)]);
+        solver.add_soft_lit(Lit::from(2), 1);
+        let result = solver.solve(None);
+        assert_eq!(result.status, MaxSatStatus::Unsat);
+        assert!(result.model.is_none());
+    }
+}
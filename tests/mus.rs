@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::mus::*;
+    use screwsat::solver::*;
+    use std::collections::HashSet;
+
+    fn satisfiable(var_num: usize, clauses: &[&Vec<Lit>]) -> bool {
+        (0..1u32 << var_num).any(|bits| {
+            clauses.iter().all(|clause| {
+                clause
+                    .iter()
+                    .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+            })
+        })
+    }
+
+    // All MUSes and MCSes by brute force
+    fn brute_force(
+        var_num: usize,
+        hards: &[Vec<Lit>],
+        groups: &[Vec<Vec<Lit>>],
+    ) -> (HashSet<Vec<usize>>, HashSet<Vec<usize>>) {
+        let n = groups.len();
+        let sat: Vec<bool> = (0..1usize << n)
+            .map(|set| {
+                let clauses: Vec<&Vec<Lit>> = hards
+                    .iter()
+                    .chain(
+                        (0..n)
+                            .filter(|i| set >> i & 1 == 1)
+                            .flat_map(|i| groups[i].iter()),
+                    )
+                    .collect();
+                satisfiable(var_num, &clauses)
+            })
+            .collect();
+        let indices = |set: usize| -> Vec<usize> { (0..n).filter(|i| set >> i & 1 == 1).collect() };
+        let all = (1usize << n) - 1;
+        let muses = (0..1usize << n)
+            .filter(|&set| !sat[set] && (0..n).all(|i| set >> i & 1 == 0 || sat[set & !(1 << i)]))
+            .map(indices)
+            .collect();
+        let mcses = (0..1usize << n)
+            .filter(|&set| {
+                sat[all & !set]
+                    && (0..n).all(|i| set >> i & 1 == 0 || !sat[all & !(set & !(1 << i))])
+            })
+            .filter(|&set| set != 0)
+            .map(indices)
+            .collect();
+        (muses, mcses)
+    }
+
+    #[test]
+    fn test_marco() {
+        let mut x: u64 = 88172645463325252;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        for _ in 0..30 {
+            let var_num = 5;
+            let mut clause = |len: u64| -> Vec<Lit> {
+                (0..len)
+                    .map(|_| Lit::new((next() % var_num) as u32, next() % 2 == 0))
+                    .collect()
+            };
+            let hards = vec![clause(3)];
+            let groups: Vec<Vec<Vec<Lit>>> = (0..7).map(|i| vec![clause(i % 2 + 1)]).collect();
+
+            let (muses, mcses) = brute_force(var_num as usize, &hards, &groups);
+            let mut found_muses = HashSet::new();
+            let mut found_mcses = HashSet::new();
+            for explanation in Marco::new(&hards, &groups, None) {
+                match explanation {
+                    Explanation::Mus(mus) => assert!(found_muses.insert(mus)),
+                    Explanation::Mcs(mcs) => assert!(found_mcses.insert(mcs)),
+                }
+            }
+            assert_eq!(found_muses, muses);
+            assert_eq!(found_mcses, mcses);
+
+            let smallest = smallest_mus(&hards, &groups, None);
+            assert_eq!(
+                smallest.as_ref().map(|mus| mus.len()),
+                muses.iter().map(|mus| mus.len()).min()
+            );
+            if let Some(mus) = smallest {
+                assert!(muses.contains(&mus));
+            }
+        }
+    }
+
+    #[test]
+    fn test_gcnf() {
+        let input = "c comment\np gcnf 2 4 4\n{0} 1 2 0\n{1} -1 0\n{2} -2 0\n{3} 1 -2 0\n";
+        let gcnf = parse_gcnf(input.as_bytes()).unwrap();
+        assert_eq!(gcnf.var_num, 2);
+        assert_eq!(gcnf.hards, vec![vec![Lit::from(1), Lit::from(2)]]);
+        assert_eq!(gcnf.groups.len(), 4);
+        assert!(gcnf.groups[3].is_empty());
+        assert_eq!(find_mus(&gcnf.hards, &gcnf.groups, None), Some(vec![0, 2]));
+        // A MUS in a given core
+        assert_eq!(
+            find_mus_in(&gcnf.hards, &gcnf.groups, &[0, 1, 3], None),
+            Some(vec![0, 1])
+        );
+        assert_eq!(find_mus_in(&gcnf.hards, &gcnf.groups, &[1, 2], None), None);
+
+        let mut output = vec![];
+        write_gcnf(&gcnf, &mut output).unwrap();
+        assert_eq!(parse_gcnf(&output[..]).unwrap(), gcnf);
+        assert!(parse_gcnf("1 2 0\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_marco_hards_unsat() {
+        let hards = vec![vec![Lit::from(1)], vec![Lit::from(-1)]];
+        let groups = vec![vec![vec![Lit::from(2)]]];
+        let explanations: Vec<_> = Marco::new(&hards, &groups, None).collect();
+        assert_eq!(explanations, vec![Explanation::Mus(vec![])]);
+
+        // No budget
+        let limit = Some(std::time::Duration::from_secs(0));
+        assert_eq!(Marco::new(&hards, &groups, limit).count(), 0);
+        assert_eq!(smallest_mus(&hards, &groups, None), Some(vec![]));
+    }
+}
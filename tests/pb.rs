@@ -0,0 +1,150 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::maxsat::MaxSatStatus;
+    use screwsat::pb::*;
+    use screwsat::solver::*;
+
+    fn satisfied(bits: u32, constraint: &PbConstraint) -> bool {
+        let sum: i64 = constraint
+            .terms
+            .iter()
+            .filter(|(lit, _)| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+            .map(|(_, coef)| coef)
+            .sum();
+        match constraint.relation {
+            Relation::AtLeast => sum >= constraint.degree,
+            Relation::AtMost => sum <= constraint.degree,
+            Relation::Equal => sum == constraint.degree,
+        }
+    }
+
+    fn bits_of(model: &[LitBool]) -> u32 {
+        model
+            .iter()
+            .enumerate()
+            .filter(|(_, &b)| b == LitBool::True)
+            .fold(0, |bits, (v, _)| bits | 1 << v)
+    }
+
+    #[test]
+    fn test_pb_brute_force() {
+        let mut rng = 12345u64;
+        let mut next = |m: u64| {
+            rng = rng.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (rng >> 33) % m
+        };
+        let n = 8;
+        for _ in 0..200 {
+            let constraints: Vec<PbConstraint> = (0..next(6) + 1)
+                .map(|_| {
+                    let terms = (0..next(5) + 1)
+                        .map(|_| {
+                            let lit = Lit::new(next(n) as u32, next(2) == 0);
+                            (lit, next(9) as i64 - 3)
+                        })
+                        .collect();
+                    let relation = match next(3) {
+                        0 => Relation::AtLeast,
+                        1 => Relation::AtMost,
+                        _ => Relation::Equal,
+                    };
+                    PbConstraint {
+                        terms,
+                        relation,
+                        degree: next(8) as i64 - 2,
+                    }
+                })
+                .collect();
+            let objective: Vec<(Lit, i64)> = (0..n as u32)
+                .map(|v| (Lit::new(v, true), next(7) as i64 - 2))
+                .collect();
+            let models: Vec<u32> = (0..1u32 << n)
+                .filter(|&bits| constraints.iter().all(|c| satisfied(bits, c)))
+                .collect();
+
+            let mut solver = PbSolver::default();
+            for _ in 0..n {
+                solver.new_var();
+            }
+            constraints.iter().for_each(|c| solver.add_constraint(c));
+            let status = solver.solve(None);
+            assert_eq!(status == Status::Sat, !models.is_empty());
+            if status == Status::Sat {
+                let bits = bits_of(&solver.assigns);
+                assert!(constraints.iter().all(|c| satisfied(bits, c)));
+            }
+
+            let value = |bits: u32| {
+                objective
+                    .iter()
+                    .filter(|(lit, _)| bits >> lit.var().0 & 1 == 1)
+                    .map(|(_, w)| w)
+                    .sum::<i64>()
+            };
+            let result = solver.minimize(&objective, None);
+            match models.iter().map(|&bits| value(bits)).min() {
+                Some(optimum) => {
+                    assert_eq!(result.status, MaxSatStatus::Optimum);
+                    assert_eq!(result.value, Some(optimum));
+                    assert_eq!(value(bits_of(&result.model.unwrap())), optimum);
+                }
+                None => assert_eq!(result.status, MaxSatStatus::Unsat),
+            }
+        }
+    }
+
+    #[test]
+    fn test_pb_pigeonhole() {
+        // 12 pigeons don't fit in 11 holes. Resolution needs an exponential proof.
+        let (pigeons, holes) = (12, 11);
+        let x = |p: u32, h: u32| Lit::new(p * holes + h, true);
+        let mut solver = PbSolver::default();
+        for p in 0..pigeons {
+            let clause: Vec<Lit> = (0..holes).map(|h| x(p, h)).collect();
+            solver.add_clause(&clause);
+        }
+        for h in 0..holes {
+            let terms: Vec<(Lit, i64)> = (0..pigeons).map(|p| (x(p, h), 1)).collect();
+            solver.add_at_most(&terms, 1);
+        }
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+
+    #[test]
+    fn test_parse_opb() {
+        let input = "* #variable= 3 #constraint= 2\nmin: +1 x1 +2 x2 ;\n+1 x1 +1 x2 +1 ~x3 >= 2 ;\n+3 x1 -2 x3 = 1 ;\n";
+        let opb = parse_opb(input.as_bytes()).unwrap();
+        assert_eq!(opb.var_num, 3);
+        assert_eq!(
+            opb.objective,
+            Some(vec![(Lit::from(1), 1), (Lit::from(2), 2)])
+        );
+        assert_eq!(
+            opb.constraints,
+            vec![
+                PbConstraint {
+                    terms: vec![(Lit::from(1), 1), (Lit::from(2), 1), (Lit::from(-3), 1)],
+                    relation: Relation::AtLeast,
+                    degree: 2,
+                },
+                PbConstraint {
+                    terms: vec![(Lit::from(1), 3), (Lit::from(3), -2)],
+                    relation: Relation::Equal,
+                    degree: 1,
+                },
+            ]
+        );
+
+        let mut solver = PbSolver::default();
+        opb.constraints
+            .iter()
+            .for_each(|c| solver.add_constraint(c));
+        let result = solver.minimize(opb.objective.as_ref().unwrap(), None);
+        // x1 and x3 are forced by 3 * x1 - 2 * x3 = 1, then x2 is forced by the first constraint.
+        assert_eq!(result.status, MaxSatStatus::Optimum);
+        assert_eq!(result.value, Some(3));
+
+        assert!(parse_opb("+1 x1 x2 >= 1 ;".as_bytes()).is_err());
+        assert!(parse_opb("+1 x1 >= ;".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,579 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::solver::*;
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    #[derive(Clone, Default)]
+    struct SharedBuf(Rc<RefCell<Vec<u8>>>);
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn parse_line(line: &str) -> (Option<String>, Vec<i64>) {
+        let mut tokens: Vec<&str> = line.split_whitespace().collect();
+        let prefix = if tokens[0].parse::<i64>().is_err() {
+            Some(tokens.remove(0).to_string())
+        } else {
+            None
+        };
+        let values = tokens
+            .into_iter()
+            .filter(|&t| t != "l")
+            .map(|t| t.parse().unwrap())
+            .collect();
+        (prefix, values)
+    }
+
+    fn to_lits(values: &[i64]) -> Vec<Lit> {
+        let mut lits: Vec<Lit> = values.iter().map(|&x| Lit::from(x as i32)).collect();
+        lits.sort();
+        lits
+    }
+
+    fn sorted(clause: &[Lit]) -> Vec<Lit> {
+        let mut clause = clause.to_vec();
+        clause.sort();
+        clause.dedup();
+        clause
+    }
+
+    fn fresh_random_unsat() -> Vec<(u32, Vec<Vec<Lit>>)> {
+        let mut problems: Vec<_> = (1..100)
+            .map(|seed| (10, random_problem(seed * 7919, 10, 60)))
+            .filter(|(var_num, clauses)| !satisfiable(*var_num, clauses))
+            .collect();
+        // Larger problems need restarts that simplify clauses at the root level.
+        problems.extend(
+            (1..6)
+                .map(|seed| (90, random_problem(seed, 90, 400)))
+                .filter(|(_, clauses)| Solver::new(90, clauses).solve(None) == Status::Unsat),
+        );
+        problems
+    }
+
+    #[test]
+    fn test_drat() {
+        for (_, clauses) in fresh_random_unsat() {
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_proof_writer(buf.clone(), ProofFormat::Drat);
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            assert_eq!(solver.solve(None), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let lines = String::from_utf8(buf.0.borrow().clone())
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let (prefix, values) = parse_line(line);
+                    let clause = to_lits(&values[..values.len() - 1]);
+                    (prefix.as_deref() == Some("d"), clause)
+                })
+                .collect::<Vec<_>>();
+            assert!(check_drat(&lines, &clauses));
+        }
+    }
+
+    #[test]
+    fn test_binary_drat() {
+        for (_, clauses) in fresh_random_unsat() {
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_proof_writer(buf.clone(), ProofFormat::BinaryDrat);
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            assert_eq!(solver.solve(None), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let bytes = buf.0.borrow().clone();
+            let mut lines = vec![];
+            let mut i = 0;
+            while i < bytes.len() {
+                assert!(bytes[i] == b'a' || bytes[i] == b'd');
+                let deleted = bytes[i] == b'd';
+                i += 1;
+                let mut values = vec![];
+                loop {
+                    let (mut x, mut shift) = (0u64, 0);
+                    loop {
+                        x |= ((bytes[i] & 0x7f) as u64) << shift;
+                        shift += 7;
+                        i += 1;
+                        if bytes[i - 1] & 0x80 == 0 {
+                            break;
+                        }
+                    }
+                    if x == 0 {
+                        break;
+                    }
+                    let var = (x >> 1) as i64;
+                    values.push(if x & 1 == 0 { var } else { -var });
+                }
+                lines.push((deleted, to_lits(&values)));
+            }
+            assert!(check_drat(&lines, &clauses));
+        }
+    }
+
+    // Check deleted or added clauses of a DRAT proof by RUP.
+    fn check_drat(lines: &[(bool, Vec<Lit>)], clauses: &[Vec<Lit>]) -> bool {
+        let mut db: Vec<Vec<Lit>> = clauses.iter().map(|c| sorted(c)).collect();
+        let mut refuted = false;
+        for (deleted, clause) in lines.iter() {
+            if *deleted {
+                let i = db.iter().position(|c| c == clause).unwrap();
+                db.swap_remove(i);
+            } else {
+                let antecedents: Vec<&Vec<Lit>> = db.iter().collect();
+                if !rup(clause, &antecedents) {
+                    return false;
+                }
+                refuted |= clause.is_empty();
+                db.push(clause.clone());
+            }
+        }
+        refuted
+    }
+
+    #[test]
+    fn test_frat() {
+        for (_, clauses) in fresh_random_unsat() {
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_proof_writer(buf.clone(), ProofFormat::Frat { hints: true });
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            assert_eq!(solver.solve(None), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+            assert!(check_frat(&proof, &clauses));
+        }
+    }
+
+    #[test]
+    fn test_search_options_proof() {
+        // Chronological backtracking, on-the-fly subsumption and simplification passes change assignments and clauses during the search.
+        let configs = vec![
+            Config {
+                chronological: Some(0),
+                ..Config::default()
+            },
+            Config {
+                on_the_fly_subsumption: true,
+                ..Config::default()
+            },
+            Config {
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(Vivification), 100),
+                ],
+                ..Config::default()
+            },
+            Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(FailedLiteralProbing), 60),
+                ],
+                ..Config::default()
+            },
+            Config {
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(PureLiteralElimination), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                    (std::sync::Arc::new(VariableElimination), 1000),
+                    (std::sync::Arc::new(Subsumption), 10000),
+                ],
+                ..Config::default()
+            },
+        ];
+        for config in configs.into_iter() {
+            for (_, clauses) in fresh_random_unsat() {
+                let buf = SharedBuf::default();
+                let mut solver = Solver::default();
+                solver.set_config(config.clone());
+                solver.set_proof_writer(buf.clone(), ProofFormat::Frat { hints: true });
+                clauses.iter().for_each(|clause| solver.add_clause(clause));
+                assert_eq!(solver.solve(None), Status::Unsat);
+                solver.flush_proof().unwrap();
+
+                let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+                assert!(check_frat(&proof, &clauses));
+            }
+        }
+    }
+
+    #[test]
+    fn test_equivalence_proof() {
+        // Equivalent literals are substituted by rewriting clauses with binary clauses of equivalences.
+        let mut checked = 0;
+        for seed in 1..80 {
+            let mut clauses = random_problem(seed, 60, 240);
+            for w in random_problem(seed + 500, 60, 10).iter() {
+                clauses.push(vec![!w[0], w[1]]);
+                clauses.push(vec![w[0], !w[1]]);
+            }
+            if Solver::new(60, &clauses).solve(None) != Status::Unsat {
+                continue;
+            }
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_config(Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                ],
+                ..Config::default()
+            });
+            solver.set_proof_writer(buf.clone(), ProofFormat::Frat { hints: true });
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            assert_eq!(solver.solve(None), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+            assert!(check_frat(&proof, &clauses), "{}", seed);
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+
+    #[test]
+    fn test_incremental_proof() {
+        // Clauses are added between `solve` calls until a problem becomes unsatisfiable.
+        for (_, clauses) in fresh_random_unsat() {
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_proof_writer(buf.clone(), ProofFormat::Frat { hints: true });
+            let mut sat = 0;
+            for chunk in clauses.chunks(clauses.len() / 4 + 1) {
+                chunk.iter().for_each(|clause| solver.add_clause(clause));
+                if solver.solve(None) == Status::Sat {
+                    sat += 1;
+                }
+            }
+            assert!(sat >= 1);
+            assert_eq!(solver.solve(None), Status::Unsat);
+            assert_eq!(solver.solve(None), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+            assert!(check_frat(&proof, &clauses));
+        }
+    }
+
+    #[test]
+    fn test_cube_and_conquer_proof() {
+        // The negation of the failed assumptions of each cube is derived in a proof.
+        for (_, clauses) in fresh_random_unsat() {
+            let buf = SharedBuf::default();
+            let mut solver = Solver::default();
+            solver.set_proof_writer(buf.clone(), ProofFormat::Frat { hints: true });
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let config = screwsat::cube::CubeConfig {
+                depth: 3,
+                ..Default::default()
+            };
+            assert_eq!(solver.solve_cube_and_conquer(&config), Status::Unsat);
+            solver.flush_proof().unwrap();
+
+            let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+            assert!(check_frat(&proof, &clauses));
+        }
+    }
+
+    #[test]
+    fn test_lrat() {
+        let configs = vec![
+            Config::default(),
+            Config {
+                restart: std::sync::Arc::new(GeometricRestart::new(10.0, 1.0)),
+                simplifiers: vec![
+                    (std::sync::Arc::new(RemoveSatisfied), 0),
+                    (std::sync::Arc::new(EquivalentLiteralSubstitution), 0),
+                    (std::sync::Arc::new(FailedLiteralProbing), 60),
+                    (std::sync::Arc::new(VariableElimination), 1000),
+                    (std::sync::Arc::new(Subsumption), 10000),
+                ],
+                ..Config::default()
+            },
+        ];
+        for config in configs.into_iter() {
+            for (_, mut clauses) in fresh_random_unsat() {
+                // Clauses before the first search may be simplified by root level assignments.
+                clauses.insert(0, vec![clauses[0][0]]);
+                let buf = SharedBuf::default();
+                let mut solver = Solver::default();
+                solver.set_config(config.clone());
+                solver.set_proof_writer(buf.clone(), ProofFormat::Lrat);
+                clauses.iter().for_each(|clause| solver.add_clause(clause));
+                assert_eq!(solver.solve(None), Status::Unsat);
+                solver.flush_proof().unwrap();
+
+                let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+                assert!(check_lrat(&proof, &clauses));
+            }
+        }
+
+        // The empty clause is derived while clauses are added. Later clauses still take their ids.
+        let clauses: Vec<Vec<Lit>> = [vec![1], vec![-1, 2], vec![-2], vec![3, 4], vec![-3]]
+            .iter()
+            .map(|c| c.iter().map(|&x| Lit::from(x)).collect())
+            .collect();
+        let buf = SharedBuf::default();
+        let mut solver = Solver::default();
+        solver.set_proof_writer(buf.clone(), ProofFormat::Lrat);
+        clauses.iter().for_each(|clause| solver.add_clause(clause));
+        solver.flush_proof().unwrap();
+        let proof = String::from_utf8(buf.0.borrow().clone()).unwrap();
+        assert!(check_lrat(&proof, &clauses));
+
+        // Original clauses can't be added after a search.
+        let mut solver = Solver::default();
+        solver.set_proof_writer(SharedBuf::default(), ProofFormat::Lrat);
+        solver.add_clause(&[Lit::from(1), Lit::from(2)]);
+        assert_eq!(solver.solve(None), Status::Sat);
+        solver.add_clause(&[Lit::from(-1)]);
+        let err = solver.flush_proof().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    /// Check an LRAT proof and return whether it derives the empty clause.
+    /// Original clauses are numbered from 1 and ids of added clauses increase.
+    fn check_lrat(proof: &str, clauses: &[Vec<Lit>]) -> bool {
+        let mut alive: std::collections::HashMap<i64, Vec<Lit>> = clauses
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (i as i64 + 1, sorted(c)))
+            .collect();
+        let mut last = clauses.len() as i64;
+        let mut refuted = false;
+        for line in proof.lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens[1] == "d" {
+                let ids: Vec<i64> = tokens[2..].iter().map(|t| t.parse().unwrap()).collect();
+                assert_eq!(ids.last(), Some(&0));
+                for id in ids[..ids.len() - 1].iter() {
+                    assert!(alive.remove(id).is_some());
+                }
+                continue;
+            }
+            let values: Vec<i64> = tokens.iter().map(|t| t.parse().unwrap()).collect();
+            let id = values[0];
+            assert!(id > last);
+            last = id;
+            let end = values.iter().skip(1).position(|&x| x == 0).unwrap() + 1;
+            let clause = to_lits(&values[1..end]);
+            let hints = &values[end + 1..values.len() - 1];
+            assert_eq!(values.last(), Some(&0));
+            // Hints propagate units in order and the last one is a conflict.
+            let mut assigned: Vec<Lit> = clause.iter().map(|&lit| !lit).collect();
+            for (i, h) in hints.iter().enumerate() {
+                let unassigned: Vec<Lit> = alive[h]
+                    .iter()
+                    .copied()
+                    .filter(|lit| !assigned.contains(&!*lit))
+                    .collect();
+                assert!(unassigned.iter().all(|lit| !assigned.contains(lit)));
+                if i + 1 == hints.len() {
+                    assert!(unassigned.is_empty());
+                } else {
+                    assert_eq!(unassigned.len(), 1);
+                    assigned.push(unassigned[0]);
+                }
+            }
+            refuted |= clause.is_empty();
+            alive.insert(id, clause);
+        }
+        refuted
+    }
+
+    /// Check a FRAT proof with hints and return whether it derives the empty clause.
+    /// All clauses must be finalized or deleted.
+    fn check_frat(proof: &str, clauses: &[Vec<Lit>]) -> bool {
+        let originals: Vec<Vec<Lit>> = clauses.iter().map(|c| sorted(c)).collect();
+        let mut alive: std::collections::HashMap<i64, Vec<Lit>> = Default::default();
+        let mut refuted = false;
+        for line in proof.lines() {
+            let (prefix, values) = parse_line(line);
+            let id = values[0];
+            let end = values.iter().skip(1).position(|&x| x == 0).unwrap() + 1;
+            let clause = to_lits(&values[1..end]);
+            match prefix.as_deref() {
+                Some("o") => {
+                    assert!(originals.contains(&sorted(&clause)));
+                    assert!(alive.insert(id, clause).is_none());
+                }
+                Some("a") => {
+                    // Hints propagate units in order and the last one is a conflict.
+                    let hints = &values[end + 1..values.len() - 1];
+                    let mut assigned: Vec<Lit> = clause.iter().map(|&lit| !lit).collect();
+                    for (i, h) in hints.iter().enumerate() {
+                        let unassigned: Vec<Lit> = sorted(&alive[h])
+                            .into_iter()
+                            .filter(|lit| !assigned.contains(&!*lit))
+                            .collect();
+                        assert!(unassigned.iter().all(|lit| !assigned.contains(lit)));
+                        if i + 1 == hints.len() {
+                            assert!(unassigned.is_empty());
+                        } else {
+                            assert_eq!(unassigned.len(), 1);
+                            assigned.push(unassigned[0]);
+                        }
+                    }
+                    refuted |= clause.is_empty();
+                    assert!(alive.insert(id, clause).is_none());
+                }
+                Some("d") | Some("f") => {
+                    assert_eq!(alive.remove(&id).map(|c| sorted(&c)), Some(sorted(&clause)));
+                }
+                _ => panic!("unknown line {}", line),
+            }
+        }
+        assert!(alive.is_empty());
+        refuted
+    }
+
+    fn random_problem(seed: u64, var_num: u32, cla_num: usize) -> Vec<Vec<Lit>> {
+        let mut x = seed;
+        let mut next = move || {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        (0..cla_num)
+            .map(|_| {
+                (0..3)
+                    .map(|_| Lit::new((next() % var_num as u64) as u32, next() % 2 == 0))
+                    .collect()
+            })
+            .collect()
+    }
+
+    // Whether `clause` is derived from `antecedents` by unit propagation(RUP).
+    fn rup(clause: &[Lit], antecedents: &[&Vec<Lit>]) -> bool {
+        let mut assigned: Vec<Lit> = clause.iter().map(|&lit| !lit).collect();
+        loop {
+            let mut changed = false;
+            for c in antecedents.iter() {
+                let mut unassigned: Vec<Lit> = c
+                    .iter()
+                    .filter(|&&lit| !assigned.contains(&!lit))
+                    .copied()
+                    .collect();
+                unassigned.sort();
+                unassigned.dedup();
+                if unassigned.iter().any(|lit| assigned.contains(lit)) {
+                    continue;
+                }
+                match unassigned.len() {
+                    0 => return true,
+                    1 => {
+                        assigned.push(unassigned[0]);
+                        changed = true;
+                    }
+                    _ => {}
+                }
+            }
+            if !changed {
+                return false;
+            }
+        }
+    }
+
+    fn satisfiable(var_num: u32, clauses: &[Vec<Lit>]) -> bool {
+        (0..1u32 << var_num).any(|bits| {
+            clauses.iter().all(|clause| {
+                clause
+                    .iter()
+                    .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+            })
+        })
+    }
+
+    #[test]
+    fn test_proof() {
+        let mut refuted = 0;
+        for seed in 1..100 {
+            let var_num = 10;
+            let clauses = random_problem(seed * 7919, var_num, 60);
+            let mut solver = Solver::default();
+            solver.enable_proof();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            let status = solver.solve(None);
+            let proof = solver.proof().unwrap();
+            assert_eq!(status == Status::Unsat, proof.empty_clause().is_some());
+            if status != Status::Unsat {
+                continue;
+            }
+            refuted += 1;
+
+            let nodes = proof.nodes();
+            for (i, node) in nodes.iter().enumerate() {
+                if node.antecedents.is_empty() {
+                    assert!(clauses.contains(&node.clause));
+                } else {
+                    assert!(node.antecedents.iter().all(|&a| a < i));
+                    let antecedents: Vec<&Vec<Lit>> =
+                        node.antecedents.iter().map(|&a| &nodes[a].clause).collect();
+                    assert!(rup(&node.clause, &antecedents));
+                }
+            }
+            assert!(!satisfiable(var_num, &proof.core()));
+
+            let mut trace = vec![];
+            proof.write_tracecheck(&mut trace).unwrap();
+            assert_eq!(
+                String::from_utf8(trace).unwrap().lines().count(),
+                nodes.len()
+            );
+        }
+        assert!(refuted > 10);
+    }
+
+    #[test]
+    fn test_explanation() {
+        let clauses: Vec<Vec<Lit>> = [[1, 2], [-1, 2], [1, -2], [-1, -2]]
+            .iter()
+            .map(|c| c.iter().map(|&x| Lit::from(x)).collect())
+            .collect();
+        let mut solver = Solver::default();
+        solver.enable_proof();
+        clauses.iter().for_each(|clause| solver.add_clause(clause));
+        assert_eq!(solver.solve(None), Status::Unsat);
+        let mut buf = vec![];
+        solver.proof().unwrap().write_explanation(&mut buf).unwrap();
+        let explanation = String::from_utf8(buf).unwrap();
+        assert!(explanation.starts_with("clause 1: x1 or x2\n"));
+        assert!(explanation.contains("derive the empty clause\n"));
+        assert!(explanation
+            .ends_with("The empty clause is reached, so the clauses are contradictory.\n"));
+
+        // Each step resolves two clauses that are written before it.
+        for (_, clauses) in fresh_random_unsat().into_iter().take(10) {
+            let mut solver = Solver::default();
+            solver.enable_proof();
+            clauses.iter().for_each(|clause| solver.add_clause(clause));
+            assert_eq!(solver.solve(None), Status::Unsat);
+            let mut buf = vec![];
+            solver.proof().unwrap().write_explanation(&mut buf).unwrap();
+            let mut written = std::collections::HashSet::new();
+            for line in String::from_utf8(buf).unwrap().lines() {
+                if let Some((name, rest)) = line.split_once(": from ") {
+                    let (a, rest) = rest.split_once(" and ").unwrap();
+                    let (b, _) = rest.split_once(" on ").unwrap();
+                    assert!(written.contains(a) && written.contains(b), "{}", line);
+                    written.insert(name.to_string());
+                } else if let Some((name, _)) = line.split_once(": ") {
+                    written.insert(name.to_string());
+                }
+            }
+        }
+    }
+}
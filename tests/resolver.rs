@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::resolver::*;
+
+    #[test]
+    fn test_resolver() {
+        let mut resolver = Resolver::default();
+        let app = resolver.add_optional_package(1);
+        let lib = resolver.add_optional_package(3);
+        let log = resolver.add_optional_package(2);
+        let unused = resolver.add_optional_package(2);
+        resolver.require(app, &[0]);
+        // app 0 depends on lib 1 or 2
+        resolver.depend(app, 0, lib, &[1, 2]);
+        // lib 2 depends on log 1, lib 1 depends on log 0
+        resolver.depend(lib, 2, log, &[1]);
+        resolver.depend(lib, 1, log, &[0]);
+        resolver.conflict((lib, 1), (log, 0));
+        match resolver.resolve(None) {
+            Resolution::Selected(selection) => {
+                assert_eq!(selection[app], Some(0));
+                assert_eq!(selection[lib], Some(2));
+                assert_eq!(selection[log], Some(1));
+                assert_eq!(selection[unused], None);
+            }
+            r => panic!("{:?}", r),
+        }
+
+        // Incremental constraints make it unsatisfiable.
+        resolver.conflict((app, 0), (log, 1));
+        match resolver.resolve(None) {
+            Resolution::Conflict(mut constraints) => {
+                constraints.sort();
+                let mut expected = vec![
+                    Constraint::Require {
+                        package: app,
+                        versions: vec![0],
+                    },
+                    Constraint::Depend {
+                        package: app,
+                        version: 0,
+                        dependency: lib,
+                        versions: vec![1, 2],
+                    },
+                    Constraint::Depend {
+                        package: lib,
+                        version: 2,
+                        dependency: log,
+                        versions: vec![1],
+                    },
+                    Constraint::Depend {
+                        package: lib,
+                        version: 1,
+                        dependency: log,
+                        versions: vec![0],
+                    },
+                    Constraint::Conflict {
+                        a: (lib, 1),
+                        b: (log, 0),
+                    },
+                    Constraint::Conflict {
+                        a: (app, 0),
+                        b: (log, 1),
+                    },
+                ];
+                expected.sort();
+                assert_eq!(constraints, expected);
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_resolver_single_version() {
+        // Two versions of the same package can't be selected.
+        let mut resolver = Resolver::default();
+        let a = resolver.add_optional_package(2);
+        let b = resolver.add_optional_package(1);
+        resolver.require(b, &[0]);
+        resolver.depend(b, 0, a, &[0]);
+        resolver.require(a, &[1]);
+        match resolver.resolve(None) {
+            Resolution::Conflict(constraints) => assert_eq!(constraints.len(), 3),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn test_resolver_exactly_one() {
+        // A package is always selected unless it's optional.
+        let mut resolver = Resolver::default();
+        let a = resolver.add_package(2);
+        let b = resolver.add_optional_package(1);
+        resolver.conflict((a, 0), (b, 0));
+        match resolver.resolve(None) {
+            Resolution::Selected(selection) => {
+                assert!(selection[a].is_some());
+                assert!(selection[a] != Some(0) || selection[b].is_none());
+            }
+            r => panic!("{:?}", r),
+        }
+
+        let c = resolver.add_package(1);
+        resolver.conflict((a, 0), (c, 0));
+        resolver.conflict((a, 1), (c, 0));
+        resolver.conflict((a, 1), (b, 0));
+        match resolver.resolve(None) {
+            Resolution::Conflict(mut constraints) => {
+                constraints.sort();
+                let mut expected = vec![
+                    Constraint::Conflict {
+                        a: (a, 0),
+                        b: (c, 0),
+                    },
+                    Constraint::Conflict {
+                        a: (a, 1),
+                        b: (c, 0),
+                    },
+                ];
+                expected.sort();
+                assert_eq!(constraints, expected);
+            }
+            r => panic!("{:?}", r),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::sampler::Sampler;
+    use screwsat::solver::*;
+    use std::collections::HashMap;
+
+    fn is_model(clauses: &[Vec<Lit>], model: &[LitBool]) -> bool {
+        clauses.iter().all(|clause| {
+            clause.iter().any(|lit| {
+                model[lit.var().0 as usize]
+                    == if lit.pos() {
+                        LitBool::True
+                    } else {
+                        LitBool::False
+                    }
+            })
+        })
+    }
+
+    #[test]
+    fn test_sampler_hashing() {
+        // 1024 * 3/4 * 3/4 = 576 models
+        let clauses = vec![
+            vec![Lit::from(1), Lit::from(2)],
+            vec![Lit::from(-3), Lit::from(4)],
+        ];
+        let vars: Vec<Var> = (0..10).map(Var).collect();
+        let samples: Vec<_> = Sampler::new(10, &clauses, &vars, 16.0, 7)
+            .take(300)
+            .collect();
+        assert_eq!(samples.len(), 300);
+        assert!(samples.iter().all(|model| is_model(&clauses, model)));
+        let ratio = |v: usize| {
+            samples
+                .iter()
+                .filter(|model| model[v] == LitBool::True)
+                .count() as f64
+                / samples.len() as f64
+        };
+        // x1 is true in 2/3 of models and x5 is true in 1/2 of models.
+        assert!((ratio(0) - 2.0 / 3.0).abs() < 0.1, "{}", ratio(0));
+        assert!((ratio(4) - 0.5).abs() < 0.1, "{}", ratio(4));
+    }
+
+    #[test]
+    fn test_sampler_exact() {
+        // 3 models on the sampling set {x1, x2}. x3 is free outside of it.
+        let clauses = vec![vec![Lit::from(1), Lit::from(2)]];
+        let vars = vec![Var(0), Var(1)];
+        let mut counts = HashMap::new();
+        for model in Sampler::new(3, &clauses, &vars, 16.0, 1).take(900) {
+            *counts
+                .entry((model[0] == LitBool::True, model[1] == LitBool::True))
+                .or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 3);
+        assert!(counts.values().all(|&c| 200 < c && c < 400), "{:?}", counts);
+
+        // Unsatisfiable
+        let clauses = vec![vec![Lit::from(1)], vec![Lit::from(-1)]];
+        assert_eq!(Sampler::new(1, &clauses, &[Var(0)], 16.0, 1).next(), None);
+    }
+}
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::schedule::{self, *};
+    use screwsat::solver::*;
+
+    #[test]
+    fn test_schedule() {
+        // 6 exams in 3 slots. Exams that share students conflict.
+        let conflicts = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (0, 5), (0, 3)];
+        let mut solver = Solver::default();
+        let grid = schedule::grid(&mut solver, 6, 3);
+        one_slot_per_task(&mut solver, &grid);
+        conflicts
+            .iter()
+            .for_each(|&(a, b)| separate(&mut solver, &grid, a, b));
+        (0..3).for_each(|slot| capacity(&mut solver, &grid, slot, 2));
+        forbid(&mut solver, &grid, 0, 0);
+        assert_eq!(solver.solve(None), Status::Sat);
+
+        let slots: Vec<usize> = decode(&solver, &grid)
+            .into_iter()
+            .map(|slot| slot.unwrap())
+            .collect();
+        assert_ne!(slots[0], 0);
+        for &(a, b) in conflicts.iter() {
+            assert_ne!(slots[a], slots[b]);
+        }
+        for slot in 0..3 {
+            assert!(slots.iter().filter(|&&s| s == slot).count() <= 2);
+        }
+
+        // 6 exams can't be scheduled in 3 slots of capacity 1.
+        let mut solver = Solver::default();
+        let grid = schedule::grid(&mut solver, 6, 3);
+        one_slot_per_task(&mut solver, &grid);
+        (0..3).for_each(|slot| capacity(&mut solver, &grid, slot, 1));
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+}
@@ -0,0 +1,89 @@
+use screwsat::server::Server;
+
+fn run(server: &mut Server, script: &str) -> (bool, Vec<String>) {
+    let mut output = vec![];
+    let alive = server.run(script.as_bytes(), &mut output).unwrap();
+    let lines = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| line.to_string())
+        .collect();
+    (alive, lines)
+}
+
+#[test]
+fn test_sessions() {
+    let mut server = Server::default();
+    let (alive, lines) = run(
+        &mut server,
+        "new a\n\
+         add a 1 2 0\n\
+         add a -1\n\
+         c a comment has no response\n\
+         \n\
+         solve a\n\
+         model a\n\
+         solve a -2 0\n\
+         failed a\n\
+         model a\n\
+         new b\n\
+         add b 1 0\n\
+         add b -1 0\n\
+         solve b timeout=1.5\n\
+         new a\n\
+         add c 1 0\n\
+         add a 1 x 0\n\
+         solve a timeout=-1\n\
+         list\n",
+    );
+    assert!(alive);
+    assert_eq!(
+        lines,
+        vec![
+            "ok",
+            "ok",
+            "ok",
+            "sat",
+            "v -1 2 0",
+            "unsat",
+            "f -2 0",
+            "error A model is not available",
+            "ok",
+            "ok",
+            "ok",
+            "unsat",
+            "error Session already exists: a",
+            "error No session: c",
+            "error Invalid literal: x",
+            "error Invalid timeout: -1",
+            "sessions a b",
+        ]
+    );
+
+    // Sessions are kept for the next input.
+    let (alive, lines) = run(&mut server, "drop b\nsolve a 2\nquit\nlist\n");
+    assert!(alive);
+    assert_eq!(lines, vec!["ok", "sat", "bye"]);
+    let (alive, lines) = run(&mut server, "list\nshutdown\nlist\n");
+    assert!(!alive);
+    assert_eq!(lines, vec!["sessions a", "bye"]);
+}
+
+#[test]
+fn test_load() {
+    let dir = std::env::temp_dir();
+    let cnf = dir.join(format!("screwsat_server_{}.cnf", std::process::id()));
+    std::fs::write(&cnf, "p cnf 3 3\n1 2 0\n-1 3 0\n-3 0\n").unwrap();
+    let mut server = Server::default();
+    let (_, lines) = run(
+        &mut server,
+        &format!(
+            "load a {}\nsolve a\nmodel a\nsolve a -2\nload b {}\n",
+            cnf.display(),
+            dir.join("screwsat_server_missing.cnf").display()
+        ),
+    );
+    std::fs::remove_file(&cnf).unwrap();
+    assert_eq!(lines[..4], ["ok", "sat", "v -1 2 -3 0", "unsat"]);
+    assert!(lines[4].starts_with("error "));
+}
@@ -0,0 +1,58 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::smtlib::*;
+
+    fn run(script: &str) -> std::io::Result<String> {
+        let mut output = vec![];
+        SmtLib::default().run(script.as_bytes(), &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_smtlib() {
+        let script = r#"
+; xor with an implication
+(set-logic QF_UF)
+(set-option :produce-models true)
+(declare-const p Bool)
+(declare-fun |q r| () Bool)
+(define-fun both () Bool (and p |q r|))
+(assert (xor p |q r|))
+(assert (=> p |q r|))
+(check-sat)
+(get-model)
+(assert (let ((x (not p)) (y |q r|)) (! (ite x (not y) true) :named last)))
+(check-sat)
+(echo "done")
+(exit)
+(check-sat)
+"#;
+        let output = run(script).unwrap();
+        assert_eq!(
+            output,
+            "sat\n(\n  (define-fun p () Bool false)\n  (define-fun |q r| () Bool true)\n)\nunsat\n\"done\"\n"
+        );
+
+        let mut smt = SmtLib::default();
+        smt.run(
+            "(declare-const a Bool) (declare-const b Bool) (declare-const c Bool)
+             (assert (= a b c)) (assert (distinct a (not c))) (assert (or a (and b c))) (check-sat)"
+                .as_bytes(),
+            std::io::sink(),
+        )
+        .unwrap();
+        assert_eq!(smt.value("a"), Some(true));
+        assert_eq!(smt.value("b"), Some(true));
+        assert_eq!(smt.value("c"), Some(true));
+        assert_eq!(smt.value("d"), None);
+    }
+
+    #[test]
+    fn test_smtlib_error() {
+        assert!(run("(declare-const x Int)").is_err());
+        assert!(run("(assert y)").is_err());
+        assert!(run("(declare-const x Bool) (assert (and x)").is_err());
+        assert!(run("(declare-const x Bool) (assert (not x)) (get-model)").is_err());
+        assert!(run("(push 1)").is_err());
+    }
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod tests {
+    use screwsat::solver::*;
+    use screwsat::symmetry::*;
+
+    fn pigeonhole(pigeons: u32, holes: u32) -> Vec<Vec<Lit>> {
+        let x = |p: u32, h: u32| Lit::new(p * holes + h, true);
+        let mut clauses: Vec<Vec<Lit>> = (0..pigeons)
+            .map(|p| (0..holes).map(|h| x(p, h)).collect())
+            .collect();
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![!x(p, h), !x(q, h)]);
+                }
+            }
+        }
+        clauses
+    }
+
+    fn satisfied(bits: u32, clause: &[Lit]) -> bool {
+        clause
+            .iter()
+            .any(|lit| (bits >> lit.var().0 & 1 == 1) == lit.pos())
+    }
+
+    #[test]
+    fn test_find_symmetries() {
+        let clauses = pigeonhole(4, 3);
+        let mut sorted: Vec<Vec<Lit>> = clauses.clone();
+        sorted.iter_mut().for_each(|clause| clause.sort());
+        sorted.sort();
+        let symmetries = find_symmetries(&clauses, 10000);
+        assert!(!symmetries.is_empty());
+        for perm in symmetries.iter() {
+            let image = |lit: Lit| {
+                let p = perm[lit.var().0 as usize];
+                if lit.pos() {
+                    p
+                } else {
+                    !p
+                }
+            };
+            let mut mapped: Vec<Vec<Lit>> = clauses
+                .iter()
+                .map(|clause| {
+                    let mut clause: Vec<Lit> = clause.iter().map(|&lit| image(lit)).collect();
+                    clause.sort();
+                    clause
+                })
+                .collect();
+            mapped.sort();
+            assert_eq!(mapped, sorted);
+        }
+        // No budget finds nothing.
+        assert!(find_symmetries(&clauses, 0).is_empty());
+    }
+
+    #[test]
+    fn test_break_symmetries() {
+        let mut x = 12345u64;
+        let mut next = move |m: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x % m
+        };
+        for seed in 0..100 {
+            // Two copies of random clauses over 5 variables and clauses between them are symmetric under swapping the copies.
+            let base: Vec<Vec<Lit>> = (0..8 + seed % 10)
+                .map(|_| {
+                    (0..3)
+                        .map(|_| Lit::new(next(5) as u32, next(2) == 0))
+                        .collect()
+                })
+                .collect();
+            let mut clauses = base.clone();
+            for clause in base.iter() {
+                clauses.push(
+                    clause
+                        .iter()
+                        .map(|lit| Lit::new(lit.var().0 + 5, lit.pos()))
+                        .collect(),
+                );
+            }
+            let (a, b) = (next(5) as u32, next(5) as u32);
+            clauses.push(vec![Lit::new(a, false), Lit::new(b + 5, false)]);
+            clauses.push(vec![Lit::new(b, false), Lit::new(a + 5, false)]);
+
+            let models = (0..1u32 << 10)
+                .filter(|&bits| clauses.iter().all(|clause| satisfied(bits, clause)))
+                .count();
+            let mut solver = Solver::new(10, &clauses);
+            let broken = break_symmetries(&mut solver, &clauses, 10000);
+            assert!(broken > 0, "seed: {}", seed);
+            let status = solver.solve(None);
+            assert_eq!(status == Status::Sat, models > 0, "seed: {}", seed);
+            if status == Status::Sat {
+                let bits = (0..10).fold(0, |bits, v| {
+                    bits | ((solver.assigns[v] == LitBool::True) as u32) << v
+                });
+                assert!(clauses.iter().all(|clause| satisfied(bits, clause)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pigeonhole_symmetries() {
+        // 9 pigeons in 8 holes take a long time without symmetry breaking.
+        let clauses = pigeonhole(9, 8);
+        let mut solver = Solver::new(72, &clauses);
+        assert!(break_symmetries(&mut solver, &clauses, 100000) > 0);
+        assert_eq!(solver.solve(None), Status::Unsat);
+    }
+}